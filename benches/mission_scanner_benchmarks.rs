@@ -50,6 +50,18 @@ fn mission_scan_benchmark(c: &mut Criterion) {
     let config = MissionScannerConfig {
         max_threads: num_cpus::get(),
         file_extensions: DEFAULT_FILE_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
+        max_mission_size: None,
+        output_dir: None,
+        extract_repros: false,
+        read_retries: 2,
+        retry_backoff_ms: 100,
+        filter: Default::default(),
+        ignore_classes: Vec::new(),
+        ignore_patterns: Vec::new(),
+        cache_dir: None,
+        progress: Default::default(),
+        min_confidence: mission_scanner::Confidence::Low,
+        mod_prefixes: Default::default(),
     };
 
     let mut group = c.benchmark_group("mission_scanner");
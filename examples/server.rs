@@ -0,0 +1,20 @@
+use std::net::SocketAddr;
+use anyhow::Result;
+use mission_scanner::{server, ClassDatabase, MissionScannerConfig};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize logging
+    env_logger::init();
+
+    let addr: SocketAddr = std::env::args().nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8080".to_string())
+        .parse()?;
+
+    // Validate uploads against a class database given as second argument
+    let database = std::env::args().nth(2)
+        .map(|path| ClassDatabase::load_from_file(path.as_ref()))
+        .transpose()?;
+
+    server::serve(addr, MissionScannerConfig::default(), database).await
+}
@@ -0,0 +1,231 @@
+//! Types shared by the mission scanner and its parsers
+//!
+//! The parsers and the scanner describe references the same way: how sure
+//! they are that a string is a class, how many instances are added and how
+//! the class is used. Keeping those types in one crate lets results of the
+//! different parsers be merged as they are, without converting through strings.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Deserialize};
+
+/// How sure the analysis is that a reference is really a class.
+///
+/// Ordered from least to most sure, so references can be filtered by a minimum.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Confidence {
+    /// Guessed from where a string appears, such as the variable it's assigned
+    /// to, or found by scanning the text of a file that couldn't be parsed
+    Low,
+    /// Resolved through script variables or expressions
+    Medium,
+    /// Written where the format or command expects a class
+    #[default]
+    High,
+}
+
+impl FromStr for Confidence {
+    type Err = ParseConfidenceError;
+
+    /// Parse `low`, `medium` or `high`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => Err(ParseConfidenceError(s.to_string())),
+        }
+    }
+}
+
+/// Error of parsing a [`Confidence`] that isn't `low`, `medium` or `high`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseConfidenceError(String);
+
+impl fmt::Display for ParseConfidenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid confidence '{}', expected low, medium or high", self.0)
+    }
+}
+
+impl std::error::Error for ParseConfidenceError {}
+
+/// Bounds of a number of instances that varies from run to run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CountRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl CountRange {
+    /// Range of a count known exactly
+    pub fn exact(count: u32) -> Self {
+        Self { min: count, max: count }
+    }
+
+    /// Check if the count doesn't vary
+    pub fn is_exact(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Add the bounds of another count
+    pub fn add(&mut self, other: CountRange) {
+        self.min = self.min.saturating_add(other.min);
+        self.max = self.max.saturating_add(other.max);
+    }
+
+    /// Multiply by the bounds of another count, such as a number of loop iterations
+    pub fn times(self, other: CountRange) -> Self {
+        Self {
+            min: self.min.saturating_mul(other.min),
+            max: self.max.saturating_mul(other.max),
+        }
+    }
+}
+
+/// Type of reference to a class
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ReferenceType {
+    /// Direct reference to a class
+    Direct,
+    /// Inheritance from a parent class
+    Inheritance,
+    /// Reference through a variable
+    Variable,
+    /// Class taken away by a remove or unassign command, e.g. `unlinkItem "NVGoggles"`
+    Removal,
+    /// Equipment of a `CfgRespawnInventory` loadout, handed out on respawn
+    RespawnInventory,
+}
+
+/// Kind of item a class argument of a command names, e.g. a weapon for `addWeapon`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ItemKind {
+    Weapon,
+    Magazine,
+    /// Inventory item, weapon attachment or linked item such as a map or NVGs
+    Item,
+    Uniform,
+    Vest,
+    Backpack,
+    Headgear,
+    Goggles,
+    Vehicle,
+}
+
+impl ItemKind {
+    /// Name of the kind as serialized, e.g. `weapon`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Weapon => "weapon",
+            ItemKind::Magazine => "magazine",
+            ItemKind::Item => "item",
+            ItemKind::Uniform => "uniform",
+            ItemKind::Vest => "vest",
+            ItemKind::Backpack => "backpack",
+            ItemKind::Headgear => "headgear",
+            ItemKind::Goggles => "goggles",
+            ItemKind::Vehicle => "vehicle",
+        }
+    }
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ItemKind {
+    type Err = ParseItemKindError;
+
+    /// Parse the serialized name of a kind, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let kinds = [
+            ItemKind::Weapon, ItemKind::Magazine, ItemKind::Item, ItemKind::Uniform, ItemKind::Vest,
+            ItemKind::Backpack, ItemKind::Headgear, ItemKind::Goggles, ItemKind::Vehicle,
+        ];
+        kinds.into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseItemKindError(s.to_string()))
+    }
+}
+
+/// Error of parsing an [`ItemKind`] that isn't one of the known kinds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseItemKindError(String);
+
+impl fmt::Display for ParseItemKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid item kind '{}'", self.0)
+    }
+}
+
+impl std::error::Error for ParseItemKindError {}
+
+/// How a class reference was discovered in SQF code
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum UsageContext {
+    /// Used in an add* command (addWeapon, addVest, etc.)
+    AddCommand(String),
+    /// Used in a function known to use class references
+    KnownFunction(String),
+    /// Directly used as a string in a context that suggests it's a class
+    DirectReference,
+    /// Quoted near a known command or function in a file that couldn't be parsed
+    TokenScan(String),
+    /// Removed by a command (removeItem, unlinkItem, etc.)
+    RemoveCommand(String),
+    /// Looked up in a top-level config class, e.g. `configFile >> "CfgWeapons" >> "arifle_MX_F"`
+    ConfigLookup(String),
+}
+
+impl fmt::Display for UsageContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsageContext::AddCommand(cmd) => write!(f, "Used in command: {}", cmd),
+            UsageContext::KnownFunction(func) => write!(f, "Used in function: {}", func),
+            UsageContext::DirectReference => write!(f, "Direct reference"),
+            UsageContext::TokenScan(command) => write!(f, "Found by token scan near: {}", command),
+            UsageContext::RemoveCommand(cmd) => write!(f, "Removed by command: {}", cmd),
+            UsageContext::ConfigLookup(config) => write!(f, "Looked up in config: {}", config),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_confidence() {
+        assert_eq!("Medium".parse(), Ok(Confidence::Medium));
+        assert!(Confidence::Low < Confidence::High);
+        assert_eq!(
+            "certain".parse::<Confidence>().unwrap_err().to_string(),
+            "Invalid confidence 'certain', expected low, medium or high"
+        );
+    }
+
+    #[test]
+    fn test_parse_item_kind() {
+        assert_eq!("Backpack".parse(), Ok(ItemKind::Backpack));
+        assert_eq!(ItemKind::Headgear.to_string(), "headgear");
+        assert!("helmet".parse::<ItemKind>().is_err());
+    }
+
+    #[test]
+    fn test_count_range() {
+        let mut count = CountRange::exact(2);
+        count.add(CountRange { min: 0, max: 3 });
+        assert_eq!(count.times(CountRange::exact(2)), CountRange { min: 4, max: 10 });
+        assert!(!count.is_exact());
+    }
+}
@@ -0,0 +1,55 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use parser_hpp::HppParser;
+
+/// Roles in the generated stress loadout file, each inheriting from the one before
+const LOADOUT_ROLES: usize = 2000;
+
+/// Generate a loadout file of `roles` classes in one inheritance chain, as loadout frameworks write them
+fn create_large_loadout(roles: usize) -> String {
+    let mut content = String::from(r#"class baseMan {
+    displayName = "Unarmed";
+    uniform[] = {};
+    vest[] = {};
+    backpack[] = {};
+    weapons[] = {};
+    magazines[] = {};
+    items[] = {};
+    linkedItems[] = {"ItemMap", "ItemCompass", "ItemWatch"};
+};
+"#);
+    let mut parent = String::from("baseMan");
+    for role in 0..roles {
+        let name = format!("role_{role}");
+        content.push_str(&format!(r#"class {name} : {parent} {{
+    displayName = "Role {role}";
+    uniform[] = {{"rhs_uniform_g3_m81_{role}", "rhs_uniform_g3_mc_{role}"}};
+    vest[] = {{"rhsusf_spcs_ocp_rifleman_{role}"}};
+    backpack[] = {{"rhsusf_assault_eagleaiii_ocp_{role}"}};
+    weapons[] = {{"rhs_weap_m4a1_{role}"}};
+    magazines[] = {{"rhs_mag_30Rnd_556x45_M855A1_Stanag_{role}", "rhs_mag_30Rnd_556x45_M855A1_Stanag_{role}"}};
+    items[] += {{"ACE_fieldDressing", "ACE_morphine_{role}"}};
+}};
+"#));
+        parent = name;
+    }
+    content
+}
+
+fn hpp_throughput(c: &mut Criterion) {
+    let loadout = create_large_loadout(LOADOUT_ROLES);
+    let mut group = c.benchmark_group("hpp_throughput");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(loadout.len() as u64));
+
+    group.bench_function("loadout_2k_roles", |b| {
+        b.iter(|| {
+            let parser = HppParser::parse_str(black_box(&loadout), "loadout.hpp").unwrap();
+            parser.parse_classes()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, hpp_throughput);
+criterion_main!(benches);
@@ -0,0 +1,224 @@
+//! Canonical config paths such as `CfgWeapons/rhs_weap_m4a1/magazines[]`
+//!
+//! Segments are separated by `/`; SQF style `configFile >> "CfgWeapons" >> "x"`
+//! is accepted too. A trailing `[]` marks an array property. Paths compare
+//! case-insensitively, like class and property names in the engine.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Roots that SQF config lookups start from, dropped when parsing
+const CONFIG_ROOTS: [&str; 3] = ["configfile", "missionconfigfile", "campaignconfigfile"];
+
+/// Path to a config class or property
+#[derive(Debug, Clone, Default)]
+pub struct ConfigPath {
+    segments: Vec<String>,
+    array: bool,
+}
+
+impl ConfigPath {
+    /// Empty path, the config root
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parse a path written with `/` or `>>` separators
+    pub fn parse(path: &str) -> Self {
+        let mut segments: Vec<String> = path.split('/')
+            .flat_map(|part| part.split(">>"))
+            .map(|segment| segment.trim().trim_matches('"').trim())
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if segments.first().is_some_and(|first| CONFIG_ROOTS.contains(&first.to_lowercase().as_str())) {
+            segments.remove(0);
+        }
+
+        let mut array = false;
+        if let Some(last) = segments.last_mut()
+            && let Some(name) = last.strip_suffix("[]")
+        {
+            *last = name.trim_end().to_string();
+            array = true;
+        }
+
+        Self { segments, array }
+    }
+
+    /// Path to a class or scalar property below this one
+    pub fn join(&self, name: &str) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(name.to_string());
+        Self { segments, array: false }
+    }
+
+    /// Path to an array property below this one
+    pub fn join_array(&self, name: &str) -> Self {
+        Self { array: true, ..self.join(name) }
+    }
+
+    /// Segments of the path, as written
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Last segment of the path
+    pub fn name(&self) -> Option<&str> {
+        self.segments.last().map(String::as_str)
+    }
+
+    /// Whether the path ends in an array property
+    pub fn is_array(&self) -> bool {
+        self.array
+    }
+
+    /// Check if another path is this one or below it
+    pub fn contains(&self, other: &ConfigPath) -> bool {
+        other.segments.len() >= self.segments.len()
+            && self.segments.iter().zip(&other.segments).all(|(a, b)| a.eq_ignore_ascii_case(b))
+    }
+
+    /// Check if the path matches a pattern.
+    ///
+    /// In the pattern, `*` within a segment matches any characters and a `**`
+    /// segment matches any number of segments. An array pattern only matches
+    /// array paths.
+    pub fn matches(&self, pattern: &ConfigPath) -> bool {
+        self.array == pattern.array && segments_match(&pattern.segments, &self.segments)
+    }
+
+    /// Check if the path ends with segments matching a pattern, ignoring `[]`
+    pub fn ends_with(&self, pattern: &ConfigPath) -> bool {
+        (0..=self.segments.len()).any(|start| segments_match(&pattern.segments, &self.segments[start..]))
+    }
+}
+
+impl From<&str> for ConfigPath {
+    fn from(path: &str) -> Self {
+        Self::parse(path)
+    }
+}
+
+impl fmt::Display for ConfigPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.segments.join("/"))?;
+        if self.array {
+            write!(f, "[]")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for ConfigPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.array == other.array
+            && self.segments.len() == other.segments.len()
+            && self.contains(other)
+    }
+}
+
+impl Eq for ConfigPath {}
+
+impl Hash for ConfigPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for segment in &self.segments {
+            segment.to_lowercase().hash(state);
+        }
+        self.array.hash(state);
+    }
+}
+
+/// Match path segments against pattern segments
+fn segments_match(pattern: &[String], segments: &[String]) -> bool {
+    match pattern.split_first() {
+        None => segments.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=segments.len()).any(|skip| segments_match(rest, &segments[skip..]))
+        }
+        Some((first, rest)) => segments.split_first()
+            .is_some_and(|(segment, remaining)| glob_match(first, segment) && segments_match(rest, remaining)),
+    }
+}
+
+/// Match a single segment against a pattern where `*` matches any characters, ignoring case
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let mut parts = pattern.split('*');
+
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    // Without a `*` the segment must match exactly
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let path = ConfigPath::parse("CfgWeapons/rhs_weap_m4a1/magazines[]");
+        assert_eq!(path.segments(), ["CfgWeapons", "rhs_weap_m4a1", "magazines"]);
+        assert!(path.is_array());
+        assert_eq!(path.to_string(), "CfgWeapons/rhs_weap_m4a1/magazines[]");
+
+        let sqf = ConfigPath::parse(r#"configFile >> "CfgWeapons" >> "rhs_weap_m4a1" >> "displayName""#);
+        assert_eq!(sqf.to_string(), "CfgWeapons/rhs_weap_m4a1/displayName");
+
+        let built = ConfigPath::root().join("CfgWeapons").join("rhs_weap_m4a1").join_array("magazines");
+        assert_eq!(built, path);
+        assert_eq!(ConfigPath::parse(" /CfgWeapons//arifle_MX_F/ ").to_string(), "CfgWeapons/arifle_MX_F");
+    }
+
+    #[test]
+    fn test_case_insensitive_comparison() {
+        use std::collections::HashSet;
+
+        let a = ConfigPath::parse("CfgWeapons/RHS_weap_M4A1/magazines[]");
+        let b = ConfigPath::parse("cfgweapons/rhs_weap_m4a1/MAGAZINES[]");
+        assert_eq!(a, b);
+        assert_ne!(a, ConfigPath::parse("CfgWeapons/rhs_weap_m4a1/magazines"));
+        assert_eq!(HashSet::from([a.clone(), b]).len(), 1);
+
+        assert!(ConfigPath::parse("cfgWeapons").contains(&a));
+        assert!(!a.contains(&ConfigPath::parse("CfgWeapons")));
+    }
+
+    #[test]
+    fn test_wildcard_matching() {
+        let path = ConfigPath::parse("CfgWeapons/rhs_weap_m4a1/magazines[]");
+        assert!(path.matches(&"CfgWeapons/*/magazines[]".into()));
+        assert!(path.matches(&"CfgWeapons/RHS_*/magazines[]".into()));
+        assert!(path.matches(&"**/magazines[]".into()));
+        // Array paths only match array patterns
+        assert!(!path.matches(&"cfg*/**".into()));
+        assert!(!path.matches(&"CfgWeapons/cup_*/magazines[]".into()));
+        assert!(!path.matches(&"CfgWeapons/*".into()));
+
+        let class = ConfigPath::parse("CfgVehicles/B_Soldier_F");
+        assert!(class.matches(&"cfg*/**".into()));
+        assert!(class.matches(&"CfgVehicles/*_F".into()));
+        assert!(class.matches(&"CfgVehicles/B_*_F".into()));
+        assert!(!class.matches(&"CfgVehicles/O_*".into()));
+        assert!(!class.matches(&"CfgVehicles/B_Soldier_F/*".into()));
+
+        assert!(path.ends_with(&"*/magazines".into()));
+        assert!(!path.ends_with(&"CfgVehicles/*".into()));
+    }
+}
@@ -0,0 +1,103 @@
+use encoding_rs::{Encoding, WINDOWS_1252};
+
+/// File content decoded to UTF-8 ahead of preprocessing
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedContent {
+    /// UTF-8 content safe to hand to the preprocessor
+    pub content: String,
+    /// Description of what had to be transcoded, if the input was not clean UTF-8
+    pub warning: Option<String>,
+}
+
+/// Decode raw file bytes to UTF-8 without failing on bad sequences.
+///
+/// Files with a UTF-16 or UTF-8 byte order mark are decoded accordingly. Mission
+/// files edited with different tools often mix UTF-8 with Windows-1252 text, so
+/// valid UTF-8 runs are kept as-is and every other byte is decoded as
+/// Windows-1252 rather than rejecting the whole file.
+pub fn decode_content(bytes: &[u8]) -> DecodedContent {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        if encoding != encoding_rs::UTF_8 {
+            let (content, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+            let warning = if had_errors {
+                format!("Decoded as {} with invalid sequences replaced", encoding.name())
+            } else {
+                format!("Transcoded from {}", encoding.name())
+            };
+            return DecodedContent { content: content.into_owned(), warning: Some(warning) };
+        }
+        return decode_mixed(&bytes[bom_length..]);
+    }
+    decode_mixed(bytes)
+}
+
+/// Keep valid UTF-8 and decode invalid bytes as Windows-1252
+fn decode_mixed(mut bytes: &[u8]) -> DecodedContent {
+    let mut content = String::with_capacity(bytes.len());
+    let mut invalid_bytes = 0;
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                content.push_str(valid);
+                break;
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                // Safe to unwrap: `valid_up_to` marks the end of valid UTF-8
+                content.push_str(std::str::from_utf8(valid).unwrap());
+
+                let bad_length = e.error_len().unwrap_or(rest.len());
+                let (bad, rest) = rest.split_at(bad_length);
+                let (decoded, _) = WINDOWS_1252.decode_without_bom_handling(bad);
+                content.push_str(&decoded);
+                invalid_bytes += bad.len();
+                bytes = rest;
+            }
+        }
+    }
+
+    let warning = (invalid_bytes > 0)
+        .then(|| format!("Transcoded {} invalid UTF-8 bytes as Windows-1252", invalid_bytes));
+    DecodedContent { content, warning }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_utf8_is_untouched() {
+        let decoded = decode_content("class Test { name = \"Café\"; };".as_bytes());
+        assert_eq!(decoded.content, "class Test { name = \"Café\"; };");
+        assert!(decoded.warning.is_none());
+    }
+
+    #[test]
+    fn test_mixed_encodings() {
+        // "Café" in UTF-8 followed by "Café" in Windows-1252
+        let mut bytes = "Café ".as_bytes().to_vec();
+        bytes.extend_from_slice(b"Caf\xe9 \x93quoted\x94");
+        let decoded = decode_content(&bytes);
+        assert_eq!(decoded.content, "Café Café \u{201c}quoted\u{201d}");
+        assert!(decoded.warning.unwrap().contains("3 invalid"));
+    }
+
+    #[test]
+    fn test_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "class A {};".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        let decoded = decode_content(&bytes);
+        assert_eq!(decoded.content, "class A {};");
+        assert!(decoded.warning.is_some());
+    }
+
+    #[test]
+    fn test_utf8_bom_is_stripped() {
+        let decoded = decode_content(b"\xef\xbb\xbfclass A {};");
+        assert_eq!(decoded.content, "class A {};");
+        assert!(decoded.warning.is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! `#include` resolution
+//!
+//! The preprocessor rejects a whole file when one of its includes is missing,
+//! which for missions usually means an include of a framework or mod file
+//! that isn't on disk. Includes are checked before preprocessing instead:
+//! those that don't resolve are blanked out, keeping line numbers, and
+//! reported as [`IncludeWarning`]s so the rest of the file is still read.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+/// An `#include` that couldn't be resolved and was left out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncludeWarning {
+    /// Path as written in the directive
+    pub include: String,
+    /// Line of the directive, starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for IncludeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unresolved include \"{}\" on line {}", self.include, self.line)
+    }
+}
+
+/// Path named by an `#include` directive
+pub(crate) fn include_path(directive: &str) -> Option<&str> {
+    let rest = directive.strip_prefix("#include")?.trim();
+    let (open, close) = match rest.chars().next()? {
+        '"' => ('"', '"'),
+        '<' => ('<', '>'),
+        _ => return None,
+    };
+    let rest = rest.strip_prefix(open)?;
+    rest.find(close).map(|end| &rest[..end])
+}
+
+/// Check if an include of a file resolves in a workspace made of layered directories.
+///
+/// `file_dir` is the directory of the including file relative to the
+/// workspace root. A leading separator starts the include at the root.
+pub(crate) fn resolves_in(layers: &[&Path], file_dir: &Path, include: &str) -> bool {
+    let include = include.replace('\\', "/");
+    let relative = include.trim_start_matches('/');
+    let from_root = relative.len() != include.len();
+    layers.iter().any(|layer| {
+        let dir = if from_root { layer.to_path_buf() } else { layer.join(file_dir) };
+        dir.join(relative).is_file()
+    })
+}
+
+/// Blank out the includes of content that don't resolve, returning a warning for each
+pub(crate) fn remove_unresolved_includes(content: &str, resolves: impl Fn(&str) -> bool) -> (String, Vec<IncludeWarning>) {
+    let mut warnings = Vec::new();
+    let lines: Vec<&str> = content.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let Some(include) = include_path(line.trim()) else {
+                return line;
+            };
+            if resolves(include) {
+                return line;
+            }
+            warnings.push(IncludeWarning { include: include.to_string(), line: index + 1 });
+            ""
+        })
+        .collect();
+    (lines.join("\n"), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_remove_unresolved_includes() {
+        let content = "#include \"gear.hpp\"\n  #include <missing.hpp>\nclass Test {};\n";
+        let (content, warnings) = remove_unresolved_includes(content, |include| include == "gear.hpp");
+
+        assert_eq!(content, "#include \"gear.hpp\"\n\nclass Test {};\n");
+        assert_eq!(warnings, [IncludeWarning { include: "missing.hpp".to_string(), line: 2 }]);
+        assert_eq!(warnings[0].to_string(), "Unresolved include \"missing.hpp\" on line 2");
+    }
+
+    #[test]
+    fn test_resolves_in_layers() {
+        let mission = tempfile::tempdir().unwrap();
+        let mods = tempfile::tempdir().unwrap();
+        fs::create_dir_all(mission.path().join("loadouts")).unwrap();
+        fs::write(mission.path().join("common.hpp"), "").unwrap();
+        fs::create_dir_all(mods.path().join("x/cba")).unwrap();
+        fs::write(mods.path().join("x/cba/macros.hpp"), "").unwrap();
+
+        let layers = [mission.path(), mods.path()];
+        let dir = Path::new("loadouts");
+        assert!(resolves_in(&layers, dir, "..\\common.hpp"));
+        assert!(resolves_in(&layers, dir, "\\x\\cba\\macros.hpp"));
+        assert!(!resolves_in(&layers, dir, "common.hpp"));
+        assert!(!resolves_in(&layers, dir, "\\x\\ace\\macros.hpp"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Engine semantics for property overrides and class inheritance
+//!
+//! A property set again replaces the earlier value, except for arrays declared
+//! with `+=`, which append to the array they would otherwise replace.
+
+use std::collections::HashSet;
+use crate::{HppClass, HppProperty, HppValue};
+
+/// Apply a property on top of existing properties the way the engine does
+pub(crate) fn apply_property(properties: &mut Vec<HppProperty>, property: HppProperty) {
+    let Some(existing) = properties.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&property.name)) else {
+        properties.push(property);
+        return;
+    };
+
+    match (&mut existing.value, property.value) {
+        (HppValue::Array(items), HppValue::Array(appended)) if property.append => {
+            items.extend(appended);
+        }
+        (value, replacement) => {
+            *value = replacement;
+            existing.append = property.append;
+        }
+    }
+}
+
+/// A class with its inheritance chain applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedClass {
+    /// Name of the class
+    pub name: String,
+    /// Ancestors from the direct parent up to the root
+    pub ancestors: Vec<String>,
+    /// Parent at the top of the chain that isn't among the given classes
+    pub missing_parent: Option<String>,
+    /// Properties after inheritance, overrides and `+=` appends
+    pub properties: Vec<HppProperty>,
+}
+
+impl ResolvedClass {
+    /// Value of a property, ignoring case
+    pub fn property(&self, name: &str) -> Option<&HppValue> {
+        self.properties.iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .map(|p| &p.value)
+    }
+
+    /// String items of an array property, including those of nested arrays,
+    /// empty if it isn't set or isn't an array
+    pub fn array(&self, name: &str) -> Vec<&str> {
+        match self.property(name) {
+            Some(value @ HppValue::Array(_)) => value.strings(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Resolve a class and its ancestors into the properties the engine would see.
+///
+/// Class names are matched case-insensitively. Inherited properties are applied
+/// from the root ancestor down, so overrides win and `+=` arrays extend the
+/// inherited array. Missing parents and inheritance cycles end the chain.
+pub fn flatten_class(name: &str, classes: &[HppClass]) -> Option<HppClass> {
+    let class = classes.iter().find(|c| c.name.eq_ignore_ascii_case(name))?;
+    let resolved = resolve_class(class, classes);
+    Some(HppClass {
+        name: class.name.clone(),
+        parent: class.parent.clone(),
+        properties: resolved.properties,
+    })
+}
+
+/// Resolve the inheritance of every class, in the order given.
+///
+/// Answers "what does `rm_ftl` actually end up with" without walking
+/// `baseMan` → `rm` → `rm_ftl` by hand. See [`flatten_class`] for the rules.
+pub fn resolve_inheritance(classes: &[HppClass]) -> Vec<ResolvedClass> {
+    classes.iter().map(|class| resolve_class(class, classes)).collect()
+}
+
+/// Resolve the inheritance of a single class
+fn resolve_class(class: &HppClass, classes: &[HppClass]) -> ResolvedClass {
+    let find = |name: &str| classes.iter().find(|c| c.name.eq_ignore_ascii_case(name));
+
+    // Collect the chain from the class up to its root ancestor
+    let mut chain = vec![class];
+    let mut seen = HashSet::from([class.name.to_lowercase()]);
+    let mut missing_parent = None;
+    while let Some(parent_name) = chain.last().and_then(|c| c.parent.as_deref()) {
+        let Some(parent) = find(parent_name) else {
+            missing_parent = Some(parent_name.to_string());
+            break;
+        };
+        if !seen.insert(parent.name.to_lowercase()) {
+            break;
+        }
+        chain.push(parent);
+    }
+
+    let mut properties = Vec::new();
+    for ancestor in chain.iter().rev() {
+        for property in &ancestor.properties {
+            apply_property(&mut properties, property.clone());
+        }
+    }
+
+    // Nothing is left to append to once inheritance is resolved
+    for property in &mut properties {
+        property.append = false;
+    }
+
+    ResolvedClass {
+        name: class.name.clone(),
+        ancestors: chain[1..].iter().map(|c| c.name.clone()).collect(),
+        missing_parent,
+        properties,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn array(name: &str, items: &[&str], append: bool) -> HppProperty {
+        HppProperty {
+            name: name.to_string(),
+            value: HppValue::Array(items.iter().map(|s| HppValue::from(*s)).collect()),
+            append,
+        }
+    }
+
+    fn class(name: &str, parent: Option<&str>, properties: Vec<HppProperty>) -> HppClass {
+        HppClass {
+            name: name.to_string(),
+            parent: parent.map(str::to_string),
+            properties,
+        }
+    }
+
+    fn items<'a>(class: &'a HppClass, name: &str) -> Vec<&'a str> {
+        match &class.properties.iter().find(|p| p.name.eq_ignore_ascii_case(name)).unwrap().value {
+            value @ HppValue::Array(_) => value.strings(),
+            other => panic!("Expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_override_replaces_inherited_value() {
+        let classes = vec![
+            class("baseMan", None, vec![array("items", &["ItemMap"], false)]),
+            class("rifleman", Some("baseMan"), vec![array("ITEMS", &["ItemCompass"], false)]),
+        ];
+
+        let flattened = flatten_class("Rifleman", &classes).unwrap();
+        assert_eq!(items(&flattened, "items"), ["ItemCompass"]);
+        assert_eq!(flattened.properties.len(), 1);
+    }
+
+    #[test]
+    fn test_append_extends_inherited_array() {
+        let classes = vec![
+            class("baseMan", None, vec![array("items", &["ItemMap"], false)]),
+            class("rifleman", Some("baseMan"), vec![array("items", &["ItemCompass"], true)]),
+            class("medic", Some("rifleman"), vec![array("items", &["ACE_morphine"], true)]),
+        ];
+
+        let flattened = flatten_class("medic", &classes).unwrap();
+        assert_eq!(items(&flattened, "items"), ["ItemMap", "ItemCompass", "ACE_morphine"]);
+        assert!(flattened.properties.iter().all(|p| !p.append));
+    }
+
+    #[test]
+    fn test_append_without_inherited_array() {
+        let classes = vec![class("standalone", None, vec![array("items", &["ItemWatch"], true)])];
+        let flattened = flatten_class("standalone", &classes).unwrap();
+        assert_eq!(items(&flattened, "items"), ["ItemWatch"]);
+    }
+
+    #[test]
+    fn test_inheritance_cycle_terminates() {
+        let classes = vec![
+            class("a", Some("b"), vec![array("items", &["from_a"], true)]),
+            class("b", Some("a"), vec![array("items", &["from_b"], false)]),
+        ];
+        let flattened = flatten_class("a", &classes).unwrap();
+        assert_eq!(items(&flattened, "items"), ["from_b", "from_a"]);
+        assert!(flatten_class("missing", &classes).is_none());
+    }
+
+    #[test]
+    fn test_resolve_inheritance() {
+        let classes = vec![
+            class("baseMan", None, vec![array("items", &["ItemMap"], false)]),
+            class("rm", Some("baseMan"), vec![array("magazines", &["30Rnd_556x45_Stanag"], false)]),
+            class("rm_ftl", Some("rm"), vec![array("items", &["ItemGPS"], true)]),
+            class("orphan", Some("notDefined"), vec![]),
+        ];
+
+        let resolved = resolve_inheritance(&classes);
+        assert_eq!(resolved.len(), 4);
+
+        let ftl = &resolved[2];
+        assert_eq!(ftl.ancestors, ["rm", "baseMan"]);
+        assert_eq!(ftl.array("ITEMS"), ["ItemMap", "ItemGPS"]);
+        assert_eq!(ftl.array("magazines"), ["30Rnd_556x45_Stanag"]);
+        assert!(ftl.array("backpackItems").is_empty());
+        assert_eq!(ftl.missing_parent, None);
+
+        assert_eq!(resolved[3].missing_parent.as_deref(), Some("notDefined"));
+    }
+
+    #[test]
+    fn test_merging_definitions_in_same_scope() {
+        let mut properties = vec![array("items", &["ItemMap"], false)];
+        apply_property(&mut properties, array("items", &["ItemGPS"], true));
+        apply_property(&mut properties, HppProperty {
+            name: "displayName".to_string(),
+            value: HppValue::String("Rifleman".to_string()),
+            append: false,
+        });
+        apply_property(&mut properties, HppProperty {
+            name: "displayname".to_string(),
+            value: HppValue::String("Grenadier".to_string()),
+            append: false,
+        });
+
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties[0].value, HppValue::Array(vec!["ItemMap".into(), "ItemGPS".into()]));
+        assert_eq!(properties[1].value, HppValue::String("Grenadier".to_string()));
+    }
+}
@@ -1,14 +1,33 @@
 use std::sync::{Arc, Mutex};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use hemtt_config::{Config, parse, Property, Class, Value, Array, Item};
 use hemtt_preprocessor::Processor;
 use hemtt_workspace::{reporting::{Codes, Processed, Code, Diagnostic, Severity}, LayerType, Workspace, WorkspacePath};
+use log::warn;
 use serde::{Serialize, Deserialize};
-use tempfile::NamedTempFile;
 
+pub mod configpath;
+mod encoding;
+mod include;
+mod inheritance;
+mod lints;
+mod macros;
+mod medical;
 mod parser;
 mod query;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub use configpath::ConfigPath;
+pub use encoding::{decode_content, DecodedContent};
+pub use include::IncludeWarning;
+use include::{remove_unresolved_includes, resolves_in};
+pub use inheritance::{flatten_class, resolve_inheritance, ResolvedClass};
+use inheritance::apply_property;
+pub use lints::{lint_categories, lint_overridden_appends, LoadoutLint, LoadoutLintKind};
+pub use macros::MacroTable;
+pub use medical::MedicalItemProperties;
 pub use parser::*;
 pub use query::DependencyExtractor;
 
@@ -23,18 +42,68 @@ pub struct HppClass {
 pub struct HppProperty {
     pub name: String,
     pub value: HppValue,
+    /// Whether the property was declared with `+=`, appending to the inherited array
+    #[serde(default)]
+    pub append: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum HppValue {
     String(String),
-    Array(Vec<String>),
+    /// Array items, with nested arrays kept as arrays
+    Array(Vec<HppValue>),
     Number(i64),
+    Float(f64),
+    /// `true` or `false`, which configs write as bare words
+    Bool(bool),
     Class(HppClass),
 }
 
+impl HppValue {
+    /// Get the value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            HppValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a number, if it is an integer or float
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            HppValue::Number(n) => Some(*n as f64),
+            HppValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Strings of the value, with those of nested arrays flattened in order
+    pub fn strings(&self) -> Vec<&str> {
+        match self {
+            HppValue::String(s) => vec![s],
+            HppValue::Array(items) => items.iter().flat_map(HppValue::strings).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl From<&str> for HppValue {
+    fn from(value: &str) -> Self {
+        HppValue::String(value.to_string())
+    }
+}
+
+/// Options controlling how config values are converted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Repeat the item of a `LIST_N(item)` macro N times instead of adding it once
+    pub expand_list_macros: bool,
+}
+
 pub struct HppParser {
     config: Config,
+    options: ParseOptions,
+    warnings: Vec<IncludeWarning>,
 }
 
 /// Parse an HPP file and return a vector of classes.
@@ -47,27 +116,175 @@ pub struct HppParser {
 /// 
 /// * `Result<Vec<HppClass>, Codes>` - List of classes found in the file or error
 pub fn parse_file(file_path: &std::path::Path) -> Result<Vec<HppClass>, Codes> {
-    let content = std::fs::read_to_string(file_path)
+    let bytes = std::fs::read(file_path)
         .map_err(|_| vec![])?;
-    
-    let parser = HppParser::new(&content)?;
+
+    // Invalid UTF-8 would make the preprocessor reject the whole file
+    let decoded = decode_content(&bytes);
+    if let Some(warning) = &decoded.warning {
+        warn!("{}: {}", file_path.display(), warning);
+    }
+
+    let parser = HppParser::new(&decoded.content)?;
     Ok(parser.parse_classes())
 }
 
+/// Directories of a mission's workspace: the mission itself, then the include roots of `macros`
+fn workspace_layers<'a>(root: &'a Path, macros: &'a MacroTable) -> Vec<&'a Path> {
+    std::iter::once(root)
+        .chain(macros.include_roots().iter().map(PathBuf::as_path))
+        .collect()
+}
+
+/// Workspace of the directories layered in order, with a memory layer on top.
+///
+/// Prepared content goes in the memory layer, the files on disk are never written.
+fn mission_workspace(layers: &[&Path]) -> Result<WorkspacePath, Codes> {
+    let mut builder = Workspace::builder();
+    for layer in layers {
+        builder = builder.physical(&layer.to_path_buf(), LayerType::Source);
+    }
+    builder
+        .memory()
+        .finish(None, false, &hemtt_common::config::PDriveOption::Disallow)
+        .map_err(|e| vec![])
+}
+
+/// Collect the hpp and ext files under a directory
+fn find_config_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            find_config_files(&path, files);
+        } else if path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hpp") || extension.eq_ignore_ascii_case("ext"))
+        {
+            files.push(path);
+        }
+    }
+}
+
 impl HppParser {
     pub fn new(content: &str) -> Result<Self, Codes> {
-        // Create a temporary workspace with the content
-        let temp_file = NamedTempFile::new().map_err(|e| vec![])?;
-        fs::write(temp_file.path(), content).map_err(|e| vec![])?;
-        
-        let parent_path = PathBuf::from(temp_file.path().parent().unwrap());
+        Self::new_with_options(content, ParseOptions::default())
+    }
+
+    /// Parse config content with explicit value conversion options.
+    ///
+    /// The content isn't in a workspace, so its `#include`s are left out and
+    /// reported by [`HppParser::warnings`].
+    pub fn new_with_options(content: &str, options: ParseOptions) -> Result<Self, Codes> {
+        Self::parse_str_with_options(content, "config.hpp", options)
+    }
+
+    /// Parse config content held in memory, such as an upload, without touching disk.
+    ///
+    /// `virtual_name` names the content in the memory workspace and its
+    /// diagnostics, e.g. `description.ext`. As with [`HppParser::new`], the
+    /// content's `#include`s are left out and reported by [`HppParser::warnings`].
+    pub fn parse_str(content: &str, virtual_name: &str) -> Result<Self, Codes> {
+        Self::parse_str_with_options(content, virtual_name, ParseOptions::default())
+    }
+
+    /// Parse config content held in memory with explicit value conversion options
+    pub fn parse_str_with_options(content: &str, virtual_name: &str, options: ParseOptions) -> Result<Self, Codes> {
+        let (content, warnings) = remove_unresolved_includes(content, |_| false);
+
         let workspace = Workspace::builder()
-            .physical(&parent_path, LayerType::Source)
+            .memory()
             .finish(None, false, &hemtt_common::config::PDriveOption::Disallow)
             .map_err(|e| vec![])?;
-            
-        let path = workspace.join(temp_file.path().file_name().unwrap().to_str().unwrap()).map_err(|e| vec![])?;
-        let processed = match Processor::run(&path) {
+        let path = workspace.join(virtual_name).map_err(|e| vec![])?;
+        path.create_file().map_err(|e| vec![])?
+            .write_all(content.as_bytes()).map_err(|e| vec![])?;
+        Self::process(&path, options, warnings)
+    }
+
+    /// Parse a file of a mission with the mission directory as the workspace, so its
+    /// `#include`s resolve.
+    ///
+    /// The include roots of `macros` are layered below the mission directory,
+    /// for includes of framework or mod files. Includes that resolve nowhere
+    /// are left out and reported by [`HppParser::warnings`].
+    pub fn new_in_workspace(root: &Path, file: &Path, options: ParseOptions, macros: &MacroTable) -> Result<Self, Codes> {
+        let layers = workspace_layers(root, macros);
+        let workspace = mission_workspace(&layers)?;
+        Self::process_in_workspace(&workspace, &layers, root, file, options, macros)
+    }
+
+    /// Parse every hpp and ext file under a directory, sharing one workspace between them.
+    ///
+    /// Setting up a workspace for each file dominates the time taken on large
+    /// missions, so the directory is layered once and each file is prepared in
+    /// its memory layer. `#include`s resolve against the directory as with
+    /// [`HppParser::new_in_workspace`]. Files that fail to parse are logged
+    /// and left out. Files are returned sorted by path.
+    pub fn parse_dir(root: &Path) -> Vec<(PathBuf, Vec<HppClass>)> {
+        let macros = MacroTable::new();
+        let layers = workspace_layers(root, &macros);
+        let workspace = match mission_workspace(&layers) {
+            Ok(workspace) => workspace,
+            Err(_) => {
+                warn!("{}: Failed to create workspace", root.display());
+                return Vec::new();
+            }
+        };
+
+        let mut files = Vec::new();
+        find_config_files(root, &mut files);
+        files.sort();
+        files.into_iter()
+            .filter_map(|file| {
+                match Self::process_in_workspace(&workspace, &layers, root, &file, ParseOptions::default(), &macros) {
+                    Ok(parser) => Some((file, parser.parse_classes())),
+                    Err(codes) => {
+                        let messages: Vec<String> = codes.iter().map(|code| code.message()).collect();
+                        warn!("{}: Failed to parse: {}", file.display(), messages.join("; "));
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Prepare a file of a mission in the memory layer of its workspace and parse it
+    fn process_in_workspace(
+        workspace: &WorkspacePath,
+        layers: &[&Path],
+        root: &Path,
+        file: &Path,
+        options: ParseOptions,
+        macros: &MacroTable,
+    ) -> Result<Self, Codes> {
+        let relative = file.strip_prefix(root).map_err(|e| vec![])?;
+        let bytes = fs::read(file).map_err(|e| vec![])?;
+        let decoded = decode_content(&bytes);
+        if let Some(warning) = &decoded.warning {
+            warn!("{}: {}", file.display(), warning);
+        }
+
+        let file_dir = relative.parent().unwrap_or(Path::new(""));
+        let (content, warnings) = remove_unresolved_includes(&decoded.content, |include| {
+            resolves_in(layers, file_dir, include)
+        });
+        let content = format!("{}{}", macros.prelude_for(&content), content);
+
+        let virtual_path = relative.components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = workspace.join(&virtual_path).map_err(|e| vec![])?;
+        path.create_file().map_err(|e| vec![])?
+            .write_all(content.as_bytes()).map_err(|e| vec![])?;
+        Self::process(&path, options, warnings)
+    }
+
+    /// Preprocess and parse a file of a workspace
+    fn process(path: &WorkspacePath, options: ParseOptions, warnings: Vec<IncludeWarning>) -> Result<Self, Codes> {
+        let processed = match Processor::run(path) {
             Ok(processed) => processed,
             Err((_, e)) => {
                 // Create a custom error that implements Code
@@ -86,98 +303,186 @@ impl HppParser {
         
         Ok(Self {
             config: report.into_config(),
+            options,
+            warnings,
         })
     }
 
+    /// Parse config content with the macros of other files of the mission defined.
+    ///
+    /// Macros the content defines itself take precedence over the shared ones.
+    pub fn new_with_macros(content: &str, options: ParseOptions, macros: &MacroTable) -> Result<Self, Codes> {
+        let content = format!("{}{}", macros.prelude_for(content), content);
+        Self::new_with_options(&content, options)
+    }
+
+    /// Includes left out because they couldn't be resolved
+    pub fn warnings(&self) -> &[IncludeWarning] {
+        &self.warnings
+    }
+
     pub fn parse_classes(&self) -> Vec<HppClass> {
         let mut classes = Vec::new();
         self.extract_classes(&self.config, &mut classes);
         classes
     }
 
+    /// Parse the top-level classes, keeping nested classes as [`HppValue::Class`]
+    /// properties of their parent instead of listing them alongside it
+    pub fn parse_class_tree(&self) -> Vec<HppClass> {
+        self.build_tree(&self.config.0)
+    }
+
     fn extract_classes(&self, config: &Config, classes: &mut Vec<HppClass>) {
-        for property in config.0.iter() {
-            if let Property::Class(class) = property {
-                if let Class::Local { name, parent, properties, .. } = class {
-                    let mut hpp_class = HppClass {
-                        name: name.as_str().to_string(),
-                        parent: parent.as_ref().map(|p| p.as_str().to_string()),
-                        properties: Vec::new(),
-                    };
-
-                    // Extract properties from the class
-                    for prop in properties {
-                        if let Property::Entry { name, value, .. } = prop {
-                            hpp_class.properties.push(HppProperty {
-                                name: name.as_str().to_string(),
-                                value: self.convert_value(value),
-                            });
-                        }
-                    }
+        for (name, parent, properties) in merge_definitions(&config.0) {
+            classes.push(HppClass {
+                name,
+                parent,
+                properties: self.convert_entries(&properties),
+            });
 
-                    classes.push(hpp_class);
+            let nested = nested_classes(&properties);
+            if !nested.is_empty() {
+                self.extract_classes(&Config(nested), classes);
+            }
+        }
+    }
 
-                    for prop in properties {
-                        if let Property::Class(_) = prop {
-                            let mut nested_classes = Vec::new();
-                            let nested_config = Config(vec![prop.clone()]);
-                            self.extract_classes(&nested_config, &mut nested_classes);
-                            classes.extend(nested_classes);
-                        }
-                    }
+    fn build_tree(&self, properties: &[Property]) -> Vec<HppClass> {
+        merge_definitions(properties).into_iter()
+            .map(|(name, parent, properties)| {
+                let mut hpp_class = HppClass {
+                    name,
+                    parent,
+                    properties: self.convert_entries(&properties),
+                };
+                for nested in self.build_tree(&nested_classes(&properties)) {
+                    hpp_class.properties.push(HppProperty {
+                        name: nested.name.clone(),
+                        value: HppValue::Class(nested),
+                        append: false,
+                    });
                 }
+                hpp_class
+            })
+            .collect()
+    }
+
+    /// Convert the entries of a class, later definitions overriding earlier ones
+    fn convert_entries(&self, properties: &[&Property]) -> Vec<HppProperty> {
+        let mut converted = Vec::new();
+        for prop in properties {
+            if let Property::Entry { name, value, .. } = prop {
+                apply_property(&mut converted, HppProperty {
+                    name: name.as_str().to_string(),
+                    value: self.convert_value(value),
+                    append: matches!(value, Value::Array(Array { expand: true, .. })),
+                });
             }
         }
+        converted
     }
 
     fn convert_value(&self, value: &Value) -> HppValue {
         match value {
-            Value::Str(s) => HppValue::String(s.value().to_string()),
-            Value::Number(n) => {
-                match n {
-                    hemtt_config::Number::Int32 { value, .. } => HppValue::Number(*value as i64),
-                    hemtt_config::Number::Int64 { value, .. } => HppValue::Number(*value),
-                    hemtt_config::Number::Float32 { value, .. } => HppValue::Number(*value as i64),
-                }
-            }
-            Value::Array(arr) => {
-                let mut values = Vec::new();
-                for item in arr.items.iter() {
-                    match item {
-                        Item::Str(s) => values.push(s.value().to_string()),
-                        Item::Number(n) => values.push(n.to_string()),
-                        Item::Macro(m) => {
-                            let macro_name = m.name.value();
-                            
-                            if macro_name.starts_with("LIST_") {
-                                // Just add the inner item once, don't expand based on count
-                                if let Some(first_arg) = m.args.first() {
-                                    values.push(first_arg.value().to_string());
-                                }
+            Value::Str(s) => convert_string(s.value()),
+            Value::Number(n) => convert_number(n),
+            Value::Array(arr) => HppValue::Array(self.convert_items(&arr.items)),
+            _ => HppValue::String(String::new()), // Default for unhandled types
+        }
+    }
+
+    fn convert_items(&self, items: &[Item]) -> Vec<HppValue> {
+        let mut values = Vec::new();
+        for item in items {
+            match item {
+                Item::Str(s) => values.push(convert_string(s.value())),
+                Item::Number(n) => values.push(convert_number(n)),
+                Item::Array(nested) => values.push(HppValue::Array(self.convert_items(nested))),
+                Item::Macro(m) => {
+                    let macro_name = m.name.value();
+                    
+                    if let Some(count) = macro_name.strip_prefix("LIST_") {
+                        // The item is added once unless real quantities were asked for
+                        if let Some(first_arg) = m.args.first() {
+                            let count = if self.options.expand_list_macros {
+                                count.parse().unwrap_or(1)
                             } else {
-                                // For complex macros with multiple arguments, preserve as a single string
-                                let args_str = m.args.iter()
-                                    .map(|arg| arg.value().to_string())
-                                    .collect::<Vec<_>>()
-                                    .join(", ");
-                                
-                                if !m.args.is_empty() {
-                                    values.push(format!("{}({})", macro_name, args_str));
-                                } else {
-                                    values.push(macro_name.to_string());
-                                }
-                            }
+                                1
+                            };
+                            values.extend(std::iter::repeat_n(HppValue::from(first_arg.value()), count));
+                        }
+                    } else {
+                        // For complex macros with multiple arguments, preserve as a single string
+                        let args_str = m.args.iter()
+                            .map(|arg| arg.value().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        
+                        if !m.args.is_empty() {
+                            values.push(HppValue::String(format!("{}({})", macro_name, args_str)));
+                        } else {
+                            values.push(HppValue::from(macro_name));
                         }
-                        _ => {}
                     }
                 }
-                HppValue::Array(values)
+                _ => {}
             }
-            _ => HppValue::String(String::new()), // Default for unhandled types
         }
+        values
+    }
+}
+
+/// Convert a config string; configs have no boolean type, so `true` and
+/// `false` are read as booleans
+fn convert_string(value: &str) -> HppValue {
+    match value {
+        "true" => HppValue::Bool(true),
+        "false" => HppValue::Bool(false),
+        _ => HppValue::from(value),
+    }
+}
+
+/// Convert a number, keeping floats as floats
+fn convert_number(number: &hemtt_config::Number) -> HppValue {
+    match number {
+        hemtt_config::Number::Int32 { value, .. } => HppValue::Number(*value as i64),
+        hemtt_config::Number::Int64 { value, .. } => HppValue::Number(*value),
+        hemtt_config::Number::Float32 { value, .. } => HppValue::Float(*value as f64),
     }
 }
 
+/// Class definitions of a scope by name, with their properties.
+///
+/// The engine merges repeated definitions of a class in the same scope
+/// (e.g. in description.ext and an included hpp) rather than replacing them.
+fn merge_definitions(properties: &[Property]) -> Vec<(String, Option<String>, Vec<&Property>)> {
+    let mut definitions: Vec<(String, Option<String>, Vec<&Property>)> = Vec::new();
+    for property in properties {
+        if let Property::Class(Class::Local { name, parent, properties, .. }) = property {
+            let parent = parent.as_ref().map(|p| p.as_str().to_string());
+            match definitions.iter_mut().find(|(n, _, _)| n.eq_ignore_ascii_case(name.as_str())) {
+                Some((_, existing_parent, merged)) => {
+                    if parent.is_some() {
+                        *existing_parent = parent;
+                    }
+                    merged.extend(properties.iter());
+                }
+                None => definitions.push((name.as_str().to_string(), parent, properties.iter().collect())),
+            }
+        }
+    }
+    definitions
+}
+
+/// The class properties among a class's properties
+fn nested_classes(properties: &[&Property]) -> Vec<Property> {
+    properties.iter()
+        .filter(|prop| matches!(prop, Property::Class(_)))
+        .map(|prop| (*prop).clone())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,15 +544,215 @@ mod tests {
         let uniform_prop = test_class.properties.iter().find(|p| p.name == "uniform").unwrap();
         if let HppValue::Array(uniforms) = &uniform_prop.value {
             // Check that the array contains items with these strings (possibly with quotes)
-            assert!(uniforms.iter().any(|u| u.contains("usp_g3c_kp_mx_aor2")), 
+            assert!(uniforms.iter().filter_map(HppValue::as_str).any(|u| u.contains("usp_g3c_kp_mx_aor2")), 
                    "Missing 'usp_g3c_kp_mx_aor2'. Found: {:?}", uniforms);
-            assert!(uniforms.iter().any(|u| u.contains("usp_g3c_rs_kp_mx_aor2")), 
+            assert!(uniforms.iter().filter_map(HppValue::as_str).any(|u| u.contains("usp_g3c_rs_kp_mx_aor2")), 
                    "Missing 'usp_g3c_rs_kp_mx_aor2'. Found: {:?}", uniforms);
-            assert!(uniforms.iter().any(|u| u.contains("usp_g3c_rs2_kp_mx_aor2")), 
+            assert!(uniforms.iter().filter_map(HppValue::as_str).any(|u| u.contains("usp_g3c_rs2_kp_mx_aor2")), 
                    "Missing 'usp_g3c_rs2_kp_mx_aor2'. Found: {:?}", uniforms);
             assert_eq!(uniforms.len(), 3); // Should have 3 items because LIST_2 is not expanded
         } else {
             panic!("Expected uniform to be an array");
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_expand_list_macro() {
+        let content = r#"
+            class Test {
+                items[] = {
+                    LIST_3("ACE_fieldDressing"),
+                    "ItemMap"
+                };
+            };
+        "#;
+        let options = ParseOptions { expand_list_macros: true };
+        let classes = HppParser::new_with_options(content, options).unwrap().parse_classes();
+
+        let items = classes[0].properties.iter().find(|p| p.name == "items").unwrap();
+        assert_eq!(items.value, HppValue::Array(vec![
+            "ACE_fieldDressing".into(),
+            "ACE_fieldDressing".into(),
+            "ACE_fieldDressing".into(),
+            "ItemMap".into(),
+        ]));
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let content = r#"
+            class Test {
+                armor = 0.5;
+                hidden = true;
+                scope = 2;
+                positions[] = {{1.5, 2}, {3, "four"}, false};
+            };
+        "#;
+        let classes = HppParser::new(content).unwrap().parse_classes();
+        let value = |name: &str| &classes[0].properties.iter().find(|p| p.name == name).unwrap().value;
+
+        assert_eq!(value("armor"), &HppValue::Float(0.5));
+        assert_eq!(value("armor").as_f64(), Some(0.5));
+        assert_eq!(value("hidden"), &HppValue::Bool(true));
+        assert_eq!(value("scope"), &HppValue::Number(2));
+        assert_eq!(value("positions"), &HppValue::Array(vec![
+            HppValue::Array(vec![HppValue::Float(1.5), HppValue::Number(2)]),
+            HppValue::Array(vec![HppValue::Number(3), "four".into()]),
+            HppValue::Bool(false),
+        ]));
+        assert_eq!(value("positions").strings(), ["four"]);
+    }
+
+    #[test]
+    fn test_shared_macros() {
+        let mut macros = MacroTable::new();
+        macros.collect("#define RIFLE \"rhs_weap_m4a1\"\n#define PISTOL \"rhsusf_weap_m9\"\n");
+
+        let content = r#"
+            #define PISTOL "rhsusf_weap_glock17g4"
+            class Rifleman {
+                weapons[] = {RIFLE, PISTOL};
+            };
+        "#;
+        let classes = HppParser::new_with_macros(content, ParseOptions::default(), &macros)
+            .unwrap()
+            .parse_classes();
+
+        assert_eq!(classes[0].properties[0].value, HppValue::Array(vec![
+            "rhs_weap_m4a1".into(),
+            "rhsusf_weap_glock17g4".into(),
+        ]));
+    }
+
+    #[test]
+    fn test_includes_resolve_in_mission_workspace() {
+        let mission = tempfile::tempdir().unwrap();
+        fs::create_dir_all(mission.path().join("loadouts")).unwrap();
+        fs::write(mission.path().join("common.hpp"), "#define RIFLE \"rhs_weap_m4a1\"\nclass Base { uniform = \"rhs_uniform_g3_m81\"; };\n").unwrap();
+        let file = mission.path().join("loadouts").join("rifleman.hpp");
+        fs::write(&file, "#include \"..\\common.hpp\"\n#include \"\\x\\tmf\\macros.hpp\"\nclass Rifleman : Base { weapons[] = {RIFLE}; };\n").unwrap();
+
+        let parser = HppParser::new_in_workspace(mission.path(), &file, ParseOptions::default(), &MacroTable::new()).unwrap();
+        let classes = parser.parse_classes();
+
+        assert!(classes.iter().any(|c| c.name == "Base"));
+        let rifleman = classes.iter().find(|c| c.name == "Rifleman").unwrap();
+        assert_eq!(rifleman.properties[0].value, HppValue::Array(vec!["rhs_weap_m4a1".into()]));
+        assert_eq!(parser.warnings(), [IncludeWarning { include: "\\x\\tmf\\macros.hpp".to_string(), line: 2 }]);
+    }
+
+    #[test]
+    fn test_parse_dir() {
+        let mission = tempfile::tempdir().unwrap();
+        fs::create_dir_all(mission.path().join("loadouts")).unwrap();
+        fs::write(mission.path().join("description.ext"), "#include \"loadouts\\west.hpp\"\nclass CfgRoles {};\n").unwrap();
+        fs::write(mission.path().join("loadouts").join("west.hpp"), "class Rifleman { uniform = \"U_B_CombatUniform_mcam\"; };\n").unwrap();
+        fs::write(mission.path().join("loadouts").join("broken.hpp"), "class Broken {\n").unwrap();
+        fs::write(mission.path().join("init.sqf"), "player addWeapon \"arifle_MX_F\";\n").unwrap();
+
+        let parsed = HppParser::parse_dir(mission.path());
+        let files: Vec<&Path> = parsed.iter().map(|(file, _)| file.as_path()).collect();
+        assert_eq!(files, [mission.path().join("description.ext"), mission.path().join("loadouts").join("west.hpp")]);
+        assert!(parsed[0].1.iter().any(|c| c.name == "Rifleman"));
+        assert!(parsed[0].1.iter().any(|c| c.name == "CfgRoles"));
+        assert_eq!(parsed[1].1.len(), 1);
+    }
+
+    #[test]
+    fn test_includes_without_workspace_are_warnings() {
+        let parser = HppParser::new("#include \"gear.hpp\"\nclass Test {};\n").unwrap();
+        assert_eq!(parser.parse_classes().len(), 1);
+        assert_eq!(parser.warnings(), [IncludeWarning { include: "gear.hpp".to_string(), line: 1 }]);
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let parser = HppParser::parse_str("class Rifleman { uniform = \"U_B_CombatUniform_mcam\"; };\n", "description.ext").unwrap();
+        let classes = parser.parse_classes();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].properties[0].value, HppValue::String("U_B_CombatUniform_mcam".to_string()));
+    }
+
+    #[test]
+    fn test_parse_class_tree() {
+        let content = r#"
+            class CfgRespawnInventory {
+                class WEST1 {
+                    weapons[] = {"arifle_MX_F"};
+                };
+            };
+            class CfgRespawnInventory {
+                class WEST2 : WEST1 {
+                    uniformClass = "U_B_CombatUniform_mcam";
+                };
+            };
+        "#;
+        let classes = HppParser::new(content).unwrap().parse_class_tree();
+
+        assert_eq!(classes.len(), 1);
+        let nested: Vec<&HppClass> = classes[0].properties.iter()
+            .filter_map(|p| match &p.value {
+                HppValue::Class(class) => Some(class),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(nested.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["WEST1", "WEST2"]);
+        assert_eq!(nested[1].parent.as_deref(), Some("WEST1"));
+        assert_eq!(nested[0].properties[0].value, HppValue::Array(vec!["arifle_MX_F".into()]));
+    }
+
+    #[test]
+    fn test_repeated_class_definitions_merge() {
+        // As when description.ext and an included hpp both define the class
+        let content = r#"
+            class Rifleman {
+                displayName = "Rifleman";
+                items[] = {"ItemMap"};
+                class primaryWeapon {
+                    name = "rhs_weap_m4a1";
+                };
+            };
+            class Rifleman {
+                displayName = "Rifleman (Desert)";
+                items[] += {"ItemCompass"};
+                class primaryWeapon {
+                    optic = "rhsusf_acc_acog";
+                };
+            };
+        "#;
+        let classes = HppParser::new(content).unwrap().parse_classes();
+
+        let riflemen: Vec<_> = classes.iter().filter(|c| c.name == "Rifleman").collect();
+        assert_eq!(riflemen.len(), 1, "Definitions should be merged: {:?}", classes);
+
+        let rifleman = riflemen[0];
+        let display_name = rifleman.properties.iter().find(|p| p.name == "displayName").unwrap();
+        assert_eq!(display_name.value, HppValue::String("Rifleman (Desert)".to_string()));
+        let items = rifleman.properties.iter().find(|p| p.name == "items").unwrap();
+        assert_eq!(items.value, HppValue::Array(vec!["ItemMap".into(), "ItemCompass".into()]));
+
+        let weapon = classes.iter().find(|c| c.name == "primaryWeapon").unwrap();
+        assert_eq!(weapon.properties.len(), 2);
+        assert_eq!(classes.iter().filter(|c| c.name == "primaryWeapon").count(), 1);
+    }
+
+    #[test]
+    fn test_append_is_recorded() {
+        let content = r#"
+            class Base {
+                items[] = {"ItemMap"};
+            };
+            class Medic : Base {
+                items[] += {"ACE_morphine"};
+            };
+        "#;
+        let classes = HppParser::new(content).unwrap().parse_classes();
+
+        let medic = classes.iter().find(|c| c.name == "Medic").unwrap();
+        assert!(medic.properties[0].append);
+
+        let flattened = flatten_class("medic", &classes).unwrap();
+        assert_eq!(flattened.properties[0].value,
+            HppValue::Array(vec!["ItemMap".into(), "ACE_morphine".into()]));
+    }
+}
@@ -0,0 +1,242 @@
+//! Lints for loadout configs
+//!
+//! Loadouts that parse fine can still hand out other gear than intended: a
+//! class replacing an array its parent appends to loses the appended items,
+//! an item written both as `LIST_N(item)` and on its own is usually counted
+//! twice by mistake, and a class in the wrong array, such as a vest in
+//! `uniform[]`, is silently not equipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use hemtt_config::{Item, Property, Value};
+use mission_scanner_types::ItemKind;
+use serde::{Serialize, Deserialize};
+
+use crate::{merge_definitions, nested_classes, HppClass, HppParser, HppValue};
+
+/// Loadout arrays and the kind of item they hold
+const CATEGORY_ARRAYS: [(&str, ItemKind); 10] = [
+    ("uniform", ItemKind::Uniform),
+    ("vest", ItemKind::Vest),
+    ("backpack", ItemKind::Backpack),
+    ("headgear", ItemKind::Headgear),
+    ("goggles", ItemKind::Goggles),
+    ("primaryweapon", ItemKind::Weapon),
+    ("secondaryweapon", ItemKind::Weapon),
+    ("sidearmweapon", ItemKind::Weapon),
+    ("handgunweapon", ItemKind::Weapon),
+    ("magazines", ItemKind::Magazine),
+];
+
+/// What is wrong with a loadout property
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LoadoutLintKind {
+    /// The array is set with `=` while the parent appends to it with `+=`
+    OverriddenAppend { parent: String },
+    /// The item is in the array both as `LIST_N(item)` and on its own
+    ListedTwice { item: String },
+    /// The item is of another kind than the array holds
+    WrongCategory { item: String, expected: ItemKind, found: ItemKind },
+}
+
+/// Problem found in a property of a loadout class
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoadoutLint {
+    /// Loadout class the property is in
+    pub class_name: String,
+    /// Name of the property
+    pub property: String,
+    pub kind: LoadoutLintKind,
+}
+
+impl fmt::Display for LoadoutLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}: ", self.class_name, self.property)?;
+        match &self.kind {
+            LoadoutLintKind::OverriddenAppend { parent } => {
+                write!(f, "replaces the array {} appends to, dropping the appended items", parent)
+            }
+            LoadoutLintKind::ListedTwice { item } => {
+                write!(f, "{} is listed both with LIST_N and on its own", item)
+            }
+            LoadoutLintKind::WrongCategory { item, expected, found } => {
+                write!(f, "{} is a {}, not a {}", item, found, expected)
+            }
+        }
+    }
+}
+
+/// Find arrays classes set with `=` that their parent appends to with `+=`.
+///
+/// Classes are looked up by name ignoring case among `classes`, as from
+/// [`HppParser::parse_classes`].
+pub fn lint_overridden_appends(classes: &[HppClass]) -> Vec<LoadoutLint> {
+    let mut lints = Vec::new();
+    for class in classes {
+        let Some(parent) = class.parent.as_deref()
+            .and_then(|parent| classes.iter().find(|c| c.name.eq_ignore_ascii_case(parent)))
+        else {
+            continue;
+        };
+        for property in class.properties.iter().filter(|p| !p.append && matches!(p.value, HppValue::Array(_))) {
+            let appended = parent.properties.iter()
+                .any(|p| p.append && p.name.eq_ignore_ascii_case(&property.name));
+            if appended {
+                lints.push(LoadoutLint {
+                    class_name: class.name.clone(),
+                    property: property.name.clone(),
+                    kind: LoadoutLintKind::OverriddenAppend { parent: parent.name.clone() },
+                });
+            }
+        }
+    }
+    lints
+}
+
+/// Find items of loadout arrays that are of another kind than the array holds.
+///
+/// `category_of` gives the kind of a class, or `None` when it isn't known, in
+/// which case the item isn't checked.
+pub fn lint_categories(classes: &[HppClass], category_of: impl Fn(&str) -> Option<ItemKind>) -> Vec<LoadoutLint> {
+    let mut lints = Vec::new();
+    for class in classes {
+        for property in &class.properties {
+            let name = property.name.to_lowercase();
+            let Some((_, expected)) = CATEGORY_ARRAYS.iter().find(|(array, _)| *array == name) else {
+                continue;
+            };
+            for item in property.value.strings() {
+                if let Some(found) = category_of(item).filter(|found| found != expected) {
+                    lints.push(LoadoutLint {
+                        class_name: class.name.clone(),
+                        property: property.name.clone(),
+                        kind: LoadoutLintKind::WrongCategory { item: item.to_string(), expected: *expected, found },
+                    });
+                }
+            }
+        }
+    }
+    lints
+}
+
+impl HppParser {
+    /// Find items written both as `LIST_N(item)` and on their own in the same array.
+    ///
+    /// Read from the config before `LIST_N` macros are converted, since the
+    /// converted values no longer tell how an item was written.
+    pub fn lint_list_macros(&self) -> Vec<LoadoutLint> {
+        let mut lints = Vec::new();
+        list_macro_lints(&self.config.0, &mut lints);
+        lints
+    }
+}
+
+fn list_macro_lints(properties: &[Property], lints: &mut Vec<LoadoutLint>) {
+    for (class_name, _, properties) in merge_definitions(properties) {
+        for property in &properties {
+            let Property::Entry { name, value: Value::Array(array), .. } = property else {
+                continue;
+            };
+            let mut listed = BTreeMap::new();
+            let mut single = BTreeSet::new();
+            collect_items(&array.items, &mut listed, &mut single);
+            lints.extend(listed.into_iter()
+                .filter(|(key, _)| single.contains(key))
+                .map(|(_, item)| LoadoutLint {
+                    class_name: class_name.clone(),
+                    property: name.as_str().to_string(),
+                    kind: LoadoutLintKind::ListedTwice { item },
+                }));
+        }
+        list_macro_lints(&nested_classes(&properties), lints);
+    }
+}
+
+/// Sort the items of an array by how they're written, keyed by lowercase name
+fn collect_items(items: &[Item], listed: &mut BTreeMap<String, String>, single: &mut BTreeSet<String>) {
+    for item in items {
+        match item {
+            Item::Str(s) => {
+                single.insert(s.value().to_lowercase());
+            }
+            Item::Macro(m) if m.name.value().starts_with("LIST_") => {
+                if let Some(arg) = m.args.first() {
+                    let item = arg.value().trim_matches('"').to_string();
+                    listed.entry(item.to_lowercase()).or_insert(item);
+                }
+            }
+            Item::Array(nested) => collect_items(nested, listed, single),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HppProperty;
+
+    fn class(name: &str, parent: Option<&str>, properties: Vec<HppProperty>) -> HppClass {
+        HppClass { name: name.to_string(), parent: parent.map(str::to_string), properties }
+    }
+
+    fn array(name: &str, items: &[&str], append: bool) -> HppProperty {
+        HppProperty {
+            name: name.to_string(),
+            value: HppValue::Array(items.iter().map(|item| HppValue::from(*item)).collect()),
+            append,
+        }
+    }
+
+    #[test]
+    fn test_lint_overridden_appends() {
+        let classes = vec![
+            class("baseMan", None, vec![array("items", &["ItemMap"], false)]),
+            class("rm", Some("baseMan"), vec![array("items", &["ACE_fieldDressing"], true)]),
+            class("rm_ftl", Some("RM"), vec![array("items", &["ItemGPS"], false)]),
+            class("rm_lat", Some("rm"), vec![array("items", &["ACE_morphine"], true)]),
+        ];
+
+        let lints = lint_overridden_appends(&classes);
+        assert_eq!(lints, [LoadoutLint {
+            class_name: "rm_ftl".to_string(),
+            property: "items".to_string(),
+            kind: LoadoutLintKind::OverriddenAppend { parent: "rm".to_string() },
+        }]);
+        assert_eq!(lints[0].to_string(), "rm_ftl.items: replaces the array rm appends to, dropping the appended items");
+    }
+
+    #[test]
+    fn test_lint_categories() {
+        let classes = vec![class("rm", None, vec![
+            array("uniform", &["U_B_CombatUniform_mcam", "V_PlateCarrier1_rgr"], false),
+            array("vest", &["V_PlateCarrier1_rgr"], false),
+            array("items", &["V_PlateCarrier1_rgr"], false),
+        ])];
+        let category_of = |class_name: &str| match class_name {
+            "U_B_CombatUniform_mcam" => Some(ItemKind::Uniform),
+            "V_PlateCarrier1_rgr" => Some(ItemKind::Vest),
+            _ => None,
+        };
+
+        let lints = lint_categories(&classes, category_of);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].to_string(), "rm.uniform: V_PlateCarrier1_rgr is a vest, not a uniform");
+    }
+
+    #[test]
+    fn test_lint_list_macros() {
+        let content = r#"
+            class rm {
+                items[] = {LIST_10("ACE_fieldDressing"), "ace_fielddressing", LIST_2("ACE_morphine"), "ItemMap"};
+            };
+        "#;
+        let lints = HppParser::new(content).unwrap().lint_list_macros();
+        assert_eq!(lints, [LoadoutLint {
+            class_name: "rm".to_string(),
+            property: "items".to_string(),
+            kind: LoadoutLintKind::ListedTwice { item: "ACE_fieldDressing".to_string() },
+        }]);
+    }
+}
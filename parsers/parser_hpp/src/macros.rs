@@ -0,0 +1,212 @@
+//! Macro definitions shared between the config files of a mission
+//!
+//! Each file is preprocessed on its own, so a `#define` in one hpp file is
+//! unknown to another even when description.ext includes both. A
+//! [`MacroTable`] collects the definitions of every file up front and is
+//! prepended to each file as it is parsed, with the file's own definitions
+//! taking precedence. Registered files have their `#include`s followed,
+//! resolved against the including file and then the include roots.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::encoding::decode_content;
+use crate::include::include_path;
+
+/// `#define` directives by macro name, with the directories includes are resolved against
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MacroTable {
+    definitions: BTreeMap<String, String>,
+    include_roots: Vec<PathBuf>,
+}
+
+impl MacroTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory `#include` paths are resolved against, after the including file's directory
+    pub fn add_include_root(&mut self, dir: impl Into<PathBuf>) {
+        self.include_roots.push(dir.into());
+    }
+
+    /// Directories `#include` paths are resolved against
+    pub fn include_roots(&self) -> &[PathBuf] {
+        &self.include_roots
+    }
+
+    /// Define a macro, replacing an earlier definition of the same name
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.definitions.insert(name.to_string(), format!("#define {} {}", name, value));
+    }
+
+    /// Add the `#define`s of config content, removing macros it `#undef`s
+    pub fn collect(&mut self, content: &str) {
+        for directive in directives(content) {
+            if let Some(rest) = directive.strip_prefix("#define")
+                && let Some(name) = macro_name(rest)
+            {
+                self.definitions.insert(name.to_string(), directive.clone());
+            } else if let Some(rest) = directive.strip_prefix("#undef")
+                && let Some(name) = macro_name(rest)
+            {
+                self.definitions.remove(name);
+            }
+        }
+    }
+
+    /// Add the `#define`s of a file and of every file it includes
+    pub fn register_file(&mut self, path: &Path) -> Result<(), String> {
+        let mut visited = HashSet::new();
+        self.register_file_once(path, &mut visited)
+    }
+
+    fn register_file_once(&mut self, path: &Path, visited: &mut HashSet<PathBuf>) -> Result<(), String> {
+        if !visited.insert(path.canonicalize().unwrap_or_else(|_| path.to_path_buf())) {
+            return Ok(());
+        }
+        let bytes = fs::read(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let content = decode_content(&bytes).content;
+
+        for include in directives(&content).iter().filter_map(|directive| include_path(directive)) {
+            // Includes that don't resolve are left for the preprocessor to report
+            if let Some(included) = self.resolve_include(path, include) {
+                self.register_file_once(&included, visited)?;
+            }
+        }
+        self.collect(&content);
+        Ok(())
+    }
+
+    /// Find the file an `#include` of a file refers to
+    pub fn resolve_include(&self, from: &Path, include: &str) -> Option<PathBuf> {
+        let include = include.replace('\\', "/");
+        // A leading separator means the path starts at a mod prefix rather than the including file
+        let relative = include.trim_start_matches('/');
+        let local = (relative.len() == include.len())
+            .then(|| from.parent().map(|dir| dir.join(relative)))
+            .flatten();
+        local.into_iter()
+            .chain(self.include_roots.iter().map(|root| root.join(relative)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Check if a macro is defined, matching case like the preprocessor
+    pub fn contains(&self, name: &str) -> bool {
+        self.definitions.contains_key(name)
+    }
+
+    /// Names of the defined macros, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(String::as_str)
+    }
+
+    /// Number of defined macros
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    /// Check if no macros are defined
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// The `#define`s to prepend to content, leaving out macros the content defines itself
+    pub fn prelude_for(&self, content: &str) -> String {
+        let mut own = MacroTable::new();
+        own.collect(content);
+        self.definitions.iter()
+            .filter(|(name, _)| !own.contains(name))
+            .map(|(_, directive)| format!("{}\n", directive))
+            .collect()
+    }
+}
+
+/// Preprocessor directives of content, with continued lines joined
+fn directives(content: &str) -> Vec<String> {
+    let mut directives = Vec::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim_start();
+        if !line.starts_with('#') {
+            continue;
+        }
+        let mut directive = line.trim_end().to_string();
+        while directive.ends_with('\\') {
+            let Some(next) = lines.next() else {
+                break;
+            };
+            directive.push('\n');
+            directive.push_str(next.trim_end());
+        }
+        directives.push(directive);
+    }
+    directives
+}
+
+/// Name of the macro following `#define` or `#undef`
+fn macro_name(rest: &str) -> Option<&str> {
+    // The directive must be followed by whitespace, `#defineX` is not a define
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_definitions() {
+        let mut macros = MacroTable::new();
+        macros.collect(r#"
+            #define RIFLE "rhs_weap_m4a1"
+            #define MAG(n) \
+                "rhs_mag_30Rnd_556x45_M855A1_Stanag", n
+            #define TEMP 1
+            #undef TEMP
+            #include "gear.hpp"
+        "#);
+
+        assert_eq!(macros.names().collect::<Vec<_>>(), ["MAG", "RIFLE"]);
+        let prelude = macros.prelude_for("");
+        assert!(prelude.contains("#define RIFLE \"rhs_weap_m4a1\"\n"));
+        assert!(prelude.contains("#define MAG(n) \\\n"));
+    }
+
+    #[test]
+    fn test_prelude_skips_own_definitions() {
+        let mut macros = MacroTable::new();
+        macros.define("RIFLE", "\"rhs_weap_m4a1\"");
+        macros.define("PISTOL", "\"rhsusf_weap_m9\"");
+
+        let prelude = macros.prelude_for("#define RIFLE \"arifle_MX_F\"\nclass Test {};");
+        assert_eq!(prelude, "#define PISTOL \"rhsusf_weap_m9\"\n");
+    }
+
+    #[test]
+    fn test_register_file_follows_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("loadouts")).unwrap();
+        fs::create_dir_all(root.path().join("x/common")).unwrap();
+        fs::write(dir.path().join("loadouts/west.hpp"),
+            "#include \"..\\macros.hpp\"\n#include \"\\x\\common\\gear.hpp\"\n#include \"missing.hpp\"\n").unwrap();
+        fs::write(dir.path().join("macros.hpp"), "#define RIFLE \"rhs_weap_m4a1\"\n#include \"loadouts\\west.hpp\"\n").unwrap();
+        fs::write(root.path().join("x/common/gear.hpp"), "#define PISTOL \"rhsusf_weap_m9\"\n").unwrap();
+
+        let mut macros = MacroTable::new();
+        macros.add_include_root(root.path());
+        macros.register_file(&dir.path().join("loadouts/west.hpp")).unwrap();
+
+        assert!(macros.contains("RIFLE"));
+        assert!(macros.contains("PISTOL"));
+        assert_eq!(macros.len(), 2);
+    }
+}
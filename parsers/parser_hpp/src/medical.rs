@@ -0,0 +1,125 @@
+//! Typed properties of ACE medical treatments
+//!
+//! ACE defines each treatment in `ACE_Medical_Treatment_Actions`, listing the
+//! items it uses and how long it takes. Engine configs write booleans as `0`
+//! and `1`, and some values can be code instead of a number, so the values
+//! are read leniently and left unset when they have the wrong type.
+
+use serde::{Serialize, Deserialize};
+
+use crate::{HppClass, HppProperty, HppValue};
+
+/// Properties of a medical treatment and the items it uses
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MedicalItemProperties {
+    /// Name of the treatment class
+    pub name: String,
+    pub display_name: Option<String>,
+    /// Treatment category, e.g. `bandage` or `medication`
+    pub category: Option<String>,
+    /// Items any one of which is needed for the treatment
+    pub items: Vec<String>,
+    /// Whether the item is used up by the treatment
+    pub consume_item: bool,
+    /// Seconds the treatment takes, unset when computed by a function
+    pub treatment_time: Option<f64>,
+    /// Medical level needed, unset when decided by a function
+    pub medic_required: Option<i64>,
+    pub allow_self_treatment: bool,
+    /// Body parts the treatment can be applied to
+    pub allowed_selections: Vec<String>,
+    /// Condition code the treatment also needs
+    pub condition: Option<String>,
+}
+
+impl MedicalItemProperties {
+    /// Read the properties of a treatment class.
+    ///
+    /// Only the properties of the class itself are read, so classes inheriting
+    /// from a base treatment should be flattened with
+    /// [`flatten_class`](crate::flatten_class) first. Property names are
+    /// matched ignoring case.
+    pub fn from_hpp_class(class: &HppClass) -> Self {
+        let value = |name: &str| property(&class.properties, name);
+        let text = |name: &str| value(name)
+            .and_then(HppValue::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let strings = |name: &str| value(name)
+            .map(|v| v.strings().into_iter().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Self {
+            name: class.name.clone(),
+            display_name: text("displayName"),
+            category: text("category"),
+            items: strings("items"),
+            consume_item: value("consumeItem").and_then(as_bool).unwrap_or(false),
+            treatment_time: value("treatmentTime").and_then(HppValue::as_f64),
+            medic_required: value("medicRequired").and_then(|v| match v {
+                HppValue::Number(n) => Some(*n),
+                HppValue::Bool(b) => Some(*b as i64),
+                _ => None,
+            }),
+            allow_self_treatment: value("allowSelfTreatment").and_then(as_bool).unwrap_or(true),
+            allowed_selections: strings("allowedSelections"),
+            condition: text("condition"),
+        }
+    }
+}
+
+fn property<'a>(properties: &'a [HppProperty], name: &str) -> Option<&'a HppValue> {
+    properties.iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+        .map(|p| &p.value)
+}
+
+/// Read a config boolean, written as a number or `true`/`false`
+fn as_bool(value: &HppValue) -> Option<bool> {
+    match value {
+        HppValue::Bool(b) => Some(*b),
+        HppValue::Number(n) => Some(*n != 0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(name: &str, value: HppValue) -> HppProperty {
+        HppProperty { name: name.to_string(), value, append: false }
+    }
+
+    #[test]
+    fn test_from_hpp_class() {
+        let class = HppClass {
+            name: "Morphine".to_string(),
+            parent: Some("BasicBandage".to_string()),
+            properties: vec![
+                property("displayName", "Inject Morphine".into()),
+                property("CATEGORY", "medication".into()),
+                property("items", HppValue::Array(vec!["ACE_morphine".into()])),
+                property("consumeItem", HppValue::Number(1)),
+                property("treatmentTime", HppValue::Float(2.5)),
+                property("medicRequired", "ace_medical_treatment_fnc_medicRequired".into()),
+                property("allowedSelections", HppValue::Array(vec!["LeftArm".into(), "RightArm".into()])),
+                property("condition", "".into()),
+            ],
+        };
+
+        let medical = MedicalItemProperties::from_hpp_class(&class);
+        assert_eq!(medical, MedicalItemProperties {
+            name: "Morphine".to_string(),
+            display_name: Some("Inject Morphine".to_string()),
+            category: Some("medication".to_string()),
+            items: vec!["ACE_morphine".to_string()],
+            consume_item: true,
+            treatment_time: Some(2.5),
+            medic_required: None,
+            allow_self_treatment: true,
+            allowed_selections: vec!["LeftArm".to_string(), "RightArm".to_string()],
+            condition: None,
+        });
+    }
+}
@@ -1,11 +1,12 @@
 use std::collections::{HashMap, HashSet};
+use crate::configpath::ConfigPath;
 use crate::{HppClass, HppValue};
 
 /// Represents a query pattern to search for and extract data from HPP classes
 #[derive(Debug, Clone)]
 pub struct QueryPattern {
     /// The path to search for (e.g. "baseMan/uniform")
-    path: Vec<String>,
+    path: ConfigPath,
     /// Properties to extract from matching classes
     properties: HashSet<String>,
 }
@@ -14,21 +15,17 @@ impl QueryPattern {
     /// Create a new query pattern
     pub fn new(path: &str, properties: &[&str]) -> Self {
         Self {
-            path: path.split('/').map(String::from).collect(),
+            path: ConfigPath::parse(path),
             properties: properties.iter().map(|&s| s.to_string()).collect(),
         }
     }
 
     /// Check if a class matches this pattern's path
     fn matches_path(&self, class_path: &[String]) -> bool {
-        if class_path.len() < self.path.len() {
-            return false;
-        }
-        
         // Check if the end of the class_path matches our pattern path
-        let start_idx = class_path.len() - self.path.len();
-        class_path[start_idx..].iter().zip(&self.path)
-            .all(|(a, b)| b == "*" || a == b)
+        class_path.iter()
+            .fold(ConfigPath::root(), |path, name| path.join(name))
+            .ends_with(&self.path)
     }
 }
 
@@ -120,8 +117,8 @@ impl DependencyExtractor {
                             HppValue::String(s) => {
                                 dependencies.insert(s.to_string());
                             }
-                            HppValue::Array(arr) => {
-                                dependencies.extend(arr.iter().cloned());
+                            HppValue::Array(_) => {
+                                dependencies.extend(value.strings().into_iter().map(str::to_string));
                             }
                             HppValue::Class(nested_class) => {
                                 // For nested classes, process them with the current path
@@ -164,11 +161,13 @@ mod tests {
             properties: vec![
                 HppProperty {
                     name: "uniform".to_string(),
-                    value: HppValue::Array(vec!["test_uniform".to_string()]),
+                    value: HppValue::Array(vec!["test_uniform".into()]),
+                    append: false,
                 },
                 HppProperty {
                     name: "vest".to_string(),
-                    value: HppValue::Array(vec!["test_vest".to_string()]),
+                    value: HppValue::Array(vec!["test_vest".into()]),
+                    append: false,
                 },
             ],
         };
@@ -189,6 +188,7 @@ mod tests {
                 HppProperty {
                     name: "name".to_string(),
                     value: HppValue::String("test_rifle".to_string()),
+                    append: false,
                 },
             ],
         };
@@ -200,6 +200,7 @@ mod tests {
                 HppProperty {
                     name: "primaryWeapon".to_string(),
                     value: HppValue::Class(nested_class),
+                    append: false,
                 },
             ],
         };
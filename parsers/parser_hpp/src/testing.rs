@@ -0,0 +1,150 @@
+//! Proptest strategies for config classes
+//!
+//! Enabled with the `testing` feature so downstream crates can fuzz code built
+//! on top of [`HppClass`]. [`write_classes`] renders generated classes back to
+//! config source for parser round trips.
+
+use std::collections::HashSet;
+use proptest::prelude::*;
+use crate::{HppClass, HppProperty, HppValue};
+
+/// Words that cannot be used as names
+const KEYWORDS: [&str; 3] = ["class", "delete", "import"];
+
+/// Identifier usable as a class or property name
+pub fn identifier() -> impl Strategy<Value = String> {
+    "[A-Za-z_][A-Za-z0-9_]{0,15}"
+        .prop_filter("config keyword", |name| !KEYWORDS.contains(&name.to_lowercase().as_str()))
+}
+
+/// String value without quotes or preprocessor characters
+pub fn string_value() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9_ .()-]{0,24}"
+}
+
+/// String, array or number value; nested classes are generated as classes instead
+pub fn hpp_value() -> impl Strategy<Value = HppValue> {
+    prop_oneof![
+        string_value().prop_map(HppValue::String),
+        prop::collection::vec(identifier().prop_map(HppValue::String), 0..6).prop_map(HppValue::Array),
+        any::<i32>().prop_map(|n| HppValue::Number(n as i64)),
+    ]
+}
+
+/// Property; only arrays may be declared with `+=`
+pub fn hpp_property() -> impl Strategy<Value = HppProperty> {
+    (identifier(), hpp_value(), any::<bool>()).prop_map(|(name, value, append)| {
+        let append = append && matches!(value, HppValue::Array(_));
+        HppProperty { name, value, append }
+    })
+}
+
+/// Class without a parent whose property names are unique ignoring case
+pub fn hpp_class() -> impl Strategy<Value = HppClass> {
+    (identifier(), prop::collection::vec(hpp_property(), 0..8)).prop_map(|(name, properties)| {
+        let mut seen = HashSet::new();
+        HppClass {
+            name,
+            parent: None,
+            properties: properties.into_iter()
+                .filter(|p| seen.insert(p.name.to_lowercase()))
+                .collect(),
+        }
+    })
+}
+
+/// Classes with unique names, each possibly inheriting from an earlier one
+pub fn hpp_classes() -> impl Strategy<Value = Vec<HppClass>> {
+    prop::collection::vec((hpp_class(), any::<prop::sample::Index>(), any::<bool>()), 0..8)
+        .prop_map(|generated| {
+            let mut seen = HashSet::new();
+            let mut classes: Vec<HppClass> = Vec::new();
+            for (mut class, parent, inherits) in generated {
+                if !seen.insert(class.name.to_lowercase()) {
+                    continue;
+                }
+                if inherits && !classes.is_empty() {
+                    class.parent = Some(classes[parent.index(classes.len())].name.clone());
+                }
+                classes.push(class);
+            }
+            classes
+        })
+}
+
+/// Render classes as config source
+pub fn write_classes(classes: &[HppClass]) -> String {
+    let mut source = String::new();
+    for class in classes {
+        match &class.parent {
+            Some(parent) => source.push_str(&format!("class {} : {} {{\n", class.name, parent)),
+            None => source.push_str(&format!("class {} {{\n", class.name)),
+        }
+        for property in &class.properties {
+            source.push_str(&format!("    {};\n", write_property(property)));
+        }
+        source.push_str("};\n");
+    }
+    source
+}
+
+/// Render a single property assignment
+fn write_property(property: &HppProperty) -> String {
+    match &property.value {
+        HppValue::Array(items) => {
+            let operator = if property.append { "+=" } else { "=" };
+            format!("{}[] {} {}", property.name, operator, write_value(&property.value).unwrap_or_default())
+        }
+        HppValue::Class(class) => write_classes(std::slice::from_ref(class)).trim_end().trim_end_matches(';').to_string(),
+        value => format!("{} = {}", property.name, write_value(value).unwrap_or_default()),
+    }
+}
+
+/// Render a value that can appear on the right of an assignment or in an array
+fn write_value(value: &HppValue) -> Option<String> {
+    match value {
+        HppValue::String(s) => Some(format!("\"{}\"", s.replace('"', "\"\""))),
+        HppValue::Number(n) => Some(n.to_string()),
+        HppValue::Float(f) => Some(format!("{:?}", f)),
+        HppValue::Bool(b) => Some(b.to_string()),
+        HppValue::Array(items) => {
+            let items: Vec<_> = items.iter().filter_map(write_value).collect();
+            Some(format!("{{{}}}", items.join(", ")))
+        }
+        HppValue::Class(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{flatten_class, HppParser};
+
+    proptest! {
+        #[test]
+        fn serde_round_trip(classes in hpp_classes()) {
+            let json = serde_json::to_string(&classes).unwrap();
+            let decoded: Vec<HppClass> = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(decoded, classes);
+        }
+
+        #[test]
+        fn flattened_classes_keep_own_properties(classes in hpp_classes()) {
+            for class in &classes {
+                let flattened = flatten_class(&class.name, &classes).unwrap();
+                prop_assert!(flattened.properties.iter().all(|p| !p.append));
+                for property in class.properties.iter().filter(|p| !p.append) {
+                    let found = flattened.properties.iter()
+                        .find(|p| p.name.eq_ignore_ascii_case(&property.name));
+                    prop_assert_eq!(found.map(|p| &p.value), Some(&property.value));
+                }
+            }
+        }
+
+        #[test]
+        fn parser_round_trip(classes in hpp_classes()) {
+            let parsed = HppParser::new(&write_classes(&classes)).unwrap().parse_classes();
+            prop_assert_eq!(parsed, classes);
+        }
+    }
+}
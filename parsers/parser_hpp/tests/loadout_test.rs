@@ -30,7 +30,7 @@ fn test_loadout_parsing() {
     let uniform_prop = rifleman.properties.iter().find(|p| p.name == "uniform").unwrap();
     if let HppValue::Array(uniforms) = &uniform_prop.value {
         // The quoted string is returned from the parser since LIST macros are preserved as strings
-        assert!(uniforms.iter().any(|u| u.contains("usp_g3c_kp_mx_aor2")), 
+        assert!(uniforms.iter().filter_map(HppValue::as_str).any(|u| u.contains("usp_g3c_kp_mx_aor2")), 
                 "Could not find usp_g3c_kp_mx_aor2 in: {:?}", uniforms);
     } else {
         panic!("Expected uniform to be an array");
@@ -14,11 +14,7 @@ fn test_profilenamespace_expression_parsing() {
     let color_frame_prop = progress_class.properties.iter()
         .find(|p| p.name == "colorFrame").unwrap();
     if let HppValue::Array(values) = &color_frame_prop.value {
-        assert_eq!(values.len(), 4);
-        assert_eq!(values[0], "0");
-        assert_eq!(values[1], "0");
-        assert_eq!(values[2], "0");
-        assert_eq!(values[3], "0");
+        assert_eq!(values, &[HppValue::Number(0), HppValue::Number(0), HppValue::Number(0), HppValue::Number(0)]);
     } else {
         panic!("Expected colorFrame to be an array");
     }
@@ -28,6 +24,7 @@ fn test_profilenamespace_expression_parsing() {
         .find(|p| p.name == "colorBar").unwrap();
     if let HppValue::Array(values) = &color_bar_prop.value {
         assert_eq!(values.len(), 4);
+        let values: Vec<&str> = values.iter().filter_map(HppValue::as_str).collect();
         // Verify that profilenamespace expressions are properly preserved as complete strings
         assert!(values[0].contains("(profilenamespace getvariable ['GUI_BCG_RGB_R',0.13])"));
         assert!(values[1].contains("(profilenamespace getvariable ['GUI_BCG_RGB_G',0.54])"));
@@ -46,15 +43,8 @@ fn test_profilenamespace_expression_parsing() {
     let x_prop = progress_class.properties.iter()
         .find(|p| p.name == "x").unwrap();
     
-    // Depending on the implementation, this could be treated as a number or a string
     match &x_prop.value {
-        HppValue::Number(num) => {
-            // Allow for potential floating point conversion
-            assert!(*num == 0 || *num == 344, "Expected x to be either 0 or 344, got {}", num);
-        },
-        HppValue::String(s) => {
-            assert!(s == "0.344", "Expected x to be \"0.344\", got \"{}\"", s);
-        },
-        _ => panic!("Expected x to be either a number or string")
+        HppValue::Float(x) => assert!((x - 0.344).abs() < 1e-6, "Expected x to be 0.344, got {}", x),
+        other => panic!("Expected x to be a float, got {:?}", other)
     }
 } 
\ No newline at end of file
@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use parser_sqf::parse_str;
+
+/// Lines of the generated stress arsenal, about the size of a large framework gear script
+const ARSENAL_LINES: usize = 5000;
+
+/// Generate an arsenal script of at least `min_lines` lines: gear functions per role
+/// assigning classes to a unit, followed by an arsenal box whitelisting every class
+fn create_arsenal_script(min_lines: usize) -> String {
+    let mut code = String::from("params [\"_unit\", \"_box\"];\nprivate _arsenal = [];\n");
+    let mut lines = 2;
+    let mut role = 0;
+    while lines < min_lines {
+        code.push_str(&format!(r#"
+if (_unit getVariable ["role", ""] == "role_{role}") then {{
+    removeAllWeapons _unit;
+    removeAllItems _unit;
+    _unit forceAddUniform "rhs_uniform_g3_m81_{role}";
+    _unit addVest "rhsusf_spcs_ocp_rifleman_{role}";
+    _unit addBackpack "rhsusf_assault_eagleaiii_ocp_{role}";
+    _unit addHeadgear "rhsusf_ach_helmet_ocp_{role}";
+    _unit addWeapon "rhs_weap_m4a1_{role}";
+    _unit addPrimaryWeaponItem "rhsusf_acc_eotech_552_{role}";
+    for "_i" from 1 to 6 do {{ _unit addMagazine "rhs_mag_30Rnd_556x45_M855A1_Stanag_{role}"; }};
+    _unit addItemToUniform "ACE_fieldDressing_{role}";
+    _unit addItemToVest "ACE_morphine_{role}";
+    _unit linkItem "ItemMap";
+    _unit linkItem "TFAR_anprc152_{role}";
+    _arsenal append ["rhs_weap_m4a1_{role}", "rhsusf_acc_eotech_552_{role}", "ACE_morphine_{role}"];
+}};
+"#));
+        lines += 18;
+        role += 1;
+    }
+    code.push_str("[_box, _arsenal] call ace_arsenal_fnc_initBox;\n");
+    code
+}
+
+fn sqf_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sqf_throughput");
+    group.sample_size(10);
+
+    let arsenal = create_arsenal_script(ARSENAL_LINES);
+    group.throughput(Throughput::Bytes(arsenal.len() as u64));
+    group.bench_function("arsenal_5k_lines", |b| {
+        b.iter(|| parse_str(black_box(&arsenal), "arsenal.sqf").unwrap())
+    });
+
+    let real_world = include_str!("benchmark_file_large.sqf");
+    group.throughput(Throughput::Bytes(real_world.len() as u64));
+    group.bench_function("real_world_large", |b| {
+        b.iter(|| parse_str(black_box(real_world), "fn_ETH_globalFunctions.sqf").unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, sqf_throughput);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
 use hemtt_sqf::Expression;
-use std::collections::HashMap;
 use crate::models::UsageContext;
-use super::evaluator::SqfValue;
+use crate::interpreter::{evaluate_expression, Environment, SqfValue};
 
 /// Handles array operations and value extraction
 pub struct ArrayHandler {
@@ -11,7 +10,7 @@ pub struct ArrayHandler {
 
 impl ArrayHandler {
     /// Create a new array handler with a callback for class references
-    pub fn new<F>(callback: F) -> Self 
+    pub fn new<F>(callback: F) -> Self
     where
         F: Fn(String, UsageContext) + Send + 'static
     {
@@ -26,7 +25,7 @@ impl ArrayHandler {
         operation: &str,
         lhs: &Expression,
         rhs: &Expression,
-        variables: &HashMap<String, SqfValue>,
+        variables: &Environment,
         context: UsageContext,
     ) -> Option<SqfValue> {
         match operation.to_lowercase().as_str() {
@@ -41,24 +40,13 @@ impl ArrayHandler {
         &self,
         lhs: &Expression,
         rhs: &Expression,
-        variables: &HashMap<String, SqfValue>,
+        variables: &Environment,
     ) -> Option<SqfValue> {
         let lhs_value = self.evaluate_expression_to_value(lhs, variables);
         let rhs_value = self.evaluate_expression_to_value(rhs, variables);
 
         match (lhs_value, rhs_value) {
-            (SqfValue::Array(mut left), SqfValue::Array(right)) => {
-                left.extend(right);
-                Some(SqfValue::Array(left))
-            },
-            (SqfValue::Array(mut arr), other) => {
-                arr.push(other);
-                Some(SqfValue::Array(arr))
-            },
-            (other, SqfValue::Array(mut arr)) => {
-                arr.insert(0, other);
-                Some(SqfValue::Array(arr))
-            },
+            (lhs @ SqfValue::Array(_), rhs) | (lhs, rhs @ SqfValue::Array(_)) => Some(lhs.concat(rhs)),
             (lhs, rhs) => Some(SqfValue::Array(vec![lhs, rhs]))
         }
     }
@@ -68,7 +56,7 @@ impl ArrayHandler {
         &self,
         lhs: &Expression,
         rhs: &Expression,
-        variables: &HashMap<String, SqfValue>,
+        variables: &Environment,
         context: UsageContext,
         operation: &str,
     ) -> Option<SqfValue> {
@@ -78,7 +66,7 @@ impl ArrayHandler {
         };
 
         let value = self.evaluate_expression_to_value(rhs, variables);
-        
+
         // For strings, add them as references
         if let SqfValue::String(s) = &value {
             (self.reference_callback)(s.clone(), context);
@@ -97,40 +85,18 @@ impl ArrayHandler {
     pub fn evaluate_expression_to_value(
         &self,
         expr: &Expression,
-        variables: &HashMap<String, SqfValue>
+        variables: &Environment
     ) -> SqfValue {
-        match expr {
-            Expression::String(s, _, _) => SqfValue::String(s.to_string()),
-            Expression::Array(elements, _) => {
-                let values: Vec<_> = elements.iter()
-                    .map(|e| self.evaluate_expression_to_value(e, variables))
-                    .collect();
-                SqfValue::Array(values)
-            },
-            Expression::Variable(name, _) => {
-                variables.get(name).cloned().unwrap_or(SqfValue::Unknown)
-            },
-            _ => SqfValue::Unknown
-        }
+        evaluate_expression(expr, variables)
     }
 
     /// Extract array values from an expression
     pub fn extract_array_values(
         &self,
         expr: &Expression,
-        variables: &HashMap<String, SqfValue>,
+        variables: &Environment,
         result: &mut Vec<String>
     ) {
-        match self.evaluate_expression_to_value(expr, variables) {
-            SqfValue::String(s) => result.push(s),
-            SqfValue::Array(values) => {
-                for value in values {
-                    if let SqfValue::String(s) = value {
-                        result.push(s);
-                    }
-                }
-            },
-            _ => {}
-        }
+        result.extend(self.evaluate_expression_to_value(expr, variables).strings());
     }
-} 
\ No newline at end of file
+}
@@ -1,64 +1,156 @@
 use hemtt_sqf::{Expression, Statement, Statements, BinaryCommand, UnaryCommand};
-use crate::models::{ClassReference, UsageContext, AnalysisResult};
-use std::collections::{HashMap, HashSet};
+use crate::models::{ArsenalBox, AssetReference, ClassReference, Confidence, CountRange, ItemKind, PoolCandidate, RandomPool, UsageContext, AnalysisResult, AnalysisOptions};
+use crate::interpreter::{evaluate_expression, Environment, SqfValue};
+use crate::inventory_sim::InventoryOperation;
+use crate::loadout::loadout_classes;
+use crate::registry::{is_arsenal_function, is_config_root, slot_removal_command, FunctionRegistry};
+use crate::signatures::{ArgumentRole, ArgumentSignature, CommandSignatures, Operand};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use super::array_handler::ArrayHandler;
 
-/// Represents a value in SQF execution
-#[derive(Debug, Clone, PartialEq)]
-pub enum SqfValue {
-    String(String),
-    Array(Vec<SqfValue>),
-    Partial(Vec<String>),
-    Unknown,
-}
-
 /// SQF evaluator that tracks variable usage to identify class references
 pub struct Evaluator {
     /// Current state of variables
-    variables: HashMap<String, SqfValue>,
-    /// Class references found through function usage
-    references: Arc<Mutex<HashMap<String, HashSet<UsageContext>>>>,
+    variables: Environment,
+    /// Class references found through function usage, with how each context used them
+    references: Arc<Mutex<HashMap<String, HashMap<UsageContext, Usage>>>>,
     /// Current execution scope name
     current_scope: String,
-    /// The set of function names that indicate class references
-    class_reference_functions: HashSet<String>,
+    /// Functions and commands that indicate class references
+    class_reference_functions: FunctionRegistry,
     /// Array handler for array operations
     array_handler: ArrayHandler,
+    /// Options controlling which heuristics are applied
+    options: AnalysisOptions,
+    /// Argument roles of known commands
+    signatures: Arc<CommandSignatures>,
+    /// Strings seen in non-class argument positions (marker names, sounds, ...)
+    non_class_strings: HashSet<String>,
+    /// Texture and material paths passed to appearance commands
+    assets: BTreeSet<AssetReference>,
+    /// Paths of scripts run or compiled, as written
+    script_paths: BTreeSet<String>,
+    /// Code given as strings to event handlers, actions and `compile`
+    embedded_code: Vec<String>,
+    /// Arsenal items by lowercase box id, with the id as first written
+    arsenal_boxes: BTreeMap<String, (String, BTreeSet<String>)>,
+    /// Commands used to empty whole inventory slots
+    removed_slots: BTreeSet<&'static str>,
+    /// Add and remove commands applied to units, in script order
+    inventory_operations: Vec<InventoryOperation>,
+    /// Times the code being evaluated runs, from the enclosing `for` loops
+    repetitions: CountRange,
+    /// Class arguments picking one of several candidates, in script order
+    random_pools: Vec<RandomPool>,
+}
+
+/// How a class was used in one context
+#[derive(Debug, Clone, Copy)]
+struct Usage {
+    /// Best confidence seen
+    confidence: Confidence,
+    /// Instances added, counting loop iterations
+    count: CountRange,
+    /// Kind of item named by the signature of the command
+    kind: Option<ItemKind>,
+}
+
+/// Id of an object that isn't a plain variable or command
+const UNKNOWN_OBJECT_ID: &str = "<unknown>";
+
+/// Name an object by the variable or command holding it, e.g. `_box` or `player`
+fn object_id(expr: Option<&Expression>) -> String {
+    match expr {
+        Some(Expression::Variable(name, _)) => name.clone(),
+        Some(Expression::NularCommand(command, _)) => command.name.clone(),
+        _ => UNKNOWN_OBJECT_ID.to_string(),
+    }
+}
+
+/// Check if an expression is made only of literals, so its strings are written in place
+fn is_literal(expr: &Expression) -> bool {
+    match expr {
+        Expression::String(..) | Expression::Number(..) | Expression::Boolean(..) => true,
+        Expression::Array(elements, _) => elements.iter().all(is_literal),
+        _ => false,
+    }
+}
+
+/// Confidence of classes read from an expression: high when written in place,
+/// medium when resolved through variables
+fn literal_confidence(expr: &Expression) -> Confidence {
+    if is_literal(expr) {
+        Confidence::High
+    } else {
+        Confidence::Medium
+    }
+}
+
+/// Drop the `%ALL` style wildcards arsenal functions accept in place of classes
+fn arsenal_strings(items: Vec<String>) -> Vec<String> {
+    items.into_iter()
+        .filter(|item| !item.is_empty() && !item.starts_with('%'))
+        .collect()
+}
+
+/// Element of an operand at an argument's index, or the operand itself when it has none
+fn argument_element(index: Option<usize>, value: SqfValue) -> Option<SqfValue> {
+    Some(match (index, value) {
+        (Some(index), SqfValue::Array(mut values)) => {
+            if index >= values.len() {
+                return None;
+            }
+            values.swap_remove(index)
+        }
+        // The element of each array the operand may be
+        (Some(index), SqfValue::Either(alternatives)) => {
+            let elements: Vec<_> = alternatives.into_iter()
+                .filter_map(|alternative| argument_element(Some(index), alternative))
+                .collect();
+            elements.into_iter().reduce(SqfValue::either)?
+        }
+        // Operands documented as arrays are sometimes passed as a single value
+        (_, value) => value,
+    })
 }
 
 impl Default for Evaluator {
     fn default() -> Self {
-        // Initialize with known functions that indicate class references
-        let mut class_reference_functions = HashSet::new();
-        
-        // Add functions
-        class_reference_functions.insert("ace_arsenal_fnc_initbox".to_string());
-        
-        // Add commands that take class references
-        for cmd in &[
-            "addWeapon", "addWeaponCargo", "addWeaponGlobal", "addWeaponCargoGlobal",
-            "addMagazine", "addMagazineCargo", "addMagazineGlobal", "addMagazineCargoGlobal",
-            "addItem", "addItemCargo", "addItemToBackpack", "addItemToUniform", "addItemToVest",
-            "addBackpack", "addBackpackCargo", "addBackpackGlobal", "addBackpackCargoGlobal",
-            "addGoggles", "addHeadgear", "forceAddUniform", "addVest", "addUniform",
-            "linkItem",
-        ] {
-            class_reference_functions.insert(cmd.to_string().to_lowercase());
-        }
+        Self::with_options(AnalysisOptions::default())
+    }
+}
+
+impl Evaluator {
+    /// Create an evaluator with the given analysis options
+    pub fn with_options(options: AnalysisOptions) -> Self {
+        let signatures = options.command_signatures.clone()
+            .unwrap_or_else(CommandSignatures::embedded);
+
+        // Known functions and commands that indicate class references
+        let class_reference_functions = FunctionRegistry::for_options(&options);
 
         // Create a new evaluator with a reference callback
         let references = Arc::new(Mutex::new(HashMap::new()));
-        let variables = HashMap::new();
+        let variables = Environment::new();
         let current_scope = String::new();
 
-        // Create the array handler with a closure that captures references
+        // Create the array handler with a closure that captures references.
+        // Strings pushed into arrays are only a guess at class usage, so strict
+        // mode relies on the array later reaching a known command instead.
         let references_clone = Arc::clone(&references);
+        let strict = options.strict;
         let array_handler = ArrayHandler::new(move |s: String, ctx: UsageContext| {
+            if strict {
+                return;
+            }
             references_clone.lock().unwrap()
                 .entry(s)
-                .or_insert_with(HashSet::new)
-                .insert(ctx);
+                .or_insert_with(HashMap::new)
+                .entry(ctx)
+                .or_insert(Usage { confidence: Confidence::Low, count: CountRange::exact(0), kind: None })
+                .count
+                .add(CountRange::exact(1));
         });
 
         Self {
@@ -67,11 +159,20 @@ impl Default for Evaluator {
             current_scope,
             class_reference_functions,
             array_handler,
+            options,
+            signatures,
+            non_class_strings: HashSet::new(),
+            assets: BTreeSet::new(),
+            script_paths: BTreeSet::new(),
+            embedded_code: Vec::new(),
+            arsenal_boxes: BTreeMap::new(),
+            removed_slots: BTreeSet::new(),
+            inventory_operations: Vec::new(),
+            repetitions: CountRange::exact(1),
+            random_pools: Vec::new(),
         }
     }
-}
 
-impl Evaluator {
     /// Evaluate a complete SQF script
     pub fn evaluate_script(&mut self, statements: &Statements) {
         for statement in statements.content() {
@@ -83,12 +184,12 @@ impl Evaluator {
     fn evaluate_statement(&mut self, stmt: &Statement) {
         match stmt {
             Statement::Expression(expr, _) => {
-                println!("Evaluating expression");
+                log::trace!("Evaluating expression");
                 self.evaluate_expression(expr);
             },
             Statement::AssignGlobal(name, expr, _) | Statement::AssignLocal(name, expr, _) => {
                 let var_name = name.clone();
-                println!("Assigning to variable: {}", var_name);
+                log::trace!("Assigning to variable: {}", var_name);
                 self.current_scope = var_name.clone();
                 
                 // First evaluate the expression to get any direct references
@@ -96,10 +197,10 @@ impl Evaluator {
                 
                 // Then evaluate to value for storage
                 let value = self.array_handler.evaluate_expression_to_value(expr, &self.variables);
-                println!("Value: {:?}", value);
+                log::trace!("Value: {:?}", value);
                 
                 // Store the value for later use
-                self.variables.insert(var_name, value);
+                self.variables.set(&var_name, value);
                 self.current_scope.clear();
             }
         }
@@ -108,49 +209,117 @@ impl Evaluator {
     /// Evaluate an expression and track class reference usage
     fn evaluate_expression(&mut self, expr: &Expression) {
         match expr {
+            Expression::BinaryCommand(BinaryCommand::ConfigPath, _, _, _) => self.evaluate_config_path(expr),
             Expression::BinaryCommand(cmd, lhs, rhs, _) => {
                 if let BinaryCommand::Named(name) = cmd {
                     let cmd_name = name.to_string();
                     let cmd_name_lower = cmd_name.to_lowercase();
-                    println!("Processing command: {}", cmd_name);
+                    log::trace!("Processing command: {}", cmd_name);
+                    self.record_non_class_strings(&cmd_name_lower, Some(lhs), rhs);
                     
+                    // Branches of if/then/else each start from the variables before the if
+                    if cmd_name_lower == "then" {
+                        self.evaluate_expression(lhs);
+                        self.evaluate_branches(rhs);
+                        return;
+                    }
+
+                    // The loop body runs once per element with `_x` set to it
+                    if cmd_name_lower == "foreach" {
+                        self.evaluate_expression(rhs);
+                        self.evaluate_for_each(lhs, rhs);
+                        return;
+                    }
+
+                    // Classes added in a counted loop are added once per iteration
+                    if cmd_name_lower == "do" {
+                        self.evaluate_expression(lhs);
+                        let outer = self.repetitions;
+                        let mut iterations = self.for_iterations(lhs).unwrap_or(CountRange::exact(1));
+                        iterations.max = iterations.max.max(1);
+                        self.repetitions = outer.times(iterations);
+                        self.evaluate_expression(rhs);
+                        self.repetitions = outer;
+                        return;
+                    }
+
                     // Check if this is a function call that indicates class references
                     if cmd_name_lower == "call" {
                         if let Expression::Variable(func_name, _) = &**rhs {
-                            if self.class_reference_functions.contains(&func_name.to_string().to_lowercase()) {
-                                println!("Found class reference function: {}", func_name);
+                            if self.class_reference_functions.contains(func_name) {
+                                log::trace!("Found class reference function: {}", func_name);
+                                // Functions with a signature, such as mod functions from a
+                                // user table, take their classes at the positions it lists
+                                let class_arguments: Vec<_> = self.signatures.class_arguments(func_name)
+                                    .copied()
+                                    .collect();
+                                if !class_arguments.is_empty() {
+                                    for argument in &class_arguments {
+                                        self.extract_class_from_argument(argument, Some(lhs), lhs, UsageContext::KnownFunction(func_name.to_string()));
+                                    }
+                                    return;
+                                }
                                 // Handle known function that takes class references
                                 self.handle_class_reference_function(&func_name.to_string(), lhs);
                                 return;
                             }
                         }
                     } 
+                    // Loadout arrays hold classes at fixed positions, mixed with counts and flags
+                    else if cmd_name_lower == "setunitloadout" {
+                        let loadout = evaluate_expression(rhs, &self.variables);
+                        let confidence = literal_confidence(rhs);
+                        for class_name in loadout_classes(&loadout) {
+                            self.add_reference(class_name, UsageContext::AddCommand(cmd_name.clone()), confidence);
+                        }
+                        return;
+                    }
                     // Check if this is a command that takes class references
-                    else if self.class_reference_functions.contains(&cmd_name_lower) {
-                        println!("Found class reference command: {}", cmd_name);
-                        // For add* commands, we don't care about the left operand (target unit)
-                        // We only care about the right operand which contains the class name
-                        if let Expression::String(s, _, _) = &**rhs {
-                            self.add_reference(s.to_string(), UsageContext::AddCommand(cmd_name));
-                        } else {
-                            // If the right operand is not a direct string, try to extract class references
-                            self.extract_class_from_expression(rhs, UsageContext::AddCommand(cmd_name));
+                    else if self.class_reference_functions.contains(&cmd_name) {
+                        log::trace!("Found class reference command: {}", cmd_name);
+                        // The signature table says which operand holds the class name;
+                        // for add* commands the left operand is just the target unit
+                        let class_arguments: Vec<_> = self.signatures.class_arguments(&cmd_name_lower)
+                            .copied()
+                            .collect();
+                        for argument in &class_arguments {
+                            self.extract_class_from_argument(argument, Some(lhs), rhs, UsageContext::AddCommand(cmd_name.clone()));
+                        }
+                        let removed_arguments: Vec<_> = self.signatures.removed_class_arguments(&cmd_name_lower)
+                            .copied()
+                            .collect();
+                        for argument in &removed_arguments {
+                            self.extract_class_from_argument(argument, Some(lhs), rhs, UsageContext::RemoveCommand(cmd_name.clone()));
+                        }
+
+                        // Classes that resolve to a single string can be replayed on the unit
+                        let class_name = class_arguments.iter().chain(&removed_arguments)
+                            .find_map(|argument| match self.argument_value(argument, Some(lhs), rhs) {
+                                Some(SqfValue::String(class_name)) => Some(class_name),
+                                _ => None,
+                            });
+                        if let Some(class_name) = class_name {
+                            self.inventory_operations.push(InventoryOperation {
+                                unit: object_id(Some(lhs)),
+                                command: cmd_name,
+                                class_name: Some(class_name),
+                            });
                         }
                         return;
                     }
                     // Handle selectRandomWeighted command
                     else if cmd_name_lower == "selectrandomweighted" {
-                        println!("Processing selectRandomWeighted");
+                        log::trace!("Processing selectRandomWeighted");
                         // Extract strings from the array argument
                         if let Expression::Array(elements, _) = &**lhs {
                             for (i, element) in elements.iter().enumerate() {
                                 if i % 2 == 0 { // Even indices are items, odd are weights
                                     if let Expression::String(s, _, _) = element {
-                                        println!("Found selectRandomWeighted item: {}", s);
+                                        log::trace!("Found selectRandomWeighted item: {}", s);
                                         // Store the string in current scope if we have one
-                                        if !self.current_scope.is_empty() {
-                                            println!("Adding reference in scope {}: {}", self.current_scope, s);
-                                            self.add_reference(s.to_string(), UsageContext::DirectReference);
+                                        if !self.options.strict && !self.current_scope.is_empty() {
+                                            log::trace!("Adding reference in scope {}: {}", self.current_scope, s);
+                                            self.add_reference(s.to_string(), UsageContext::DirectReference, Confidence::Low);
                                         }
                                     }
                                 }
@@ -172,7 +341,7 @@ impl Evaluator {
                                 &self.variables,
                                 UsageContext::DirectReference
                             ) {
-                                self.variables.insert(var_name.to_string(), value);
+                                self.variables.set(var_name, value);
                             }
                         }
                         return;
@@ -190,9 +359,10 @@ impl Evaluator {
                 }
             },
             Expression::String(s, _, _) => {
-                // Only add string as reference if we're in a known class reference context
-                if !self.current_scope.is_empty() {
-                    self.add_reference(s.to_string(), UsageContext::DirectReference);
+                // Only add string as reference if we're in a known class reference context.
+                // Strict mode never guesses from the assignment scope.
+                if !self.options.strict && !self.current_scope.is_empty() {
+                    self.add_reference(s.to_string(), UsageContext::DirectReference, Confidence::Low);
                 }
             },
             Expression::Code(code) => {
@@ -203,9 +373,24 @@ impl Evaluator {
             },
             Expression::UnaryCommand(cmd, operand, _) => {
                 if let UnaryCommand::Named(name) = cmd {
-                    if self.class_reference_functions.contains(&name.to_string().to_lowercase()) {
+                    self.record_non_class_strings(&name.to_lowercase(), None, operand);
+                    if let Some(command) = slot_removal_command(name) {
+                        self.removed_slots.insert(command);
+                        self.inventory_operations.push(InventoryOperation {
+                            unit: object_id(Some(operand)),
+                            command: name.clone(),
+                            class_name: None,
+                        });
+                        return;
+                    }
+                    if self.class_reference_functions.contains(name) {
                         // Some unary commands might take class references
-                        self.extract_class_from_expression(operand, UsageContext::AddCommand(name.to_string().to_lowercase()));
+                        let class_arguments: Vec<_> = self.signatures.class_arguments(name)
+                            .copied()
+                            .collect();
+                        for argument in class_arguments {
+                            self.extract_class_from_argument(&argument, None, operand, UsageContext::AddCommand(name.to_string().to_lowercase()));
+                        }
                         return;
                     }
                 }
@@ -215,49 +400,370 @@ impl Evaluator {
         }
     }
 
+    /// Evaluate the branches of an if/then/else.
+    ///
+    /// Either branch may run, so afterwards a variable assigned differently in
+    /// the branches may hold the value of either one.
+    fn evaluate_branches(&mut self, branches: &Expression) {
+        let (then_branch, else_branch) = match branches {
+            Expression::BinaryCommand(BinaryCommand::Else, then_branch, else_branch, _) => {
+                (&**then_branch, Some(&**else_branch))
+            }
+            then_branch => (then_branch, None),
+        };
+
+        let before = self.variables.clone();
+        self.evaluate_expression(then_branch);
+        let after_then = std::mem::replace(&mut self.variables, before.clone());
+        if let Some(else_branch) = else_branch {
+            self.evaluate_expression(else_branch);
+        }
+        let after_else = std::mem::take(&mut self.variables);
+        self.variables = Environment::merge_branches(&before, &after_then, &after_else);
+    }
+
+    /// Add the class a config path looks up, e.g. `arifle_MX_F` in
+    /// `configFile >> "CfgWeapons" >> "arifle_MX_F" >> "displayName"`.
+    ///
+    /// Only the class directly below a top-level class of `configFile` is a
+    /// class of the game or a mod; deeper entries are its subclasses and
+    /// properties, and `missionConfigFile` holds the mission's own classes.
+    fn evaluate_config_path(&mut self, expr: &Expression) {
+        let mut segments = Vec::new();
+        let mut current = expr;
+        while let Expression::BinaryCommand(BinaryCommand::ConfigPath, lhs, rhs, _) = current {
+            segments.push(&**rhs);
+            current = lhs;
+        }
+        segments.reverse();
+        // Names written in the path are config entries, not classes to guess from
+        for segment in segments.iter().filter(|segment| !matches!(segment, Expression::String(..))) {
+            self.evaluate_expression(segment);
+        }
+
+        let is_game_config = matches!(current, Expression::NularCommand(command, _) if is_config_root(&command.name));
+        let (true, Some(config), Some(class)) = (is_game_config, segments.first(), segments.get(1)) else {
+            return;
+        };
+        let SqfValue::String(config) = evaluate_expression(config, &self.variables) else {
+            return;
+        };
+        let confidence = literal_confidence(class);
+        for class_name in evaluate_expression(class, &self.variables).strings() {
+            self.add_reference(class_name, UsageContext::ConfigLookup(config.clone()), confidence);
+        }
+    }
+
+    /// Evaluate a forEach body for each element of the iterated array.
+    ///
+    /// When the array isn't known the body is evaluated once with `_x` unknown.
+    /// `_x` and `_forEachIndex` are restored afterwards for enclosing loops.
+    fn evaluate_for_each(&mut self, body: &Expression, array: &Expression) {
+        let elements = match evaluate_expression(array, &self.variables) {
+            // The body runs for the elements of whichever array it is
+            SqfValue::Either(alternatives) => alternatives.into_iter()
+                .flat_map(|alternative| match alternative {
+                    SqfValue::Array(values) => values,
+                    value => vec![value],
+                })
+                .collect(),
+            SqfValue::Array(values) => values,
+            SqfValue::Partial(values) => values.into_iter().map(SqfValue::String).collect(),
+            _ => vec![SqfValue::Unknown],
+        };
+
+        let outer_element = self.variables.remove("_x");
+        let outer_index = self.variables.remove("_forEachIndex");
+        for (index, element) in elements.into_iter().enumerate() {
+            self.variables.set("_x", element);
+            self.variables.set("_forEachIndex", SqfValue::Number(index as f64));
+            self.evaluate_expression(body);
+        }
+        self.variables.remove("_x");
+        self.variables.remove("_forEachIndex");
+        if let Some(value) = outer_element {
+            self.variables.set("_x", value);
+        }
+        if let Some(value) = outer_index {
+            self.variables.set("_forEachIndex", value);
+        }
+    }
+
     /// Extract class references from an expression based on a usage context
     fn extract_class_from_expression(&mut self, expr: &Expression, context: UsageContext) {
         let mut result = Vec::new();
         self.array_handler.extract_array_values(expr, &self.variables, &mut result);
         
         // Process extracted class names
+        let confidence = literal_confidence(expr);
         for class_name in result {
-            self.add_reference(class_name, context.clone());
+            self.add_reference(class_name, context.clone(), confidence);
         }
     }
 
     /// Handle functions known to use class references (like ace_arsenal_fnc_initBox)
     fn handle_class_reference_function(&mut self, func_name: &str, args: &Expression) {
         let context = UsageContext::KnownFunction(func_name.to_string());
-        
-        // Extract arguments based on the function
-        if func_name.to_lowercase() == "ace_arsenal_fnc_initbox" {
-            // ace_arsenal_fnc_initBox can be called with [box, items] or just [items]
-            if let Expression::Array(elements, _) = args {
-                // Get the items argument (either first or second element depending on call format)
-                let items_arg = if elements.len() >= 2 {
-                    &elements[1]
-                } else if elements.len() == 1 {
-                    &elements[0]
-                } else {
-                    return;
-                };
-                
-                // Extract class references from the items argument
-                self.extract_class_from_expression(items_arg, context);
-            }
-        } else {
+
+        if !is_arsenal_function(func_name) {
             // For other known functions, just process all arguments
             self.extract_class_from_expression(args, context);
+            return;
+        }
+
+        // Arsenal functions take [box, items, global]; ace_arsenal_fnc_initBox also accepts just [items]
+        let (box_arg, items, confidence) = match args {
+            Expression::Array(elements, _) => match elements.as_slice() {
+                [box_arg, items_arg, ..] => (Some(box_arg), self.arsenal_items(items_arg), literal_confidence(items_arg)),
+                [items_arg] if func_name.eq_ignore_ascii_case("ace_arsenal_fnc_initbox") => {
+                    (None, self.arsenal_items(items_arg), literal_confidence(items_arg))
+                }
+                _ => return,
+            },
+            // Arguments built up in a variable, so the box can't be named
+            args => match evaluate_expression(args, &self.variables) {
+                SqfValue::Array(values) if values.len() >= 2 => {
+                    (None, arsenal_strings(values[1].strings()), Confidence::Medium)
+                }
+                _ => return,
+            },
+        };
+
+        for item in &items {
+            self.add_reference(item.clone(), context.clone(), confidence);
+        }
+        self.add_arsenal_items(box_arg, items);
+    }
+
+    /// Classes in the items argument of an arsenal function, nested arrays included
+    fn arsenal_items(&self, items_arg: &Expression) -> Vec<String> {
+        let mut items = Vec::new();
+        self.array_handler.extract_array_values(items_arg, &self.variables, &mut items);
+        arsenal_strings(items)
+    }
+
+    /// Record items offered by the arsenal of an object, merging repeated calls on the same object
+    fn add_arsenal_items(&mut self, box_arg: Option<&Expression>, items: Vec<String>) {
+        let box_id = object_id(box_arg);
+        self.arsenal_boxes.entry(box_id.to_lowercase())
+            .or_insert_with(|| (box_id, BTreeSet::new()))
+            .1
+            .extend(items);
+    }
+
+    /// Resolve the value held by one argument position of a command
+    fn argument_value(&self, argument: &ArgumentSignature, lhs: Option<&Expression>, rhs: &Expression) -> Option<SqfValue> {
+        let operand = match argument.operand {
+            Operand::Left => lhs?,
+            Operand::Right => rhs,
+        };
+
+        let value = evaluate_expression(operand, &self.variables);
+        argument_element(argument.index, value)
+    }
+
+    /// Confidence of the classes in one argument position of a command
+    fn argument_confidence(argument: &ArgumentSignature, lhs: Option<&Expression>, rhs: &Expression) -> Confidence {
+        let operand = match (argument.operand, lhs) {
+            (Operand::Left, Some(lhs)) => lhs,
+            (Operand::Left, None) => return Confidence::Medium,
+            (Operand::Right, _) => rhs,
+        };
+        match (argument.index, operand) {
+            (Some(index), Expression::Array(elements, _)) => {
+                elements.get(index).map_or(Confidence::Medium, literal_confidence)
+            }
+            (_, operand) => literal_confidence(operand),
+        }
+    }
+
+    /// Number of iterations of a `for "_i" from A to B (step C)` loop header,
+    /// if its bounds are known. Random bounds give a range of iterations
+    fn for_iterations(&self, header: &Expression) -> Option<CountRange> {
+        let (range, step) = match header {
+            Expression::BinaryCommand(BinaryCommand::Named(name), range, step, _) if name.eq_ignore_ascii_case("step") => {
+                (&**range, self.number_bounds(step)?)
+            }
+            range => (range, (1.0, 1.0)),
+        };
+        let Expression::BinaryCommand(BinaryCommand::Named(to), from, end, _) = range else {
+            return None;
+        };
+        let Expression::BinaryCommand(BinaryCommand::Named(from), _, start, _) = &**from else {
+            return None;
+        };
+        if !to.eq_ignore_ascii_case("to") || !from.eq_ignore_ascii_case("from") {
+            return None;
+        }
+
+        let (first_min, first_max) = self.number_bounds(start)?;
+        let (last_min, last_max) = self.number_bounds(end)?;
+        let (step, step_max) = step;
+        // A step that isn't positive never reaches the end, or counts down, which isn't followed
+        if step.is_nan() || step <= 0.0 || step != step_max {
+            return None;
+        }
+        let iterations = |first: f64, last: f64| if last < first {
+            0
+        } else {
+            // Casts saturate, so huge or infinite bounds give u32::MAX
+            (((last - first) / step).floor() as u32).saturating_add(1)
+        };
+        Some(CountRange {
+            min: iterations(first_max, last_min),
+            max: iterations(first_min, last_max),
+        })
+    }
+
+    /// Smallest and largest value a numeric expression can have, following
+    /// `random`, rounding and arithmetic
+    fn number_bounds(&self, expr: &Expression) -> Option<(f64, f64)> {
+        match expr {
+            Expression::UnaryCommand(UnaryCommand::Named(name), operand, _) => {
+                let name = name.to_lowercase();
+                match name.as_str() {
+                    // `random [min, mid, max]` is a gaussian between min and max
+                    "random" => match evaluate_expression(operand, &self.variables) {
+                        SqfValue::Array(values) => match values.as_slice() {
+                            [SqfValue::Number(min), _, SqfValue::Number(max)] => Some((*min, *max)),
+                            _ => None,
+                        },
+                        // `random x` never returns x itself, so `floor random 3` is at most 2
+                        _ => self.number_bounds(operand).map(|(min, max)| (
+                            if min < 0.0 { min.next_up() } else { 0.0 },
+                            if max > 0.0 { max.next_down() } else { 0.0 },
+                        )),
+                    },
+                    "floor" | "ceil" | "round" => {
+                        let round = match name.as_str() {
+                            "floor" => f64::floor,
+                            "ceil" => f64::ceil,
+                            _ => f64::round,
+                        };
+                        self.number_bounds(operand).map(|(min, max)| (round(min), round(max)))
+                    }
+                    _ => None,
+                }
+            }
+            Expression::BinaryCommand(command, lhs, rhs, _) => {
+                let operator = match command {
+                    BinaryCommand::Add => "+",
+                    BinaryCommand::Sub => "-",
+                    BinaryCommand::Mul => "*",
+                    BinaryCommand::Named(name) => name.as_str(),
+                    _ => return None,
+                };
+                let (a_min, a_max) = self.number_bounds(lhs)?;
+                let (b_min, b_max) = self.number_bounds(rhs)?;
+                match operator {
+                    "+" => Some((a_min + b_min, a_max + b_max)),
+                    "-" => Some((a_min - b_max, a_max - b_min)),
+                    "*" => {
+                        let products = [a_min * b_min, a_min * b_max, a_max * b_min, a_max * b_max];
+                        Some((products.into_iter().fold(f64::INFINITY, f64::min), products.into_iter().fold(f64::NEG_INFINITY, f64::max)))
+                    }
+                    _ => None,
+                }
+            }
+            expr => match evaluate_expression(expr, &self.variables) {
+                SqfValue::Number(n) => Some((n, n)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Count given after the class by commands taking `[class, count]`, or 1
+    fn argument_count(&self, argument: &ArgumentSignature, lhs: Option<&Expression>, rhs: &Expression) -> u32 {
+        if argument.index != Some(0) {
+            return 1;
+        }
+        let count_argument = ArgumentSignature { index: Some(1), ..*argument };
+        match self.argument_value(&count_argument, lhs, rhs) {
+            Some(SqfValue::Number(count)) if count >= 1.0 => count as u32,
+            _ => 1,
+        }
+    }
+
+    /// Extract class references from a class argument position of a command
+    fn extract_class_from_argument(
+        &mut self,
+        argument: &ArgumentSignature,
+        lhs: Option<&Expression>,
+        rhs: &Expression,
+        context: UsageContext,
+    ) {
+        if let Some(value) = self.argument_value(argument, lhs, rhs) {
+            let confidence = Self::argument_confidence(argument, lhs, rhs);
+            let count = self.argument_count(argument, lhs, rhs);
+            self.record_random_pool(&value, &context);
+            for class_name in value.strings() {
+                self.add_references(class_name, context.clone(), confidence, count, argument.kind);
+            }
+        }
+    }
+
+    /// Remember the candidates of a class argument holding one of several classes
+    fn record_random_pool(&mut self, value: &SqfValue, context: &UsageContext) {
+        let candidates: Vec<PoolCandidate> = match value {
+            SqfValue::Partial(classes) => classes.iter()
+                .map(|class_name| PoolCandidate { class_name: class_name.clone(), weight: None })
+                .collect(),
+            SqfValue::Weighted(classes) => classes.iter()
+                .map(|(class_name, weight)| PoolCandidate { class_name: class_name.clone(), weight: Some(*weight) })
+                .collect(),
+            _ => return,
+        };
+        if candidates.len() < 2 {
+            return;
+        }
+        // Loops pass the same pool on every iteration
+        let pool = RandomPool { context: context.to_string(), candidates };
+        if !self.random_pools.contains(&pool) {
+            self.random_pools.push(pool);
+        }
+    }
+
+    /// Remember strings passed in argument positions that never hold classes
+    fn record_non_class_strings(&mut self, command: &str, lhs: Option<&Expression>, rhs: &Expression) {
+        let arguments: Vec<_> = self.signatures.non_class_arguments(command)
+            .copied()
+            .collect();
+        for argument in arguments {
+            if let Some(value) = self.argument_value(&argument, lhs, rhs) {
+                let strings = value.strings();
+                if argument.role == ArgumentRole::Texture {
+                    // Procedural textures like "#(argb,8,8,3)color(1,0,0,1)" are not files
+                    self.assets.extend(strings.iter()
+                        .filter(|path| !path.is_empty() && !path.starts_with('#'))
+                        .map(|path| AssetReference { path: path.clone(), context: command.to_string() }));
+                }
+                if argument.role == ArgumentRole::Script {
+                    self.script_paths.extend(strings.iter().filter(|path| !path.is_empty()).cloned());
+                }
+                if argument.role == ArgumentRole::Code {
+                    self.embedded_code.extend(strings.iter().filter(|code| !code.trim().is_empty()).cloned());
+                }
+                self.non_class_strings.extend(strings);
+            }
         }
     }
 
-    /// Add a class reference with usage context
-    fn add_reference(&mut self, class_name: String, context: UsageContext) {
-        self.references.lock().unwrap()
+    /// Add a class reference with usage context, keeping the best confidence seen for it
+    fn add_reference(&mut self, class_name: String, context: UsageContext, confidence: Confidence) {
+        self.add_references(class_name, context, confidence, 1, None);
+    }
+
+    /// Add several instances of a class at once, as many times as the enclosing loops run
+    fn add_references(&mut self, class_name: String, context: UsageContext, confidence: Confidence, count: u32, kind: Option<ItemKind>) {
+        let mut references = self.references.lock().unwrap();
+        let usage = references
             .entry(class_name)
-            .or_insert_with(HashSet::new)
-            .insert(context);
+            .or_insert_with(HashMap::new)
+            .entry(context)
+            .or_insert(Usage { confidence, count: CountRange::exact(0), kind });
+        usage.confidence = usage.confidence.max(confidence);
+        usage.kind = usage.kind.or(kind);
+        usage.count.add(CountRange::exact(count).times(self.repetitions));
     }
 
     /// Get all found class references with their contexts
@@ -265,45 +771,48 @@ impl Evaluator {
         let mut references = Vec::new();
         let refs = self.references.lock().unwrap();
         for (class_name, contexts) in refs.iter() {
-            for context in contexts {
+            // Heuristic references used as marker names, sounds, etc. are not classes
+            let is_non_class = self.non_class_strings.contains(class_name);
+            for (context, usage) in contexts {
+                if is_non_class && *context == UsageContext::DirectReference {
+                    continue;
+                }
                 references.push(ClassReference {
                     class_name: class_name.clone(),
                     context: context.to_string(),
+                    usage: context.clone(),
+                    location: None,
+                    confidence: usage.confidence,
+                    count: usage.count.max,
+                    count_range: Some(usage.count).filter(|count| !count.is_exact()),
+                    removed: matches!(context, UsageContext::RemoveCommand(_)),
+                    kind: usage.kind,
                 });
             }
         }
-        AnalysisResult { references }
-    }
-
-    /// Get a reference to the set of class reference functions
-    pub fn get_class_reference_functions(&self) -> &HashSet<String> {
-        &self.class_reference_functions
+        let arsenal_boxes = self.arsenal_boxes.into_values()
+            .map(|(box_id, items)| ArsenalBox { box_id, items: items.into_iter().collect() })
+            .collect();
+        AnalysisResult {
+            references,
+            assets: self.assets.into_iter().collect(),
+            script_paths: self.script_paths.into_iter().collect(),
+            embedded_code: self.embedded_code,
+            arsenal_boxes,
+            removed_slots: self.removed_slots.into_iter().map(str::to_string).collect(),
+            inventory_operations: self.inventory_operations,
+            random_pools: self.random_pools,
+        }
     }
 
-    /// Quick check if content contains any class reference functions
+    /// Quick check if content mentions any function of a registry
     /// Uses a buffered reader to efficiently scan large files
-    pub fn should_evaluate<R: std::io::BufRead>(reader: R) -> bool {
-        // Create default evaluator to get the function set
-        let evaluator = Self::default();
-        let functions = evaluator.get_class_reference_functions();
-        
-        // Convert all functions to lowercase once
-        let functions_lower: HashSet<String> = functions.iter()
-            .map(|f| f.to_lowercase())
-            .collect();
-            
-        // Buffer for the current line
-        let mut line_buffer = String::new();
-        
+    pub fn should_evaluate_with<R: std::io::BufRead>(reader: R, functions: &FunctionRegistry) -> bool {
         // Read the file line by line
         for line in reader.lines() {
             match line {
                 Ok(line) => {
-                    line_buffer.clear();
-                    line_buffer.push_str(&line.to_lowercase());
-                    
-                    // Check if any function exists in this line
-                    if functions_lower.iter().any(|func| line_buffer.contains(func)) {
+                    if functions.is_mentioned_in(&line) {
                         return true;
                     }
                 }
@@ -317,7 +826,15 @@ impl Evaluator {
 
 /// Evaluate an SQF script to extract all class references
 pub fn evaluate_sqf(statements: &Statements) -> Result<AnalysisResult, String> {
-    let mut evaluator = Evaluator::default();
+    evaluate_sqf_with_options(statements, &AnalysisOptions::default())
+}
+
+/// Evaluate an SQF script with explicit analysis options
+pub fn evaluate_sqf_with_options(
+    statements: &Statements,
+    options: &AnalysisOptions,
+) -> Result<AnalysisResult, String> {
+    let mut evaluator = Evaluator::with_options(options.clone());
     evaluator.evaluate_script(statements);
     Ok(evaluator.into_result())
 }
@@ -325,41 +842,20 @@ pub fn evaluate_sqf(statements: &Statements) -> Result<AnalysisResult, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use hemtt_sqf::parser::{run as parse_sqf, database::Database};
-    use hemtt_workspace::reporting::{Processed, Output, Token, Symbol};
-    use hemtt_workspace::position::{Position, LineCol};
-    use std::sync::Arc;
-    use hemtt_common::config::PDriveOption;
-    use hemtt_workspace::Workspace;
-    use std::io::Write;
+    use crate::test_support::parse_code;
 
     fn evaluate_code(code: &str) -> Vec<ClassReference> {
-        let database = Database::a3(false);
-        let workspace = Workspace::builder()
-            .memory()
-            .finish(None, false, &PDriveOption::Disallow)
-            .unwrap();
-        let test_file = workspace.join("test.sqf").unwrap();
-        test_file.create_file().unwrap().write_all(code.as_bytes()).unwrap();
-        
-        let processed = Processed::new(
-            vec![Output::Direct(Arc::new(Token::new(
-                Symbol::Word(code.to_string()),
-                Position::new(
-                    LineCol(0, (1, 0)),
-                    LineCol(code.len(), (1, code.len())),
-                    test_file.clone(),
-                )
-            )))],
-            HashMap::new(),
-            vec![],
-            false,
-        ).unwrap();
-        
-        let statements = parse_sqf(&database, &processed).unwrap();
+        let statements = parse_code(code);
         evaluate_sqf(&statements).unwrap().references
     }
 
+    fn evaluate_code_strict(code: &str) -> Vec<ClassReference> {
+        let statements = parse_code(code);
+        evaluate_sqf_with_options(&statements, &AnalysisOptions::strict())
+            .unwrap()
+            .references
+    }
+
     #[test]
     fn test_add_commands() {
         let code = r#"
@@ -474,6 +970,58 @@ mod tests {
         assert!(reference_names.contains(&"rhsusf_spcs_ocp".to_string()));
     }
 
+    #[test]
+    fn test_conditional_item_assignment() {
+        let code = r#"
+            if (_isMedic) then {
+                _weapon = "rhs_weap_m4a1";
+                _unit addItem "ACE_surgicalKit";
+            } else {
+                _weapon = "rhs_weap_m249_pip";
+                _unit addItem "ACE_EntrenchingTool";
+            };
+            if (_isLeader) then {
+                _vest = "rhsusf_spcs_ocp_squadleader";
+            };
+            _unit addWeapon _weapon;
+            _unit addVest _vest;
+        "#;
+        // Strict mode only reports what reaches the add commands
+        let reference_names: HashSet<_> = evaluate_code_strict(code).into_iter()
+            .map(|r| r.class_name)
+            .collect();
+
+        assert!(reference_names.contains("rhs_weap_m4a1"));
+        assert!(reference_names.contains("rhs_weap_m249_pip"));
+        assert!(reference_names.contains("ACE_surgicalKit"));
+        assert!(reference_names.contains("ACE_EntrenchingTool"));
+        assert!(reference_names.contains("rhsusf_spcs_ocp_squadleader"));
+    }
+
+    #[test]
+    fn test_for_each_resolves_elements() {
+        let code = r#"
+            _magArray = ["rhs_mag_30Rnd_556x45_M855A1_Stanag", "rhs_mag_m67"];
+            {_unit addItemToBackpack _x} forEach _magArray;
+            {
+                _unit addItemToVest _x;
+            } forEach ["ACE_fieldDressing", "ACE_morphine"];
+        "#;
+        let references = evaluate_code_strict(code);
+
+        let backpack: HashSet<_> = references.iter()
+            .filter(|r| r.context.contains("addItemToBackpack"))
+            .map(|r| r.class_name.as_str())
+            .collect();
+        assert_eq!(backpack, HashSet::from(["rhs_mag_30Rnd_556x45_M855A1_Stanag", "rhs_mag_m67"]));
+
+        let vest: HashSet<_> = references.iter()
+            .filter(|r| r.context.contains("addItemToVest"))
+            .map(|r| r.class_name.as_str())
+            .collect();
+        assert_eq!(vest, HashSet::from(["ACE_fieldDressing", "ACE_morphine"]));
+    }
+
     #[test]
     fn test_nested_variables() {
         let code = r#"
@@ -608,16 +1156,47 @@ mod tests {
         assert!(reference_names.contains("Binocular"));
     }
 
+    #[test]
+    fn test_set_unit_loadout() {
+        let code = r#"
+            _loadout = [
+                ["arifle_MX_F", "", "acc_pointer_IR", "optic_Aco", ["30Rnd_65x39_caseless_mag", 30], [], ""],
+                [], [],
+                ["U_B_CombatUniform_mcam", [["FirstAidKit", 1], ["SmokeShell", 2, 1]]],
+                ["V_PlateCarrier1_rgr", []],
+                [],
+                "H_HelmetB", "",
+                [],
+                ["ItemMap", "", "ItemRadio", "ItemCompass", "ItemWatch", ""]
+            ];
+            _unit setUnitLoadout _loadout;
+            player setUnitLoadout "B_Soldier_F";
+        "#;
+        let references = evaluate_code_strict(code);
+
+        let reference_names: HashSet<_> = references.iter()
+            .map(|r| r.class_name.as_str())
+            .collect();
+        for class in [
+            "arifle_MX_F", "acc_pointer_IR", "optic_Aco", "30Rnd_65x39_caseless_mag", "U_B_CombatUniform_mcam",
+            "FirstAidKit", "SmokeShell", "V_PlateCarrier1_rgr", "H_HelmetB", "ItemMap", "ItemRadio", "B_Soldier_F",
+        ] {
+            assert!(reference_names.contains(class), "Missing {}", class);
+        }
+        assert!(!reference_names.contains(""));
+    }
+
     #[test]
     fn test_should_evaluate() {
+        let functions = FunctionRegistry::for_options(&AnalysisOptions::default());
         let content_with_match = "player addWeapon \"rhs_weap_m4a1\";";
-        assert!(Evaluator::should_evaluate(std::io::BufReader::new(content_with_match.as_bytes())));
+        assert!(Evaluator::should_evaluate_with(std::io::BufReader::new(content_with_match.as_bytes()), &functions));
         
         let content_with_arsenal = "items call ace_arsenal_fnc_initBox;";
-        assert!(Evaluator::should_evaluate(std::io::BufReader::new(content_with_arsenal.as_bytes())));
+        assert!(Evaluator::should_evaluate_with(std::io::BufReader::new(content_with_arsenal.as_bytes()), &functions));
         
         let content_without_match = "player setPos [0, 0, 0]; hint \"No class references\";";
-        assert!(!Evaluator::should_evaluate(std::io::BufReader::new(content_without_match.as_bytes())));
+        assert!(!Evaluator::should_evaluate_with(std::io::BufReader::new(content_without_match.as_bytes()), &functions));
     }
 
     #[test]
@@ -653,6 +1232,57 @@ mod tests {
         assert!(reference_names.contains(&"rhsusf_spcs_ocp".to_string()));
     }
 
+    #[test]
+    fn test_arsenal_boxes() {
+        let code = r#"
+            _rifles = ["rhs_weap_m4a1", "rhs_weap_m16a4"];
+            [_box, _rifles + ["ItemMap"]] call ace_arsenal_fnc_initBox;
+            [_BOX, ["ItemMap", "ItemCompass"]] call ace_arsenal_fnc_initBox;
+            [medicCrate, ["ACE_fieldDressing"]] call ace_arsenal_fnc_initBox;
+        "#;
+        let statements = parse_code(code);
+        let boxes = evaluate_sqf(&statements).unwrap().arsenal_boxes;
+
+        assert_eq!(boxes, vec![
+            ArsenalBox {
+                box_id: "_box".to_string(),
+                items: vec!["ItemCompass", "ItemMap", "rhs_weap_m16a4", "rhs_weap_m4a1"].into_iter().map(String::from).collect(),
+            },
+            ArsenalBox {
+                box_id: "medicCrate".to_string(),
+                items: vec!["ACE_fieldDressing".to_string()],
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_bis_virtual_arsenal_functions() {
+        let code = r#"
+            _rifles = ["arifle_MX_F", "arifle_Katiba_F"];
+            _weapons = [_rifles, ["hgun_P07_F"]];
+            [crate, _weapons, true] call BIS_fnc_addVirtualWeaponCargo;
+            [crate, "30Rnd_65x39_caseless_mag"] call BIS_fnc_addVirtualMagazineCargo;
+            [crate, ["B_AssaultPack_mcamo"], false] call bis_fnc_addVirtualBackpackCargo;
+            [crate, ["%ALL"], true] call BIS_fnc_addVirtualItemCargo;
+            _args = [crate, ["ItemGPS"], true];
+            _args call BIS_fnc_addVirtualItemCargo;
+        "#;
+        let statements = parse_code(code);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        let names: HashSet<_> = result.references.iter().map(|r| r.class_name.as_str()).collect();
+        for class in ["arifle_MX_F", "arifle_Katiba_F", "hgun_P07_F", "30Rnd_65x39_caseless_mag", "B_AssaultPack_mcamo", "ItemGPS"] {
+            assert!(names.contains(class), "Missing {}", class);
+        }
+        assert!(!names.contains("%ALL"));
+
+        let crate_box = result.arsenal_boxes.iter().find(|b| b.box_id == "crate").unwrap();
+        assert_eq!(crate_box.items.len(), 5);
+        // The box of arguments passed as a variable isn't known
+        let unknown = result.arsenal_boxes.iter().find(|b| b.box_id == UNKNOWN_OBJECT_ID).unwrap();
+        assert_eq!(unknown.items, ["ItemGPS"]);
+    }
+
     #[test]
     fn test_real_arsenal_file() {
         let code = include_str!("../tests/example_data/arsenal.sqf");
@@ -714,4 +1344,374 @@ mod tests {
             println!("  {}", name);
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_strict_mode_skips_scope_guesses() {
+        let code = r#"
+            _marker = "respawn_west";
+            _weapon = "rhs_weap_m4a1";
+            _mags = [];
+            _mags pushBack "rhs_mag_30Rnd_556x45_M855A1_Stanag";
+            _unit addWeapon _weapon;
+            _unit addVest "rhsusf_spcs_ocp";
+        "#;
+
+        let lenient: HashSet<_> = evaluate_code(code).into_iter()
+            .map(|r| r.class_name)
+            .collect();
+        assert!(lenient.contains("respawn_west"));
+
+        let strict: HashSet<_> = evaluate_code_strict(code).into_iter()
+            .map(|r| r.class_name)
+            .collect();
+        assert!(strict.contains("rhs_weap_m4a1"));
+        assert!(strict.contains("rhsusf_spcs_ocp"));
+        assert!(!strict.contains("respawn_west"));
+        assert!(!strict.contains("rhs_mag_30Rnd_556x45_M855A1_Stanag"));
+    }
+
+    #[test]
+    fn test_strict_mode_keeps_arsenal_arrays() {
+        let code = r#"
+            _items = ["item1"];
+            _items pushBack "item2";
+            [_box, _items] call ace_arsenal_fnc_initBox;
+        "#;
+
+        let strict: HashSet<_> = evaluate_code_strict(code).into_iter()
+            .map(|r| r.class_name)
+            .collect();
+        assert!(strict.contains("item1"));
+        assert!(strict.contains("item2"));
+    }
+
+    #[test]
+    fn test_non_class_arguments_are_filtered() {
+        let code = r#"
+            _marker = "respawn_west";
+            _marker setMarkerType "hd_dot";
+            _anim = "AmovPercMstpSnonWnonDnon";
+            player playMove _anim;
+            _message = "Loadout applied";
+            hint _message;
+            _weapon = "rhs_weap_m4a1";
+            _unit addWeapon _weapon;
+        "#;
+        let references = evaluate_code(code);
+
+        let reference_names: HashSet<_> = references.iter()
+            .map(|r| r.class_name.clone())
+            .collect();
+
+        assert!(reference_names.contains("rhs_weap_m4a1"));
+        assert!(!reference_names.contains("respawn_west"));
+        assert!(!reference_names.contains("AmovPercMstpSnonWnonDnon"));
+        assert!(!reference_names.contains("Loadout applied"));
+    }
+
+    #[test]
+    fn test_user_command_signatures() {
+        let overrides = CommandSignatures::from_toml_str(r#"
+            [setVariable]
+            right = { index = 1, role = "class" }
+        "#).unwrap();
+        let options = AnalysisOptions {
+            command_signatures: Some(Arc::new(CommandSignatures::with_overrides(&overrides))),
+            ..AnalysisOptions::default()
+        };
+
+        let statements = parse_code(r#"
+            _unit setVariable ["spare_weapon", "rhs_weap_m4a1"];
+            _unit addVest "rhsusf_spcs_ocp";
+        "#);
+        let references = evaluate_sqf_with_options(&statements, &options).unwrap().references;
+
+        let reference_names: HashSet<_> = references.iter()
+            .map(|r| r.class_name.clone())
+            .collect();
+        assert!(reference_names.contains("rhs_weap_m4a1"));
+        assert!(reference_names.contains("rhsusf_spcs_ocp"));
+        assert!(!reference_names.contains("spare_weapon"));
+    }
+
+    #[test]
+    fn test_user_function_signatures() {
+        let overrides = CommandSignatures::from_toml_str(r#"
+            [grad_loadout_fnc_addWeapon]
+            left = { index = 1, role = "class", kind = "weapon" }
+            [zen_common_fnc_spawnVehicle]
+            left = { index = 0, role = "class", kind = "vehicle" }
+        "#).unwrap();
+        let options = AnalysisOptions {
+            command_signatures: Some(Arc::new(CommandSignatures::with_overrides(&overrides))),
+            ..AnalysisOptions::default()
+        };
+
+        let statements = parse_code(r#"
+            [_unit, "rhs_weap_ak74m", "ignored_flag"] call GRAD_loadout_fnc_addWeapon;
+            ["B_MRAP_01_F", getPos player] call zen_common_fnc_spawnVehicle;
+            _unit addVest "rhsusf_spcs_ocp";
+        "#);
+        let references = evaluate_sqf_with_options(&statements, &options).unwrap().references;
+
+        let kinds: HashMap<_, _> = references.iter()
+            .map(|r| (r.class_name.as_str(), r.kind))
+            .collect();
+        assert_eq!(kinds.get("rhs_weap_ak74m"), Some(&Some(ItemKind::Weapon)));
+        assert_eq!(kinds.get("B_MRAP_01_F"), Some(&Some(ItemKind::Vehicle)));
+        assert_eq!(kinds.get("rhsusf_spcs_ocp"), Some(&Some(ItemKind::Vest)));
+        assert!(!kinds.contains_key("ignored_flag"));
+        assert!(references.iter().any(|r| r.usage == UsageContext::KnownFunction("GRAD_loadout_fnc_addWeapon".to_string())));
+    }
+
+    #[test]
+    fn test_user_known_functions() {
+        let options = AnalysisOptions {
+            known_functions: Some(Arc::new(["TAG_fnc_giveItems"].into_iter().collect())),
+            ..AnalysisOptions::default()
+        };
+
+        let statements = parse_code(r#"
+            ["ACE_fieldDressing", "ACE_morphine"] call tag_fnc_GIVEITEMS;
+        "#);
+        let references = evaluate_sqf_with_options(&statements, &options).unwrap().references;
+
+        assert!(references.iter().any(|r| r.class_name == "ACE_fieldDressing"));
+        assert!(references.iter().any(|r| r.class_name == "ACE_morphine"));
+        assert!(Evaluator::should_evaluate_with(
+            "[] call TAG_FNC_GIVEITEMS;".as_bytes(),
+            &FunctionRegistry::for_options(&options),
+        ));
+    }
+
+    #[test]
+    fn test_confidence_levels() {
+        let references = evaluate_code(r#"
+            _unit addVest "rhsusf_spcs_ocp";
+            _weapon = "rhs_weap_m4a1";
+            _unit addWeapon _weapon;
+            _marker = "respawn_west";
+            [_box, ["ItemMap"]] call ace_arsenal_fnc_initBox;
+        "#);
+
+        let best = |class_name: &str| references.iter()
+            .filter(|r| r.class_name == class_name)
+            .map(|r| r.confidence)
+            .max();
+        assert_eq!(best("rhsusf_spcs_ocp"), Some(Confidence::High));
+        assert_eq!(best("rhs_weap_m4a1"), Some(Confidence::Medium));
+        assert_eq!(best("respawn_west"), Some(Confidence::Low));
+        assert_eq!(best("ItemMap"), Some(Confidence::High));
+    }
+
+    #[test]
+    fn test_texture_paths_are_assets() {
+        let statements = parse_code(r##"
+            _veh setObjectTextureGlobal [0, "\rhsusf\addons\rhsusf_m1a1\data\body_d_co.paa"];
+            _veh setObjectTexture [1, "#(argb,8,8,3)color(1,0,0,1)"];
+            _veh setObjectMaterialGlobal [0, "\a3\data_f\default.rvmat"];
+        "##);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        let paths: Vec<_> = result.assets.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, [
+            "\\a3\\data_f\\default.rvmat",
+            "\\rhsusf\\addons\\rhsusf_m1a1\\data\\body_d_co.paa",
+        ]);
+        assert!(result.references.is_empty(), "Texture paths are not classes: {:?}", result.references);
+    }
+
+    #[test]
+    fn test_script_paths() {
+        let statements = parse_code(r#"
+            [player] execVM "loadouts\rifleman.sqf";
+            _path = "functions\fn_gear.sqf";
+            fnc_gear = compile preprocessFileLineNumbers _path;
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        assert_eq!(result.script_paths, ["functions\\fn_gear.sqf", "loadouts\\rifleman.sqf"]);
+        assert!(result.references.iter().all(|r| !r.class_name.ends_with(".sqf")));
+    }
+
+    #[test]
+    fn test_embedded_code() {
+        let statements = parse_code(r#"
+            player addEventHandler ["Respawn", "(_this select 0) addWeapon 'arifle_MX_F'"];
+            _box addAction ["Take medkit", { player addItem "Medikit" }];
+            [] spawn compile "player addItem 'FirstAidKit'";
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        assert_eq!(result.embedded_code, [
+            "(_this select 0) addWeapon 'arifle_MX_F'",
+            "player addItem 'FirstAidKit'",
+        ]);
+        // Code blocks are evaluated in place, and code strings are no classes
+        assert!(result.references.iter().any(|r| r.class_name == "Medikit"));
+        assert!(result.references.iter().all(|r| !r.class_name.contains("addWeapon")));
+    }
+
+    #[test]
+    fn test_config_lookups() {
+        let references = evaluate_code(r#"
+            _name = getText (configFile >> "CfgWeapons" >> "rhs_weap_m4a1" >> "displayName");
+            {
+                _mass = getNumber (configFile >> "CfgMagazines" >> _x >> "mass");
+            } forEach ["rhs_mag_30Rnd_556x45_M855A1_Stanag"];
+            _loadout = missionConfigFile >> "CfgRespawnInventory" >> "WEST1";
+        "#);
+
+        let lookup = |class_name: &str| references.iter()
+            .find(|r| r.class_name == class_name && r.context.starts_with("Looked up in config"));
+        let weapon = lookup("rhs_weap_m4a1").expect("weapon looked up in CfgWeapons");
+        assert_eq!(weapon.context, "Looked up in config: CfgWeapons");
+        assert_eq!(weapon.confidence, Confidence::High);
+        assert_eq!(lookup("rhs_mag_30Rnd_556x45_M855A1_Stanag").map(|r| r.confidence), Some(Confidence::Medium));
+        assert!(lookup("WEST1").is_none());
+        assert!(!references.iter().any(|r| r.class_name == "displayName" || r.class_name == "CfgWeapons"));
+    }
+
+    #[test]
+    fn test_removal_commands() {
+        let statements = parse_code(r#"
+            removeAllWeapons _unit;
+            removeUniform _unit;
+            _unit addWeapon "rhs_weap_m4a1";
+            _unit unlinkItem "NVGoggles";
+            _nvg = "rhsusf_ANPVS_14";
+            _unit removeItem _nvg;
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        let removed: BTreeSet<_> = result.references.iter()
+            .filter(|r| r.removed)
+            .map(|r| r.class_name.as_str())
+            .collect();
+        assert_eq!(removed, BTreeSet::from(["NVGoggles", "rhsusf_ANPVS_14"]));
+        assert!(result.references.iter().any(|r| r.class_name == "rhs_weap_m4a1" && !r.removed));
+        assert!(result.references.iter()
+            .any(|r| r.context == "Removed by command: unlinkItem" && r.confidence == Confidence::High));
+        assert_eq!(result.removed_slots, ["removeAllWeapons", "removeUniform"]);
+    }
+
+    #[test]
+    fn test_inventory_operations() {
+        let statements = parse_code(r#"
+            removeAllWeapons player;
+            player addWeapon "rhs_weap_m4a1";
+            {
+                _unit addMagazine _x;
+            } forEach ["rhs_mag_30Rnd_556x45_M855A1_Stanag", "rhs_mag_30Rnd_556x45_M855A1_Stanag"];
+            _unit addVest (selectRandom ["V_PlateCarrier1_rgr", "V_PlateCarrier2_rgr"]);
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        let operations: Vec<_> = result.inventory_operations.iter()
+            .map(|op| (op.unit.as_str(), op.command.as_str(), op.class_name.as_deref()))
+            .collect();
+        assert_eq!(operations, [
+            ("player", "removeAllWeapons", None),
+            ("player", "addWeapon", Some("rhs_weap_m4a1")),
+            ("_unit", "addMagazine", Some("rhs_mag_30Rnd_556x45_M855A1_Stanag")),
+            ("_unit", "addMagazine", Some("rhs_mag_30Rnd_556x45_M855A1_Stanag")),
+        ]);
+    }
+
+    #[test]
+    fn test_random_pools() {
+        let statements = parse_code(r#"
+            _unit forceAddUniform (selectRandom ["U_B_CombatUniform_mcam", "U_B_CombatUniform_mcam_vest"]);
+            _headgear = selectRandomWeighted ["H_HelmetB", 3, "H_HelmetSpecB", 1];
+            _unit addHeadgear _headgear;
+            _unit addVest "V_PlateCarrier1_rgr";
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        let candidate = |class_name: &str, weight| PoolCandidate { class_name: class_name.to_string(), weight };
+        assert_eq!(result.random_pools, [
+            RandomPool {
+                context: "Used in command: forceAddUniform".to_string(),
+                candidates: vec![
+                    candidate("U_B_CombatUniform_mcam", None),
+                    candidate("U_B_CombatUniform_mcam_vest", None),
+                ],
+            },
+            RandomPool {
+                context: "Used in command: addHeadgear".to_string(),
+                candidates: vec![candidate("H_HelmetB", Some(3.0)), candidate("H_HelmetSpecB", Some(1.0))],
+            },
+        ]);
+        assert!(result.references.iter().any(|r| r.class_name == "H_HelmetSpecB"));
+    }
+
+    #[test]
+    fn test_branch_arrays_are_not_pools() {
+        let statements = parse_code(r#"
+            if (_medic) then {
+                _items = ["ACE_fieldDressing", "ACE_morphine"];
+            } else {
+                _items = ["ACE_elasticBandage"];
+            };
+            { _unit addItem _x } forEach _items;
+            _unit addItem _items;
+        "#);
+        let result = evaluate_sqf(&statements).unwrap();
+
+        assert!(result.random_pools.is_empty());
+        for class_name in ["ACE_fieldDressing", "ACE_morphine", "ACE_elasticBandage"] {
+            assert!(result.references.iter().any(|r| r.class_name == class_name), "{}", class_name);
+        }
+    }
+
+    #[test]
+    fn test_item_counts() {
+        let references = evaluate_code(r#"
+            for "_i" from 1 to 5 do {
+                _unit addItemToBackpack "ACE_fieldDressing";
+            };
+            for "_i" from 0 to 9 step 2 do {
+                for "_j" from 1 to 2 do {
+                    _unit addItemToVest "ACE_morphine";
+                };
+            };
+            _unit addMagazines ["rhs_mag_30Rnd_556x45_M855A1_Stanag", 6];
+            _box addItemCargoGlobal ["ACE_elasticBandage", 20];
+            {
+                _unit addItem _x;
+            } forEach ["ACE_tourniquet", "ACE_tourniquet"];
+            _unit addWeapon "rhs_weap_m4a1";
+        "#);
+
+        let count = |class_name: &str| references.iter()
+            .filter(|r| r.class_name == class_name)
+            .map(|r| r.count)
+            .sum::<u32>();
+        assert_eq!(count("ACE_fieldDressing"), 5);
+        assert_eq!(count("ACE_morphine"), 10);
+        assert_eq!(count("rhs_mag_30Rnd_556x45_M855A1_Stanag"), 6);
+        assert_eq!(count("ACE_elasticBandage"), 20);
+        assert_eq!(count("ACE_tourniquet"), 2);
+        assert_eq!(count("rhs_weap_m4a1"), 1);
+    }
+
+    #[test]
+    fn test_random_loop_bounds() {
+        let references = evaluate_code(r#"
+            for "_i" from 1 to 3 + (floor random 3) do {
+                _unit addMagazine "rhs_mag_30Rnd_556x45_M855A1_Stanag";
+            };
+            for "_i" from 1 to 4 do {
+                _unit addItem "ACE_fieldDressing";
+            };
+        "#);
+
+        let reference = |class_name: &str| references.iter().find(|r| r.class_name == class_name).unwrap();
+        let magazines = reference("rhs_mag_30Rnd_556x45_M855A1_Stanag");
+        assert_eq!(magazines.count_range, Some(CountRange { min: 3, max: 5 }));
+        assert_eq!(magazines.count, 5);
+        let bandages = reference("ACE_fieldDressing");
+        assert_eq!(bandages.count_range, None);
+        assert_eq!(bandages.count, 4);
+    }
+}
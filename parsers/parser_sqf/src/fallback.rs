@@ -0,0 +1,175 @@
+//! Token scan for SQF files that can't be parsed
+//!
+//! A syntax error anywhere in a file makes the parser reject all of it. When
+//! soft failing, the file is instead split into statements at `;`, `{` and
+//! `}`, and every quoted identifier in a statement that mentions a known
+//! command or function is taken as a class name. Nothing is evaluated, so
+//! these references are only [`Confidence::Low`].
+
+use std::collections::BTreeSet;
+
+use crate::models::{ClassReference, Confidence, UsageContext};
+use crate::registry::FunctionRegistry;
+use crate::signatures::CommandSignatures;
+
+/// A token of SQF source, as far as the scan needs to know
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    String(String),
+    Separator,
+    Other,
+}
+
+/// Find class names passed to known commands and functions without parsing
+pub fn scan_tokens(content: &str, functions: &FunctionRegistry) -> Vec<ClassReference> {
+    let signatures = CommandSignatures::embedded();
+    let mut seen = BTreeSet::new();
+    let mut references = Vec::new();
+    let tokens = tokenize(content);
+
+    for statement in tokens.split(|token| *token == Token::Separator) {
+        let Some(command) = statement.iter().find_map(|token| match token {
+            Token::Word(word) if functions.contains(word) => Some(word),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let removed = signatures.removed_class_arguments(command).next().is_some();
+        let kind = signatures.class_arguments(command)
+            .chain(signatures.removed_class_arguments(command))
+            .find_map(|argument| argument.kind);
+        for token in statement {
+            if let Token::String(value) = token {
+                if is_class_name(value) && seen.insert((value.clone(), command.to_lowercase())) {
+                    references.push(ClassReference {
+                        class_name: value.clone(),
+                        context: UsageContext::TokenScan(command.clone()).to_string(),
+                        usage: UsageContext::TokenScan(command.clone()),
+                        location: None,
+                        confidence: Confidence::Low,
+                        count: 1,
+                        count_range: None,
+                        removed,
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+    references
+}
+
+/// Check if a string could be a class name
+fn is_class_name(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split source into tokens, dropping comments and whitespace.
+///
+/// Unterminated strings and comments run to the end of the content rather
+/// than failing, since the content is already known to be broken.
+fn tokenize(content: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' | '\'' => {
+                // Quotes are escaped by doubling them
+                let mut value = String::new();
+                while let Some(c2) = chars.next() {
+                    if c2 == c {
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    value.push(c2);
+                }
+                tokens.push(Token::String(value));
+            }
+            ';' | '{' | '}' => tokens.push(Token::Separator),
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+            c if c.is_whitespace() => {}
+            _ => tokens.push(Token::Other),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::AnalysisOptions;
+
+    fn class_names(code: &str) -> Vec<String> {
+        let functions = FunctionRegistry::for_options(&AnalysisOptions::default());
+        scan_tokens(code, &functions).into_iter().map(|r| r.class_name).collect()
+    }
+
+    #[test]
+    fn test_scan_broken_code() {
+        let code = r#"
+            // _unit addWeapon "commented_out";
+            _unit addWeapon "rhs_weap_m4a1";
+            _unit addItemToVest "ACE_fieldDressing"
+            if (_broken then {
+                _unit forceAddUniform 'rhs_uniform_g3_m81';
+            };
+            [_box, ["ItemMap", "ItemGPS"]] call ace_arsenal_fnc_initBox;
+            hint "Loadout applied";
+            _unit addWeapon "rhs_weap_m4a1";
+        "#;
+
+        assert_eq!(class_names(code), [
+            "rhs_weap_m4a1",
+            "ACE_fieldDressing",
+            "rhs_uniform_g3_m81",
+            "ItemMap",
+            "ItemGPS",
+        ]);
+    }
+
+    #[test]
+    fn test_scan_skips_non_class_strings() {
+        let code = r#"player addItem "a3\ui_f\data\item.paa"; player addItem "He said ""hi"""; /* unterminated"#;
+        assert!(class_names(code).is_empty());
+    }
+
+    #[test]
+    fn test_scan_references_are_low_confidence() {
+        let functions = FunctionRegistry::for_options(&AnalysisOptions::default());
+        let references = scan_tokens("player addWeapon \"arifle_MX_F\"", &functions);
+        assert_eq!(references[0].confidence, Confidence::Low);
+        assert_eq!(references[0].context, "Found by token scan near: addWeapon");
+    }
+}
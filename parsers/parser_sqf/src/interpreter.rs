@@ -0,0 +1,341 @@
+//! Shared partial-evaluation engine for SQF expressions
+//!
+//! Both the evaluator and the array handler need to know what value an
+//! expression holds without executing the script. This module resolves
+//! constants, array literals, concatenations and variable lookups against an
+//! [`Environment`], leaving anything it cannot prove as [`SqfValue::Unknown`].
+
+use hemtt_sqf::{BinaryCommand, Expression, UnaryCommand};
+use std::collections::HashMap;
+
+/// Represents a value in SQF execution
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqfValue {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Array(Vec<SqfValue>),
+    /// One of several possible strings (e.g. the result of `selectRandom`)
+    Partial(Vec<String>),
+    /// One of several strings picked by `selectRandomWeighted`, with their weights
+    Weighted(Vec<(String, f64)>),
+    /// One of several values that aren't all strings, such as an array
+    /// assigned differently in the branches of an `if`
+    Either(Vec<SqfValue>),
+    Unknown,
+}
+
+impl SqfValue {
+    /// Collect every string this value may hold, flattening nested arrays
+    pub fn strings(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        self.collect_strings(&mut result);
+        result
+    }
+
+    fn collect_strings(&self, result: &mut Vec<String>) {
+        match self {
+            SqfValue::String(s) => result.push(s.clone()),
+            SqfValue::Partial(values) => result.extend(values.iter().cloned()),
+            SqfValue::Weighted(values) => result.extend(values.iter().map(|(value, _)| value.clone())),
+            SqfValue::Array(values) | SqfValue::Either(values) => {
+                for value in values {
+                    value.collect_strings(result);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Get the value as a number if it is a known constant
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            SqfValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// One of two values, keeping each alternative whole
+    pub fn either(first: SqfValue, second: SqfValue) -> SqfValue {
+        let mut alternatives = Vec::new();
+        for value in [first, second] {
+            let values = match value {
+                SqfValue::Either(values) => values,
+                value => vec![value],
+            };
+            for value in values {
+                if !alternatives.contains(&value) {
+                    alternatives.push(value);
+                }
+            }
+        }
+        if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            SqfValue::Either(alternatives)
+        }
+    }
+
+    /// Concatenate two values the way SQF's `+` operator does for arrays
+    pub fn concat(self, other: SqfValue) -> SqfValue {
+        match (self, other) {
+            // Each alternative is concatenated on its own
+            (SqfValue::Either(values), other) => SqfValue::Either(values.into_iter().map(|value| value.concat(other.clone())).collect()),
+            (value, SqfValue::Either(others)) => SqfValue::Either(others.into_iter().map(|other| value.clone().concat(other)).collect()),
+            (SqfValue::Array(mut left), SqfValue::Array(right)) => {
+                left.extend(right);
+                SqfValue::Array(left)
+            }
+            (SqfValue::Array(mut arr), other) => {
+                arr.push(other);
+                SqfValue::Array(arr)
+            }
+            (other, SqfValue::Array(mut arr)) => {
+                arr.insert(0, other);
+                SqfValue::Array(arr)
+            }
+            (SqfValue::Number(a), SqfValue::Number(b)) => SqfValue::Number(a + b),
+            (SqfValue::String(a), SqfValue::String(b)) => SqfValue::String(a + &b),
+            (lhs, rhs) => SqfValue::Array(vec![lhs, rhs]),
+        }
+    }
+}
+
+/// Variable environment used during partial evaluation
+///
+/// SQF variable names are case-insensitive, so all lookups are normalized to
+/// lowercase.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    variables: HashMap<String, SqfValue>,
+}
+
+impl Environment {
+    /// Create an empty environment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up the value of a variable
+    pub fn get(&self, name: &str) -> Option<&SqfValue> {
+        self.variables.get(&name.to_lowercase())
+    }
+
+    /// Assign a value to a variable
+    pub fn set(&mut self, name: &str, value: SqfValue) {
+        self.variables.insert(name.to_lowercase(), value);
+    }
+
+    /// Remove a variable, returning its previous value
+    pub fn remove(&mut self, name: &str) -> Option<SqfValue> {
+        self.variables.remove(&name.to_lowercase())
+    }
+
+    /// Combine the environments after two alternative branches.
+    ///
+    /// Variables the branches agree on keep their value. Others hold any of
+    /// the strings they may have after either branch, or when either holds an
+    /// array, either value whole, so arrays keep their shape for `forEach` and
+    /// indexed arguments. Where one branch didn't assign a variable, its value
+    /// before the branches is used.
+    pub fn merge_branches(before: &Environment, first: &Environment, second: &Environment) -> Environment {
+        let mut merged = Environment::new();
+        for name in first.variables.keys().chain(second.variables.keys()) {
+            if merged.variables.contains_key(name) {
+                continue;
+            }
+            let value_after = |env: &Environment| env.variables.get(name)
+                .or_else(|| before.variables.get(name))
+                .cloned()
+                .unwrap_or(SqfValue::Unknown);
+            let (a, b) = (value_after(first), value_after(second));
+
+            let value = if a == b {
+                a
+            } else if [&a, &b].iter().any(|value| matches!(value, SqfValue::Array(_) | SqfValue::Either(_))) {
+                SqfValue::either(a, b)
+            } else {
+                let mut strings = a.strings();
+                for string in b.strings() {
+                    if !strings.contains(&string) {
+                        strings.push(string);
+                    }
+                }
+                if strings.is_empty() { SqfValue::Unknown } else { SqfValue::Partial(strings) }
+            };
+            merged.variables.insert(name.clone(), value);
+        }
+        merged
+    }
+}
+
+/// Evaluate an expression to the value it is known to hold
+pub fn evaluate_expression(expr: &Expression, env: &Environment) -> SqfValue {
+    match expr {
+        Expression::String(s, _, _) => SqfValue::String(s.to_string()),
+        Expression::Number(n, _) => SqfValue::Number(n.0 as f64),
+        Expression::Boolean(b, _) => SqfValue::Boolean(*b),
+        Expression::Array(elements, _) => SqfValue::Array(
+            elements.iter().map(|e| evaluate_expression(e, env)).collect(),
+        ),
+        Expression::Variable(name, _) => env.get(name).cloned().unwrap_or(SqfValue::Unknown),
+        Expression::BinaryCommand(BinaryCommand::Add, lhs, rhs, _) => {
+            evaluate_expression(lhs, env).concat(evaluate_expression(rhs, env))
+        }
+        Expression::BinaryCommand(BinaryCommand::Named(name), lhs, rhs, _) if name == "+" => {
+            evaluate_expression(lhs, env).concat(evaluate_expression(rhs, env))
+        }
+        Expression::UnaryCommand(UnaryCommand::Named(name), operand, _)
+            if name.eq_ignore_ascii_case("selectrandom")
+                || name.eq_ignore_ascii_case("selectrandomweighted") =>
+        {
+            let values = match evaluate_expression(operand, env) {
+                SqfValue::Array(values) => values,
+                _ => return SqfValue::Unknown,
+            };
+            if !name.eq_ignore_ascii_case("selectrandomweighted") {
+                return SqfValue::Partial(values.iter().flat_map(|v| v.strings()).collect());
+            }
+            // selectRandomWeighted alternates candidates and weights
+            let pairs: Option<Vec<_>> = values.chunks(2)
+                .map(|pair| match pair {
+                    [SqfValue::String(class), weight] => weight.as_number().map(|w| (class.clone(), w)),
+                    _ => None,
+                })
+                .collect();
+            match pairs {
+                Some(pairs) => SqfValue::Weighted(pairs),
+                None => SqfValue::Partial(values.iter().step_by(2).flat_map(|v| v.strings()).collect()),
+            }
+        }
+        Expression::UnaryCommand(UnaryCommand::Minus, operand, _) => {
+            match evaluate_expression(operand, env) {
+                SqfValue::Number(n) => SqfValue::Number(-n),
+                _ => SqfValue::Unknown,
+            }
+        }
+        _ => SqfValue::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_code;
+    use hemtt_sqf::Statement;
+
+    /// Evaluate every assignment in the code and return the resulting environment
+    fn run(code: &str) -> Environment {
+        let statements = parse_code(code);
+        let mut env = Environment::new();
+        for statement in statements.content() {
+            if let Statement::AssignLocal(name, expr, _) | Statement::AssignGlobal(name, expr, _) = statement {
+                let value = evaluate_expression(expr, &env);
+                env.set(name, value);
+            }
+        }
+        env
+    }
+
+    #[test]
+    fn test_merge_branches() {
+        let mut before = Environment::new();
+        before.set("_kept", SqfValue::String("ItemMap".to_string()));
+        before.set("_vest", SqfValue::String("V_Rangemaster_belt".to_string()));
+
+        let mut first = before.clone();
+        first.set("_weapon", SqfValue::String("arifle_MX_F".to_string()));
+        first.set("_vest", SqfValue::String("V_PlateCarrier1_rgr".to_string()));
+        let mut second = before.clone();
+        second.set("_weapon", SqfValue::String("arifle_Katiba_F".to_string()));
+        second.set("_count", SqfValue::Number(2.0));
+
+        let merged = Environment::merge_branches(&before, &first, &second);
+        assert_eq!(merged.get("_kept"), Some(&SqfValue::String("ItemMap".to_string())));
+        assert_eq!(merged.get("_weapon").unwrap().strings(), ["arifle_MX_F", "arifle_Katiba_F"]);
+        assert_eq!(merged.get("_vest").unwrap().strings(), ["V_PlateCarrier1_rgr", "V_Rangemaster_belt"]);
+        assert_eq!(merged.get("_count"), Some(&SqfValue::Unknown));
+    }
+
+    #[test]
+    fn test_merge_branches_keeps_arrays() {
+        let array = |items: &[&str]| SqfValue::Array(items.iter().map(|item| SqfValue::String(item.to_string())).collect());
+        let before = Environment::new();
+        let mut first = before.clone();
+        first.set("_items", array(&["ACE_fieldDressing", "ACE_morphine"]));
+        let mut second = before.clone();
+        second.set("_items", array(&["ACE_elasticBandage"]));
+
+        let merged = Environment::merge_branches(&before, &first, &second);
+        let items = merged.get("_items").unwrap();
+        assert_eq!(items, &SqfValue::Either(vec![array(&["ACE_fieldDressing", "ACE_morphine"]), array(&["ACE_elasticBandage"])]));
+        assert_eq!(items.strings(), ["ACE_fieldDressing", "ACE_morphine", "ACE_elasticBandage"]);
+        assert_eq!(items.clone().concat(array(&["ItemMap"])).strings(),
+            ["ACE_fieldDressing", "ACE_morphine", "ItemMap", "ACE_elasticBandage", "ItemMap"]);
+    }
+
+    #[test]
+    fn test_constants() {
+        let env = run(r#"
+            _name = "rhs_weap_m4a1";
+            _count = 5;
+            _flag = true;
+        "#);
+
+        assert_eq!(env.get("_name"), Some(&SqfValue::String("rhs_weap_m4a1".to_string())));
+        assert_eq!(env.get("_count"), Some(&SqfValue::Number(5.0)));
+        assert_eq!(env.get("_flag"), Some(&SqfValue::Boolean(true)));
+    }
+
+    #[test]
+    fn test_array_concatenation() {
+        let env = run(r#"
+            _a = ["item1", "item2"];
+            _b = ["item3"];
+            _c = _a + _b;
+        "#);
+
+        let value = env.get("_c").unwrap();
+        assert_eq!(value.strings(), vec!["item1", "item2", "item3"]);
+    }
+
+    #[test]
+    fn test_variable_names_are_case_insensitive() {
+        let env = run(r#"
+            _Weapons = ["weapon1"];
+            _all = _WEAPONS + ["weapon2"];
+        "#);
+
+        assert_eq!(env.get("_all").unwrap().strings(), vec!["weapon1", "weapon2"]);
+    }
+
+    #[test]
+    fn test_select_random_produces_partial() {
+        let env = run(r#"
+            _uniform = selectRandom ["uniform1", "uniform2"];
+            _vest = selectRandomWeighted ["vest1", 3, "vest2", 1];
+        "#);
+
+        assert_eq!(
+            env.get("_uniform"),
+            Some(&SqfValue::Partial(vec!["uniform1".to_string(), "uniform2".to_string()]))
+        );
+        assert_eq!(
+            env.get("_vest"),
+            Some(&SqfValue::Weighted(vec![("vest1".to_string(), 3.0), ("vest2".to_string(), 1.0)]))
+        );
+        assert_eq!(env.get("_vest").unwrap().strings(), ["vest1", "vest2"]);
+    }
+
+    #[test]
+    fn test_unknown_values() {
+        let env = run(r#"
+            _unit = player;
+            _missing = _undefined;
+        "#);
+
+        assert_eq!(env.get("_unit"), Some(&SqfValue::Unknown));
+        assert_eq!(env.get("_missing"), Some(&SqfValue::Unknown));
+        assert!(env.get("_never_assigned").is_none());
+    }
+}
@@ -0,0 +1,200 @@
+//! Reconstruction of unit loadouts from inventory commands
+//!
+//! The evaluator records every add and remove command applied to a unit in
+//! script order. Replaying them per unit gives the loadout the script ends up
+//! with: `removeAllWeapons` followed by `addWeapon` leaves just the added
+//! weapon, and a class added twice is counted twice. Units are named by the
+//! variable or command they're held in (`_unit`, `player`), ignoring case.
+//!
+//! Branches of an `if` are both replayed in source order, and
+//! `setUnitLoadout` and container cargo commands are not simulated.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+/// One add or remove command applied to a unit, in script order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InventoryOperation {
+    /// Variable or command naming the unit, e.g. `_unit` or `player`
+    pub unit: String,
+    /// Command as written, e.g. `addWeapon`
+    pub command: String,
+    /// Class added or removed; unset for commands emptying a whole slot
+    pub class_name: Option<String>,
+}
+
+/// Loadout of one unit after replaying its inventory commands
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UnitLoadout {
+    /// Variable or command naming the unit, as first written
+    pub unit: String,
+    pub uniform: Option<String>,
+    pub vest: Option<String>,
+    pub backpack: Option<String>,
+    pub headgear: Option<String>,
+    pub goggles: Option<String>,
+    /// Weapons by class, with how many were added
+    pub weapons: BTreeMap<String, usize>,
+    /// Magazines by class, with how many were added
+    pub magazines: BTreeMap<String, usize>,
+    /// Items carried in the containers by class, with how many were added
+    pub items: BTreeMap<String, usize>,
+    /// Items linked to the unit's slots (map, radio, NVGs, ...)
+    pub assigned_items: BTreeSet<String>,
+}
+
+impl UnitLoadout {
+    fn new(unit: &str) -> Self {
+        Self { unit: unit.to_string(), ..Self::default() }
+    }
+
+    /// Apply one command, returning `false` if it isn't an inventory command
+    fn apply(&mut self, command: &str, class_name: Option<&str>) -> bool {
+        let command = command.to_lowercase();
+        match (command.as_str(), class_name) {
+            ("forceadduniform" | "adduniform", Some(class)) => self.uniform = Some(class.to_string()),
+            ("addvest", Some(class)) => self.vest = Some(class.to_string()),
+            ("addbackpack" | "addbackpackglobal", Some(class)) => self.backpack = Some(class.to_string()),
+            ("addheadgear", Some(class)) => self.headgear = Some(class.to_string()),
+            ("addgoggles", Some(class)) => self.goggles = Some(class.to_string()),
+            ("addweapon" | "addweaponglobal", Some(class)) => add(&mut self.weapons, class),
+            ("addmagazine" | "addmagazineglobal", Some(class)) => add(&mut self.magazines, class),
+            ("additem" | "additemtouniform" | "additemtovest" | "additemtobackpack", Some(class)) => {
+                add(&mut self.items, class)
+            }
+            ("linkitem", Some(class)) => {
+                self.assigned_items.insert(class.to_string());
+            }
+            ("removeweapon" | "removeweaponglobal", Some(class)) => remove(&mut self.weapons, class),
+            ("removemagazine" | "removemagazineglobal", Some(class)) => remove(&mut self.magazines, class),
+            ("removemagazines", Some(class)) => {
+                self.magazines.remove(class);
+            }
+            ("removeitem" | "removeitemfromuniform" | "removeitemfromvest" | "removeitemfrombackpack", Some(class)) => {
+                // Linked items are removed when none is carried
+                if self.items.contains_key(class) {
+                    remove(&mut self.items, class);
+                } else {
+                    self.assigned_items.remove(class);
+                }
+            }
+            ("removeitems", Some(class)) => {
+                self.items.remove(class);
+            }
+            // An unassigned item moves from its slot into the containers
+            ("unassignitem", Some(class)) => {
+                if self.assigned_items.remove(class) {
+                    add(&mut self.items, class);
+                }
+            }
+            ("unlinkitem", Some(class)) => {
+                self.assigned_items.remove(class);
+            }
+            ("removeuniform", _) => self.uniform = None,
+            ("removevest", _) => self.vest = None,
+            ("removebackpack" | "removebackpackglobal", _) => self.backpack = None,
+            ("removeheadgear", _) => self.headgear = None,
+            ("removegoggles", _) => self.goggles = None,
+            ("removeallweapons", _) => {
+                self.weapons.clear();
+                self.magazines.clear();
+            }
+            ("removeallitems", _) => self.items.clear(),
+            ("removeallitemswithmagazines", _) => {
+                self.items.clear();
+                self.magazines.clear();
+            }
+            ("removeallassigneditems", _) => self.assigned_items.clear(),
+            ("removeallcontainers", _) => {
+                self.uniform = None;
+                self.vest = None;
+                self.backpack = None;
+                self.items.clear();
+                self.magazines.clear();
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+fn add(counts: &mut BTreeMap<String, usize>, class_name: &str) {
+    *counts.entry(class_name.to_string()).or_default() += 1;
+}
+
+fn remove(counts: &mut BTreeMap<String, usize>, class_name: &str) {
+    if let Some(count) = counts.get_mut(class_name) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(class_name);
+        }
+    }
+}
+
+/// Replay inventory commands in order, giving the final loadout of each unit.
+///
+/// Units are sorted by lowercase name. Commands that don't change a unit's
+/// inventory are skipped, and units without any inventory command are left out.
+pub fn simulate_loadouts(operations: &[InventoryOperation]) -> Vec<UnitLoadout> {
+    let mut loadouts: BTreeMap<String, UnitLoadout> = BTreeMap::new();
+    let mut changed = BTreeSet::new();
+    for operation in operations {
+        let key = operation.unit.to_lowercase();
+        let loadout = loadouts.entry(key.clone()).or_insert_with(|| UnitLoadout::new(&operation.unit));
+        if loadout.apply(&operation.command, operation.class_name.as_deref()) {
+            changed.insert(key);
+        }
+    }
+    loadouts.into_iter()
+        .filter(|(key, _)| changed.contains(key))
+        .map(|(_, loadout)| loadout)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(unit: &str, command: &str, class_name: Option<&str>) -> InventoryOperation {
+        InventoryOperation {
+            unit: unit.to_string(),
+            command: command.to_string(),
+            class_name: class_name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_replay_in_order() {
+        let loadouts = simulate_loadouts(&[
+            operation("_unit", "addWeapon", Some("arifle_MX_F")),
+            operation("_unit", "removeAllWeapons", None),
+            operation("_unit", "forceAddUniform", Some("U_B_CombatUniform_mcam")),
+            operation("_unit", "addVest", Some("V_PlateCarrier1_rgr")),
+            operation("_unit", "addWeapon", Some("rhs_weap_m4a1")),
+            operation("_unit", "addMagazine", Some("30Rnd_556x45_Stanag")),
+            operation("_UNIT", "addMagazine", Some("30Rnd_556x45_Stanag")),
+            operation("_unit", "addMagazine", Some("30Rnd_556x45_Stanag")),
+            operation("_unit", "removeMagazine", Some("30Rnd_556x45_Stanag")),
+            operation("_unit", "linkItem", Some("NVGoggles")),
+            operation("_unit", "unassignItem", Some("NVGoggles")),
+            operation("_unit", "removeVest", None),
+            operation("player", "addHeadgear", Some("H_HelmetB")),
+            operation("_box", "addItemCargoGlobal", Some("FirstAidKit")),
+        ]);
+
+        assert_eq!(loadouts.len(), 2);
+        let player = &loadouts[1];
+        assert_eq!(player.unit, "player");
+        assert_eq!(player.headgear.as_deref(), Some("H_HelmetB"));
+
+        let unit = &loadouts[0];
+        assert_eq!(unit.unit, "_unit");
+        assert_eq!(unit.uniform.as_deref(), Some("U_B_CombatUniform_mcam"));
+        assert_eq!(unit.vest, None);
+        assert_eq!(unit.weapons, BTreeMap::from([("rhs_weap_m4a1".to_string(), 1)]));
+        assert_eq!(unit.magazines, BTreeMap::from([("30Rnd_556x45_Stanag".to_string(), 2)]));
+        assert_eq!(unit.items, BTreeMap::from([("NVGoggles".to_string(), 1)]));
+        assert!(unit.assigned_items.is_empty());
+    }
+}
@@ -5,21 +5,40 @@
 
 // Declare modules
 mod models;
+mod interpreter;
+mod signatures;
+mod registry;
 mod evaluator;
 mod array_handler;
+mod loadout;
+mod fallback;
+mod inventory_sim;
+mod location;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 use std::path::Path;
 use std::fs;
 use std::sync::Arc;
 use std::collections::HashMap;
-use std::io;
+use std::io::{self, Write};
 use hemtt_sqf::parser::{run as parse_sqf, database::Database, ParserError};
 use hemtt_sqf::Error as SqfError;
 
-use hemtt_workspace::{reporting::{Processed, Output, Token, Symbol}, position::{Position, LineCol}, WorkspacePath, Error as WorkspaceError};
+use hemtt_common::config::PDriveOption;
+use hemtt_workspace::{reporting::{Processed, Output, Token, Symbol}, position::{Position, LineCol}, Workspace, WorkspacePath, Error as WorkspaceError};
 
 // Export our public types
-pub use models::{ClassReference, UsageContext};
+pub use location::{locate_class_name, SourceLocation};
+pub use models::{AnalysisResult, ArsenalBox, AssetReference, ClassReference, Confidence, CountRange, ItemKind, PoolCandidate, RandomPool, UsageContext, AnalysisOptions};
+pub use fallback::scan_tokens;
+pub use inventory_sim::{simulate_loadouts, InventoryOperation, UnitLoadout};
+pub use interpreter::{evaluate_expression, Environment, SqfValue};
+pub use loadout::loadout_classes;
+pub use signatures::{ArgumentRole, ArgumentSignature, CommandSignatures, Operand};
+pub use registry::{FunctionRegistry, ARSENAL_FUNCTIONS, SLOT_REMOVAL_COMMANDS};
 
 #[derive(Debug)]
 pub enum Error {
@@ -56,12 +75,91 @@ impl From<SqfError> for Error {
 /// # Returns
 /// * `Result<Vec<ClassReference>, Error>` - List of found class references or error
 pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>, Error> {
+    parse_file_with_options(file_path, &AnalysisOptions::default())
+}
+
+/// Parse an SQF file with explicit analysis options.
+///
+/// Use [`AnalysisOptions::strict`] when only references provable from command
+/// semantics should be reported.
+pub fn parse_file_with_options(
+    file_path: &Path,
+    options: &AnalysisOptions,
+) -> Result<Vec<ClassReference>, Error> {
+    let functions = FunctionRegistry::for_options(options);
+    Ok(analyze_file(file_path, options, &functions)?
+        .map(|result| result.references)
+        .unwrap_or_default())
+}
+
+/// Extract texture and material paths passed to appearance commands in an SQF file.
+///
+/// Only files mentioning one of those commands are fully parsed.
+pub fn extract_asset_references(file_path: &Path) -> Result<Vec<AssetReference>, Error> {
+    let commands: FunctionRegistry = CommandSignatures::embedded().asset_commands().collect();
+
+    Ok(analyze_file(file_path, &AnalysisOptions::default(), &commands)?
+        .map(|result| result.assets)
+        .unwrap_or_default())
+}
+
+/// Extract the paths of scripts an SQF file runs or compiles, such as
+/// `[] execVM "loadouts\rifleman.sqf"` or `preprocessFileLineNumbers "fn.sqf"`.
+///
+/// Paths are returned as written, relative to the mission root. Only files
+/// mentioning one of those commands are fully parsed.
+pub fn extract_script_paths(file_path: &Path) -> Result<Vec<String>, Error> {
+    let commands: FunctionRegistry = CommandSignatures::embedded().script_commands().collect();
+
+    Ok(analyze_file(file_path, &AnalysisOptions::default(), &commands)?
+        .map(|result| result.script_paths)
+        .unwrap_or_default())
+}
+
+/// Extract the virtual arsenals an SQF file sets up, one per object.
+///
+/// Only files mentioning an arsenal function are fully parsed.
+pub fn extract_arsenal_boxes(file_path: &Path) -> Result<Vec<ArsenalBox>, Error> {
+    Ok(analyze_file(file_path, &AnalysisOptions::default(), &FunctionRegistry::arsenal_functions())?
+        .map(|result| result.arsenal_boxes)
+        .unwrap_or_default())
+}
+
+/// Reconstruct the final loadout of every unit an SQF file equips.
+///
+/// Add and remove commands are replayed in order per unit variable; see
+/// [`simulate_loadouts`] for what is simulated.
+pub fn reconstruct_loadouts(file_path: &Path) -> Result<Vec<UnitLoadout>, Error> {
+    let options = AnalysisOptions::default();
+    Ok(analyze_file(file_path, &options, &FunctionRegistry::for_options(&options))?
+        .map(|result| simulate_loadouts(&result.inventory_operations))
+        .unwrap_or_default())
+}
+
+/// Extract the class arguments of an SQF file that pick one of several classes.
+///
+/// Each pool lists the candidates of one randomized argument, such as a
+/// uniform chosen with `selectRandom`, with the weights given to
+/// `selectRandomWeighted`.
+pub fn extract_random_pools(file_path: &Path) -> Result<Vec<RandomPool>, Error> {
+    let options = AnalysisOptions::default();
+    Ok(analyze_file(file_path, &options, &FunctionRegistry::for_options(&options))?
+        .map(|result| result.random_pools)
+        .unwrap_or_default())
+}
+
+/// Parse and evaluate an SQF file if it mentions any of the given functions
+fn analyze_file(
+    file_path: &Path,
+    options: &AnalysisOptions,
+    functions: &FunctionRegistry,
+) -> Result<Option<AnalysisResult>, Error> {
     // First do a quick scan with buffered reading
     let file = fs::File::open(file_path)?;
     let reader = std::io::BufReader::new(file);
     
-    if !evaluator::Evaluator::should_evaluate(reader) {
-        return Ok(Vec::new());
+    if !evaluator::Evaluator::should_evaluate_with(reader, functions) {
+        return Ok(None);
     }
     
     // If we found a match, now read the whole file for full parsing
@@ -73,6 +171,187 @@ pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>, Error> {
     // Create database with workspace
     let database = Database::a3_with_workspace(&workspace_path, false)?;
 
+    let source = file_path.display().to_string();
+    let mut result = analyze_content(&database, workspace_path, &source, &content, options, functions)?;
+    merge_embedded_code(&mut result, options, functions, 0);
+    locate_references(&mut result.references, &content, file_path);
+    Ok(Some(result))
+}
+
+/// Parse SQF content held in memory, such as an upload, and extract all class
+/// references without touching disk.
+///
+/// `virtual_name` names the content in the memory workspace and in log
+/// messages, e.g. `init.sqf`, and is the file of the references' locations.
+pub fn parse_str(content: &str, virtual_name: &str) -> Result<Vec<ClassReference>, Error> {
+    parse_str_with_options(content, virtual_name, &AnalysisOptions::default())
+}
+
+/// Parse SQF content held in memory with explicit analysis options
+pub fn parse_str_with_options(
+    content: &str,
+    virtual_name: &str,
+    options: &AnalysisOptions,
+) -> Result<Vec<ClassReference>, Error> {
+    let functions = FunctionRegistry::for_options(options);
+    if !evaluator::Evaluator::should_evaluate_with(content.as_bytes(), &functions) {
+        return Ok(Vec::new());
+    }
+
+    let mut result = analyze_code(content, virtual_name, options, &functions)?;
+    merge_embedded_code(&mut result, options, &functions, 0);
+    locate_references(&mut result.references, content, Path::new(virtual_name));
+    Ok(result.references)
+}
+
+/// Analyze SQF content held in memory with explicit analysis options, keeping
+/// the assets and script paths it finds along with its class references.
+///
+/// `None` when the content mentions none of the class, asset or script
+/// commands, in which case it isn't parsed.
+pub fn analyze_str_with_options(
+    content: &str,
+    virtual_name: &str,
+    options: &AnalysisOptions,
+) -> Result<Option<AnalysisResult>, Error> {
+    let functions = FunctionRegistry::for_options(options);
+    let signatures = options.command_signatures.clone()
+        .unwrap_or_else(CommandSignatures::embedded);
+    let mut mentioned = functions.clone();
+    mentioned.extend(signatures.asset_commands().chain(signatures.script_commands()));
+    if !evaluator::Evaluator::should_evaluate_with(content.as_bytes(), &mentioned) {
+        return Ok(None);
+    }
+
+    let mut result = analyze_code(content, virtual_name, options, &functions)?;
+    merge_embedded_code(&mut result, options, &functions, 0);
+    locate_references(&mut result.references, content, Path::new(virtual_name));
+    Ok(Some(result))
+}
+
+/// Set the location of each reference to where its class name first appears in the content
+fn locate_references(references: &mut [ClassReference], content: &str, file: &Path) {
+    for reference in references {
+        reference.location = locate_class_name(content, &reference.class_name)
+            .map(|(line, column)| SourceLocation { file: file.to_path_buf(), line, column });
+    }
+}
+
+/// Extract class references from SQF code held in memory, such as the code
+/// of a trigger or an init attribute in mission.sqm.
+///
+/// References have no location, since the code isn't a file of its own.
+pub fn parse_code_with_options(code: &str, options: &AnalysisOptions) -> Result<Vec<ClassReference>, Error> {
+    let functions = FunctionRegistry::for_options(options);
+    if !evaluator::Evaluator::should_evaluate_with(code.as_bytes(), &functions) {
+        return Ok(Vec::new());
+    }
+
+    let mut result = analyze_code(code, "code.sqf", options, &functions)?;
+    merge_embedded_code(&mut result, options, &functions, 0);
+    Ok(result.references)
+}
+
+/// Extract the virtual arsenals SQF code held in memory sets up, such as the
+/// init attribute of an object in mission.sqm
+pub fn extract_arsenal_boxes_from_code(code: &str) -> Result<Vec<ArsenalBox>, Error> {
+    let options = AnalysisOptions::default();
+    let functions = FunctionRegistry::arsenal_functions();
+    if !evaluator::Evaluator::should_evaluate_with(code.as_bytes(), &functions) {
+        return Ok(Vec::new());
+    }
+
+    let mut result = analyze_code(code, "code.sqf", &options, &functions)?;
+    merge_embedded_code(&mut result, &options, &functions, 0);
+    Ok(result.arsenal_boxes)
+}
+
+/// Extract the add and remove commands SQF content held in memory applies
+/// to units, in script order.
+///
+/// Replay them with [`simulate_loadouts`] for the loadouts the script leaves.
+pub fn parse_inventory_changes(content: &str, virtual_name: &str) -> Result<Vec<InventoryOperation>, Error> {
+    let options = AnalysisOptions::default();
+    let functions = FunctionRegistry::for_options(&options);
+    if !evaluator::Evaluator::should_evaluate_with(content.as_bytes(), &functions) {
+        return Ok(Vec::new());
+    }
+
+    let mut result = analyze_code(content, virtual_name, &options, &functions)?;
+    merge_embedded_code(&mut result, &options, &functions, 0);
+    Ok(result.inventory_operations)
+}
+
+/// Nesting depth up to which code given as strings is evaluated, e.g. an
+/// action added as a string inside an event handler given as a string
+const MAX_EMBEDDED_CODE_DEPTH: usize = 4;
+
+/// Parse and evaluate SQF code held in memory, as a file of a memory workspace
+fn analyze_code(
+    code: &str,
+    virtual_name: &str,
+    options: &AnalysisOptions,
+    functions: &FunctionRegistry,
+) -> Result<AnalysisResult, Error> {
+    let workspace = Workspace::builder()
+        .memory()
+        .finish(None, false, &PDriveOption::Disallow)?;
+    let code_file = workspace.join(virtual_name)?;
+    code_file.create_file()?.write_all(code.as_bytes())?;
+
+    let database = Database::a3(false);
+    analyze_content(&database, code_file, virtual_name, code, options, functions)
+}
+
+/// Evaluate the code an analysis found in strings, such as event handlers,
+/// and add what it finds to the analysis.
+///
+/// Code that doesn't mention any of the functions is skipped, and code that
+/// fails to parse is logged and skipped, since not every string is code.
+fn merge_embedded_code(result: &mut AnalysisResult, options: &AnalysisOptions, functions: &FunctionRegistry, depth: usize) {
+    if depth >= MAX_EMBEDDED_CODE_DEPTH {
+        return;
+    }
+    for code in result.embedded_code.clone() {
+        if !evaluator::Evaluator::should_evaluate_with(code.as_bytes(), functions) {
+            continue;
+        }
+        match analyze_code(&code, "embedded.sqf", options, functions) {
+            Ok(mut embedded) => {
+                merge_embedded_code(&mut embedded, options, functions, depth + 1);
+                merge_result(result, embedded);
+            }
+            Err(e) => log::warn!("Skipping embedded code that failed to parse: {:?}", e),
+        }
+    }
+}
+
+/// Add the findings of another analysis, except its embedded code
+fn merge_result(result: &mut AnalysisResult, other: AnalysisResult) {
+    result.references.extend(other.references);
+    result.assets.extend(other.assets);
+    result.assets.sort();
+    result.assets.dedup();
+    result.script_paths.extend(other.script_paths);
+    result.script_paths.sort();
+    result.script_paths.dedup();
+    result.arsenal_boxes.extend(other.arsenal_boxes);
+    result.removed_slots.extend(other.removed_slots);
+    result.removed_slots.sort();
+    result.removed_slots.dedup();
+    result.inventory_operations.extend(other.inventory_operations);
+    result.random_pools.extend(other.random_pools);
+}
+
+/// Parse and evaluate SQF content, falling back to a token scan when soft failing
+fn analyze_content(
+    database: &Database,
+    workspace_path: WorkspacePath,
+    source: &str,
+    content: &str,
+    options: &AnalysisOptions,
+    functions: &FunctionRegistry,
+) -> Result<AnalysisResult, Error> {
     // Create processed context with file info
     let processed = Processed::new(
         vec![Output::Direct(Arc::new(Token::new(
@@ -88,15 +367,28 @@ pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>, Error> {
         false,
     )?;
 
-    // Parse and analyze
-    let statements = parse_sqf(&database, &processed)
-        .map_err(Error::ParserError)?;
-
-    // Use the evaluator to extract class references
-    evaluator::evaluate_sqf(&statements)
-        .map_err(|e| Error::UnparseableSyntax(e))
-        .map(|result| result.references)
+    let parsed = parse_sqf(database, &processed)
+        .map_err(Error::ParserError)
+        .and_then(|statements| evaluator::evaluate_sqf_with_options(&statements, options)
+            .map_err(Error::UnparseableSyntax));
+    match parsed {
+        Ok(result) => Ok(result),
+        Err(e) if options.soft_fail => {
+            log::warn!("Scanning tokens of {} after it failed to parse: {:?}", source, e);
+            Ok(AnalysisResult {
+                references: fallback::scan_tokens(content, functions),
+                assets: Vec::new(),
+                script_paths: Vec::new(),
+                embedded_code: Vec::new(),
+                arsenal_boxes: Vec::new(),
+                removed_slots: Vec::new(),
+                inventory_operations: Vec::new(),
+                random_pools: Vec::new(),
+            })
+        }
+        Err(e) => Err(e),
+    }
 }
 
 // Re-export evaluator for convenience
-pub use evaluator::evaluate_sqf;
\ No newline at end of file
+pub use evaluator::{evaluate_sqf, evaluate_sqf_with_options};
\ No newline at end of file
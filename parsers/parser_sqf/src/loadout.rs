@@ -0,0 +1,199 @@
+//! Decoding of `setUnitLoadout` arrays
+//!
+//! A loadout as returned by `getUnitLoadout` is a fixed layout of nested arrays:
+//!
+//! ```text
+//! [primary, secondary, handgun, uniform, vest, backpack, headgear, goggles, binocular, linked items]
+//! ```
+//!
+//! Weapons are `[weapon, muzzle, pointer, optic, [magazine, ammo], [magazine, ammo], bipod]`,
+//! containers are `[class, [[item, count], [magazine, count, ammo], [weapon, count]]]` and the
+//! linked items are `[map, gps, radio, compass, watch, nvg]`. Empty slots hold `""` or `[]`.
+//! `setUnitLoadout` also takes `[loadout, refill]` or the class name of a unit.
+
+use crate::interpreter::SqfValue;
+
+/// Number of slots in a full loadout array
+const LOADOUT_SLOTS: usize = 10;
+
+/// Slots holding a weapon array: primary, secondary, handgun and binocular
+const WEAPON_SLOTS: [usize; 4] = [0, 1, 2, 8];
+
+/// Slots holding a container array: uniform, vest and backpack
+const CONTAINER_SLOTS: [usize; 3] = [3, 4, 5];
+
+/// Slots holding a single class: headgear and goggles
+const CLASS_SLOTS: [usize; 2] = [6, 7];
+
+/// Slot holding the linked items
+const LINKED_ITEMS_SLOT: usize = 9;
+
+/// Collect every class a `setUnitLoadout` argument gives to a unit
+pub fn loadout_classes(loadout: &SqfValue) -> Vec<String> {
+    let slots = match loadout {
+        SqfValue::Array(slots) => slots,
+        // A loadout picked in the branches of an `if`
+        SqfValue::Either(loadouts) => return loadouts.iter().flat_map(loadout_classes).collect(),
+        // A unit class whose loadout is copied
+        value => return class_names(value),
+    };
+
+    // `[loadout, refill]` wraps the loadout array
+    if let [inner @ SqfValue::Array(inner_slots), SqfValue::Boolean(_)] = slots.as_slice() {
+        if inner_slots.len() == LOADOUT_SLOTS {
+            return loadout_classes(inner);
+        }
+    }
+
+    let mut classes = Vec::new();
+    for index in WEAPON_SLOTS {
+        if let Some(weapon) = slots.get(index) {
+            weapon_classes(weapon, &mut classes);
+        }
+    }
+    for index in CONTAINER_SLOTS {
+        if let Some(container) = slots.get(index) {
+            container_classes(container, &mut classes);
+        }
+    }
+    for index in CLASS_SLOTS {
+        if let Some(value) = slots.get(index) {
+            classes.extend(class_names(value));
+        }
+    }
+    if let Some(SqfValue::Array(items)) = slots.get(LINKED_ITEMS_SLOT) {
+        classes.extend(items.iter().flat_map(class_names));
+    }
+    classes
+}
+
+/// Classes of a weapon array: the weapon, its attachments and its loaded magazines
+fn weapon_classes(weapon: &SqfValue, classes: &mut Vec<String>) {
+    let SqfValue::Array(parts) = weapon else {
+        classes.extend(class_names(weapon));
+        return;
+    };
+    for part in parts {
+        match part {
+            // Loaded magazines are `[magazine, ammo]`
+            SqfValue::Array(magazine) => {
+                if let Some(class) = magazine.first() {
+                    classes.extend(class_names(class));
+                }
+            }
+            part => classes.extend(class_names(part)),
+        }
+    }
+}
+
+/// Classes of a container array: the container and everything stored in it
+fn container_classes(container: &SqfValue, classes: &mut Vec<String>) {
+    let SqfValue::Array(parts) = container else {
+        classes.extend(class_names(container));
+        return;
+    };
+    if let Some(class) = parts.first() {
+        classes.extend(class_names(class));
+    }
+    let Some(SqfValue::Array(items)) = parts.get(1) else {
+        return;
+    };
+    for item in items {
+        match item {
+            // Items and magazines are `[class, count, ...]`, stored weapons are `[[weapon, ...], count]`
+            SqfValue::Array(entry) => match entry.first() {
+                Some(weapon @ SqfValue::Array(_)) => weapon_classes(weapon, classes),
+                Some(class) => classes.extend(class_names(class)),
+                None => {}
+            },
+            item => classes.extend(class_names(item)),
+        }
+    }
+}
+
+/// Non-empty class names held by a single slot
+fn class_names(value: &SqfValue) -> Vec<String> {
+    match value {
+        SqfValue::String(class) => vec![class.clone()],
+        SqfValue::Partial(_) | SqfValue::Weighted(_) | SqfValue::Either(_) => value.strings(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .filter(|class| !class.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> SqfValue {
+        SqfValue::String(value.to_string())
+    }
+
+    fn array(values: Vec<SqfValue>) -> SqfValue {
+        SqfValue::Array(values)
+    }
+
+    fn magazine(class: &str, count: f64) -> SqfValue {
+        array(vec![string(class), SqfValue::Number(count)])
+    }
+
+    fn loadout() -> SqfValue {
+        array(vec![
+            array(vec![
+                string("arifle_MX_F"), string("muzzle_snds_H"), string("acc_pointer_IR"), string("optic_Aco"),
+                magazine("30Rnd_65x39_caseless_mag", 30.0), array(vec![]), string(""),
+            ]),
+            array(vec![]),
+            array(vec![string("hgun_P07_F"), string(""), string(""), string(""), magazine("16Rnd_9x21_Mag", 16.0), array(vec![]), string("")]),
+            array(vec![string("U_B_CombatUniform_mcam"), array(vec![
+                magazine("FirstAidKit", 1.0),
+                array(vec![string("Chemlight_green"), SqfValue::Number(1.0), SqfValue::Number(1.0)]),
+            ])]),
+            array(vec![string("V_PlateCarrier1_rgr"), array(vec![
+                array(vec![string("30Rnd_65x39_caseless_mag"), SqfValue::Number(3.0), SqfValue::Number(30.0)]),
+            ])]),
+            array(vec![string("B_AssaultPack_mcamo"), array(vec![
+                array(vec![array(vec![string("launch_NLAW_F"), string(""), string(""), string(""), array(vec![]), array(vec![]), string("")]), SqfValue::Number(1.0)]),
+            ])]),
+            string("H_HelmetB"),
+            string(""),
+            array(vec![string("Binocular"), string(""), string(""), string(""), array(vec![]), array(vec![]), string("")]),
+            array(vec![string("ItemMap"), string(""), string("ItemRadio"), string("ItemCompass"), string("ItemWatch"), string("")]),
+        ])
+    }
+
+    #[test]
+    fn test_loadout_classes() {
+        let classes = loadout_classes(&loadout());
+        let expected = [
+            "arifle_MX_F", "muzzle_snds_H", "acc_pointer_IR", "optic_Aco", "30Rnd_65x39_caseless_mag",
+            "hgun_P07_F", "16Rnd_9x21_Mag", "Binocular",
+            "U_B_CombatUniform_mcam", "FirstAidKit", "Chemlight_green",
+            "V_PlateCarrier1_rgr", "30Rnd_65x39_caseless_mag",
+            "B_AssaultPack_mcamo", "launch_NLAW_F",
+            "H_HelmetB",
+            "ItemMap", "ItemRadio", "ItemCompass", "ItemWatch",
+        ];
+        assert_eq!(classes, expected);
+    }
+
+    #[test]
+    fn test_loadout_with_refill_flag() {
+        let wrapped = array(vec![loadout(), SqfValue::Boolean(true)]);
+        assert_eq!(loadout_classes(&wrapped), loadout_classes(&loadout()));
+    }
+
+    #[test]
+    fn test_unit_class_and_random_slots() {
+        assert_eq!(loadout_classes(&string("B_Soldier_F")), ["B_Soldier_F"]);
+        assert!(loadout_classes(&SqfValue::Unknown).is_empty());
+
+        let partial = array(vec![
+            array(vec![]), array(vec![]), array(vec![]), array(vec![]), array(vec![]), array(vec![]),
+            SqfValue::Partial(vec!["H_HelmetB".to_string(), "H_HelmetSpecB".to_string()]),
+        ]);
+        assert_eq!(loadout_classes(&partial), ["H_HelmetB", "H_HelmetSpecB"]);
+    }
+}
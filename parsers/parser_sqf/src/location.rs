@@ -0,0 +1,61 @@
+//! Source locations of class references
+//!
+//! The evaluator works on values, not tokens, so references are located
+//! afterwards by searching the source for the class name.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// Where in a file a class reference was found
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct SourceLocation {
+    /// File containing the reference
+    pub file: PathBuf,
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column in characters, starting at 1
+    pub column: usize,
+}
+
+/// Find the first occurrence of a class name in source text.
+///
+/// Matches ignore case, like class names in the engine, and must not be part
+/// of a longer identifier. Returns the 1-based line and column.
+pub fn locate_class_name(content: &str, class_name: &str) -> Option<(usize, usize)> {
+    if class_name.is_empty() {
+        return None;
+    }
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+    // ASCII lowercasing keeps byte offsets valid in the original line
+    let needle = class_name.to_ascii_lowercase();
+
+    for (line_index, line) in content.lines().enumerate() {
+        let lowercase = line.to_ascii_lowercase();
+        let mut start = 0;
+        while let Some(found) = lowercase[start..].find(&needle) {
+            let index = start + found;
+            let end = index + needle.len();
+            let bounded_before = !line[..index].chars().next_back().is_some_and(is_identifier);
+            let bounded_after = !line[end..].chars().next().is_some_and(is_identifier);
+            if bounded_before && bounded_after {
+                return Some((line_index + 1, line[..index].chars().count() + 1));
+            }
+            start = end;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_class_name() {
+        let content = "// rhs_weap_m4a1_blockII\n_unit addWeapon \"RHS_weap_m4a1\";\n";
+        assert_eq!(locate_class_name(content, "rhs_weap_m4a1"), Some((2, 18)));
+        assert_eq!(locate_class_name(content, "rhs_weap"), None);
+        assert_eq!(locate_class_name(content, ""), None);
+    }
+}
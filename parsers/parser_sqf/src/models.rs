@@ -1,41 +1,129 @@
 //! Core data structures for SQF parsing and analysis
 
-use std::fmt;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::inventory_sim::InventoryOperation;
+use crate::location::SourceLocation;
+use crate::registry::FunctionRegistry;
+use crate::signatures::CommandSignatures;
+
+pub use mission_scanner_types::{Confidence, CountRange, ItemKind, UsageContext};
 
 /// Represents a class reference found in SQF code
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct ClassReference {
     /// The class name/ID
     pub class_name: String,
     /// The context where it was found (scope/conditions)
     pub context: String,
+    /// How the class was used, which `context` describes
+    pub usage: UsageContext,
+    /// Where the class name first appears in the file, when parsed from a file
+    pub location: Option<SourceLocation>,
+    /// How sure the analysis is that the string is used as a class
+    pub confidence: Confidence,
+    /// Instances added in this context, counting loop iterations and
+    /// the count of `[class, count]` arguments. The maximum when the count varies
+    pub count: u32,
+    /// Fewest and most instances, when loop bounds like `random 5` make the count vary
+    pub count_range: Option<CountRange>,
+    /// Whether the class is taken away by a remove or unassign command instead of used
+    pub removed: bool,
+    /// Kind of item the command or function names, from its signature
+    pub kind: Option<ItemKind>,
 }
 
-/// Represents how a class reference was discovered
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum UsageContext {
-    /// Used in an add* command (addWeapon, addVest, etc.)
-    AddCommand(String),
-    /// Used in a function known to use class references
-    KnownFunction(String),
-    /// Directly used as a string in a context that suggests it's a class
-    DirectReference,
+/// Options controlling how SQF code is analyzed
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisOptions {
+    /// Only emit references provable from command semantics.
+    ///
+    /// Disables the assignment-scope and array-building heuristics, which is
+    /// what audit tooling wants when results feed automated mod removal.
+    pub strict: bool,
+    /// Command signature table to use instead of the embedded default.
+    ///
+    /// Build one with [`CommandSignatures::with_overrides`] to add or change
+    /// commands without losing the defaults.
+    pub command_signatures: Option<Arc<CommandSignatures>>,
+    /// Additional functions whose arguments are class names.
+    ///
+    /// Added on top of the built-in functions and the class commands of the
+    /// signature table.
+    pub known_functions: Option<Arc<FunctionRegistry>>,
+    /// Scan the tokens of files that can't be parsed instead of failing.
+    ///
+    /// Quoted names near known commands and functions are reported with
+    /// [`Confidence::Low`], so one syntax error doesn't lose a whole file.
+    pub soft_fail: bool,
 }
 
-impl fmt::Display for UsageContext {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            UsageContext::AddCommand(cmd) => write!(f, "Used in command: {}", cmd),
-            UsageContext::KnownFunction(func) => write!(f, "Used in function: {}", func),
-            UsageContext::DirectReference => write!(f, "Direct reference"),
-        }
+impl AnalysisOptions {
+    /// Options for an audit-grade, heuristic-free analysis
+    pub fn strict() -> Self {
+        Self { strict: true, ..Self::default() }
     }
 }
 
+/// Represents a mod asset path (texture, material) found in SQF code
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetReference {
+    /// The asset path as written in the script
+    pub path: String,
+    /// The command the path was passed to
+    pub context: String,
+}
+
+/// Equipment a virtual arsenal offers on one object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArsenalBox {
+    /// Variable or command naming the object, e.g. `_box` or `this`
+    pub box_id: String,
+    /// Classes offered, deduplicated and sorted
+    pub items: Vec<String>,
+}
+
+/// Classes of which only one is used, picked at random when the script runs
+///
+/// Built when a class argument comes from `selectRandom` or
+/// `selectRandomWeighted`, or differs between the branches of an `if`, so
+/// reports can show "one of these uniforms" instead of every candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomPool {
+    /// How the picked class is used, as in [`ClassReference::context`]
+    pub context: String,
+    /// Candidates in the order written
+    pub candidates: Vec<PoolCandidate>,
+}
+
+/// One class a [`RandomPool`] may pick
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolCandidate {
+    pub class_name: String,
+    /// Weight given to `selectRandomWeighted`, unset when all candidates are equally likely
+    pub weight: Option<f64>,
+}
+
 /// Represents the result of analyzing SQF code
 #[derive(Debug, Clone)]
 pub struct AnalysisResult {
     pub references: Vec<ClassReference>,
+    pub assets: Vec<AssetReference>,
+    /// Paths of scripts run or compiled with `execVM`, `preprocessFile` and
+    /// the like, as written and sorted
+    pub script_paths: Vec<String>,
+    /// Code given as strings to event handlers, actions and `compile`, in
+    /// script order. Analysis of a file or of code held in memory evaluates
+    /// it too and merges what it finds
+    pub embedded_code: Vec<String>,
+    /// Arsenals set up by the script, one per object
+    pub arsenal_boxes: Vec<ArsenalBox>,
+    /// Commands emptying whole inventory slots, such as `removeAllWeapons`, sorted
+    pub removed_slots: Vec<String>,
+    /// Add and remove commands applied to units, in script order
+    pub inventory_operations: Vec<InventoryOperation>,
+    /// Class arguments picking one of several candidates, in script order
+    pub random_pools: Vec<RandomPool>,
 }
 
 #[cfg(test)]
@@ -47,16 +135,37 @@ mod tests {
         let ref1 = ClassReference {
             class_name: "test_item".to_string(),
             context: "test_scope".to_string(),
+            usage: UsageContext::DirectReference,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+            removed: false,
+            kind: None,
         };
         
         let ref2 = ClassReference {
             class_name: "test_item".to_string(),
             context: "test_scope".to_string(),
+            usage: UsageContext::DirectReference,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+            removed: false,
+            kind: None,
         };
         
         let ref3 = ClassReference {
             class_name: "different_item".to_string(),
             context: "test_scope".to_string(),
+            usage: UsageContext::DirectReference,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+            removed: false,
+            kind: None,
         };
         
         assert_eq!(ref1, ref2);
@@ -77,5 +186,9 @@ mod tests {
             UsageContext::DirectReference.to_string(),
             "Direct reference"
         );
+        assert_eq!(
+            UsageContext::RemoveCommand("unlinkItem".to_string()).to_string(),
+            "Removed by command: unlinkItem"
+        );
     }
 }
\ No newline at end of file
@@ -0,0 +1,187 @@
+//! Case-insensitive registry of functions and commands taking class names
+//!
+//! SQF identifiers are case-insensitive, so every name is normalized once on
+//! insertion and lookups never have to remember to lowercase.
+
+use std::collections::HashSet;
+use crate::models::AnalysisOptions;
+use crate::signatures::CommandSignatures;
+
+/// Functions adding items to a virtual arsenal on an object, called with `[box, items, global]`
+pub const ARSENAL_FUNCTIONS: &[&str] = &[
+    "ace_arsenal_fnc_initBox",
+    "BIS_fnc_addVirtualItemCargo",
+    "BIS_fnc_addVirtualWeaponCargo",
+    "BIS_fnc_addVirtualMagazineCargo",
+    "BIS_fnc_addVirtualBackpackCargo",
+];
+
+/// Functions known to take class names that are not commands in the signature table
+const BUILTIN_FUNCTIONS: &[&str] = ARSENAL_FUNCTIONS;
+
+/// Config roots whose lookups name classes of the game and its mods
+pub const CONFIG_ROOTS: &[&str] = &["configFile"];
+
+/// Commands emptying inventory slots of a unit without naming a class
+pub const SLOT_REMOVAL_COMMANDS: &[&str] = &[
+    "removeUniform",
+    "removeVest",
+    "removeBackpack",
+    "removeBackpackGlobal",
+    "removeHeadgear",
+    "removeGoggles",
+    "removeAllWeapons",
+    "removeAllItems",
+    "removeAllItemsWithMagazines",
+    "removeAllAssignedItems",
+    "removeAllContainers",
+    "removeAllPrimaryWeaponItems",
+    "removeAllSecondaryWeaponItems",
+    "removeAllHandgunItems",
+    "removeAllBinocularItems",
+];
+
+/// Check if a function adds items to a virtual arsenal, ignoring case
+pub fn is_arsenal_function(name: &str) -> bool {
+    ARSENAL_FUNCTIONS.iter().any(|function| function.eq_ignore_ascii_case(name))
+}
+
+/// Check if a nular command is a config root listed in [`CONFIG_ROOTS`], ignoring case
+pub fn is_config_root(name: &str) -> bool {
+    CONFIG_ROOTS.iter().any(|root| root.eq_ignore_ascii_case(name))
+}
+
+/// Get a slot removal command as listed in [`SLOT_REMOVAL_COMMANDS`], ignoring case
+pub fn slot_removal_command(name: &str) -> Option<&'static str> {
+    SLOT_REMOVAL_COMMANDS.iter().copied().find(|command| command.eq_ignore_ascii_case(name))
+}
+
+/// Set of function and command names that indicate class references
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionRegistry {
+    names: HashSet<String>,
+}
+
+impl FunctionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in functions, the config roots and
+    /// every class or removal command of a signature table
+    pub fn from_signatures(signatures: &CommandSignatures) -> Self {
+        let mut registry = Self::new();
+        registry.extend(BUILTIN_FUNCTIONS.iter().copied());
+        registry.extend(SLOT_REMOVAL_COMMANDS.iter().copied());
+        registry.extend(CONFIG_ROOTS.iter().copied());
+        registry.extend(signatures.class_commands());
+        registry.extend(signatures.removal_commands());
+        registry
+    }
+
+    /// Create a registry of the virtual arsenal functions
+    pub fn arsenal_functions() -> Self {
+        ARSENAL_FUNCTIONS.iter().copied().collect()
+    }
+
+    /// Create the registry used for a set of analysis options
+    pub fn for_options(options: &AnalysisOptions) -> Self {
+        let signatures = options.command_signatures.clone()
+            .unwrap_or_else(CommandSignatures::embedded);
+        let mut registry = Self::from_signatures(&signatures);
+        if let Some(extra) = &options.known_functions {
+            registry.merge(extra);
+        }
+        registry
+    }
+
+    /// Add a function name
+    pub fn insert(&mut self, name: &str) {
+        self.names.insert(name.to_lowercase());
+    }
+
+    /// Add every function name from an iterator
+    pub fn extend<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        for name in names {
+            self.insert(name);
+        }
+    }
+
+    /// Add every function of another registry
+    pub fn merge(&mut self, other: &FunctionRegistry) {
+        self.names.extend(other.names.iter().cloned());
+    }
+
+    /// Check if a function name is registered, ignoring case
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&name.to_lowercase())
+    }
+
+    /// Check if any registered name appears in a piece of text, ignoring case
+    pub fn is_mentioned_in(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.names.iter().any(|name| text.contains(name.as_str()))
+    }
+
+    /// Iterate over the registered names (lowercase)
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Number of registered names
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Check if the registry is empty
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl<'a> FromIterator<&'a str> for FunctionRegistry {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut registry = Self::new();
+        registry.extend(iter);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lookups_ignore_case() {
+        let registry: FunctionRegistry = ["ACE_arsenal_fnc_initBox"].into_iter().collect();
+        assert!(registry.contains("ace_arsenal_fnc_initbox"));
+        assert!(registry.contains("ACE_ARSENAL_FNC_INITBOX"));
+        assert!(registry.is_mentioned_in("[box, _items] call ACE_Arsenal_fnc_InitBox;"));
+        assert!(!registry.is_mentioned_in("hint \"hello\";"));
+    }
+
+    #[test]
+    fn test_default_registry() {
+        let registry = FunctionRegistry::for_options(&AnalysisOptions::default());
+        assert!(registry.contains("addWeapon"));
+        assert!(registry.contains("AddItemCargoGlobal"));
+        assert!(registry.contains("ace_arsenal_fnc_initBox"));
+        assert!(registry.contains("bis_fnc_addvirtualweaponcargo"));
+        assert!(registry.contains("removeItem"));
+        assert!(registry.contains("removeallweapons"));
+        assert!(!registry.contains("setMarkerType"));
+    }
+
+    #[test]
+    fn test_user_functions_extend_defaults() {
+        let options = AnalysisOptions {
+            known_functions: Some(Arc::new(["TAG_fnc_giveLoadout"].into_iter().collect())),
+            ..AnalysisOptions::default()
+        };
+        let registry = FunctionRegistry::for_options(&options);
+        assert!(registry.contains("tag_fnc_giveloadout"));
+        assert!(registry.contains("addWeapon"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Command signature database for argument roles
+//!
+//! Maps SQF commands to what their arguments hold: class names, marker names,
+//! sounds and so on. The evaluator uses class positions to attribute
+//! references and the other roles to discard heuristic references that are
+//! really marker names, animations or text. Class positions may also name the
+//! [`ItemKind`] they hold. The default table is embedded from
+//! `data/command_signatures.toml` and can be extended or overridden by users,
+//! which is how functions of mods such as `zen_fnc_…` or `grad_…` are taught
+//! to the evaluator.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use mission_scanner_types::ItemKind;
+
+/// Embedded default signature table
+const EMBEDDED_SIGNATURES: &str = include_str!("../data/command_signatures.toml");
+
+/// What an argument of a command holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgumentRole {
+    Class,
+    /// Class name taken away from a unit, e.g. by `removeItem`
+    RemovedClass,
+    MarkerName,
+    MarkerType,
+    MarkerColor,
+    Animation,
+    Sound,
+    Text,
+    VariableName,
+    /// Texture or material path, usually pointing into a mod
+    Texture,
+    /// Path of a script run or compiled, e.g. by `execVM`
+    Script,
+    /// Code run later, given as a string or a code block, e.g. to `addEventHandler`
+    Code,
+}
+
+/// Which side of a command an argument is on.
+///
+/// Unary commands only have a right operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Left,
+    Right,
+}
+
+/// The role of one argument position of a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgumentSignature {
+    /// Side of the command the argument is on
+    pub operand: Operand,
+    /// Index into the operand when it is an array, or `None` for the whole operand
+    pub index: Option<usize>,
+    /// What the argument holds
+    pub role: ArgumentRole,
+    /// Kind of item a class argument names, when the command tells
+    pub kind: Option<ItemKind>,
+}
+
+/// Operand entry as written in the TOML table
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OperandEntry {
+    Whole(ArgumentRole),
+    Table {
+        index: Option<usize>,
+        role: ArgumentRole,
+        kind: Option<ItemKind>,
+    },
+}
+
+impl OperandEntry {
+    fn into_signature(self, operand: Operand) -> ArgumentSignature {
+        match self {
+            OperandEntry::Whole(role) => ArgumentSignature { operand, index: None, role, kind: None },
+            OperandEntry::Table { index, role, kind } => ArgumentSignature { operand, index, role, kind },
+        }
+    }
+}
+
+/// Command entry as written in the TOML table
+#[derive(Debug, Deserialize)]
+struct CommandEntry {
+    left: Option<OperandEntry>,
+    right: Option<OperandEntry>,
+}
+
+/// Lookup of argument roles by command name (case-insensitive)
+#[derive(Debug, Clone, Default)]
+pub struct CommandSignatures {
+    by_command: HashMap<String, Vec<ArgumentSignature>>,
+}
+
+impl CommandSignatures {
+    /// Get the embedded default signature table
+    pub fn embedded() -> Arc<CommandSignatures> {
+        static EMBEDDED: OnceLock<Arc<CommandSignatures>> = OnceLock::new();
+        EMBEDDED.get_or_init(|| {
+            Arc::new(Self::from_toml_str(EMBEDDED_SIGNATURES)
+                .expect("embedded command signatures are valid"))
+        }).clone()
+    }
+
+    /// Parse a signature table from TOML
+    pub fn from_toml_str(content: &str) -> Result<Self, String> {
+        let entries: HashMap<String, CommandEntry> = toml::from_str(content)
+            .map_err(|e| format!("Invalid command signature table: {}", e))?;
+
+        let mut signatures = Self::default();
+        for (command, entry) in entries {
+            let mut arguments = Vec::new();
+            if let Some(left) = entry.left {
+                arguments.push(left.into_signature(Operand::Left));
+            }
+            if let Some(right) = entry.right {
+                arguments.push(right.into_signature(Operand::Right));
+            }
+            signatures.by_command.insert(command.to_lowercase(), arguments);
+        }
+        Ok(signatures)
+    }
+
+    /// Load a signature table from a TOML file
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Create the embedded table with user overrides applied on top
+    pub fn with_overrides(overrides: &CommandSignatures) -> Self {
+        let mut signatures = (*Self::embedded()).clone();
+        signatures.merge(overrides);
+        signatures
+    }
+
+    /// Load a TOML file of user commands and functions on top of the embedded table
+    pub fn with_overrides_from_file(path: &Path) -> Result<Self, String> {
+        Ok(Self::with_overrides(&Self::from_file(path)?))
+    }
+
+    /// Apply another table on top of this one, replacing whole command entries
+    pub fn merge(&mut self, other: &CommandSignatures) {
+        for (command, arguments) in &other.by_command {
+            self.by_command.insert(command.clone(), arguments.clone());
+        }
+    }
+
+    /// Add or replace the signature of a single command
+    pub fn insert(&mut self, command: &str, arguments: Vec<ArgumentSignature>) {
+        self.by_command.insert(command.to_lowercase(), arguments);
+    }
+
+    /// Get every argument signature of a command
+    pub fn arguments(&self, command: &str) -> &[ArgumentSignature] {
+        self.by_command.get(&command.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Get the argument positions of a command that hold class names
+    pub fn class_arguments(&self, command: &str) -> impl Iterator<Item = &ArgumentSignature> {
+        self.arguments(command).iter().filter(|a| a.role == ArgumentRole::Class)
+    }
+
+    /// Get the argument positions of a command that hold class names it removes
+    pub fn removed_class_arguments(&self, command: &str) -> impl Iterator<Item = &ArgumentSignature> {
+        self.arguments(command).iter().filter(|a| a.role == ArgumentRole::RemovedClass)
+    }
+
+    /// Get the argument positions of a command that never hold class names
+    pub fn non_class_arguments(&self, command: &str) -> impl Iterator<Item = &ArgumentSignature> {
+        self.arguments(command).iter()
+            .filter(|a| a.role != ArgumentRole::Class && a.role != ArgumentRole::RemovedClass)
+    }
+
+    /// Get the lowercase names of all commands taking a texture or material path
+    pub fn asset_commands(&self) -> impl Iterator<Item = &str> {
+        self.by_command.iter()
+            .filter(|(_, arguments)| arguments.iter().any(|a| a.role == ArgumentRole::Texture))
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// Get the lowercase names of all commands taking a script path
+    pub fn script_commands(&self) -> impl Iterator<Item = &str> {
+        self.by_command.iter()
+            .filter(|(_, arguments)| arguments.iter().any(|a| a.role == ArgumentRole::Script))
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// Get the lowercase names of all commands taking a class argument
+    pub fn class_commands(&self) -> impl Iterator<Item = &str> {
+        self.by_command.iter()
+            .filter(|(_, arguments)| arguments.iter().any(|a| a.role == ArgumentRole::Class))
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// Get the lowercase names of all commands removing a class
+    pub fn removal_commands(&self) -> impl Iterator<Item = &str> {
+        self.by_command.iter()
+            .filter(|(_, arguments)| arguments.iter().any(|a| a.role == ArgumentRole::RemovedClass))
+            .map(|(command, _)| command.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_table_parses() {
+        let signatures = CommandSignatures::embedded();
+        assert!(signatures.class_commands().any(|c| c == "addweapon"));
+        assert!(signatures.class_commands().any(|c| c == "addweaponcargoglobal"));
+        assert!(!signatures.class_commands().any(|c| c == "setmarkertype"));
+        assert!(signatures.asset_commands().any(|c| c == "setobjecttextureglobal"));
+        assert!(signatures.script_commands().any(|c| c == "preprocessfilelinenumbers"));
+        assert!(signatures.arguments("addAction").iter().any(|a| a.index == Some(1) && a.role == ArgumentRole::Code));
+        assert!(signatures.removal_commands().any(|c| c == "unlinkitem"));
+        assert!(!signatures.class_commands().any(|c| c == "removeitem"));
+    }
+
+    #[test]
+    fn test_lookup_is_case_insensitive() {
+        let signatures = CommandSignatures::embedded();
+        let arguments = signatures.arguments("SETMARKERTYPE");
+        assert_eq!(arguments.len(), 2);
+        assert!(arguments.iter().any(|a| a.operand == Operand::Left && a.role == ArgumentRole::MarkerName));
+        assert!(arguments.iter().any(|a| a.operand == Operand::Right && a.role == ArgumentRole::MarkerType));
+    }
+
+    #[test]
+    fn test_indexed_arguments() {
+        let signatures = CommandSignatures::embedded();
+        let cargo: Vec<_> = signatures.class_arguments("addItemCargoGlobal").collect();
+        assert_eq!(cargo.len(), 1);
+        assert_eq!(cargo[0].index, Some(0));
+        assert_eq!(cargo[0].kind, Some(ItemKind::Item));
+    }
+
+    #[test]
+    fn test_item_kinds() {
+        let signatures = CommandSignatures::embedded();
+        let kind = |command| signatures.class_arguments(command).next().and_then(|a| a.kind);
+        assert_eq!(kind("addWeapon"), Some(ItemKind::Weapon));
+        assert_eq!(kind("addMagazines"), Some(ItemKind::Magazine));
+        assert_eq!(kind("forceAddUniform"), Some(ItemKind::Uniform));
+        assert_eq!(kind("addItemToVest"), Some(ItemKind::Item));
+        assert_eq!(kind("setUnitLoadout"), None);
+        let removed = signatures.removed_class_arguments("removeWeapon").next().unwrap();
+        assert_eq!(removed.kind, Some(ItemKind::Weapon));
+
+        let custom = CommandSignatures::from_toml_str(r#"
+            [zen_common_fnc_spawnVehicle]
+            left = { index = 0, role = "class", kind = "vehicle" }
+        "#).unwrap();
+        let argument = custom.class_arguments("ZEN_common_fnc_spawnVehicle").next().unwrap();
+        assert_eq!((argument.operand, argument.index, argument.kind), (Operand::Left, Some(0), Some(ItemKind::Vehicle)));
+    }
+
+    #[test]
+    fn test_user_overrides() {
+        let overrides = CommandSignatures::from_toml_str(r#"
+            [grad_loadout_fnc_addItem]
+            right = { index = 1, role = "class" }
+
+            [hint]
+            right = "class"
+        "#).unwrap();
+        let signatures = CommandSignatures::with_overrides(&overrides);
+
+        let custom: Vec<_> = signatures.class_arguments("GRAD_loadout_fnc_addItem").collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].index, Some(1));
+        assert!(signatures.class_commands().any(|c| c == "hint"));
+        assert!(signatures.class_commands().any(|c| c == "addweapon"));
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        assert!(CommandSignatures::from_toml_str("[addWeapon]\nright = \"not_a_role\"").is_err());
+        assert!(CommandSignatures::from_toml_str("[addWeapon]\nright = { role = \"class\", kind = \"tank\" }").is_err());
+    }
+}
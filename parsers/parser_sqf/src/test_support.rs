@@ -0,0 +1,38 @@
+//! Shared helpers for unit tests
+
+use hemtt_common::config::PDriveOption;
+use hemtt_sqf::parser::{run as parse_sqf, database::Database};
+use hemtt_sqf::Statements;
+use hemtt_workspace::position::{LineCol, Position};
+use hemtt_workspace::reporting::{Output, Processed, Symbol, Token};
+use hemtt_workspace::Workspace;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Parse SQF code held in memory into statements
+pub fn parse_code(code: &str) -> Statements {
+    let database = Database::a3(false);
+    let workspace = Workspace::builder()
+        .memory()
+        .finish(None, false, &PDriveOption::Disallow)
+        .unwrap();
+    let test_file = workspace.join("test.sqf").unwrap();
+    test_file.create_file().unwrap().write_all(code.as_bytes()).unwrap();
+
+    let processed = Processed::new(
+        vec![Output::Direct(Arc::new(Token::new(
+            Symbol::Word(code.to_string()),
+            Position::new(
+                LineCol(0, (1, 0)),
+                LineCol(code.len(), (1, code.len())),
+                test_file.clone(),
+            )
+        )))],
+        HashMap::new(),
+        vec![],
+        false,
+    ).unwrap();
+
+    parse_sqf(&database, &processed).unwrap()
+}
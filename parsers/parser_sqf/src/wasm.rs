@@ -0,0 +1,30 @@
+//! JavaScript bindings for running the SQF analysis in a browser
+//!
+//! Built with the `wasm` feature. Only the in-memory entry points are bound,
+//! since a web page has no mission directory to read. Results are returned as
+//! JSON strings, with errors as their debug text.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_str, simulate_loadouts};
+
+/// Class references of SQF content, as a JSON array
+#[wasm_bindgen(js_name = parseClassReferences)]
+pub fn parse_class_references(content: &str, virtual_name: &str) -> Result<String, String> {
+    let references = parse_str(content, virtual_name).map_err(|e| format!("{:?}", e))?;
+    serde_json::to_string(&references).map_err(|e| e.to_string())
+}
+
+/// Add and remove commands SQF content applies to units, in script order, as a JSON array
+#[wasm_bindgen(js_name = parseInventoryChanges)]
+pub fn parse_inventory_changes(content: &str, virtual_name: &str) -> Result<String, String> {
+    let operations = crate::parse_inventory_changes(content, virtual_name).map_err(|e| format!("{:?}", e))?;
+    serde_json::to_string(&operations).map_err(|e| e.to_string())
+}
+
+/// Loadouts SQF content leaves each unit with, as a JSON array
+#[wasm_bindgen(js_name = reconstructLoadouts)]
+pub fn reconstruct_loadouts(content: &str, virtual_name: &str) -> Result<String, String> {
+    let operations = crate::parse_inventory_changes(content, virtual_name).map_err(|e| format!("{:?}", e))?;
+    serde_json::to_string(&simulate_loadouts(&operations)).map_err(|e| e.to_string())
+}
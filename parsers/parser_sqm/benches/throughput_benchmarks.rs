@@ -0,0 +1,101 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use parser_sqm::{extract_attributed_dependencies, extract_class_dependencies};
+
+/// Size of the generated stress mission, close to the largest missions seen in the wild
+const LARGE_MISSION_BYTES: usize = 10 * 1024 * 1024;
+
+fn create_unit(index: usize) -> String {
+    format!(r#"
+            class Item{index} {{
+                dataType = "Object";
+                class PositionInfo {{
+                    position[] = {{{x}, 5, {y}}};
+                }};
+                side = "West";
+                flags = 7;
+                class Attributes {{
+                    name = "unit_{index}";
+                    class Inventory {{
+                        class primaryWeapon {{
+                            name = "rhs_weap_m4a1_{weapon}";
+                            optics = "rhsusf_acc_eotech_552";
+                            class primaryMuzzleMag {{
+                                name = "rhs_mag_30Rnd_556x45_M855A1_Stanag";
+                                ammoLeft = 30;
+                            }};
+                        }};
+                        class uniform {{
+                            typeName = "rhs_uniform_g3_m81";
+                            isBackpack = 0;
+                            class ItemCargo {{
+                                items = 2;
+                                class Item0 {{
+                                    name = "ACE_fieldDressing";
+                                    count = 10;
+                                }};
+                                class Item1 {{
+                                    name = "ACE_morphine_{item}";
+                                    count = 2;
+                                }};
+                            }};
+                        }};
+                        class vest {{
+                            typeName = "rhsusf_spcs_ocp_rifleman";
+                            isBackpack = 0;
+                            class MagazineCargo {{
+                                items = 1;
+                                class Item0 {{
+                                    name = "rhs_mag_30Rnd_556x45_M855A1_Stanag";
+                                    count = 6;
+                                    ammoLeft = 30;
+                                }};
+                            }};
+                        }};
+                        map = "ItemMap";
+                        compass = "ItemCompass";
+                        watch = "ItemWatch";
+                        radio = "TFAR_anprc152";
+                        headgear = "rhsusf_ach_helmet_ocp";
+                    }};
+                }};
+                id = {index};
+                type = "B_Soldier_F";
+            }};"#,
+        index = index,
+        x = index % 1000,
+        y = index / 1000,
+        weapon = index % 50,
+        item = index % 20,
+    )
+}
+
+/// Generate a mission.sqm of at least `min_bytes`, made of one large group of equipped units
+fn create_large_mission(min_bytes: usize) -> String {
+    let mut content = String::from("version = 54;\nclass Mission {\n    class Entities {\n");
+    let mut units = 0;
+    while content.len() < min_bytes {
+        content.push_str(&create_unit(units));
+        units += 1;
+    }
+    content.push_str(&format!("\n        items = {units};\n    }};\n}};\n"));
+    content
+}
+
+fn sqm_throughput(c: &mut Criterion) {
+    let mission = create_large_mission(LARGE_MISSION_BYTES);
+    let mut group = c.benchmark_group("sqm_throughput");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(mission.len() as u64));
+
+    group.bench_function("class_dependencies_10mb", |b| {
+        b.iter(|| extract_class_dependencies(black_box(&mission)))
+    });
+    group.bench_function("attributed_dependencies_10mb", |b| {
+        b.iter(|| extract_attributed_dependencies(black_box(&mission)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, sqm_throughput);
+criterion_main!(benches);
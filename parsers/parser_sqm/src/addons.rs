@@ -0,0 +1,94 @@
+//! Addons a mission declares it needs
+//!
+//! The editor writes every addon the placed objects come from into the
+//! `addons[]` array at the top of mission.sqm, and older missions list more
+//! in `addOnsAuto[]` inside `class Mission`. The game refuses to load a
+//! mission whose addons are missing, so the arrays are the mission's own
+//! idea of its dependencies. They are read straight from the text, wherever
+//! they appear, so the rest of the file doesn't need to parse.
+
+use std::collections::HashSet;
+
+/// Names of the arrays listing required addons, compared ignoring case
+const ADDON_ARRAYS: &[&str] = &["addons", "addOnsAuto"];
+
+/// Extract the addons listed in the `addons[]` and `addOnsAuto[]` arrays of SQM content
+///
+/// Binarized files must be decoded with [`decode_sqm`](crate::decode_sqm) first.
+/// Addons are returned in the order they are listed, without repeats, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// use parser_sqm::extract_required_addons;
+///
+/// let sqm_content = r#"
+/// version = 54;
+/// addons[] = {"A3_Characters_F", "rhsusf_c_weapons"};
+/// class Mission {
+///     addOnsAuto[] = {"ace_cargo", "A3_Characters_F"};
+/// };"#;
+///
+/// assert_eq!(extract_required_addons(sqm_content), ["A3_Characters_F", "rhsusf_c_weapons", "ace_cargo"]);
+/// ```
+pub fn extract_required_addons(sqm_content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut addons = Vec::new();
+    let mut chars = sqm_content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            read_string(&mut chars);
+            continue;
+        }
+        if !(c.is_alphabetic() || c == '_') {
+            continue;
+        }
+
+        let mut word = c.to_string();
+        while let Some(&next) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            word.push(next);
+            chars.next();
+        }
+        if !ADDON_ARRAYS.iter().any(|name| name.eq_ignore_ascii_case(&word)) {
+            continue;
+        }
+
+        // Expect `[] = {` before the list, allowing whitespace between tokens
+        let opens_array = ['[', ']', '=', '{'].iter().all(|&expected| {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            chars.next_if_eq(&expected).is_some()
+        });
+        if !opens_array {
+            continue;
+        }
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let addon = read_string(&mut chars);
+                    if !addon.is_empty() && seen.insert(addon.to_lowercase()) {
+                        addons.push(addon);
+                    }
+                }
+                '}' => break,
+                _ => {}
+            }
+        }
+    }
+    addons
+}
+
+/// Read the rest of a string whose opening quote was consumed; quotes are escaped by doubling
+fn read_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        if c == '"' && chars.next_if_eq(&'"').is_none() {
+            break;
+        }
+        value.push(c);
+    }
+    value
+}
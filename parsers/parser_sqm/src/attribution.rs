@@ -0,0 +1,52 @@
+use std::borrow::Cow;
+use hemtt_sqm::Class;
+use crate::entities::{child, custom_attributes};
+use crate::models::ClassExt;
+
+/// Custom attribute holding a group's callsign
+const GROUP_ID_ATTRIBUTE: &str = "groupID";
+
+/// Side, group and unit a dependency belongs to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DependencyOwner {
+    /// Side of the owning group or object (`West`, `East`, `Independent`, `Civilian`, `Empty`)
+    pub side: Option<String>,
+    /// Callsign of the owning group
+    pub group: Option<String>,
+    /// Variable name of the owning unit
+    pub unit: Option<String>,
+}
+
+impl DependencyOwner {
+    /// Owner of everything inside a class, given the owner of the class's parent.
+    ///
+    /// Most classes are neither groups nor objects and keep the parent's owner borrowed.
+    pub(crate) fn enter(&self, class: &Class) -> Cow<'_, Self> {
+        let data_type = class.get_property_str("dataType");
+        if !matches!(data_type, Some("Group" | "Object")) {
+            return Cow::Borrowed(self);
+        }
+
+        let mut owner = self.clone();
+        owner.side = class.get_property_string("side").or(owner.side);
+        if data_type == Some("Group") {
+            owner.group = child(class, "CustomAttributes")
+                .and_then(|attributes| custom_attributes(attributes).remove(GROUP_ID_ATTRIBUTE))
+                .and_then(|callsign| callsign.as_str().map(str::to_string));
+            owner.unit = None;
+        } else {
+            owner.unit = child(class, "Attributes")
+                .and_then(|attributes| attributes.get_property_string("name"));
+        }
+        Cow::Owned(owner)
+    }
+}
+
+/// Class dependency with the side, group and unit it was found under
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttributedDependency {
+    /// Class name of the dependency
+    pub class_name: String,
+    /// Who the dependency belongs to
+    pub owner: DependencyOwner,
+}
@@ -0,0 +1,181 @@
+//! Binarized (rapified) mission.sqm files
+//!
+//! The editor can save missions as a raw binarized config. The file starts
+//! with the `\0raP` signature, followed by the root class body at offset 16.
+//! A class body is the parent name and a compressed count of entries; nested
+//! class entries point to their own body by absolute offset. Decoding turns
+//! the file back into text SQM, which the regular parser then reads.
+
+/// Signature at the start of a binarized config
+pub const RAP_SIGNATURE: &[u8; 4] = b"\0raP";
+
+/// Offset of the root class body
+const ROOT_BODY_OFFSET: usize = 16;
+
+/// Deepest class nesting accepted, guarding against offsets that loop
+const MAX_DEPTH: usize = 64;
+
+/// Check if content is a binarized config
+pub fn is_binarized(content: &[u8]) -> bool {
+    content.starts_with(RAP_SIGNATURE)
+}
+
+/// Decode mission.sqm content into text, whether it is binarized or not
+pub fn decode_sqm(content: &[u8]) -> Result<String, String> {
+    if is_binarized(content) {
+        derapify(content)
+    } else {
+        String::from_utf8(content.to_vec()).map_err(|e| format!("SQM is not valid UTF-8: {}", e))
+    }
+}
+
+/// Convert a binarized config into the equivalent text
+pub fn derapify(content: &[u8]) -> Result<String, String> {
+    if !is_binarized(content) {
+        return Err("Missing raP signature".into());
+    }
+    let mut output = String::new();
+    let mut reader = Reader { bytes: content, position: ROOT_BODY_OFFSET };
+    write_class_body(&mut reader, &mut output, 0)?;
+    Ok(output)
+}
+
+/// Write the entries of the class body at the reader's position
+fn write_class_body(reader: &mut Reader, output: &mut String, depth: usize) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        return Err(format!("Classes nested deeper than {}", MAX_DEPTH));
+    }
+    let _parent = reader.string()?;
+    let count = reader.compressed_int()?;
+    let indent = "\t".repeat(depth);
+
+    for _ in 0..count {
+        match reader.byte()? {
+            0 => {
+                let name = reader.string()?;
+                let offset = reader.u32()? as usize;
+                let mut body = Reader { bytes: reader.bytes, position: offset };
+                let parent = body.string()?;
+                body.position = offset;
+
+                output.push_str(&format!("{}class {}", indent, name));
+                if !parent.is_empty() {
+                    output.push_str(&format!(" : {}", parent));
+                }
+                output.push_str(&format!("\n{}{{\n", indent));
+                write_class_body(&mut body, output, depth + 1)?;
+                output.push_str(&format!("{}}};\n", indent));
+            }
+            1 => {
+                let kind = reader.byte()?;
+                let name = reader.string()?;
+                let value = reader.scalar(kind)?;
+                output.push_str(&format!("{}{}={};\n", indent, name, value));
+            }
+            2 => {
+                let name = reader.string()?;
+                let array = reader.array(0)?;
+                output.push_str(&format!("{}{}[]={};\n", indent, name, array));
+            }
+            3 => output.push_str(&format!("{}class {};\n", indent, reader.string()?)),
+            4 => output.push_str(&format!("{}delete {};\n", indent, reader.string()?)),
+            5 => {
+                let flags = reader.u32()?;
+                let name = reader.string()?;
+                let array = reader.array(0)?;
+                let operator = if flags == 1 { "+=" } else { "=" };
+                output.push_str(&format!("{}{}[]{}{};\n", indent, name, operator, array));
+            }
+            entry => return Err(format!("Unknown entry type {} at byte {}", entry, reader.position - 1)),
+        }
+    }
+    Ok(())
+}
+
+/// Cursor over binarized config bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.position.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format!("Unexpected end of data at byte {}", self.position))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a zero-terminated string
+    fn string(&mut self) -> Result<String, String> {
+        let rest = self.bytes.get(self.position..).unwrap_or_default();
+        let len = rest.iter().position(|&b| b == 0)
+            .ok_or_else(|| format!("Unterminated string at byte {}", self.position))?;
+        let value = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.position += len + 1;
+        Ok(value)
+    }
+
+    /// Read an integer stored 7 bits per byte, low bits first
+    fn compressed_int(&mut self) -> Result<u32, String> {
+        let mut value = 0u32;
+        for shift in (0..32).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7F) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(format!("Compressed integer too long at byte {}", self.position))
+    }
+
+    /// Read a value of a type id shared by properties and array elements, as text
+    fn scalar(&mut self, kind: u8) -> Result<String, String> {
+        match kind {
+            0 | 4 => {
+                let value = self.string()?;
+                Ok(if kind == 0 { format!("\"{}\"", value.replace('"', "\"\"")) } else { value })
+            }
+            1 => {
+                let bytes = self.take(4)?;
+                Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).to_string())
+            }
+            2 => Ok((self.u32()? as i32).to_string()),
+            6 => {
+                let bytes = self.take(8)?;
+                let mut value = [0; 8];
+                value.copy_from_slice(bytes);
+                Ok(i64::from_le_bytes(value).to_string())
+            }
+            kind => Err(format!("Unknown value type {} at byte {}", kind, self.position)),
+        }
+    }
+
+    /// Read an array, nested arrays included, as text
+    fn array(&mut self, depth: usize) -> Result<String, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("Arrays nested deeper than {}", MAX_DEPTH));
+        }
+        let count = self.compressed_int()?;
+        let mut elements = Vec::new();
+        for _ in 0..count {
+            let element = match self.byte()? {
+                3 => self.array(depth + 1)?,
+                kind => self.scalar(kind)?,
+            };
+            elements.push(element);
+        }
+        Ok(format!("{{{}}}", elements.join(",")))
+    }
+}
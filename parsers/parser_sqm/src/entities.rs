@@ -0,0 +1,306 @@
+use std::collections::BTreeMap;
+use hemtt_sqm::{Class, SqmFile, Value};
+use crate::models::{ClassExt, CuratorAddons, CuratorModule, CuratorSetup, LogicEntity, Marker, MissionObjects, Trigger};
+use crate::parser::parse_sqm_content;
+
+/// Custom attribute set by ACE when a box is turned into an ACE arsenal
+const ACE_ARSENAL_ATTRIBUTE: &str = "ace_arsenal_attribute";
+/// Custom attribute holding a vehicle's ACE cargo space
+const ACE_CARGO_SPACE_ATTRIBUTE: &str = "ace_cargo_space";
+/// Logic type of the Zeus game master module
+const CURATOR_MODULE: &str = "ModuleCurator_F";
+/// Logic type of the module making more addons available to Zeus
+const CURATOR_ADD_ADDONS_MODULE: &str = "ModuleCuratorAddAddons_F";
+
+/// Value of an SQM property or attribute
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize), serde(untagged))]
+pub enum SqmValue {
+    String(String),
+    Number(f64),
+    Array(Vec<SqmValue>),
+}
+
+impl SqmValue {
+    /// Get the value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SqmValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a number, if it is one
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            SqmValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Value> for SqmValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(s) => SqmValue::String(s.clone()),
+            Value::Number(n) => SqmValue::Number(*n),
+            Value::Integer(i) => SqmValue::Number(*i as f64),
+            Value::Array(items) => SqmValue::Array(items.iter().map(SqmValue::from).collect()),
+        }
+    }
+}
+
+/// Object placed in the mission (unit, vehicle, box, ...)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+pub struct SqmEntity {
+    /// Class name of the object (its `type`)
+    pub class_name: String,
+    /// Variable name given in the editor, if any
+    pub name: Option<String>,
+    /// Plain 3DEN attributes such as `skill` or `description`
+    pub attributes: BTreeMap<String, SqmValue>,
+    /// Attributes from `class CustomAttributes`, keyed by their `property`
+    pub custom_attributes: BTreeMap<String, SqmValue>,
+}
+
+impl SqmEntity {
+    /// Look up a plain or custom attribute by name, ignoring case
+    pub fn attribute(&self, name: &str) -> Option<&SqmValue> {
+        self.attributes.iter()
+            .chain(self.custom_attributes.iter())
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// AI skill set in the editor
+    pub fn skill(&self) -> Option<f64> {
+        self.attribute("skill").and_then(SqmValue::as_number)
+    }
+
+    /// ACE cargo space set in the editor
+    pub fn cargo_space(&self) -> Option<f64> {
+        self.attribute(ACE_CARGO_SPACE_ATTRIBUTE).and_then(SqmValue::as_number)
+    }
+
+    /// Check if the object was turned into an ACE arsenal in the editor
+    pub fn is_arsenal_enabled(&self) -> bool {
+        self.attribute(ACE_ARSENAL_ATTRIBUTE).is_some()
+    }
+
+    /// SQF of the object's init attribute, with doubled quotes unescaped
+    pub fn init(&self) -> Option<String> {
+        self.attribute("init")
+            .and_then(SqmValue::as_str)
+            .filter(|code| !code.trim().is_empty())
+            .map(unescape_quotes)
+    }
+
+    /// Check if the object is a player slot (playable or the player)
+    pub fn is_playable(&self) -> bool {
+        ["isPlayable", "isPlayer"].iter()
+            .any(|name| self.attribute(name).and_then(SqmValue::as_number) == Some(1.0))
+    }
+}
+
+/// Extract every placed object with its editor attributes from SQM content
+pub fn extract_entities(sqm_content: &str) -> Vec<SqmEntity> {
+    match parse_sqm_content(sqm_content) {
+        Ok(sqm_file) => entities_from_file(&sqm_file),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Collect the objects of every Mission class of a parsed SQM file
+fn entities_from_file(sqm_file: &SqmFile) -> Vec<SqmEntity> {
+    let is_object = |class: &Class| class.get_property_str("dataType") == Some("Object");
+
+    sqm_file.classes.get("Mission").into_iter()
+        .flatten()
+        .flat_map(|mission| mission.find_classes(is_object))
+        .filter_map(entity_from_class)
+        .collect()
+}
+
+/// Build an entity from an object class, skipping objects without a type
+fn entity_from_class(class: &Class) -> Option<SqmEntity> {
+    let class_name = class.get_property_string("type")?;
+
+    let attributes_class = child(class, "Attributes");
+    let attributes = attributes_class
+        .map(|attributes| attributes.properties.iter()
+            .map(|(key, value)| (key.clone(), SqmValue::from(value)))
+            .collect())
+        .unwrap_or_default();
+    let name = attributes_class.and_then(|attributes| attributes.get_property_string("name"));
+
+    Some(SqmEntity {
+        class_name,
+        name,
+        attributes,
+        custom_attributes: child(class, "CustomAttributes")
+            .map(custom_attributes)
+            .unwrap_or_default(),
+    })
+}
+
+/// Extract the markers, triggers and logics of SQM content
+///
+/// Reads the `dataType` entries of the editor's `class Entities` as well as the
+/// `class Markers` and `class Sensors` lists of missions saved before 3DEN.
+/// Doubled quotes in trigger code are unescaped.
+pub fn extract_mission_objects(sqm_content: &str) -> MissionObjects {
+    match parse_sqm_content(sqm_content) {
+        Ok(sqm_file) => mission_objects_from_file(&sqm_file),
+        Err(_) => MissionObjects::default(),
+    }
+}
+
+/// Collect the markers, triggers and logics of every Mission class of a parsed SQM file
+fn mission_objects_from_file(sqm_file: &SqmFile) -> MissionObjects {
+    let mut objects = MissionObjects::default();
+    for mission in sqm_file.classes.get("Mission").into_iter().flatten() {
+        for class in mission.find_classes(|class| class.properties.contains_key("dataType")) {
+            match class.get_property_str("dataType") {
+                Some("Marker") => objects.markers.push(marker_from_class(class)),
+                Some("Trigger") => objects.triggers.push(trigger_from_class(class)),
+                Some("Logic") => objects.logics.extend(logic_from_class(class)),
+                _ => {}
+            }
+        }
+
+        let items = |list: &str| child(mission, list).into_iter()
+            .flat_map(|list| list.classes.values().flatten());
+        objects.markers.extend(items("Markers").map(marker_from_class));
+        objects.triggers.extend(items("Sensors").map(trigger_from_class));
+    }
+    objects
+}
+
+fn marker_from_class(class: &Class) -> Marker {
+    Marker {
+        name: property(class, &["name"]),
+        marker_type: property(class, &["type"]),
+        position: position(class),
+        text: property(class, &["text"]),
+    }
+}
+
+/// Build a trigger, reading the 3DEN attribute names and the older `exp*` ones
+fn trigger_from_class(class: &Class) -> Trigger {
+    let code = |names: &[&str]| property(class, names).map(|code| unescape_quotes(&code));
+    Trigger {
+        name: property(class, &["name"]),
+        trigger_type: property(class, &["type"]),
+        position: position(class),
+        text: property(class, &["text"]),
+        condition: code(&["condition", "expCond"]),
+        on_activation: code(&["onActivation", "expActiv"]),
+        on_deactivation: code(&["onDeactivation", "expDesactiv"]),
+    }
+}
+
+/// Build a logic, skipping logics without a type
+fn logic_from_class(class: &Class) -> Option<LogicEntity> {
+    Some(LogicEntity {
+        class_name: class.get_property_string("type")?,
+        name: property(class, &["name"]),
+        position: position(class),
+    })
+}
+
+/// Extract the Zeus modules of SQM content and the addons they make available
+///
+/// Reads the `ModuleCurator_F` game master modules with their owner and
+/// default addons, and the addons listed by `ModuleCuratorAddAddons_F`
+/// modules, comma separated.
+pub fn extract_curators(sqm_content: &str) -> CuratorSetup {
+    match parse_sqm_content(sqm_content) {
+        Ok(sqm_file) => curators_from_file(&sqm_file),
+        Err(_) => CuratorSetup::default(),
+    }
+}
+
+/// Collect the Zeus modules of every Mission class of a parsed SQM file
+fn curators_from_file(sqm_file: &SqmFile) -> CuratorSetup {
+    let mut setup = CuratorSetup::default();
+    let is_logic = |class: &Class| class.get_property_str("dataType") == Some("Logic");
+    for mission in sqm_file.classes.get("Mission").into_iter().flatten() {
+        for class in mission.find_classes(is_logic) {
+            let Some(logic_type) = class.get_property_str("type") else {
+                continue;
+            };
+            let attributes = child(class, "CustomAttributes").map(custom_attributes).unwrap_or_default();
+            let attribute = |name: &str| attributes.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&format!("{}_{}", logic_type, name)))
+                .map(|(_, value)| value);
+
+            if logic_type.eq_ignore_ascii_case(CURATOR_MODULE) {
+                setup.modules.push(CuratorModule {
+                    name: property(class, &["name"]),
+                    owner: attribute("Owner").and_then(SqmValue::as_str)
+                        .filter(|owner| !owner.is_empty())
+                        .map(str::to_string),
+                    addons: attribute("Addons").and_then(SqmValue::as_number).and_then(CuratorAddons::from_value),
+                });
+            } else if logic_type.eq_ignore_ascii_case(CURATOR_ADD_ADDONS_MODULE) {
+                let addons = attribute("Addons").and_then(SqmValue::as_str).unwrap_or_default();
+                setup.added_addons.extend(addons.split(',')
+                    .map(str::trim)
+                    .filter(|addon| !addon.is_empty())
+                    .map(str::to_string));
+            }
+        }
+    }
+    setup.added_addons.sort();
+    setup.added_addons.dedup();
+    setup
+}
+
+/// Get the first of several string properties set on a class or its `class Attributes`
+fn property(class: &Class, names: &[&str]) -> Option<String> {
+    let attributes = child(class, "Attributes");
+    names.iter().find_map(|name| class.get_property_string(name)
+        .or_else(|| attributes.and_then(|attributes| attributes.get_property_string(name))))
+}
+
+/// Position of a class, set directly or in its `class PositionInfo`
+fn position(class: &Class) -> Option<[f64; 3]> {
+    let value = class.properties.get("position")
+        .or_else(|| child(class, "PositionInfo").and_then(|info| info.properties.get("position")))?;
+    let Value::Array(items) = value else {
+        return None;
+    };
+    let coordinates: Vec<f64> = items.iter()
+        .filter_map(|item| SqmValue::from(item).as_number())
+        .collect();
+    match coordinates[..] {
+        [x, y, z, ..] => Some([x, y, z]),
+        [x, y] => Some([x, y, 0.0]),
+        _ => None,
+    }
+}
+
+/// Undo the doubling of quotes inside SQM strings
+pub(crate) fn unescape_quotes(value: &str) -> String {
+    value.replace("\"\"", "\"")
+}
+
+/// Read `class AttributeN { property = ...; class Value { class data { value = ...; }; }; }` entries
+pub(crate) fn custom_attributes(class: &Class) -> BTreeMap<String, SqmValue> {
+    class.classes.values()
+        .flatten()
+        .filter_map(|attribute| {
+            let property = attribute.get_property_string("property")?;
+            let value = child(attribute, "Value")
+                .and_then(|value| child(value, "data"))
+                .and_then(|data| data.properties.get("value"))?;
+            Some((property, SqmValue::from(value)))
+        })
+        .collect()
+}
+
+/// Get the first child class with a name
+pub(crate) fn child<'a>(class: &'a Class, name: &str) -> Option<&'a Class> {
+    class.classes.get(name).and_then(|classes| classes.first())
+}
@@ -1,13 +1,29 @@
 pub mod models;
+mod addons;
+mod attribution;
+mod binarized;
+mod entities;
 mod parser;
 mod query;
+#[cfg(feature = "parallel")]
+mod sections;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 use std::collections::HashSet;
 use parser::parse_sqm_content;
 use query::DependencyExtractor;
 
+pub use addons::extract_required_addons;
+pub use attribution::{AttributedDependency, DependencyOwner};
+pub use binarized::{decode_sqm, derapify, is_binarized, RAP_SIGNATURE};
+pub use entities::{extract_curators, extract_entities, extract_mission_objects, SqmEntity, SqmValue};
+pub use models::{CuratorAddons, CuratorModule, CuratorSetup, LogicEntity, Marker, MissionObjects, Trigger};
+
 /// Extract class dependencies from SQM content
 /// 
+/// Binarized files must be decoded with [`decode_sqm`] first.
+///
 /// This function parses an SQM file and extracts all dependencies including:
 /// - Weapons, magazines, and other equipment
 /// - Uniforms, vests, backpacks, headgear
@@ -46,4 +62,55 @@ pub fn extract_class_dependencies(sqm_content: &str) -> HashSet<String> {
         }
         Err(_) => HashSet::new()
     }
-}
\ No newline at end of file
+}
+
+/// Extract class dependencies from SQM content with the side, group and unit
+/// each one was found under.
+///
+/// The same class appears once per owner that uses it.
+pub fn extract_attributed_dependencies(sqm_content: &str) -> Vec<AttributedDependency> {
+    match parse_sqm_content(sqm_content) {
+        Ok(sqm_file) => {
+            let mut seen = HashSet::new();
+            DependencyExtractor::new(&sqm_file)
+                .extract_attributed_dependencies()
+                .into_iter()
+                .filter(|dependency| seen.insert(dependency.clone()))
+                .collect()
+        }
+        Err(_) => Vec::new()
+    }
+}
+
+/// Extract class dependencies from SQM content, parsing the mission's entities in parallel.
+///
+/// Meant for huge files: each `ItemN` of `class Mission { class Entities }`
+/// is parsed on its own and the dependency sets are merged, which gives the
+/// same dependencies as [`extract_class_dependencies`]. Content without
+/// entities is parsed as a whole.
+#[cfg(feature = "parallel")]
+pub fn extract_class_dependencies_parallel(sqm_content: &str) -> HashSet<String> {
+    match sections::split_entities(sqm_content) {
+        Some(sections) => sections.extract(extract_class_dependencies).into_iter().flatten().collect(),
+        None => extract_class_dependencies(sqm_content),
+    }
+}
+
+/// Extract attributed dependencies from SQM content, parsing the mission's entities in parallel.
+///
+/// Dependencies are in file order with each entity's in the order
+/// [`extract_attributed_dependencies`] finds them.
+#[cfg(feature = "parallel")]
+pub fn extract_attributed_dependencies_parallel(sqm_content: &str) -> Vec<AttributedDependency> {
+    match sections::split_entities(sqm_content) {
+        Some(sections) => {
+            let mut seen = HashSet::new();
+            sections.extract(extract_attributed_dependencies)
+                .into_iter()
+                .flatten()
+                .filter(|dependency| seen.insert(dependency.clone()))
+                .collect()
+        }
+        None => extract_attributed_dependencies(sqm_content),
+    }
+}
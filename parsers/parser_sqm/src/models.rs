@@ -10,6 +10,9 @@ pub(crate) trait ClassExt {
 
     /// Extract property value as a string if it exists
     fn get_property_string(&self, name: &str) -> Option<String>;
+
+    /// Borrow property value as a string if it exists, without copying it
+    fn get_property_str(&self, name: &str) -> Option<&str>;
 }
 
 impl ClassExt for Class {
@@ -35,9 +38,13 @@ impl ClassExt for Class {
     }
     
     fn get_property_string(&self, name: &str) -> Option<String> {
+        self.get_property_str(name).map(str::to_string)
+    }
+
+    fn get_property_str(&self, name: &str) -> Option<&str> {
         self.properties.get(name).and_then(|value| {
             match value {
-                Value::String(s) => Some(s.clone()),
+                Value::String(s) => Some(s.as_str()),
                 _ => None,
             }
         })
@@ -45,11 +52,14 @@ impl ClassExt for Class {
 }
 
 /// Utility for collecting dependencies from SQM files
-pub(crate) struct DependencyCollector {
-    dependencies: HashSet<String>,
+///
+/// Class names repeat thousands of times in big missions, so they are borrowed
+/// from the parsed file while collecting and each distinct name is copied once.
+pub(crate) struct DependencyCollector<'a> {
+    dependencies: HashSet<&'a str>,
 }
 
-impl DependencyCollector {
+impl<'a> DependencyCollector<'a> {
     pub fn new() -> Self {
         Self {
             dependencies: HashSet::new(),
@@ -57,18 +67,136 @@ impl DependencyCollector {
     }
     
     /// Add a dependency string if it's valid
+    pub fn add_dependency(&mut self, dependency: &'a str) {
+        if Self::is_valid(dependency) {
+            self.dependencies.insert(dependency);
+        }
+    }
+
+    /// Check if a dependency string is valid
     /// 
     /// Dependencies are invalid if:
     /// - They are empty strings
     /// - They contain a colon (typically used for special commands)
-    pub fn add_dependency(&mut self, dependency: String) {
-        if !dependency.is_empty() && !dependency.contains(':') {
-            self.dependencies.insert(dependency);
-        }
+    pub fn is_valid(dependency: &str) -> bool {
+        !dependency.is_empty() && !dependency.contains(':')
     }
     
     /// Consume this collector and return the HashSet of dependencies
     pub fn get_dependencies(self) -> HashSet<String> {
-        self.dependencies
+        self.dependencies.into_iter().map(str::to_string).collect()
     }
-}
\ No newline at end of file
+}
+/// Map marker placed in the editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    /// Variable name of the marker
+    pub name: Option<String>,
+    /// Marker type, such as `hd_dot` or `mil_objective`
+    pub marker_type: Option<String>,
+    /// Position in the world, as `[x, y, z]`
+    pub position: Option<[f64; 3]>,
+    /// Text shown next to the marker
+    pub text: Option<String>,
+}
+
+/// Trigger placed in the editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    /// Variable name of the trigger
+    pub name: Option<String>,
+    /// Trigger type, such as `EmptyDetector`
+    pub trigger_type: Option<String>,
+    /// Position in the world, as `[x, y, z]`
+    pub position: Option<[f64; 3]>,
+    /// Text shown for the trigger in the editor
+    pub text: Option<String>,
+    /// SQF condition of the trigger
+    pub condition: Option<String>,
+    /// SQF run when the trigger activates
+    pub on_activation: Option<String>,
+    /// SQF run when the trigger deactivates
+    pub on_deactivation: Option<String>,
+}
+
+impl Trigger {
+    /// SQF the trigger runs on activation and deactivation
+    pub fn statements(&self) -> impl Iterator<Item = &str> {
+        self.on_activation.iter()
+            .chain(&self.on_deactivation)
+            .map(String::as_str)
+            .filter(|code| !code.trim().is_empty())
+    }
+}
+
+/// Game logic placed in the editor, such as a module
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicEntity {
+    /// Class name of the logic (its `type`)
+    pub class_name: String,
+    /// Variable name of the logic
+    pub name: Option<String>,
+    /// Position in the world, as `[x, y, z]`
+    pub position: Option<[f64; 3]>,
+}
+
+/// Addons a Zeus module gives its curator by default, from its `Addons` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CuratorAddons {
+    /// No addons
+    None,
+    /// Addons the scenario uses
+    Scenario,
+    /// Every official addon
+    Official,
+    /// Every loaded addon, unofficial ones included
+    All,
+}
+
+impl CuratorAddons {
+    /// Read the value of the module's `Addons` attribute
+    pub fn from_value(value: f64) -> Option<Self> {
+        match value as i64 {
+            0 => Some(CuratorAddons::None),
+            1 => Some(CuratorAddons::Scenario),
+            2 => Some(CuratorAddons::Official),
+            3 => Some(CuratorAddons::All),
+            _ => None,
+        }
+    }
+}
+
+/// Zeus game master module (`ModuleCurator_F`) placed in the editor
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuratorModule {
+    /// Variable name of the module
+    pub name: Option<String>,
+    /// Who becomes the curator: a player UID, a unit's variable name or `#adminLogged`
+    pub owner: Option<String>,
+    /// Addons available by default, unset when the attribute isn't saved
+    pub addons: Option<CuratorAddons>,
+}
+
+/// Zeus setup of a mission
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CuratorSetup {
+    /// Game master modules
+    pub modules: Vec<CuratorModule>,
+    /// Addons (CfgPatches classes) `ModuleCuratorAddAddons_F` modules make available, sorted
+    pub added_addons: Vec<String>,
+}
+
+impl CuratorSetup {
+    /// Check if the mission has a Zeus
+    pub fn is_present(&self) -> bool {
+        !self.modules.is_empty()
+    }
+}
+
+/// Markers, triggers and logics of a mission
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MissionObjects {
+    pub markers: Vec<Marker>,
+    pub triggers: Vec<Trigger>,
+    pub logics: Vec<LogicEntity>,
+}
@@ -1,27 +1,33 @@
 use std::collections::HashSet;
 use hemtt_sqm::{Class, SqmFile, Value};
+use crate::attribution::{AttributedDependency, DependencyOwner};
 use crate::models::{ClassExt, DependencyCollector};
 
+/// Properties of an editor weapon class naming the weapon and its attachments.
+///
+/// The editor writes the laser or light as `flashlight`; `pointer` is accepted too.
+const WEAPON_PROPERTIES: &[&str] = &["name", "muzzle", "optics", "flashlight", "pointer", "underBarrel"];
+
 /// Represents a query pattern to search for and extract data from SQM classes
 #[derive(Debug, Clone)]
 pub struct QueryPattern {
     /// The path to search for (e.g. "Inventory/primaryWeapon")
-    path: Vec<String>,
+    path: Vec<&'static str>,
     /// Properties to extract from matching classes
-    properties: Vec<String>,
+    properties: &'static [&'static str],
 }
 
 impl QueryPattern {
     /// Create a new query pattern
-    pub fn new(path: &str, properties: &[&str]) -> Self {
+    pub fn new(path: &'static str, properties: &'static [&'static str]) -> Self {
         Self {
-            path: path.split('/').map(String::from).collect(),
-            properties: properties.iter().map(|&s| s.to_string()).collect(),
+            path: path.split('/').collect(),
+            properties,
         }
     }
 
     /// Check if a class matches this pattern's path
-    fn matches_path(&self, class_path: &[String]) -> bool {
+    fn matches_path(&self, class_path: &[&str]) -> bool {
         if class_path.len() < self.path.len() {
             return false;
         }
@@ -29,7 +35,7 @@ impl QueryPattern {
         // Check if the end of the class_path matches our pattern path
         let start_idx = class_path.len() - self.path.len();
         class_path[start_idx..].iter().zip(&self.path)
-            .all(|(a, b)| b == "*" || a == b)
+            .all(|(a, b)| *b == "*" || a == b)
     }
 }
 
@@ -49,17 +55,20 @@ impl<'a> DependencyExtractor<'a> {
                 "map", "compass", "watch", "radio", "gps", "goggles"
             ]),
             
-            // Primary weapon and magazines
-            QueryPattern::new("Inventory/primaryWeapon", &["name", "muzzle"]),
+            // Primary weapon, attachments and magazines
+            QueryPattern::new("Inventory/primaryWeapon", WEAPON_PROPERTIES),
             QueryPattern::new("Inventory/primaryWeapon/primaryMuzzleMag", &["name"]),
+            QueryPattern::new("Inventory/primaryWeapon/secondaryMuzzleMag", &["name"]),
             
-            // Secondary weapon and magazines
-            QueryPattern::new("Inventory/secondaryWeapon", &["name", "muzzle"]),
+            // Secondary weapon, attachments and magazines
+            QueryPattern::new("Inventory/secondaryWeapon", WEAPON_PROPERTIES),
             QueryPattern::new("Inventory/secondaryWeapon/primaryMuzzleMag", &["name"]),
+            QueryPattern::new("Inventory/secondaryWeapon/secondaryMuzzleMag", &["name"]),
             
-            // Handgun weapon and magazines
-            QueryPattern::new("Inventory/handgunWeapon", &["name", "muzzle"]),
+            // Handgun weapon, attachments and magazines
+            QueryPattern::new("Inventory/handgunWeapon", WEAPON_PROPERTIES),
             QueryPattern::new("Inventory/handgunWeapon/primaryMuzzleMag", &["name"]),
+            QueryPattern::new("Inventory/handgunWeapon/secondaryMuzzleMag", &["name"]),
             
             // Container contents
             QueryPattern::new("Inventory/*/ItemCargo/Item*", &["name"]),
@@ -75,43 +84,70 @@ impl<'a> DependencyExtractor<'a> {
     /// Extract all class dependencies from the SQM file
     pub fn extract_dependencies(&self) -> HashSet<String> {
         let mut collector = DependencyCollector::new();
+        for mission_class in self.get_mission_classes() {
+            self.collect_class(mission_class, &mut Vec::new(), &mut collector);
+        }
+        collector.get_dependencies()
+    }
+
+    /// Extract all class dependencies with the side, group and unit they belong to
+    pub fn extract_attributed_dependencies(&self) -> Vec<AttributedDependency> {
+        let mut dependencies = Vec::new();
         
         // Process all Mission classes
         for mission_class in self.get_mission_classes() {
-            self.process_class(mission_class, &[], &mut collector);
+            self.process_class(mission_class, &mut Vec::new(), &DependencyOwner::default(), &mut dependencies);
         }
         
-        collector.get_dependencies()
+        dependencies
+    }
+
+    /// Collect the dependencies of a class and its children recursively, borrowing the class names
+    fn collect_class(&self, class: &'a Class, class_path: &mut Vec<&'a str>, collector: &mut DependencyCollector<'a>) {
+        class_path.push(&class.name);
+        for value in self.matching_values(class, class_path) {
+            collector.add_dependency(value);
+        }
+        for child_class in class.classes.values().flatten() {
+            self.collect_class(child_class, class_path, collector);
+        }
+        class_path.pop();
     }
     
     /// Process a class and its children recursively
-    fn process_class(&self, class: &Class, current_path: &[String], collector: &mut DependencyCollector) {
-        // Build the current class path
-        let mut class_path = current_path.to_vec();
-        class_path.push(class.name.clone());
+    fn process_class(
+        &self,
+        class: &'a Class,
+        class_path: &mut Vec<&'a str>,
+        owner: &DependencyOwner,
+        dependencies: &mut Vec<AttributedDependency>,
+    ) {
+        class_path.push(&class.name);
+        let owner = owner.enter(class);
         
-        // Check each pattern against the current class
-        for pattern in &self.patterns {
-            if pattern.matches_path(&class_path) {
-                // Extract properties defined in the pattern
-                for prop_name in &pattern.properties {
-                    if let Some(value) = class.get_property_string(prop_name) {
-                        collector.add_dependency(value);
-                    }
-                }
+        for value in self.matching_values(class, class_path) {
+            if DependencyCollector::is_valid(value) {
+                dependencies.push(AttributedDependency { class_name: value.to_string(), owner: owner.as_ref().clone() });
             }
         }
         
         // Process child classes
-        for (child_name, child_classes) in &class.classes {
-            for child_class in child_classes {
-                self.process_class(child_class, &class_path, collector);
-            }
+        for child_class in class.classes.values().flatten() {
+            self.process_class(child_class, class_path, &owner, dependencies);
         }
+        class_path.pop();
+    }
+
+    /// Values of the properties extracted by every pattern matching a class's path
+    fn matching_values<'c>(&'c self, class: &'a Class, class_path: &'c [&'a str]) -> impl Iterator<Item = &'a str> + 'c {
+        self.patterns.iter()
+            .filter(|pattern| pattern.matches_path(class_path))
+            .flat_map(|pattern| pattern.properties.iter())
+            .filter_map(|prop_name| class.get_property_str(prop_name))
     }
     
     /// Get all Mission classes from the SQM file
-    fn get_mission_classes(&self) -> Vec<&Class> {
+    fn get_mission_classes(&self) -> Vec<&'a Class> {
         self.sqm_file.classes.get("Mission")
             .map(|classes| classes.iter().collect())
             .unwrap_or_default()
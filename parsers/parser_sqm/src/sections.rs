@@ -0,0 +1,186 @@
+//! Splitting of huge mission.sqm files into sections parsed in parallel
+//!
+//! Nearly all of a large mission.sqm is the `class Entities` of `class Mission`,
+//! one `ItemN` class per placed group or object. The Item classes don't depend
+//! on each other, so they are cut out of the text and each one is parsed on
+//! its own, wrapped back in `class Mission { class Entities { ... }; };` so
+//! the query paths and owners stay the same. The rest of the file is parsed
+//! once without them. Only the structure is scanned to find the cuts: strings
+//! are skipped and braces counted.
+
+use std::ops::Range;
+
+use rayon::prelude::*;
+
+/// Class found in SQM text, with byte ranges into the text
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ClassSpan<'a> {
+    name: &'a str,
+    /// From `class` to after the closing `}` and its `;`
+    span: Range<usize>,
+    /// Between the braces
+    body: Range<usize>,
+}
+
+/// Mission split into its entities and everything else
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EntitySections<'a> {
+    /// Every `ItemN` class of `class Mission { class Entities }`, as written
+    pub items: Vec<&'a str>,
+    /// The file with the Entities body emptied
+    pub rest: String,
+}
+
+impl EntitySections<'_> {
+    /// Run an extraction on the rest of the file and on each Item in parallel,
+    /// returning the results in file order
+    pub fn extract<T: Send>(&self, extract: impl Fn(&str) -> T + Sync) -> Vec<T> {
+        let (rest, items) = rayon::join(
+            || extract(&self.rest),
+            || self.items.par_iter().map(|item| extract(&wrap_item(item))).collect::<Vec<_>>(),
+        );
+        std::iter::once(rest).chain(items).collect()
+    }
+}
+
+/// Cut the Item classes of the mission's entities out of SQM content.
+///
+/// Returns `None` when the content has no `class Mission { class Entities }`
+/// or its braces don't balance, so it should be parsed as a whole.
+pub(crate) fn split_entities(content: &str) -> Option<EntitySections<'_>> {
+    let mission = top_level_classes(content, 0..content.len())?
+        .into_iter()
+        .find(|class| class.name.eq_ignore_ascii_case("Mission"))?;
+    let entities = top_level_classes(content, mission.body.clone())?
+        .into_iter()
+        .find(|class| class.name.eq_ignore_ascii_case("Entities"))?;
+    let items = top_level_classes(content, entities.body.clone())?
+        .into_iter()
+        .map(|item| &content[item.span])
+        .collect();
+
+    let rest = format!("{}{}", &content[..entities.body.start], &content[entities.body.end..]);
+    Some(EntitySections { items, rest })
+}
+
+/// Put an Item back under the classes it was cut from
+fn wrap_item(item: &str) -> String {
+    format!("class Mission {{\nclass Entities {{\n{}\n}};\n}};\n", item)
+}
+
+/// Classes directly inside a range of SQM text, or `None` if its braces don't balance
+fn top_level_classes(content: &str, range: Range<usize>) -> Option<Vec<ClassSpan<'_>>> {
+    let bytes = content.as_bytes();
+    let mut classes = Vec::new();
+    let mut depth = 0usize;
+    let mut i = range.start;
+
+    while i < range.end {
+        match bytes[i] {
+            b'"' => i = skip_string(bytes, i + 1),
+            b'{' => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' => {
+                depth = depth.checked_sub(1)?;
+                i += 1;
+            }
+            c if depth == 0 && is_word_start(c) => {
+                let word_end = word_end(bytes, i);
+                if &content[i..word_end] == "class" {
+                    if let Some(class) = class_at(content, i, word_end, range.end) {
+                        i = class.span.end;
+                        classes.push(class);
+                        continue;
+                    }
+                }
+                i = word_end;
+            }
+            _ => i += 1,
+        }
+    }
+    (depth == 0).then_some(classes)
+}
+
+/// Read the class whose `class` keyword is at `start..keyword_end`, if it has a body
+fn class_at(content: &str, start: usize, keyword_end: usize, end: usize) -> Option<ClassSpan<'_>> {
+    let bytes = content.as_bytes();
+    let name_start = skip_whitespace(bytes, keyword_end, end);
+    let name_end = word_end(bytes, name_start);
+    if name_end == name_start {
+        return None;
+    }
+
+    // Skip an inherited parent, `class Name : Parent {`
+    let mut i = skip_whitespace(bytes, name_end, end);
+    if bytes.get(i) == Some(&b':') {
+        i = skip_whitespace(bytes, i + 1, end);
+        i = skip_whitespace(bytes, word_end(bytes, i), end);
+    }
+    if i >= end || bytes[i] != b'{' {
+        return None;
+    }
+
+    let body_start = i + 1;
+    let mut depth = 1;
+    i = body_start;
+    while i < end {
+        match bytes[i] {
+            b'"' => {
+                i = skip_string(bytes, i + 1);
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if i >= end {
+        return None;
+    }
+
+    let body = body_start..i;
+    let after = skip_whitespace(bytes, i + 1, end);
+    let span_end = if after < end && bytes[after] == b';' { after + 1 } else { i + 1 };
+    Some(ClassSpan { name: &content[name_start..name_end], span: start..span_end, body })
+}
+
+/// Index after the string whose opening quote is before `i`; quotes are escaped by doubling
+fn skip_string(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() {
+        if bytes[i] == b'"' {
+            if bytes.get(i + 1) == Some(&b'"') {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize, end: usize) -> usize {
+    while i < end && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn is_word_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn word_end(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+        i += 1;
+    }
+    i
+}
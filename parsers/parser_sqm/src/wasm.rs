@@ -0,0 +1,33 @@
+//! JavaScript bindings for auditing mission.sqm files in a browser
+//!
+//! Built with the `wasm` feature. The browser has no threads to spare, so
+//! build without the default `parallel` feature. Binarized files must be
+//! decoded with `decodeSqm` first.
+
+use wasm_bindgen::prelude::*;
+
+/// Class dependencies of SQM content, sorted
+#[wasm_bindgen(js_name = extractClassDependencies)]
+pub fn extract_class_dependencies(sqm_content: &str) -> Vec<String> {
+    let mut dependencies: Vec<String> = crate::extract_class_dependencies(sqm_content).into_iter().collect();
+    dependencies.sort();
+    dependencies
+}
+
+/// Addons listed in the `addons[]` and `addOnsAuto[]` arrays of SQM content
+#[wasm_bindgen(js_name = extractRequiredAddons)]
+pub fn extract_required_addons(sqm_content: &str) -> Vec<String> {
+    crate::extract_required_addons(sqm_content)
+}
+
+/// Objects placed in the mission with their editor attributes, as a JSON array
+#[wasm_bindgen(js_name = parseItems)]
+pub fn parse_items(sqm_content: &str) -> Result<String, String> {
+    serde_json::to_string(&crate::extract_entities(sqm_content)).map_err(|e| e.to_string())
+}
+
+/// Text of SQM content that may be binarized, or an error if it can't be decoded
+#[wasm_bindgen(js_name = decodeSqm)]
+pub fn decode_sqm(bytes: &[u8]) -> Result<String, String> {
+    crate::decode_sqm(bytes)
+}
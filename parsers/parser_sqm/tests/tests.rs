@@ -49,6 +49,29 @@ mod tests {
         assert!(dependencies.contains("pca_eagle_a3_od"));
         assert!(dependencies.contains("simc_pasgt_m81"));
     }
+
+    #[test]
+    fn test_parallel_sections_match_whole_file() {
+        use parser_sqm::{
+            extract_attributed_dependencies, extract_attributed_dependencies_parallel,
+            extract_class_dependencies_parallel,
+        };
+
+        let mission_content = std::fs::read_to_string("tests/fixtures/example_mission.sqm")
+            .expect("Unable to read example mission file");
+
+        assert_eq!(
+            extract_class_dependencies_parallel(&mission_content),
+            extract_class_dependencies(&mission_content)
+        );
+
+        let mut parallel = extract_attributed_dependencies_parallel(&mission_content);
+        let mut whole = extract_attributed_dependencies(&mission_content);
+        let key = |d: &parser_sqm::AttributedDependency| (d.class_name.clone(), d.owner.clone());
+        parallel.sort_by_key(key);
+        whole.sort_by_key(key);
+        assert_eq!(parallel, whole);
+    }
     
     #[test]
     fn test_parse_nested_inventory_with_cargo() {
@@ -163,6 +186,45 @@ mod tests {
         assert!(dependencies.contains("test_pistol_mag"));
     }
 
+    #[test]
+    fn test_parse_weapon_attachments() {
+        let input = r#"
+        class Mission {
+            class Item1 {
+                class Attributes {
+                    class Inventory {
+                        class primaryWeapon {
+                            name = "arifle_MX_GL_F";
+                            optics = "optic_Hamr";
+                            muzzle = "muzzle_snds_H";
+                            flashlight = "acc_pointer_IR";
+                            underBarrel = "bipod_01_F_snd";
+                            class primaryMuzzleMag {
+                                name = "30Rnd_65x39_caseless_mag";
+                            };
+                            class secondaryMuzzleMag {
+                                name = "1Rnd_HE_Grenade_shell";
+                            };
+                        };
+                        class handgunWeapon {
+                            name = "hgun_P07_F";
+                            pointer = "acc_flashlight_pistol";
+                        };
+                    };
+                };
+            };
+        };"#;
+
+        let dependencies = extract_class_dependencies(input);
+        assert_eq!(dependencies.len(), 9);
+        for class in [
+            "arifle_MX_GL_F", "optic_Hamr", "muzzle_snds_H", "acc_pointer_IR", "bipod_01_F_snd",
+            "30Rnd_65x39_caseless_mag", "1Rnd_HE_Grenade_shell", "hgun_P07_F", "acc_flashlight_pistol",
+        ] {
+            assert!(dependencies.contains(class), "Missing {}", class);
+        }
+    }
+
     #[test]
     fn test_deep_nested_structure() {
         let input = r#"class Mission {
@@ -279,4 +341,539 @@ mod tests {
             assert!(dependencies.contains(&format!("test_rifle_{}", i)));
         }
     }
+
+    #[test]
+    fn test_extract_entity_attributes() {
+        use parser_sqm::{extract_entities, SqmValue};
+
+        let input = r#"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Object";
+                    class PositionInfo {
+                        position[]={1,2,3};
+                    };
+                    side="Empty";
+                    class Attributes {
+                        name="supply_box";
+                    };
+                    id=12;
+                    type="Box_NATO_Equip_F";
+                    class CustomAttributes {
+                        class Attribute0 {
+                            property="ace_arsenal_attribute";
+                            expression="[_this, _value] call ace_arsenal_fnc_initBox;";
+                            class Value {
+                                class data {
+                                    singleType="ARRAY";
+                                    value="[[],0]";
+                                };
+                            };
+                        };
+                        class Attribute1 {
+                            property="ace_cargo_space";
+                            expression="";
+                            class Value {
+                                class data {
+                                    singleType="SCALAR";
+                                    value=8;
+                                };
+                            };
+                        };
+                        nAttributes=2;
+                    };
+                };
+                class Item1 {
+                    dataType="Group";
+                    side="West";
+                    class Entities {
+                        class Item0 {
+                            dataType="Object";
+                            class Attributes {
+                                skill=0.6;
+                                description="Rifleman";
+                            };
+                            id=14;
+                            type="B_Soldier_F";
+                        };
+                    };
+                };
+            };
+        };"#;
+
+        let entities = extract_entities(input);
+        assert_eq!(entities.len(), 2);
+
+        let bx = entities.iter().find(|e| e.class_name == "Box_NATO_Equip_F").unwrap();
+        assert_eq!(bx.name.as_deref(), Some("supply_box"));
+        assert!(bx.is_arsenal_enabled());
+        assert_eq!(bx.cargo_space(), Some(8.0));
+
+        let soldier = entities.iter().find(|e| e.class_name == "B_Soldier_F").unwrap();
+        assert!(!soldier.is_arsenal_enabled());
+        assert_eq!(soldier.skill(), Some(0.6));
+        assert_eq!(soldier.attribute("Description"), Some(&SqmValue::String("Rifleman".to_string())));
+    }
+
+    #[test]
+    fn test_attribute_dependencies_to_owner() {
+        use parser_sqm::{extract_attributed_dependencies, DependencyOwner};
+
+        let input = r#"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Group";
+                    side="East";
+                    class Entities {
+                        class Item0 {
+                            dataType="Object";
+                            class Attributes {
+                                name="opfor_lead";
+                                class Inventory {
+                                    class primaryWeapon {
+                                        name="rhs_weap_ak74m";
+                                    };
+                                    uniform="rhs_uniform_msv_emr";
+                                };
+                            };
+                            side="East";
+                            type="rhs_msv_emr_rifleman";
+                        };
+                    };
+                    class CustomAttributes {
+                        class Attribute0 {
+                            property="groupID";
+                            expression="[_this, _value] call CBA_fnc_setCallsign";
+                            class Value {
+                                class data {
+                                    singleType="STRING";
+                                    value="Red 1";
+                                };
+                            };
+                        };
+                        nAttributes=1;
+                    };
+                };
+                class Item1 {
+                    dataType="Group";
+                    side="West";
+                    class Entities {
+                        class Item0 {
+                            dataType="Object";
+                            class Attributes {
+                                class Inventory {
+                                    uniform="CUP_U_B_USArmy_Base";
+                                };
+                            };
+                            side="West";
+                            type="CUP_B_US_Soldier";
+                        };
+                    };
+                };
+                class Item2 {
+                    dataType="Object";
+                    side="Empty";
+                    type="Box_NATO_Equip_F";
+                };
+            };
+        };"#;
+
+        let dependencies = extract_attributed_dependencies(input);
+        let owner_of = |class_name: &str| dependencies.iter()
+            .find(|d| d.class_name == class_name)
+            .map(|d| d.owner.clone())
+            .unwrap();
+
+        let opfor = DependencyOwner {
+            side: Some("East".to_string()),
+            group: Some("Red 1".to_string()),
+            unit: Some("opfor_lead".to_string()),
+        };
+        assert_eq!(owner_of("rhs_weap_ak74m"), opfor);
+        assert_eq!(owner_of("rhs_uniform_msv_emr"), opfor);
+        assert_eq!(owner_of("rhs_msv_emr_rifleman"), opfor);
+
+        let blufor = owner_of("CUP_U_B_USArmy_Base");
+        assert_eq!(blufor.side.as_deref(), Some("West"));
+        assert_eq!(blufor.group, None);
+        assert_eq!(blufor.unit, None);
+
+        assert_eq!(owner_of("Box_NATO_Equip_F").side.as_deref(), Some("Empty"));
+    }
+
+    #[test]
+    fn test_count_playable_entities() {
+        use parser_sqm::extract_entities;
+
+        let input = r#"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Group";
+                    side="West";
+                    class Entities {
+                        class Item0 {
+                            dataType="Object";
+                            class Attributes {
+                                isPlayer=1;
+                            };
+                            type="B_Soldier_TL_F";
+                        };
+                        class Item1 {
+                            dataType="Object";
+                            class Attributes {
+                                isPlayable=1;
+                            };
+                            type="B_Soldier_F";
+                        };
+                        class Item2 {
+                            dataType="Object";
+                            class Attributes {
+                            };
+                            type="B_Soldier_F";
+                        };
+                    };
+                };
+            };
+        };"#;
+
+        let entities = extract_entities(input);
+        assert_eq!(entities.len(), 3);
+        assert_eq!(entities.iter().filter(|e| e.is_playable()).count(), 2);
+    }
+
+    /// Entry of a class body written by `rapify`
+    enum RapEntry {
+        Class(&'static str, Vec<RapEntry>),
+        Text(&'static str, &'static str),
+        Int(&'static str, i32),
+        Float(&'static str, f32),
+        Array(&'static str, Vec<&'static str>),
+    }
+
+    /// Binarize class entries the way the editor does
+    fn rapify(entries: &[RapEntry]) -> Vec<u8> {
+        let mut bytes = b"\0raP".to_vec();
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(8u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        write_body(&mut bytes, entries);
+        bytes
+    }
+
+    fn write_body(bytes: &mut Vec<u8>, entries: &[RapEntry]) {
+        let string = |bytes: &mut Vec<u8>, value: &str| {
+            bytes.extend(value.as_bytes());
+            bytes.push(0);
+        };
+        string(bytes, "");
+        bytes.push(entries.len() as u8);
+
+        let mut children = Vec::new();
+        for entry in entries {
+            match entry {
+                RapEntry::Class(name, body) => {
+                    bytes.push(0);
+                    string(bytes, name);
+                    children.push((bytes.len(), body));
+                    bytes.extend(0u32.to_le_bytes());
+                }
+                RapEntry::Text(name, value) => {
+                    bytes.extend([1, 0]);
+                    string(bytes, name);
+                    string(bytes, value);
+                }
+                RapEntry::Int(name, value) => {
+                    bytes.extend([1, 2]);
+                    string(bytes, name);
+                    bytes.extend(value.to_le_bytes());
+                }
+                RapEntry::Float(name, value) => {
+                    bytes.extend([1, 1]);
+                    string(bytes, name);
+                    bytes.extend(value.to_le_bytes());
+                }
+                RapEntry::Array(name, values) => {
+                    bytes.push(2);
+                    string(bytes, name);
+                    bytes.push(values.len() as u8);
+                    for value in values {
+                        bytes.push(0);
+                        string(bytes, value);
+                    }
+                }
+            }
+        }
+        for (offset_position, body) in children {
+            let offset = (bytes.len() as u32).to_le_bytes();
+            bytes[offset_position..offset_position + 4].copy_from_slice(&offset);
+            write_body(bytes, body);
+        }
+    }
+
+    fn binarized_mission() -> Vec<u8> {
+        use RapEntry::*;
+        rapify(&[Class("Mission", vec![
+            Class("Item0", vec![
+                Text("dataType", "Object"),
+                Class("Attributes", vec![
+                    Float("skill", 0.5),
+                    Int("isPlayable", 1),
+                    Class("Inventory", vec![
+                        Class("primaryWeapon", vec![Text("name", "arifle_MX_F")]),
+                        Text("uniform", "U_B_CombatUniform_mcam"),
+                    ]),
+                ]),
+                Array("items", vec!["FirstAidKit", "say \"hi\""]),
+            ]),
+        ])])
+    }
+
+    #[test]
+    fn test_derapify_binarized_sqm() {
+        use parser_sqm::{decode_sqm, derapify, is_binarized};
+
+        let bytes = binarized_mission();
+        assert!(is_binarized(&bytes));
+        assert_eq!(derapify(&bytes).unwrap(), "\
+class Mission
+{
+\tclass Item0
+\t{
+\t\tdataType=\"Object\";
+\t\tclass Attributes
+\t\t{
+\t\t\tskill=0.5;
+\t\t\tisPlayable=1;
+\t\t\tclass Inventory
+\t\t\t{
+\t\t\t\tclass primaryWeapon
+\t\t\t\t{
+\t\t\t\t\tname=\"arifle_MX_F\";
+\t\t\t\t};
+\t\t\t\tuniform=\"U_B_CombatUniform_mcam\";
+\t\t\t};
+\t\t};
+\t\titems[]={\"FirstAidKit\",\"say \"\"hi\"\"\"};
+\t};
+};
+");
+
+        // Text content passes through unchanged
+        assert_eq!(decode_sqm(b"version=54;").unwrap(), "version=54;");
+        assert!(derapify(b"version=54;").is_err());
+        assert!(derapify(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_parse_binarized_mission() {
+        let content = parser_sqm::decode_sqm(&binarized_mission()).unwrap();
+        let dependencies = extract_class_dependencies(&content);
+        assert!(dependencies.contains("arifle_MX_F"));
+        assert!(dependencies.contains("U_B_CombatUniform_mcam"));
+    }
+
+    #[test]
+    fn test_extract_required_addons() {
+        let mission_content = std::fs::read_to_string("tests/fixtures/mission_full_simple.sqm")
+            .expect("Unable to read example mission file");
+
+        let addons = parser_sqm::extract_required_addons(&mission_content);
+        assert_eq!(addons.len(), 75);
+        assert_eq!(addons[0], "A3_Modules_F_Curator_Misc");
+        assert!(addons.iter().any(|addon| addon == "rhsusf_c_weapons"));
+        assert!(!addons.iter().any(|addon| addon.contains("tmf\\addons")));
+
+        let input = r#"
+            description = "addons[] = {""not_an_addon""}";
+            ADDONS[]=
+            {
+                "A3_Characters_F",
+                "cba_xeh"
+            };
+            class Mission {
+                addOnsAuto[] = {"cba_xeh", "ace_cargo"};
+            };
+        "#;
+        assert_eq!(parser_sqm::extract_required_addons(input), ["A3_Characters_F", "cba_xeh", "ace_cargo"]);
+        assert!(parser_sqm::extract_required_addons("version=54;").is_empty());
+    }
+
+    #[test]
+    fn test_extract_mission_objects() {
+        use parser_sqm::extract_mission_objects;
+
+        let input = r#"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Marker";
+                    position[]={100,5,200};
+                    name="respawn_west";
+                    text="Base";
+                    type="hd_flag";
+                    id=1;
+                };
+                class Item1 {
+                    dataType="Trigger";
+                    position[]={150,5,250};
+                    class Attributes {
+                        name="trg_resupply";
+                        condition="this";
+                        onActivation="supply_box addWeaponCargo [""rhs_weap_m4a1"",2];";
+                    };
+                    id=2;
+                    type="EmptyDetector";
+                };
+                class Item2 {
+                    dataType="Logic";
+                    class PositionInfo {
+                        position[]={10,0,20};
+                    };
+                    name="zeus";
+                    id=3;
+                    type="ModuleCurator_F";
+                };
+            };
+            class Markers {
+                class Item0 {
+                    position[]={1,2};
+                    name="old_marker";
+                    type="mil_dot";
+                };
+            };
+            class Sensors {
+                class Item0 {
+                    expCond="true";
+                    expActiv="hint ""old trigger"";";
+                };
+            };
+        };"#;
+
+        let objects = extract_mission_objects(input);
+        let markers: Vec<_> = objects.markers.iter().map(|m| m.name.as_deref()).collect();
+        assert_eq!(markers, [Some("respawn_west"), Some("old_marker")]);
+        assert_eq!(objects.markers[0].marker_type.as_deref(), Some("hd_flag"));
+        assert_eq!(objects.markers[0].position, Some([100.0, 5.0, 200.0]));
+        assert_eq!(objects.markers[1].position, Some([1.0, 2.0, 0.0]));
+
+        assert_eq!(objects.triggers.len(), 2);
+        let trigger = &objects.triggers[0];
+        assert_eq!(trigger.name.as_deref(), Some("trg_resupply"));
+        assert_eq!(trigger.condition.as_deref(), Some("this"));
+        assert_eq!(trigger.on_activation.as_deref(), Some(r#"supply_box addWeaponCargo ["rhs_weap_m4a1",2];"#));
+        assert_eq!(objects.triggers[1].on_activation.as_deref(), Some(r#"hint "old trigger";"#));
+
+        assert_eq!(objects.logics.len(), 1);
+        assert_eq!(objects.logics[0].class_name, "ModuleCurator_F");
+        assert_eq!(objects.logics[0].name.as_deref(), Some("zeus"));
+        assert_eq!(objects.logics[0].position, Some([10.0, 0.0, 20.0]));
+    }
+
+    #[test]
+    fn test_extract_curators() {
+        use parser_sqm::{extract_curators, CuratorAddons};
+
+        let input = r##"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Logic";
+                    class Attributes {
+                        name="zeus_admin";
+                    };
+                    id=1;
+                    type="ModuleCurator_F";
+                    class CustomAttributes {
+                        class Attribute0 {
+                            property="ModuleCurator_F_Owner";
+                            expression="_this setVariable ['Owner',_value,true];";
+                            class Value {
+                                class data {
+                                    singleType="STRING";
+                                    value="#adminLogged";
+                                };
+                            };
+                        };
+                        class Attribute1 {
+                            property="ModuleCurator_F_Addons";
+                            expression="_this setVariable ['Addons',_value,true];";
+                            class Value {
+                                class data {
+                                    singleType="SCALAR";
+                                    value=2;
+                                };
+                            };
+                        };
+                        nAttributes=2;
+                    };
+                };
+                class Item1 {
+                    dataType="Logic";
+                    id=2;
+                    type="ModuleCuratorAddAddons_F";
+                    class CustomAttributes {
+                        class Attribute0 {
+                            property="ModuleCuratorAddAddons_F_Addons";
+                            expression="_this setVariable ['Addons',_value,true];";
+                            class Value {
+                                class data {
+                                    singleType="STRING";
+                                    value="rhs_c_weapons, rhsusf_c_weapons,,rhs_c_weapons";
+                                };
+                            };
+                        };
+                        nAttributes=1;
+                    };
+                };
+                class Item2 {
+                    dataType="Logic";
+                    id=3;
+                    type="ModuleCurator_F";
+                };
+            };
+        };"##;
+
+        let curators = extract_curators(input);
+        assert!(curators.is_present());
+        assert_eq!(curators.modules.len(), 2);
+        assert_eq!(curators.modules[0].name.as_deref(), Some("zeus_admin"));
+        assert_eq!(curators.modules[0].owner.as_deref(), Some("#adminLogged"));
+        assert_eq!(curators.modules[0].addons, Some(CuratorAddons::Official));
+        assert_eq!(curators.modules[1].addons, None);
+        assert_eq!(curators.added_addons, ["rhs_c_weapons", "rhsusf_c_weapons"]);
+
+        assert!(!extract_curators("class Mission { class Entities {}; };").is_present());
+    }
+
+    #[test]
+    fn test_entity_init_code() {
+        use parser_sqm::extract_entities;
+
+        let input = r#"class Mission {
+            class Entities {
+                class Item0 {
+                    dataType="Object";
+                    class Attributes {
+                        init="this addWeaponCargo [""rhs_weap_m4a1"",2]; this setVariable [""note"", """"];";
+                        name="supply_box";
+                    };
+                    type="Box_NATO_Equip_F";
+                };
+                class Item1 {
+                    dataType="Object";
+                    class Attributes {
+                        init="  ";
+                    };
+                    type="B_Soldier_F";
+                };
+            };
+        };"#;
+
+        let entities = extract_entities(input);
+        let box_entity = entities.iter().find(|e| e.class_name == "Box_NATO_Equip_F").unwrap();
+        assert_eq!(
+            box_entity.init().as_deref(),
+            Some(r#"this addWeaponCargo ["rhs_weap_m4a1",2]; this setVariable ["note", ""];"#)
+        );
+        let soldier = entities.iter().find(|e| e.class_name == "B_Soldier_F").unwrap();
+        assert_eq!(soldier.init(), None);
+    }
 }
@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use parser_hpp::{decode_content, parse_file as parser_hpp_file};
+use serde::{Serialize, Deserialize};
+
+use crate::formatting::audit_formatting;
+use crate::interner::Interner;
+use crate::scanner::{hpp_classes_to_references, parse_file};
+use crate::types::{ClassReference, MissionResults};
+
+/// Options for analyzing a single file
+#[derive(Debug, Clone, Default)]
+pub struct FileAnalysisOptions {
+    /// Results of a previous scan of the mission containing the file, if available.
+    ///
+    /// Used to relate the file's references to the rest of the mission without
+    /// rescanning it.
+    pub mission_context: Option<Arc<MissionResults>>,
+}
+
+/// Severity of a file diagnostic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    /// Style issues that don't affect parsing
+    Info,
+}
+
+/// Problem found while analyzing a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileDiagnostic {
+    /// How serious the problem is
+    pub severity: DiagnosticSeverity,
+    /// Description of the problem
+    pub message: String,
+}
+
+/// Class defined by a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileSymbol {
+    /// Name of the class
+    pub name: String,
+    /// Parent class, if the class inherits from one
+    pub parent: Option<String>,
+}
+
+/// Size and reference counts of a file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileMetrics {
+    /// Size of the file in bytes
+    pub byte_count: usize,
+    /// Number of lines in the file
+    pub line_count: usize,
+    /// Number of class references found
+    pub reference_count: usize,
+    /// Number of distinct classes referenced (case-insensitive)
+    pub unique_class_count: usize,
+    /// Number of those classes also referenced by other files of the mission.
+    /// Only known when mission context is available.
+    pub shared_class_count: Option<usize>,
+}
+
+/// Analysis of a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileAnalysis {
+    /// File that was analyzed
+    pub file_path: PathBuf,
+    /// Class references found in the file
+    pub references: Vec<ClassReference>,
+    /// Problems found in the file
+    pub diagnostics: Vec<FileDiagnostic>,
+    /// Classes defined by the file (config files only)
+    pub symbols: Vec<FileSymbol>,
+    /// Size and reference counts
+    pub metrics: FileMetrics,
+}
+
+/// Analyze a single file without scanning the rest of its mission.
+///
+/// Parse failures are reported as diagnostics rather than errors so editors
+/// still get metrics for files that are mid-edit. An error is only returned if
+/// the file cannot be read.
+pub fn analyze_file(file_path: &Path, options: &FileAnalysisOptions) -> Result<FileAnalysis> {
+    let content = fs::read(file_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", file_path.display(), e))?;
+    let text = String::from_utf8_lossy(&content);
+
+    let extension = file_path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    let mut symbols = Vec::new();
+    let parsed = match extension.as_str() {
+        // Parse config files directly so the class definitions are kept as symbols
+        "cpp" | "hpp" | "ext" => parser_hpp_file(file_path)
+            .map_err(|e| anyhow!("Failed to parse loadout file: {:?}", e))
+            .map(|classes| {
+                symbols = classes.iter()
+                    .map(|class| FileSymbol { name: class.name.clone(), parent: class.parent.clone() })
+                    .collect();
+                hpp_classes_to_references(classes, file_path, &Interner::new())
+            }),
+        _ => parse_file(file_path),
+    };
+
+    let mut diagnostics = Vec::new();
+    if matches!(extension.as_str(), "cpp" | "hpp" | "ext") {
+        if let Some(warning) = decode_content(&content).warning {
+            diagnostics.push(FileDiagnostic { severity: DiagnosticSeverity::Warning, message: warning });
+        }
+        for message in audit_formatting(&text).findings() {
+            diagnostics.push(FileDiagnostic { severity: DiagnosticSeverity::Info, message });
+        }
+    }
+
+    let references = match parsed {
+        Ok(references) => references,
+        Err(e) => {
+            diagnostics.push(FileDiagnostic {
+                severity: DiagnosticSeverity::Error,
+                message: e.to_string(),
+            });
+            Vec::new()
+        }
+    };
+
+    let empty_names = references.iter().filter(|r| r.class_name.is_empty()).count();
+    if empty_names > 0 {
+        diagnostics.push(FileDiagnostic {
+            severity: DiagnosticSeverity::Warning,
+            message: format!("{} references with an empty class name", empty_names),
+        });
+    }
+
+    let classes: HashSet<String> = references.iter()
+        .filter(|r| !r.class_name.is_empty())
+        .map(|r| r.class_name.to_lowercase())
+        .collect();
+
+    let shared_class_count = options.mission_context.as_ref().map(|mission| {
+        let elsewhere: HashSet<String> = mission.class_dependencies.iter()
+            .filter(|dep| dep.source_file != file_path)
+            .map(|dep| dep.class_name.to_lowercase())
+            .collect();
+        classes.intersection(&elsewhere).count()
+    });
+
+    let metrics = FileMetrics {
+        byte_count: content.len(),
+        line_count: text.lines().count(),
+        reference_count: references.len(),
+        unique_class_count: classes.len(),
+        shared_class_count,
+    };
+
+    Ok(FileAnalysis {
+        file_path: file_path.to_path_buf(),
+        references,
+        diagnostics,
+        symbols,
+        metrics,
+    })
+}
@@ -0,0 +1,160 @@
+//! Audit of virtual arsenals against an allowed-equipment whitelist
+//!
+//! Milsim groups publish the equipment their missions may hand out. Arsenals
+//! set up with `ace_arsenal_fnc_initBox` or the `BIS_fnc_addVirtual*Cargo`
+//! functions, in scripts or in the init attribute of an object in
+//! mission.sqm, are checked against that list and every class it doesn't
+//! allow is reported with the arsenal offering it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+use crate::scanner::read_sqm;
+use crate::search::ClassPattern;
+use crate::types::MissionResults;
+use crate::vfs::OsFs;
+
+/// Equipment missions may put in their arsenals
+///
+/// Written one class per line, with `*` and `?` wildcards and `#` comments:
+///
+/// ```text
+/// # Standard issue
+/// arifle_MX_*
+/// ItemMap
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EquipmentWhitelist {
+    classes: Vec<ClassPattern>,
+}
+
+impl EquipmentWhitelist {
+    /// Parse a whitelist from its text
+    pub fn parse(content: &str) -> Self {
+        let classes = content.lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(ClassPattern::glob)
+            .collect();
+        Self { classes }
+    }
+
+    /// Load a whitelist file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read whitelist {}: {}", path.display(), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// Check if a class is allowed, ignoring case
+    pub fn allows(&self, class_name: &str) -> bool {
+        self.classes.iter().any(|pattern| pattern.matches(class_name))
+    }
+
+    /// Number of classes and patterns in the whitelist
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Check if the whitelist allows nothing
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
+
+/// A class an arsenal offers that the whitelist doesn't allow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArsenalViolation {
+    /// Class offered by the arsenal
+    pub class_name: String,
+    /// Object the arsenal is on, as written in the script, or the editor
+    /// name or class of the object for init attributes
+    pub box_id: String,
+    /// Script or mission.sqm setting up the arsenal
+    pub source_file: PathBuf,
+}
+
+/// Result of auditing the arsenals of a mission
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArsenalAudit {
+    /// Name of the mission
+    pub mission_name: String,
+    /// Number of arsenals found
+    pub arsenals: usize,
+    /// Offered classes the whitelist doesn't allow, sorted by file, object and class
+    pub violations: Vec<ArsenalViolation>,
+}
+
+impl ArsenalAudit {
+    /// Check if every arsenal only offers allowed equipment
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Audit the arsenals a scanned mission sets up against a whitelist.
+///
+/// Scripts and mission.sqm are read again from disk; files that can't be
+/// read or parsed are skipped.
+pub fn audit_arsenals(mission: &MissionResults, whitelist: &EquipmentWhitelist) -> ArsenalAudit {
+    let mut boxes = Vec::new();
+    for file in &mission.sqf_files {
+        match parser_sqf::extract_arsenal_boxes(file) {
+            Ok(found) => boxes.extend(found.into_iter().map(|arsenal| (arsenal.box_id, arsenal.items, file.clone()))),
+            Err(e) => debug!("Failed to read arsenals of {}: {:?}", file.display(), e),
+        }
+    }
+    if let Some(sqm_file) = &mission.sqm_file {
+        boxes.extend(sqm_arsenals(sqm_file));
+    }
+
+    let mut violations: Vec<ArsenalViolation> = boxes.iter()
+        .flat_map(|(box_id, items, file)| items.iter()
+            .filter(|class_name| !whitelist.allows(class_name))
+            .map(|class_name| ArsenalViolation {
+                class_name: class_name.clone(),
+                box_id: box_id.clone(),
+                source_file: file.clone(),
+            }))
+        .collect();
+    violations.sort_by(|a, b| (&a.source_file, &a.box_id, &a.class_name).cmp(&(&b.source_file, &b.box_id, &b.class_name)));
+    violations.dedup();
+
+    ArsenalAudit {
+        mission_name: mission.mission_name.clone(),
+        arsenals: boxes.len(),
+        violations,
+    }
+}
+
+/// Arsenals set up in the init attributes of the objects of a mission.sqm,
+/// named after the object since the code refers to it as `this`
+fn sqm_arsenals(sqm_file: &Path) -> Vec<(String, Vec<String>, PathBuf)> {
+    let content = match read_sqm(&OsFs::default(), sqm_file) {
+        Ok(content) => content,
+        Err(e) => {
+            debug!("Failed to read arsenals of {}: {:#}", sqm_file.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut arsenals = Vec::new();
+    for entity in parser_sqm::extract_entities(&content) {
+        let Some(code) = entity.init() else {
+            continue;
+        };
+        let owner = entity.name.as_deref().unwrap_or(&entity.class_name);
+        match parser_sqf::extract_arsenal_boxes_from_code(&code) {
+            Ok(found) => arsenals.extend(found.into_iter().map(|arsenal| {
+                let box_id = if arsenal.box_id == "this" { owner.to_string() } else { arsenal.box_id };
+                (box_id, arsenal.items, sqm_file.to_path_buf())
+            })),
+            Err(e) => debug!("Failed to read arsenals in the init of {}: {:?}", owner, e),
+        }
+    }
+    arsenals
+}
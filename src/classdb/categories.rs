@@ -0,0 +1,146 @@
+//! Item categories of known classes and the commands using them
+//!
+//! A class database seldom says what kind of item each class is, but every
+//! class descends from one of a few engine base classes: rifles from
+//! `RifleCore`, vests from `Vest_Camo_Base` and so on. The category of a class
+//! is the first one found walking up its parents, so `addWeapon` called with a
+//! vest can be told apart from a typo or a missing mod.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+use crate::classdb::ClassDatabase;
+use crate::types::{ItemKind, MissionResults, SourceLocation};
+
+/// Engine base classes and the category of the classes descending from them.
+///
+/// Walking up from a class, the first of these found wins, so `Uniform_Base`
+/// is found before the `ItemCore` it inherits from.
+pub const BASE_CATEGORIES: &[(&str, ItemKind)] = &[
+    ("RifleCore", ItemKind::Weapon),
+    ("PistolCore", ItemKind::Weapon),
+    ("LauncherCore", ItemKind::Weapon),
+    ("Binocular", ItemKind::Weapon),
+    ("NVGoggles", ItemKind::Item),
+    ("Uniform_Base", ItemKind::Uniform),
+    ("Vest_Camo_Base", ItemKind::Vest),
+    ("Vest_NoCamo_Base", ItemKind::Vest),
+    ("HelmetBase", ItemKind::Headgear),
+    ("H_HelmetB", ItemKind::Headgear),
+    ("ItemCore", ItemKind::Item),
+    ("InventoryItem_Base_F", ItemKind::Item),
+    ("CA_Magazine", ItemKind::Magazine),
+    ("Bag_Base", ItemKind::Backpack),
+];
+
+/// Configs whose classes are all of one category, used when no base class
+/// of a class is known. Backpacks are in `CfgVehicles` too, but are found by
+/// their `Bag_Base` parent first
+const CONFIG_CATEGORIES: [(&str, ItemKind); 3] = [
+    ("CfgMagazines", ItemKind::Magazine),
+    ("CfgGlasses", ItemKind::Goggles),
+    ("CfgVehicles", ItemKind::Vehicle),
+];
+
+impl ClassDatabase {
+    /// Kind of item a class is, ignoring case.
+    ///
+    /// An entry's own category wins; otherwise the parents of the class are
+    /// walked up to the first one with a category or in [`BASE_CATEGORIES`],
+    /// and then the config of the class decides. `None` when none of that is
+    /// known.
+    pub fn category(&self, name: &str) -> Option<ItemKind> {
+        let mut seen = HashSet::new();
+        let mut config = None;
+        let mut current = Some(name.to_string());
+        while let Some(class_name) = current.take() {
+            if !seen.insert(class_name.to_lowercase()) {
+                break;
+            }
+            let entry = self.get(&class_name);
+            if let Some(category) = entry.and_then(|entry| entry.category) {
+                return Some(category);
+            }
+            if let Some((_, category)) = BASE_CATEGORIES.iter().find(|(base, _)| base.eq_ignore_ascii_case(&class_name)) {
+                return Some(*category);
+            }
+            let Some(entry) = entry else {
+                break;
+            };
+            config = config.or(entry.config.as_deref());
+            current = entry.parent.clone();
+        }
+        let config = config?;
+        CONFIG_CATEGORIES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(config))
+            .map(|(_, category)| *category)
+    }
+}
+
+/// Class a command is given that is of another category than it takes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryMismatch {
+    /// Class given to the command
+    pub class_name: String,
+    /// Kind of item the command takes
+    pub expected: ItemKind,
+    /// Kind of item the class is
+    pub found: ItemKind,
+    /// Command or function given the class, e.g. `Used in command: addWeapon`
+    pub usage: String,
+    /// Script calling the command
+    pub source_file: PathBuf,
+    /// Where the class name appears in the script
+    pub location: Option<SourceLocation>,
+}
+
+/// Check that the commands of a scanned mission's scripts are given classes
+/// of the category they take, such as weapons for `addWeapon`.
+///
+/// Scripts are read again from disk; those that can't be parsed are skipped.
+/// Classes whose category the database doesn't know aren't checked.
+pub fn check_item_categories(mission: &MissionResults, database: &ClassDatabase) -> Vec<CategoryMismatch> {
+    let mut mismatches = Vec::new();
+    for file in &mission.sqf_files {
+        let references = match parser_sqf::parse_file(file) {
+            Ok(references) => references,
+            Err(e) => {
+                debug!("Failed to check categories of {}: {:?}", file.display(), e);
+                continue;
+            }
+        };
+        for reference in references.into_iter().filter(|reference| !reference.removed) {
+            let Some(expected) = reference.kind else {
+                continue;
+            };
+            let Some(found) = database.category(&reference.class_name) else {
+                continue;
+            };
+            if !accepts(expected, found) {
+                mismatches.push(CategoryMismatch {
+                    class_name: reference.class_name,
+                    expected,
+                    found,
+                    usage: reference.usage.to_string(),
+                    source_file: file.clone(),
+                    location: reference.location.map(|location| SourceLocation { line: location.line, column: location.column }),
+                });
+            }
+        }
+    }
+    mismatches
+}
+
+/// Check if a command taking one kind of item accepts a class of another.
+///
+/// Inventory commands like `addItem` take anything that fits in a container,
+/// so only vehicles and backpacks are wrong for them.
+fn accepts(expected: ItemKind, found: ItemKind) -> bool {
+    match expected {
+        ItemKind::Item => !matches!(found, ItemKind::Vehicle | ItemKind::Backpack),
+        _ => expected == found,
+    }
+}
@@ -0,0 +1,89 @@
+//! Downloading published class database snapshots
+//!
+//! Snapshots are verified against a SHA-256 checksum before being written to
+//! `<cache_dir>/classdb/`. The checksum is either given by the caller or read
+//! from a `<url>.sha256` file published next to the snapshot, in the format
+//! written by `sha256sum`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use log::info;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+
+use super::CLASSDB_CACHE_DIR;
+
+/// Download a class database snapshot, verify it and store it in the cache.
+///
+/// Returns the path of the cached snapshot. Nothing is written when the
+/// checksum doesn't match.
+pub async fn fetch_class_db(url: &str, expected_sha256: Option<&str>, cache_dir: &Path) -> Result<PathBuf> {
+    let client = reqwest::Client::new();
+    let url = Url::parse(url).map_err(|e| anyhow!("Invalid class database URL {}: {}", url, e))?;
+    let file_name = snapshot_file_name(&url)?;
+
+    let expected = match expected_sha256 {
+        Some(checksum) => checksum.to_string(),
+        None => {
+            let mut checksum_url = url.clone();
+            checksum_url.set_path(&format!("{}.sha256", url.path()));
+            let response = client.get(checksum_url.clone()).send().await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| anyhow!("Failed to download checksum {}: {}", checksum_url, e))?;
+            parse_checksum_file(&response.text().await?)
+                .ok_or_else(|| anyhow!("No SHA-256 checksum in {}", checksum_url))?
+        }
+    };
+
+    info!("Downloading class database {}", url);
+    let snapshot = client.get(url.clone()).send().await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| anyhow!("Failed to download {}: {}", url, e))?
+        .bytes().await?;
+
+    verify_sha256(&snapshot, &expected)?;
+
+    let dir = cache_dir.join(CLASSDB_CACHE_DIR);
+    fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(file_name);
+    fs::write(&path, &snapshot)
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+
+    info!("Stored class database at {}", path.display());
+    Ok(path)
+}
+
+/// Name a snapshot is cached under: the last path segment of its URL.
+///
+/// The query and fragment, which may carry access tokens, are left out, and
+/// characters other than ASCII letters, digits, `.`, `-` and `_` are replaced
+/// with `_` so the name can't leave the cache directory.
+pub fn snapshot_file_name(url: &Url) -> Result<String> {
+    let segment = url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+        .ok_or_else(|| anyhow!("No file name in {}", url))?;
+    Ok(segment.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect())
+}
+
+/// Check that content hashes to a hex-encoded SHA-256 checksum
+pub fn verify_sha256(content: &[u8], expected: &str) -> Result<()> {
+    let actual = hex::encode(Sha256::digest(content));
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(anyhow!("Checksum mismatch: expected {}, got {}", expected.trim(), actual))
+    }
+}
+
+/// Read the checksum from `sha256sum` output (`<hex>  <file name>`) or a bare hex digest
+pub fn parse_checksum_file(content: &str) -> Option<String> {
+    let checksum = content.split_whitespace().next()?;
+    (checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| checksum.to_lowercase())
+}
@@ -0,0 +1,273 @@
+//! Indexed databases of the classes defined by the game and mods
+//!
+//! A class database is loaded from a JSON or CSV export of extracted configs
+//! and indexed by lowercase class name, so checking a mission's references
+//! doesn't scan every known class. Item categories are inferred from parent
+//! classes. Downloading published snapshots needs the `classdb-fetch` feature.
+
+mod categories;
+#[cfg(feature = "classdb-fetch")]
+mod fetch;
+mod suggest;
+
+use std::collections::HashMap;
+use std::fs;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::types::{ClassReference, ItemKind, MissionResults};
+
+#[cfg(feature = "classdb-fetch")]
+pub use fetch::{fetch_class_db, parse_checksum_file, snapshot_file_name, verify_sha256};
+pub use categories::{check_item_categories, CategoryMismatch, BASE_CATEGORIES};
+pub use suggest::{jaro_winkler, levenshtein, similarity, Suggestion};
+
+/// Directory inside the cache directory holding downloaded snapshots
+pub const CLASSDB_CACHE_DIR: &str = "classdb";
+
+/// Lowest similarity a known class needs to be suggested
+pub const MIN_SUGGESTION_SCORE: f64 = 0.75;
+
+/// Number of suggestions attached to each missing class
+const SUGGESTIONS_PER_CLASS: usize = 3;
+
+/// A class known to exist in the game or a mod
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClassEntry {
+    /// Name of the class
+    pub name: String,
+    /// Parent class, if it has one
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Config the class is defined in, e.g. `CfgWeapons`
+    #[serde(default)]
+    pub config: Option<String>,
+    /// Mod or addon defining the class
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Kind of item the class is, e.g. `vest`
+    #[serde(default)]
+    pub category: Option<ItemKind>,
+}
+
+/// Classes a mission references that aren't in the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissingClass {
+    /// Name of the class as first referenced
+    pub class_name: String,
+    /// Every reference to the class
+    pub references: Vec<ClassReference>,
+    /// Known classes with similar names, best first
+    #[serde(default)]
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// Class entries indexed by lowercase name
+#[derive(Debug, Clone, Default)]
+pub struct ClassDatabase {
+    entries: Vec<ClassEntry>,
+    index: HashMap<String, usize>,
+}
+
+impl ClassDatabase {
+    /// Create an empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a database export.
+    ///
+    /// `.csv` files have a `name[,parent,config,source,category]` header.
+    /// Anything else is read as JSON, either a list of entries or a list of
+    /// class names.
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read class database {}: {}", path.display(), e))?;
+
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let entries = if is_csv {
+            parse_csv(&content)
+        } else {
+            parse_json(&content)
+        };
+        entries
+            .map(Self::from_iter)
+            .map_err(|e| anyhow!("Invalid class database {}: {}", path.display(), e))
+    }
+
+    /// Add a class, replacing an existing entry with the same name
+    pub fn insert(&mut self, entry: ClassEntry) {
+        let key = entry.name.to_lowercase();
+        match self.index.get(&key) {
+            Some(&position) => self.entries[position] = entry,
+            None => {
+                self.index.insert(key, self.entries.len());
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    /// Check if a class exists, ignoring case like the engine
+    pub fn class_exists(&self, name: &str) -> bool {
+        self.index.contains_key(&name.to_lowercase())
+    }
+
+    /// Get the entry of a class, ignoring case
+    pub fn get(&self, name: &str) -> Option<&ClassEntry> {
+        self.index.get(&name.to_lowercase()).map(|&position| &self.entries[position])
+    }
+
+    /// Number of classes
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the database has no classes
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the classes a mission references that aren't in the database,
+    /// sorted by name
+    pub fn missing_classes(&self, mission: &MissionResults) -> Vec<MissingClass> {
+        let mut missing: HashMap<String, MissingClass> = HashMap::new();
+        for dep in mission.class_dependencies.iter().filter(|dep| !self.class_exists(&dep.class_name)) {
+            missing.entry(dep.class_name.to_lowercase())
+                .or_insert_with(|| MissingClass {
+                    class_name: dep.class_name.to_string(),
+                    references: Vec::new(),
+                    suggestions: self.suggest(&dep.class_name, SUGGESTIONS_PER_CLASS),
+                })
+                .references.push(dep.clone());
+        }
+
+        let mut missing: Vec<_> = missing.into_values().collect();
+        missing.sort_by_key(|class| class.class_name.to_lowercase());
+        missing
+    }
+
+    /// Rank known classes by similarity to a name, keeping at most `limit`
+    /// scoring at least [`MIN_SUGGESTION_SCORE`]
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<Suggestion> {
+        let mut suggestions: Vec<Suggestion> = self.entries.iter()
+            .map(|entry| Suggestion { class_name: entry.name.clone(), score: similarity(name, &entry.name) })
+            .filter(|suggestion| suggestion.score >= MIN_SUGGESTION_SCORE)
+            .collect();
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.class_name.cmp(&b.class_name)));
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+/// Per-user cache directory of the scanner.
+///
+/// `%LOCALAPPDATA%` on Windows, otherwise `$XDG_CACHE_HOME` or `~/.cache`,
+/// with a `mission_scanner` directory inside. `None` when none is set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        non_empty("LOCALAPPDATA")
+    } else {
+        non_empty("XDG_CACHE_HOME").or_else(|| non_empty("HOME").map(|home| home.join(".cache")))
+    };
+    base.map(|base| base.join("mission_scanner"))
+}
+
+/// Most recently downloaded snapshot in a cache directory, as stored by
+/// `fetch_class_db` in `<cache_dir>/classdb/`
+pub fn latest_snapshot(cache_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(cache_dir.join(CLASSDB_CACHE_DIR)).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max()
+        .map(|(_, path)| path)
+}
+
+impl FromIterator<ClassEntry> for ClassDatabase {
+    fn from_iter<I: IntoIterator<Item = ClassEntry>>(entries: I) -> Self {
+        let mut database = Self::new();
+        for entry in entries {
+            database.insert(entry);
+        }
+        database
+    }
+}
+
+/// Read a JSON list of entries or of class names
+fn parse_json(content: &str) -> Result<Vec<ClassEntry>> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum JsonEntry {
+        Name(String),
+        Entry(ClassEntry),
+    }
+
+    let entries: Vec<JsonEntry> = serde_json::from_str(content)?;
+    Ok(entries.into_iter()
+        .map(|entry| match entry {
+            JsonEntry::Name(name) => ClassEntry { name, parent: None, config: None, source: None, category: None },
+            JsonEntry::Entry(entry) => entry,
+        })
+        .collect())
+}
+
+/// Read CSV with a header naming the `name`, `parent`, `config`, `source` and
+/// `category` columns
+fn parse_csv(content: &str) -> Result<Vec<ClassEntry>> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = split_csv_line(lines.next().ok_or_else(|| anyhow!("Missing CSV header"))?)
+        .into_iter()
+        .map(|column| column.to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|c| c == name);
+    let name_column = column("name").ok_or_else(|| anyhow!("Missing name column in CSV header"))?;
+    let (parent_column, config_column, source_column) = (column("parent"), column("config"), column("source"));
+    let category_column = column("category");
+
+    Ok(lines
+        .map(|line| {
+            let fields = split_csv_line(line);
+            let field = |column: Option<usize>| column
+                .and_then(|c| fields.get(c))
+                .filter(|value| !value.is_empty())
+                .cloned();
+            ClassEntry {
+                name: fields.get(name_column).cloned().unwrap_or_default(),
+                parent: field(parent_column),
+                config: field(config_column),
+                source: field(source_column),
+                category: field(category_column).and_then(|category| category.parse().ok()),
+            }
+        })
+        .filter(|entry| !entry.name.is_empty())
+        .collect())
+}
+
+/// Split a CSV line into its trimmed fields.
+///
+/// Quoted fields may contain commas, and `""` in them is a literal quote.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
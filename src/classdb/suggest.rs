@@ -0,0 +1,117 @@
+//! Ranking known classes by similarity to a missing one
+//!
+//! Scores combine Jaro-Winkler, which favours a shared start, with a
+//! normalized Levenshtein distance, which catches typos anywhere in the name.
+//! Mod prefixes such as `rhs_` or `ACE_` are compared separately: every class
+//! of a mod shares its prefix, so a matching prefix shouldn't make unrelated
+//! classes look alike, and a different prefix usually means a different mod.
+
+use serde::{Serialize, Deserialize};
+
+/// Score multiplier when both names have a mod prefix and the prefixes differ
+const PREFIX_MISMATCH_PENALTY: f64 = 0.85;
+
+/// Weight of Jaro-Winkler in the combined score, the rest is Levenshtein
+const JARO_WINKLER_WEIGHT: f64 = 0.6;
+
+/// Longest segment before the first `_` that counts as a mod prefix
+const MAX_PREFIX_LEN: usize = 6;
+
+/// A known class that might be the one meant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Suggestion {
+    /// Name of the known class
+    pub class_name: String,
+    /// Similarity between 0 and 1, higher is closer
+    pub score: f64,
+}
+
+/// Similarity of two class names between 0 and 1, ignoring case
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    if a == b {
+        return 1.0;
+    }
+
+    match (mod_prefix(&a), mod_prefix(&b)) {
+        (Some(prefix_a), Some(prefix_b)) if prefix_a == prefix_b => {
+            name_similarity(&a[prefix_a.len()..], &b[prefix_b.len()..])
+        }
+        (Some(_), Some(_)) => name_similarity(&a, &b) * PREFIX_MISMATCH_PENALTY,
+        _ => name_similarity(&a, &b),
+    }
+}
+
+/// Number of single character insertions, deletions and substitutions
+/// turning one string into the other
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Jaro-Winkler similarity between 0 and 1
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let jaro = jaro(&a, &b);
+    let common_prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + common_prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, &c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == c) {
+            b_matched[j] = true;
+            a_matches.push(c);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, matched)| **matched).map(|(c, _)| c);
+    let transpositions = a_matches.iter().zip(b_matches).filter(|(x, y)| x != y).count() as f64 / 2.0;
+    let matches = a_matches.len() as f64;
+    (matches / a.len() as f64 + matches / b.len() as f64 + (matches - transpositions) / matches) / 3.0
+}
+
+/// Combined score of two lowercase names
+fn name_similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    let edit_similarity = 1.0 - levenshtein(a, b) as f64 / longest as f64;
+    JARO_WINKLER_WEIGHT * jaro_winkler(a, b) + (1.0 - JARO_WINKLER_WEIGHT) * edit_similarity
+}
+
+/// Mod prefix of a lowercase name including the `_`, e.g. `rhs_` in `rhs_weap_m4a1`
+fn mod_prefix(name: &str) -> Option<&str> {
+    let end = name.find('_')?;
+    (end > 0 && end <= MAX_PREFIX_LEN && end + 1 < name.len()).then(|| &name[..=end])
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+
+/// `name = value;` and `name[] = {...};` entries, at any class depth
+static SETTING_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([A-Za-z_][A-Za-z0-9_]*)\s*(?:\[\])?\s*=\s*(\{[^}]*\}|"[^"]*"|[^;]*);"#).unwrap()
+});
+
+/// Line and block comments
+static COMMENT_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)//[^\n]*|/\*.*?\*/").unwrap()
+});
+
+/// Settings read from a config-style file (server.cfg, a difficulty profile or
+/// description.ext)
+///
+/// Entries are keyed by name regardless of the class they are in, so
+/// `class Options { thirdPersonView = 0; };` is the `thirdPersonView` setting.
+/// Names are matched case-insensitively. Macros are not expanded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigSettings {
+    /// Setting values keyed by lowercase name, with the name as written
+    values: BTreeMap<String, (String, String)>,
+}
+
+impl ConfigSettings {
+    /// Read the settings from config content; later entries override earlier ones
+    pub fn parse(content: &str) -> Self {
+        let content = COMMENT_PATTERN.replace_all(content, "");
+        let values = SETTING_PATTERN.captures_iter(&content)
+            .map(|captures| {
+                let name = captures[1].to_string();
+                (name.to_lowercase(), (name, captures[2].trim().to_string()))
+            })
+            .collect();
+        Self { values }
+    }
+
+    /// Load the settings of a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Ok(Self::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Value of a setting as written
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(&name.to_lowercase()).map(|(_, value)| value.as_str())
+    }
+
+    /// Number of settings
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if no settings were found
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Setting the mission and the server disagree on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SettingConflict {
+    /// Name of the setting as the server writes it
+    pub setting: String,
+    /// Value required by the server
+    pub server_value: String,
+    /// Value set by the mission
+    pub mission_value: String,
+}
+
+impl std::fmt::Display for SettingConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: mission sets {}, server requires {}", self.setting, self.mission_value, self.server_value)
+    }
+}
+
+/// Compare the settings a mission sets against those a server requires.
+///
+/// Only settings present on both sides are compared. Values are compared
+/// ignoring case, quotes and whitespace.
+pub fn check_compatibility(server: &ConfigSettings, mission: &ConfigSettings) -> Vec<SettingConflict> {
+    server.values.iter()
+        .filter_map(|(key, (name, server_value))| {
+            let (_, mission_value) = mission.values.get(key)?;
+            (normalize(server_value) != normalize(mission_value)).then(|| SettingConflict {
+                setting: name.clone(),
+                server_value: server_value.clone(),
+                mission_value: mission_value.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Compare a mission's description.ext against a server's required settings.
+///
+/// A mission without a description.ext has no conflicts.
+pub fn check_mission_compatibility(server: &ConfigSettings, mission_dir: &Path) -> Result<Vec<SettingConflict>> {
+    let description = mission_dir.join("description.ext");
+    if !description.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(check_compatibility(server, &ConfigSettings::load(&description)?))
+}
+
+/// Normalize a value for comparison
+fn normalize(value: &str) -> String {
+    value.chars()
+        .filter(|c| !c.is_whitespace() && *c != '"')
+        .collect::<String>()
+        .to_lowercase()
+}
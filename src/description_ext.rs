@@ -0,0 +1,211 @@
+//! Scanning a mission's description.ext by what its top-level classes mean
+//!
+//! Most of the file is read like any other config, but some classes hold
+//! dependencies under names the loadout scanner doesn't know:
+//! `CfgRespawnInventory` loadouts list `weapons[]` and `uniformClass`, which
+//! are reported as respawn inventory, and `CfgSounds`, `CfgMusic` and
+//! `CfgRadio` point at sound files in the mission.
+//! `CfgLoadouts` and `Params` are read like loadouts, so their parents and
+//! equipment are references. `CfgFunctions` declares the mission's functions,
+//! whose files are scanned as scripts wherever they are.
+
+use std::path::Path;
+
+use anyhow::Result;
+use parser_hpp::{HppClass, HppValue};
+
+use crate::interner::Interner;
+use crate::scanner::{hpp_classes_to_references, parse_config, ConfigContext};
+use crate::types::{AssetReference, ClassReference, Confidence, ReferenceType};
+use crate::vfs::Vfs;
+
+/// File name of the mission config
+pub const DESCRIPTION_EXT_FILE_NAME: &str = "description.ext";
+
+/// Array properties of a respawn loadout holding classes
+const RESPAWN_ARRAYS: [&str; 5] = ["weapons", "magazines", "items", "linkeditems", "backpackitems"];
+
+/// Scalar properties of a respawn loadout holding classes
+const RESPAWN_PROPERTIES: [&str; 4] = ["uniformclass", "backpack", "vest", "headgear"];
+
+/// Classes whose entries play a sound file named first in `sound[]`
+const SOUND_CLASSES: [&str; 3] = ["cfgsounds", "cfgmusic", "cfgradio"];
+
+/// Folder of a `CfgFunctions` category without a `file` property
+const DEFAULT_FUNCTIONS_FOLDER: &str = "functions";
+
+/// Extension of function files without an `ext` property
+const DEFAULT_FUNCTION_EXTENSION: &str = ".sqf";
+
+/// What a description.ext depends on
+#[derive(Debug, Clone, Default)]
+pub struct DescriptionExt {
+    /// Class references, respawn loadouts included
+    pub references: Vec<ClassReference>,
+    /// Sound and music files
+    pub assets: Vec<AssetReference>,
+    /// Functions declared in `CfgFunctions`
+    pub functions: Vec<MissionFunction>,
+}
+
+/// Function a mission declares in `CfgFunctions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissionFunction {
+    /// Name the function is called by, e.g. `TAG_fnc_gear`
+    pub name: String,
+    /// Path of the function's file from the mission root, with backslashes
+    /// as in the config. Paths starting with a backslash point into addons
+    pub file: String,
+}
+
+/// Check if a file is a mission's description.ext
+pub fn is_description_ext(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.eq_ignore_ascii_case(DESCRIPTION_EXT_FILE_NAME))
+}
+
+/// Read and scan a description.ext with what the mission's config files share
+pub fn parse_description_ext(vfs: &dyn Vfs, file_path: &Path, context: &ConfigContext) -> Result<DescriptionExt> {
+    let classes = parse_config(vfs, file_path, context)?.parse_class_tree();
+    Ok(analyze_description_ext(&classes, file_path, &context.interner))
+}
+
+/// Scan the class tree of a description.ext, as from [`parser_hpp::HppParser::parse_class_tree`],
+/// interning the class names of its references with `interner`
+pub fn analyze_description_ext(classes: &[HppClass], file_path: &Path, interner: &Interner) -> DescriptionExt {
+    let mut ext = DescriptionExt::default();
+    let mut generic = Vec::new();
+
+    for class in classes {
+        let name = class.name.to_lowercase();
+        if name == "cfgrespawninventory" {
+            let loadouts: Vec<&str> = nested(class).map(|loadout| loadout.name.as_str()).collect();
+            for loadout in nested(class) {
+                ext.references.extend(respawn_references(loadout, &loadouts, file_path, interner));
+            }
+        } else if SOUND_CLASSES.contains(&name.as_str()) {
+            for sound in nested(class) {
+                ext.assets.extend(sound_asset(&class.name, sound, file_path));
+            }
+        } else if name == "cfgfunctions" {
+            ext.functions.extend(nested(class).flat_map(declared_functions));
+        } else {
+            flatten(class, &mut generic);
+        }
+    }
+
+    ext.references.extend(hpp_classes_to_references(generic, file_path, interner));
+    ext
+}
+
+/// Class references of a `CfgRespawnInventory` loadout.
+///
+/// A parent naming another of the `loadouts` is config inheritance between
+/// loadouts, not a class the mission needs.
+fn respawn_references(loadout: &HppClass, loadouts: &[&str], file_path: &Path, interner: &Interner) -> Vec<ClassReference> {
+    let reference = |class_name: &str, reference_type: ReferenceType, property: &str| ClassReference {
+        class_name: interner.intern(class_name),
+        reference_type,
+        context: format!("description.ext:CfgRespawnInventory/{}/{}", loadout.name, property),
+        source_file: file_path.to_path_buf(),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+
+    let mut references: Vec<ClassReference> = loadout.parent.iter()
+        .filter(|parent| !loadouts.iter().any(|name| name.eq_ignore_ascii_case(parent)))
+        .map(|parent| reference(parent, ReferenceType::Inheritance, "parent"))
+        .collect();
+    for property in &loadout.properties {
+        let name = property.name.to_lowercase();
+        let values: Vec<&str> = match &property.value {
+            HppValue::Array(_) if RESPAWN_ARRAYS.contains(&name.as_str()) => property.value.strings(),
+            HppValue::String(value) if RESPAWN_PROPERTIES.contains(&name.as_str()) => vec![value.as_str()],
+            _ => continue,
+        };
+        references.extend(values.into_iter()
+            .map(|value| value.trim().trim_matches('"'))
+            .filter(|value| !value.is_empty())
+            .map(|value| reference(value, ReferenceType::RespawnInventory, &property.name)));
+    }
+    references
+}
+
+/// The file played by an entry of `CfgSounds`, `CfgMusic` or `CfgRadio`
+fn sound_asset(config: &str, sound: &HppClass, file_path: &Path) -> Option<AssetReference> {
+    let file = sound.properties.iter()
+        .find(|property| property.name.eq_ignore_ascii_case("sound"))
+        .and_then(|property| match &property.value {
+            HppValue::Array(items) => items.first().and_then(HppValue::as_str),
+            HppValue::String(value) => Some(value.as_str()),
+            _ => None,
+        })?
+        .trim()
+        .trim_matches('"');
+    (!file.is_empty()).then(|| AssetReference {
+        path: file.to_string(),
+        context: format!("description.ext:{}/{}", config, sound.name),
+        source_file: file_path.to_path_buf(),
+    })
+}
+
+/// Functions declared by one tag of `CfgFunctions`.
+///
+/// A function's file is its own `file` property, or else `fn_<name><ext>` in
+/// the folder of its category, which defaults to `functions\<category>`.
+fn declared_functions(tag: &HppClass) -> Vec<MissionFunction> {
+    let tag_name = text(tag, "tag").unwrap_or(&tag.name);
+    let mut functions = Vec::new();
+    for category in nested(tag) {
+        let folder = text(category, "file")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}\\{}", DEFAULT_FUNCTIONS_FOLDER, category.name));
+        for function in nested(category) {
+            let file = text(function, "file").map(str::to_string).unwrap_or_else(|| {
+                let extension = text(function, "ext").unwrap_or(DEFAULT_FUNCTION_EXTENSION);
+                format!("{}\\fn_{}{}", folder.trim_end_matches('\\'), function.name, extension)
+            });
+            functions.push(MissionFunction {
+                name: format!("{}_fnc_{}", tag_name, function.name),
+                file,
+            });
+        }
+    }
+    functions
+}
+
+/// A non-empty string property of a class, matched ignoring case
+fn text<'a>(class: &'a HppClass, name: &str) -> Option<&'a str> {
+    class.properties.iter()
+        .find(|property| property.name.eq_ignore_ascii_case(name))
+        .and_then(|property| property.value.as_str())
+        .map(|value| value.trim().trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// Classes nested directly in a class
+fn nested(class: &HppClass) -> impl Iterator<Item = &HppClass> {
+    class.properties.iter().filter_map(|property| match &property.value {
+        HppValue::Class(nested) => Some(nested),
+        _ => None,
+    })
+}
+
+/// Add a class and every class nested in it to a list, without the nested classes as properties
+fn flatten(class: &HppClass, classes: &mut Vec<HppClass>) {
+    classes.push(HppClass {
+        name: class.name.clone(),
+        parent: class.parent.clone(),
+        properties: class.properties.iter()
+            .filter(|property| !matches!(property.value, HppValue::Class(_)))
+            .cloned()
+            .collect(),
+    });
+    for nested in nested(class) {
+        flatten(nested, classes);
+    }
+}
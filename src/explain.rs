@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use crate::classdb::ClassDatabase;
+use crate::modset::ModPrefixes;
+use crate::types::{MissionResults, ReferenceType, SourceLocation};
+
+/// A single place where a class is referenced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClassUsage {
+    /// Name of the mission containing the reference
+    pub mission_name: String,
+    /// File the reference was found in
+    pub source_file: PathBuf,
+    /// Context where the class is referenced
+    pub context: String,
+    /// Type of reference
+    pub reference_type: ReferenceType,
+    /// Where in the file the class is referenced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// Everything known about a single class across scan results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClassExplanation {
+    /// Class name that was looked up
+    pub class_name: String,
+    /// Mod prefix of the class (e.g. `rhs_`), if it has one
+    pub prefix: Option<String>,
+    /// Mod the prefix of the class maps to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_name: Option<String>,
+    /// Whether the class is in the class database, unset without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+    /// Mod or addon defining the class according to the class database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Every usage of the class
+    pub usages: Vec<ClassUsage>,
+}
+
+impl ClassExplanation {
+    /// Check if the class is used anywhere
+    pub fn is_used(&self) -> bool {
+        !self.usages.is_empty()
+    }
+}
+
+/// Get the mod prefix of a class name, up to and including the first underscore
+///
+/// Class names without an underscore (e.g. `ItemMap`) have no prefix.
+pub fn class_prefix(class_name: &str) -> Option<&str> {
+    class_name.find('_')
+        .filter(|&idx| idx > 0)
+        .map(|idx| &class_name[..=idx])
+}
+
+/// Collect every usage of a class across scan results.
+///
+/// Arma 3 class names are case-insensitive, so the lookup is as well. The mod
+/// of the class is looked up in `mod_prefixes`, and with a class database,
+/// whether the class exists and what defines it.
+pub fn explain_class(
+    class_name: &str,
+    results: &[MissionResults],
+    mod_prefixes: &ModPrefixes,
+    database: Option<&ClassDatabase>,
+) -> ClassExplanation {
+    let usages = results.iter()
+        .flat_map(|mission| {
+            mission.class_dependencies.iter()
+                .filter(|dep| dep.class_name.eq_ignore_ascii_case(class_name))
+                .map(|dep| ClassUsage {
+                    mission_name: mission.mission_name.clone(),
+                    source_file: dep.source_file.clone(),
+                    context: dep.context.clone(),
+                    reference_type: dep.reference_type.clone(),
+                    location: dep.location,
+                })
+        })
+        .collect();
+
+    ClassExplanation {
+        class_name: class_name.to_string(),
+        prefix: class_prefix(class_name).map(str::to_string),
+        mod_name: mod_prefixes.mod_for(class_name).map(str::to_string),
+        exists: database.map(|database| database.class_exists(class_name)),
+        source: database.and_then(|database| database.get(class_name)).and_then(|entry| entry.source.clone()),
+        usages,
+    }
+}
+
+impl fmt::Display for ClassExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Class: {}", self.class_name)?;
+        match &self.prefix {
+            Some(prefix) => writeln!(f, "Prefix: {}", prefix)?,
+            None => writeln!(f, "Prefix: (none)")?,
+        }
+        match &self.mod_name {
+            Some(mod_name) => writeln!(f, "Mod: {}", mod_name)?,
+            None => writeln!(f, "Mod: (unknown)")?,
+        }
+        match (self.exists, &self.source) {
+            (Some(true), Some(source)) => writeln!(f, "Class database: defined by {}", source)?,
+            (Some(true), None) => writeln!(f, "Class database: defined")?,
+            (Some(false), _) => writeln!(f, "Class database: missing")?,
+            (None, _) => {}
+        }
+
+        if self.usages.is_empty() {
+            return writeln!(f, "Not referenced by any scanned mission");
+        }
+
+        // Group usages by mission for readability
+        let mut by_mission: BTreeMap<&str, Vec<&ClassUsage>> = BTreeMap::new();
+        for usage in &self.usages {
+            by_mission.entry(&usage.mission_name).or_default().push(usage);
+        }
+
+        writeln!(f, "Referenced {} times in {} missions:", self.usages.len(), by_mission.len())?;
+        for (mission, usages) in by_mission {
+            writeln!(f, "  {}", mission)?;
+            for usage in usages {
+                let location = usage.location.map(|l| format!(":{}", l)).unwrap_or_default();
+                writeln!(f, "    - {}{} ({:?}, {})",
+                    usage.source_file.display(), location, usage.reference_type, usage.context)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Custom reference extractors
+//!
+//! Communities keep equipment in formats of their own, such as loadout
+//! tables in JSON or framework-specific config files. Register an
+//! [`Extractor`] in [`MissionScannerConfig::extractors`](crate::MissionScannerConfig::extractors)
+//! to have the scanner read those files too:
+//!
+//! ```
+//! use std::path::Path;
+//! use mission_scanner::{ClassReference, Extractor, MissionScannerConfig};
+//!
+//! struct LoadoutList;
+//!
+//! impl Extractor for LoadoutList {
+//!     fn name(&self) -> &str {
+//!         "loadout list"
+//!     }
+//!
+//!     fn applies_to(&self, file: &Path) -> bool {
+//!         file.file_name().is_some_and(|name| name == "loadouts.txt")
+//!     }
+//!
+//!     fn extract(&self, content: &str) -> anyhow::Result<Vec<ClassReference>> {
+//!         Ok(content.lines()
+//!             .filter(|line| !line.trim().is_empty())
+//!             .map(|line| ClassReference::direct(line.trim(), "loadout list"))
+//!             .collect())
+//!     }
+//! }
+//!
+//! let mut config = MissionScannerConfig::default();
+//! config.extractors.register(LoadoutList);
+//! ```
+//!
+//! Extractors run on every file of a mission they apply to, next to the
+//! built-in parsers, in parallel with the other files.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::types::ClassReference;
+
+/// Reader of class references from files the built-in parsers don't know
+pub trait Extractor: Send + Sync {
+    /// Name of the extractor, shown in logs
+    fn name(&self) -> &str;
+
+    /// Check if the extractor reads a file of a mission
+    fn applies_to(&self, file: &Path) -> bool;
+
+    /// Extract the class references of a file's content.
+    ///
+    /// The scanner sets the source file of the returned references.
+    fn extract(&self, content: &str) -> Result<Vec<ClassReference>>;
+}
+
+/// Extractors registered for a scan, none by default
+#[derive(Clone, Default)]
+pub struct Extractors(Vec<Arc<dyn Extractor>>);
+
+impl Extractors {
+    /// Add an extractor
+    pub fn register(&mut self, extractor: impl Extractor + 'static) {
+        self.0.push(Arc::new(extractor));
+    }
+
+    /// Check if no extractor is registered
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Check if any extractor reads a file
+    pub fn applies_to(&self, file: &Path) -> bool {
+        self.0.iter().any(|extractor| extractor.applies_to(file))
+    }
+
+    /// Get the extractors reading a file
+    pub fn for_file<'a>(&'a self, file: &'a Path) -> impl Iterator<Item = &'a dyn Extractor> {
+        self.0.iter()
+            .map(|extractor| &**extractor)
+            .filter(move |extractor| extractor.applies_to(file))
+    }
+}
+
+impl fmt::Debug for Extractors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|extractor| extractor.name())).finish()
+    }
+}
@@ -0,0 +1,150 @@
+//! C interface of the scanner, built with the `ffi` feature
+//!
+//! Exported from the cdylib so launchers and tools in other languages, such as
+//! C# with P/Invoke, can embed the scanner:
+//!
+//! ```csharp
+//! [DllImport("mission_scanner")] static extern IntPtr mission_scanner_scan_mission(string missionDir);
+//! [DllImport("mission_scanner")] static extern IntPtr mission_scanner_last_error();
+//! [DllImport("mission_scanner")] static extern void mission_scanner_free_string(IntPtr s);
+//! ```
+//!
+//! Arguments are NUL-terminated UTF-8 strings. Results are the same JSON the
+//! CLI writes, as strings owned by the caller that must be released with
+//! [`mission_scanner_free_string`]. On failure the functions return null and
+//! [`mission_scanner_last_error`] describes the error of the calling thread.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+
+use crate::{scan_mission, scan_missions, ClassDatabase, MissionScannerConfig, ScanOutput};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Read a string argument, failing on null or invalid UTF-8
+///
+/// # Safety
+/// `arg` must be null or point to a NUL-terminated string.
+unsafe fn arg<'a>(arg: *const c_char, name: &str) -> Result<&'a str> {
+    if arg.is_null() {
+        return Err(anyhow!("Argument {} is null", name));
+    }
+    unsafe { CStr::from_ptr(arg) }.to_str()
+        .with_context(|| format!("Argument {} is not UTF-8", name))
+}
+
+/// Run a call, returning its result as an owned JSON string or null after
+/// recording its error or panic
+fn call<T: Serialize>(f: impl FnOnce() -> Result<T>) -> *mut c_char {
+    let result = panic::catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(anyhow!("Mission scanner panicked")))
+        .and_then(|value| Ok(serde_json::to_string(&value)?))
+        .and_then(|json| Ok(CString::new(json)?));
+
+    match result {
+        Ok(json) => {
+            LAST_ERROR.with(|e| e.borrow_mut().take());
+            json.into_raw()
+        }
+        Err(e) => {
+            let message = CString::new(format!("{:#}", e).replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Run a scan to completion on a runtime of its own
+fn block_on<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+    tokio::runtime::Runtime::new()?.block_on(future)
+}
+
+/// Class dependencies of mission.sqm content, as a sorted JSON array
+///
+/// # Safety
+/// `sqm_content` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_extract_class_dependencies(sqm_content: *const c_char) -> *mut c_char {
+    call(|| {
+        let mut dependencies: Vec<String> = parser_sqm::extract_class_dependencies(unsafe { arg(sqm_content, "sqm_content") }?)
+            .into_iter()
+            .collect();
+        dependencies.sort();
+        Ok(dependencies)
+    })
+}
+
+/// Addons listed in the `addons[]` and `addOnsAuto[]` arrays of mission.sqm content, as a JSON array
+///
+/// # Safety
+/// `sqm_content` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_extract_required_addons(sqm_content: *const c_char) -> *mut c_char {
+    call(|| Ok(parser_sqm::extract_required_addons(unsafe { arg(sqm_content, "sqm_content") }?)))
+}
+
+/// Results of scanning a mission directory, as a JSON object
+///
+/// # Safety
+/// `mission_dir` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_scan_mission(mission_dir: *const c_char) -> *mut c_char {
+    call(|| {
+        let mission_dir = Path::new(unsafe { arg(mission_dir, "mission_dir") }?);
+        let config = MissionScannerConfig::default();
+        block_on(scan_mission(mission_dir, config.max_threads, &config))
+    })
+}
+
+/// Results of scanning every mission below a directory, as the JSON object `scan` prints
+///
+/// # Safety
+/// `dir` must be a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_scan_missions(dir: *const c_char) -> *mut c_char {
+    call(|| {
+        let dir = Path::new(unsafe { arg(dir, "dir") }?);
+        block_on(scan_missions(dir, &MissionScannerConfig::default())).map(ScanOutput::new)
+    })
+}
+
+/// Classes a mission references that aren't in a class database file, as a JSON array
+///
+/// # Safety
+/// `mission_dir` and `class_db` must be NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_validate(mission_dir: *const c_char, class_db: *const c_char) -> *mut c_char {
+    call(|| {
+        let mission_dir = Path::new(unsafe { arg(mission_dir, "mission_dir") }?);
+        let database = ClassDatabase::load_from_file(Path::new(unsafe { arg(class_db, "class_db") }?))?;
+        let config = MissionScannerConfig::default();
+        let result = block_on(scan_mission(mission_dir, config.max_threads, &config))?;
+        Ok(database.missing_classes(&result))
+    })
+}
+
+/// Error of the last call on this thread that returned null, or null if it succeeded.
+/// The string must be released with [`mission_scanner_free_string`]
+#[unsafe(no_mangle)]
+pub extern "C" fn mission_scanner_last_error() -> *mut c_char {
+    LAST_ERROR.with(|e| e.borrow().clone().map_or(std::ptr::null_mut(), CString::into_raw))
+}
+
+/// Release a string returned by the scanner
+///
+/// # Safety
+/// `s` must be null or a string returned by a `mission_scanner_` function,
+/// released only once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn mission_scanner_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
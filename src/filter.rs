@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use crate::metadata::{MissionMetadata, PlayerRange};
+use crate::types::MissionResults;
+
+/// Selection of missions by tag, terrain and player count
+///
+/// An empty filter matches every mission. Tags come from the mission's
+/// mission.meta.toml, the terrain from its directory name (`co40_raid.Altis`)
+/// and the player count from the metadata, falling back to `1` up to the
+/// number of playable slots in mission.sqm.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionFilter {
+    /// Tags the mission must all have, ignoring case
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Terrain the mission must be made for, ignoring case
+    #[serde(default)]
+    pub terrain: Option<String>,
+    /// Player counts the mission must support at least one of
+    #[serde(default)]
+    pub players: Option<PlayerRange>,
+}
+
+impl MissionFilter {
+    /// Check if the filter selects every mission
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.terrain.is_none() && self.players.is_none()
+    }
+
+    /// Check if a scanned mission is selected by the filter
+    pub fn matches(&self, mission: &MissionResults) -> bool {
+        self.check(&mission.mission_dir, mission.metadata.as_ref(), Some(mission.playable_slots))
+    }
+
+    /// Check what can be known about a mission before scanning it.
+    ///
+    /// A player filter on a mission without a player range in its metadata
+    /// passes, since the slot count is only known after the scan.
+    pub fn may_match(&self, mission_dir: &Path, metadata: Option<&MissionMetadata>) -> bool {
+        self.check(mission_dir, metadata, None)
+    }
+
+    fn check(&self, mission_dir: &Path, metadata: Option<&MissionMetadata>, slots: Option<usize>) -> bool {
+        if let Some(terrain) = &self.terrain
+            && !mission_terrain(mission_dir).is_some_and(|t| t.eq_ignore_ascii_case(terrain))
+        {
+            return false;
+        }
+
+        if !self.tags.iter().all(|tag| metadata.is_some_and(|m| m.has_tag(tag))) {
+            return false;
+        }
+
+        let Some(wanted) = &self.players else {
+            return true;
+        };
+        let supported = metadata.and_then(|m| m.players)
+            .or_else(|| slots.map(|slots| PlayerRange { min: (slots as u32).min(1), max: slots as u32 }));
+        match supported {
+            Some(supported) => supported.overlaps(wanted),
+            None => slots.is_none(),
+        }
+    }
+}
+
+/// Terrain a mission is made for, from the suffix of its directory name
+pub fn mission_terrain(mission_dir: &Path) -> Option<&str> {
+    mission_variant(mission_dir.file_name()?.to_str()?).1
+}
+
+/// Split a mission name such as `co40_raid.Altis` into the name of the mission
+/// and the map it's made for.
+///
+/// A `.pbo` extension is ignored, so packed missions split like their
+/// directories. Names without a map suffix have no map.
+pub fn mission_variant(mission_name: &str) -> (&str, Option<&str>) {
+    let name = if mission_name.to_ascii_lowercase().ends_with(".pbo") {
+        &mission_name[..mission_name.len() - 4]
+    } else {
+        mission_name
+    };
+    match name.rsplit_once('.') {
+        Some((base, map)) if !base.is_empty() && !map.is_empty() => (base, Some(map)),
+        _ => (name, None),
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use crate::types::MissionResults;
+
+/// Line ending and indentation counts of a file
+///
+/// Mixed CRLF/LF line endings and mixed tab/space indentation in config files
+/// break `#include` handling in some tools and make diffs noisy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FormattingAudit {
+    /// Lines ending in CRLF
+    pub crlf_lines: usize,
+    /// Lines ending in a bare LF
+    pub lf_lines: usize,
+    /// Lines indented with tabs
+    pub tab_indented_lines: usize,
+    /// Lines indented with spaces
+    pub space_indented_lines: usize,
+}
+
+impl FormattingAudit {
+    /// Check if the file uses both CRLF and LF line endings
+    pub fn has_mixed_line_endings(&self) -> bool {
+        self.crlf_lines > 0 && self.lf_lines > 0
+    }
+
+    /// Check if the file indents some lines with tabs and others with spaces
+    pub fn has_mixed_indentation(&self) -> bool {
+        self.tab_indented_lines > 0 && self.space_indented_lines > 0
+    }
+
+    /// Check if the file has any formatting inconsistency
+    pub fn is_consistent(&self) -> bool {
+        !self.has_mixed_line_endings() && !self.has_mixed_indentation()
+    }
+
+    /// Describe each inconsistency with its counts
+    pub fn findings(&self) -> Vec<String> {
+        let mut findings = Vec::new();
+        if self.has_mixed_line_endings() {
+            findings.push(format!("Mixed line endings: {} CRLF, {} LF", self.crlf_lines, self.lf_lines));
+        }
+        if self.has_mixed_indentation() {
+            findings.push(format!("Mixed indentation: {} lines with tabs, {} with spaces",
+                self.tab_indented_lines, self.space_indented_lines));
+        }
+        findings
+    }
+}
+
+/// Count the line endings and indentation styles of file content
+pub fn audit_formatting(content: &str) -> FormattingAudit {
+    let mut audit = FormattingAudit::default();
+    for line in content.split_inclusive('\n') {
+        if line.ends_with("\r\n") {
+            audit.crlf_lines += 1;
+        } else if line.ends_with('\n') {
+            audit.lf_lines += 1;
+        }
+
+        match line.chars().next() {
+            Some('\t') => audit.tab_indented_lines += 1,
+            Some(' ') if !line.trim().is_empty() => audit.space_indented_lines += 1,
+            _ => {}
+        }
+    }
+    audit
+}
+
+/// Audit the config files of a scanned mission, keeping only inconsistent ones
+pub fn audit_mission_formatting(mission: &MissionResults) -> BTreeMap<PathBuf, FormattingAudit> {
+    mission.cpp_files.iter()
+        .filter_map(|file| {
+            let content = fs::read(file).ok()?;
+            let audit = audit_formatting(&String::from_utf8_lossy(&content));
+            (!audit.is_consistent()).then(|| (file.clone(), audit))
+        })
+        .collect()
+}
@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::types::MissionResults;
+
+/// Commit that first introduced a class reference to a mission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassIntroduction {
+    /// Class name as written where it was introduced
+    pub class_name: String,
+    /// Hash of the introducing commit
+    pub commit: String,
+    /// Author date of the commit in ISO 8601 format
+    pub date: String,
+    /// Author date of the commit as a Unix timestamp
+    pub timestamp: i64,
+    /// File the class was introduced in
+    pub source_file: PathBuf,
+}
+
+/// Check if a directory is inside a git work tree
+pub fn is_git_repository(dir: &Path) -> bool {
+    Command::new("git")
+        .arg("-C").arg(dir)
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Find the commit that introduced each class referenced by a mission.
+///
+/// Uses git's pickaxe search on every file referencing the class and keeps the
+/// earliest match, so classes are attributed to the commit that first added
+/// their name rather than to when the file was created. Classes with no
+/// matching commit (e.g. uncommitted changes) are left out. Results are sorted
+/// by date, oldest first.
+pub fn class_history(results: &MissionResults) -> Result<Vec<ClassIntroduction>> {
+    let repo_dir = &results.mission_dir;
+    if !is_git_repository(repo_dir) {
+        return Err(anyhow!("{} is not inside a git repository", repo_dir.display()));
+    }
+
+    // Earliest introduction per lowercase class name
+    let mut earliest: HashMap<String, ClassIntroduction> = HashMap::new();
+    let mut searched = HashSet::new();
+
+    for dep in &results.class_dependencies {
+        if dep.class_name.is_empty() || !searched.insert((dep.class_name.clone(), dep.source_file.clone())) {
+            continue;
+        }
+
+        let Some(introduction) = first_commit_adding(repo_dir, &dep.class_name, &dep.source_file)? else {
+            continue;
+        };
+
+        let key = dep.class_name.to_lowercase();
+        if earliest.get(&key).is_none_or(|existing| introduction.timestamp < existing.timestamp) {
+            earliest.insert(key, introduction);
+        }
+    }
+
+    let mut history: Vec<_> = earliest.into_values().collect();
+    history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then(a.class_name.cmp(&b.class_name)));
+    Ok(history)
+}
+
+/// Find the oldest commit that added a string to a file
+fn first_commit_adding(repo_dir: &Path, text: &str, file: &Path) -> Result<Option<ClassIntroduction>> {
+    let output = Command::new("git")
+        .arg("-C").arg(repo_dir)
+        .args(["log", "--reverse", "--format=%H %at %aI"])
+        .arg(format!("-S{}", text))
+        .arg("--")
+        .arg(file)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("git log failed for {}: {}",
+            file.display(), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(line) = stdout.lines().next() else {
+        return Ok(None);
+    };
+
+    let mut fields = line.splitn(3, ' ');
+    let (Some(commit), Some(timestamp), Some(date)) = (fields.next(), fields.next(), fields.next()) else {
+        return Err(anyhow!("Unexpected git log output: {}", line));
+    };
+
+    Ok(Some(ClassIntroduction {
+        class_name: text.to_string(),
+        commit: commit.to_string(),
+        date: date.to_string(),
+        timestamp: timestamp.parse()
+            .map_err(|e| anyhow!("Invalid commit timestamp '{}': {}", timestamp, e))?,
+        source_file: file.to_path_buf(),
+    }))
+}
@@ -0,0 +1,162 @@
+//! Interning of class names across a scan
+//!
+//! The same few hundred class names are referenced over and over: every unit
+//! of every mission carries `ACE_fieldDressing`, and a large collection of
+//! missions ends up holding tens of thousands of copies of it. References keep
+//! their class name as a [`Symbol`], which the scanner hands out from the
+//! [`Interner`] of its configuration, so every reference to the same name
+//! shares one allocation for the whole scan.
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Interned class name, resolving to the `&str` it was made from.
+///
+/// Cloning a symbol is cheap and shares the name. Symbols compare by content,
+/// so one made outside the interner is equal to the interned one, and they are
+/// serialized as plain strings.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    /// Name the symbol stands for
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self(Arc::from(name))
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Self(Arc::from(name))
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Symbol::from)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Symbol {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::r#gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(generator)
+    }
+}
+
+/// Set of the class names seen so far in a scan, shared between threads.
+///
+/// Clones share the same set, so the interner of a configuration is shared
+/// by every mission scanned with it. Names are kept as written, since class
+/// names ignore case but are reported the way the mission wrote them.
+#[derive(Clone, Default)]
+pub struct Interner(Arc<Mutex<HashSet<Symbol>>>);
+
+impl Interner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the symbol of a name, adding the name if it wasn't seen before
+    pub fn intern(&self, name: &str) -> Symbol {
+        let mut names = self.0.lock().unwrap();
+        if let Some(symbol) = names.get(name) {
+            return symbol.clone();
+        }
+        let symbol = Symbol::from(name);
+        names.insert(symbol.clone());
+        symbol
+    }
+
+    /// Number of distinct names interned
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Check if no name was interned yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interner").field("names", &self.len()).finish()
+    }
+}
@@ -1,15 +1,215 @@
+pub mod analysis;
+pub mod arsenal;
+pub mod classdb;
+pub mod compatibility;
+pub mod description_ext;
+pub mod explain;
+pub mod extractor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod formatting;
+pub mod history;
+pub mod interner;
+pub mod lints;
+pub mod manifest;
+pub mod medical;
+pub mod metadata;
+pub mod modset;
+pub mod pbo;
+pub mod portable;
+pub mod progress;
+#[cfg(feature = "python")]
+mod python;
+pub mod report;
+#[cfg(feature = "rules")]
+pub mod rules;
 pub mod scanner;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod search;
 pub mod types;
+pub mod vfs;
 
 pub use types::{
+    AssetCategory,
+    AssetReference,
     ClassReference,
     ClassSource,
+    Confidence,
+    CountRange,
+    CuratorAddons,
+    CuratorModule,
+    CuratorSetup,
+    DependencyOwner,
+    ItemKind,
+    MissionFootprint,
     MissionResults,
     MissionScannerConfig,
+    QuarantinedFile,
     ReferenceType,
+    ScriptCall,
+    SourceLocation,
+    UsageContext,
+};
+
+pub use interner::{Interner, Symbol};
+
+pub use arsenal::{
+    audit_arsenals,
+    ArsenalAudit,
+    ArsenalViolation,
+    EquipmentWhitelist,
+};
+
+pub use extractor::{Extractor, Extractors};
+
+pub use lints::{lint_loadout_file, lint_mission_loadouts, FileLints, LoadoutLint, LoadoutLintKind};
+
+pub use medical::{
+    cross_check_medical,
+    MedicalCrossCheck,
+    MedicalTreatments,
+    UnissuedItem,
+};
+
+pub use analysis::{
+    analyze_file,
+    DiagnosticSeverity,
+    FileAnalysis,
+    FileAnalysisOptions,
+    FileDiagnostic,
+    FileMetrics,
+    FileSymbol,
+};
+
+pub use classdb::{
+    check_item_categories,
+    CategoryMismatch,
+    ClassDatabase,
+    ClassEntry,
+    MissingClass,
+    Suggestion,
+};
+
+pub use description_ext::{
+    analyze_description_ext,
+    parse_description_ext,
+    DescriptionExt,
+    MissionFunction,
+};
+
+pub use compatibility::{
+    check_compatibility,
+    check_mission_compatibility,
+    ConfigSettings,
+    SettingConflict,
+};
+
+pub use explain::{
+    explain_class,
+    ClassExplanation,
+    ClassUsage,
+};
+
+pub use formatting::{
+    audit_formatting,
+    audit_mission_formatting,
+    FormattingAudit,
+};
+
+pub use filter::{
+    mission_terrain,
+    mission_variant,
+    MissionFilter,
+};
+
+pub use history::{
+    class_history,
+    ClassIntroduction,
+};
+
+pub use manifest::{
+    check_manifest,
+    ManifestDrift,
+    MissionManifest,
+};
+
+pub use modset::{
+    ModPrefixes,
+    RequiredMods,
+};
+
+pub use metadata::{
+    MissionMetadata,
+    PboSignatures,
+    PlayerRange,
+};
+
+pub use pbo::{
+    Pbo,
+    PboEntry,
+};
+
+pub use progress::{
+    Progress,
+    ProgressEvent,
+    ProgressSink,
+};
+
+pub use portable::{
+    export_portable,
+    import_portable,
+    PortableScan,
+};
+
+pub use report::{
+    build_report,
+    diff_missions,
+    group_variants,
+    to_csv,
+    to_html,
+    to_markdown,
+    write_csv,
+    write_html,
+    write_json_report,
+    CountChange,
+    FileSummary,
+    MissionDiff,
+    MissionReport,
+    MissionSummary,
+    MissionVariants,
+    ReportCounts,
+    ReportFile,
+    ReportReference,
+    ScanOutput,
+    ScanSummary,
+    SourceKind,
+};
+
+pub use search::{
+    find_class_references,
+    ClassPattern,
+    PathPattern,
 };
 
 pub use scanner::{
     parse_file,
+    parse_file_with_vfs,
     scan_mission,
+    scan_mission_with_vfs,
+    scan_missions,
+    scan_missions_stream,
+    scan_missions_stream_with_vfs,
+    scan_missions_with_vfs,
+    scan_pbo,
+    write_repro,
+};
+
+pub use vfs::{
+    MemoryFs,
+    OsFs,
+    Vfs,
 };
\ No newline at end of file
@@ -0,0 +1,59 @@
+//! Lints for the loadout configs of a mission
+//!
+//! Runs the checks of [`parser_hpp`] on each config file: arrays replacing
+//! what a parent appends, items listed with `LIST_N` and on their own, and,
+//! with a class database knowing item categories, classes in an array of
+//! another category.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use log::debug;
+use serde::{Serialize, Deserialize};
+
+use crate::classdb::ClassDatabase;
+use crate::scanner::{parse_config, ConfigContext};
+use crate::types::MissionResults;
+use crate::vfs::OsFs;
+
+pub use parser_hpp::{LoadoutLint, LoadoutLintKind};
+
+/// Lints of one config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLints {
+    /// Config file linted
+    pub file: PathBuf,
+    pub lints: Vec<LoadoutLint>,
+}
+
+/// Lint a loadout config file.
+///
+/// Categories are only checked with a database, and only for the classes
+/// whose category it knows.
+pub fn lint_loadout_file(path: &Path, context: &ConfigContext, database: Option<&ClassDatabase>) -> Result<Vec<LoadoutLint>> {
+    let parser = parse_config(&OsFs::default(), path, context)?;
+    let classes = parser.parse_classes();
+
+    let mut lints = parser_hpp::lint_overridden_appends(&classes);
+    lints.extend(parser.lint_list_macros());
+    if let Some(database) = database {
+        lints.extend(parser_hpp::lint_categories(&classes, |class_name| database.category(class_name)));
+    }
+    Ok(lints)
+}
+
+/// Lint the config files of a scanned mission, leaving out files without
+/// lints. Files that can't be parsed are skipped.
+pub fn lint_mission_loadouts(mission: &MissionResults, database: Option<&ClassDatabase>) -> Vec<FileLints> {
+    let context = ConfigContext::for_mission(&OsFs::default(), &mission.mission_dir, &mission.cpp_files);
+    mission.cpp_files.iter()
+        .filter_map(|file| match lint_loadout_file(file, &context, database) {
+            Ok(lints) => Some(FileLints { file: file.clone(), lints }),
+            Err(e) => {
+                debug!("Failed to lint {}: {:#}", file.display(), e);
+                None
+            }
+        })
+        .filter(|file| !file.lints.is_empty())
+        .collect()
+}
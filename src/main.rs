@@ -0,0 +1,659 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use mission_scanner::{
+    audit_arsenals,
+    check_item_categories,
+    check_mission_compatibility,
+    diff_missions,
+    explain_class,
+    check_manifest,
+    class_history,
+    cross_check_medical,
+    find_class_references,
+    lint_mission_loadouts,
+    scan_mission,
+    scan_missions,
+    to_csv,
+    to_markdown,
+    write_csv,
+    write_html,
+    write_json_report,
+    ClassDatabase,
+    ClassPattern,
+    Confidence,
+    ConfigSettings,
+    EquipmentWhitelist,
+    MedicalTreatments,
+    MissionManifest,
+    MissionResults,
+    MissionScannerConfig,
+    ModPrefixes,
+    PlayerRange,
+    ScanOutput,
+};
+
+/// Scan Arma 3 missions for the classes they depend on
+#[derive(Debug, Parser)]
+#[command(name = "mission_scanner", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Scan the missions (folders or PBOs) in a directory and print the results as JSON
+    Scan(ScanArgs),
+    /// Report classes a mission references that aren't in a class database or are given to commands taking another category
+    Validate(ValidateArgs),
+    /// Write a report of a saved scan
+    Report(ReportArgs),
+    /// Compare the class dependencies of two saved scans
+    Diff {
+        /// Scan before the change, as printed by `scan`
+        old: PathBuf,
+        /// Scan after the change
+        new: PathBuf,
+    },
+    /// Print where a single class is used according to a saved scan
+    Explain {
+        classname: String,
+        /// Scan to search, as printed by `scan`
+        #[arg(long)]
+        scan: PathBuf,
+        /// Class database to check the class exists in
+        #[arg(long, alias = "classdb", value_name = "classes.json|classes.csv")]
+        class_db: Option<PathBuf>,
+        /// TOML file of class name prefixes of mods
+        #[arg(long, alias = "mods")]
+        mod_prefixes: Option<PathBuf>,
+    },
+    /// Print every parsed class reference matching a glob in a directory
+    Find {
+        pattern: String,
+        dir: PathBuf,
+        /// Match the pattern as a regular expression
+        #[arg(long)]
+        regex: bool,
+    },
+    /// Print the disk footprint of a mission by asset category
+    Size {
+        mission_dir: PathBuf,
+        /// Flag the mission if it's larger than this many bytes
+        #[arg(long)]
+        max_size: Option<u64>,
+    },
+    /// Report drift between a mission's manifest.toml and its actual dependencies
+    Manifest { mission_dir: PathBuf },
+    /// Print when each class was first introduced according to git history
+    History { mission_dir: PathBuf },
+    /// Print the classes each side of a mission requires
+    Sides { mission_dir: PathBuf },
+    /// Report arsenal equipment of the missions in a directory that isn't on a whitelist
+    Arsenals {
+        dir: PathBuf,
+        /// Allowed equipment, one class or glob per line
+        #[arg(long)]
+        whitelist: PathBuf,
+        /// Print the per-mission reports as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Cross-check the medical items of the missions in a directory against ACE's treatments
+    Medical {
+        dir: PathBuf,
+        /// Config defining ACE_Medical_Treatment_Actions, such as ACE's medical treatment config
+        #[arg(long)]
+        config: PathBuf,
+        /// Print the per-mission reports as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Lint the loadout configs of a mission
+    Lint {
+        mission_dir: PathBuf,
+        /// Class database with item categories, to check that loadout arrays hold the right kind of item
+        #[arg(long, alias = "classdb", value_name = "classes.json|classes.csv")]
+        class_db: Option<PathBuf>,
+    },
+    /// Report settings where a mission's description.ext conflicts with a server's configs
+    Compat {
+        mission_dir: PathBuf,
+        #[arg(required = true)]
+        server_configs: Vec<PathBuf>,
+    },
+    /// Manage class database snapshots
+    #[cfg(feature = "classdb-fetch")]
+    #[command(subcommand)]
+    Classdb(ClassdbCommand),
+}
+
+/// Options selecting the references kept in the results
+#[derive(Debug, Args)]
+struct FilterArgs {
+    /// Leave a class out of the results, or every class matching a glob such as `Land_*`
+    #[arg(long = "ignore", value_name = "CLASS|GLOB")]
+    ignore: Vec<String>,
+    /// Leave out references less certain than this
+    #[arg(long, value_name = "low|medium|high", value_parser = parse::<Confidence>)]
+    min_confidence: Option<Confidence>,
+}
+
+impl FilterArgs {
+    /// Add the ignored classes, as patterns if they have wildcards, and the confidence to a config
+    fn apply(self, config: &mut MissionScannerConfig) {
+        for value in self.ignore {
+            if value.contains(['*', '?']) {
+                config.ignore_patterns.push(value);
+            } else {
+                config.ignore_classes.push(value);
+            }
+        }
+        if let Some(min_confidence) = self.min_confidence {
+            config.min_confidence = min_confidence;
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct ScanArgs {
+    /// Directory of missions or a mission.pbo
+    dir: PathBuf,
+    /// Only scan missions with this tag in their metadata
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Only scan missions on this terrain
+    #[arg(long)]
+    terrain: Option<String>,
+    /// Only scan missions whose player count overlaps a range such as 20-40
+    #[arg(long, value_parser = parse::<PlayerRange>)]
+    players: Option<PlayerRange>,
+    /// Write versioned reports into a directory instead of printing the results
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Write a CSV of every reference
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// Only scan the files matching a glob from the mission root, such as `loadouts/**`
+    #[arg(long = "include", value_name = "GLOB")]
+    include_globs: Vec<String>,
+    /// Leave the files matching a glob from the mission root out, such as `**/briefing/**`
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude_globs: Vec<String>,
+    #[command(flatten)]
+    filter: FilterArgs,
+    /// Cache parsed files in a directory
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// TOML file of class name prefixes of mods
+    #[arg(long)]
+    mod_prefixes: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct ValidateArgs {
+    mission_dir: PathBuf,
+    /// Known classes, as JSON or CSV. The latest snapshot downloaded with `classdb fetch` when unset
+    #[arg(long, alias = "classdb", value_name = "classes.json|classes.csv")]
+    class_db: Option<PathBuf>,
+    /// Cache directory `classdb fetch` downloaded into, instead of the per-user one
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    #[command(flatten)]
+    filter: FilterArgs,
+}
+
+#[derive(Debug, Args)]
+struct ReportArgs {
+    /// Scan to report, as printed by `scan`
+    scan: PathBuf,
+    #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+    format: ReportFormat,
+    /// Directory of the JSON reports, or file of the other formats. CSV and markdown are printed when unset
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Earlier scan to compare with in the markdown report, listing the classes added since
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Number of entries of each list in the markdown report
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+    /// Highlight classes missing from this class database in the HTML and markdown reports
+    #[arg(long, alias = "classdb", value_name = "classes.json|classes.csv")]
+    class_db: Option<PathBuf>,
+    /// TOML file of class name prefixes of mods, for the HTML report
+    #[arg(long)]
+    mod_prefixes: Option<PathBuf>,
+}
+
+/// Format of the `report` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    /// A report per mission and a summary, see `write_json_report`
+    Json,
+    /// A row per reference
+    Csv,
+    /// A self-contained page to share, see `write_html`
+    Html,
+    /// A short summary to post on merge requests, see `to_markdown`
+    Markdown,
+}
+
+#[cfg(feature = "classdb-fetch")]
+#[derive(Debug, Subcommand)]
+enum ClassdbCommand {
+    /// Download a class database snapshot into the cache directory
+    Fetch {
+        url: String,
+        /// Expected SHA-256 of the snapshot, instead of the published `.sha256` file
+        #[arg(long)]
+        sha256: Option<String>,
+        /// Cache directory to download into, instead of the per-user one
+        #[arg(long)]
+        cache_dir: Option<PathBuf>,
+    },
+}
+
+/// Parse an argument with its `FromStr` implementation
+fn parse<T: FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    value.parse().map_err(|e: T::Err| e.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    match Cli::parse().command {
+        Command::Scan(args) => run_scan(args).await,
+        Command::Validate(args) => run_validate(args).await,
+        Command::Report(args) => run_report(args),
+        Command::Diff { old, new } => run_diff(&old, &new),
+        Command::Explain { classname, scan, class_db, mod_prefixes } => {
+            run_explain(&classname, &scan, class_db.as_deref(), mod_prefixes.as_deref())
+        }
+        Command::Find { pattern, dir, regex } => run_find(&pattern, &dir, regex).await,
+        Command::Size { mission_dir, max_size } => run_size(&mission_dir, max_size).await,
+        Command::Manifest { mission_dir } => run_manifest(&mission_dir).await,
+        Command::History { mission_dir } => run_history(&mission_dir).await,
+        Command::Sides { mission_dir } => run_sides(&mission_dir).await,
+        Command::Arsenals { dir, whitelist, json } => run_arsenals(&dir, &whitelist, json).await,
+        Command::Medical { dir, config, json } => run_medical(&dir, &config, json).await,
+        Command::Lint { mission_dir, class_db } => run_lint(&mission_dir, class_db.as_deref()).await,
+        Command::Compat { mission_dir, server_configs } => run_compat(&mission_dir, &server_configs),
+        #[cfg(feature = "classdb-fetch")]
+        Command::Classdb(ClassdbCommand::Fetch { url, sha256, cache_dir }) => run_classdb_fetch(&url, sha256.as_deref(), cache_dir).await,
+    }
+}
+
+/// Print where a single class is used according to a saved scan, which mod
+/// provides it and whether a class database knows it
+fn run_explain(class_name: &str, scan_file: &Path, class_db: Option<&Path>, prefixes: Option<&Path>) -> Result<()> {
+    let results = load_scan_results(scan_file)?;
+    let mut mod_prefixes = ModPrefixes::default();
+    if let Some(prefixes) = prefixes {
+        mod_prefixes.extend(&ModPrefixes::load(prefixes)?);
+    }
+    let database = class_db.map(ClassDatabase::load_from_file).transpose()?;
+    print!("{}", explain_class(class_name, &results, &mod_prefixes, database.as_ref()));
+    Ok(())
+}
+
+/// Scan the missions (folders or PBOs) in a directory selected by the filter options and print the results as JSON,
+/// or write versioned reports with `--output` and a CSV of every reference with `--csv`
+async fn run_scan(args: ScanArgs) -> Result<()> {
+    let mut config = MissionScannerConfig::default();
+    config.filter.tags = args.tags;
+    config.filter.terrain = args.terrain;
+    config.filter.players = args.players;
+    config.output_dir = args.output;
+    config.cache_dir = args.cache_dir;
+    config.include_globs = args.include_globs;
+    config.exclude_globs = args.exclude_globs;
+    args.filter.apply(&mut config);
+    if let Some(path) = &args.mod_prefixes {
+        config.mod_prefixes.extend(&ModPrefixes::load(path)?);
+    }
+
+    let results = scan_missions(&args.dir, &config).await?;
+    let selected = results.len();
+    if let Some(csv) = &args.csv {
+        write_csv(&results, csv)?;
+    }
+    match &config.output_dir {
+        Some(output_dir) => {
+            let written = write_json_report(&results, output_dir)?;
+            eprintln!("Wrote {} report files to {}", written.len(), output_dir.display());
+        }
+        None if args.csv.is_none() => println!("{}", serde_json::to_string_pretty(&ScanOutput::new(results))?),
+        None => {}
+    }
+    eprintln!("{} missions selected", selected);
+    Ok(())
+}
+
+/// Write the report of a saved scan in a format
+fn run_report(args: ReportArgs) -> Result<()> {
+    let results = load_scan_results(&args.scan)?;
+    match (args.format, &args.output) {
+        (ReportFormat::Json, Some(output_dir)) => {
+            let written = write_json_report(&results, output_dir)?;
+            eprintln!("Wrote {} report files to {}", written.len(), output_dir.display());
+        }
+        (ReportFormat::Json, None) => return Err(anyhow!("JSON reports require --output <dir>")),
+        (ReportFormat::Csv, Some(path)) => write_csv(&results, path)?,
+        (ReportFormat::Csv, None) => print!("{}", to_csv(&results)),
+        (ReportFormat::Html, Some(path)) => {
+            let mut mod_prefixes = ModPrefixes::default();
+            if let Some(prefixes) = &args.mod_prefixes {
+                mod_prefixes.extend(&ModPrefixes::load(prefixes)?);
+            }
+            let database = args.class_db.as_deref().map(ClassDatabase::load_from_file).transpose()?;
+            write_html(&results, &mod_prefixes, database.as_ref(), path)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        (ReportFormat::Html, None) => return Err(anyhow!("HTML reports require --output <file>")),
+        (ReportFormat::Markdown, output) => {
+            let baseline = args.baseline.as_deref().map(load_scan_results).transpose()?;
+            let database = args.class_db.as_deref().map(ClassDatabase::load_from_file).transpose()?;
+            let markdown = to_markdown(&results, baseline.as_deref(), database.as_ref(), args.top);
+            match output {
+                Some(path) => fs::write(path, markdown)
+                    .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?,
+                None => print!("{}", markdown),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the classes each mission gained, lost or uses a different number of times between two saved scans
+fn run_diff(old: &Path, new: &Path) -> Result<()> {
+    let old: BTreeMap<String, MissionResults> = load_scan_results(old)?.into_iter()
+        .map(|result| (result.mission_name.clone(), result))
+        .collect();
+    let new = load_scan_results(new)?;
+
+    for mission in &new {
+        let Some(previous) = old.get(&mission.mission_name) else {
+            println!("{}: new mission with {} class references", mission.mission_name, mission.class_dependencies.len());
+            continue;
+        };
+
+        let diff = diff_missions(previous, mission);
+        if diff.is_empty() {
+            continue;
+        }
+        println!("{}:", diff.mission_name);
+        for class in &diff.added {
+            println!("  + {}", class);
+        }
+        for class in &diff.removed {
+            println!("  - {}", class);
+        }
+        for change in &diff.changed {
+            println!("  ~ {} ({} -> {})", change.class_name, change.old_count, change.new_count);
+        }
+    }
+    for name in old.keys().filter(|name| !new.iter().any(|mission| &mission.mission_name == *name)) {
+        println!("{}: mission removed", name);
+    }
+    Ok(())
+}
+
+/// Print every parsed class reference matching a pattern in a directory
+async fn run_find(pattern: &str, dir: &Path, use_regex: bool) -> Result<()> {
+    let pattern = if use_regex {
+        ClassPattern::regex(pattern)?
+    } else {
+        ClassPattern::glob(pattern)
+    };
+
+    let config = MissionScannerConfig::default();
+    let mut matches = find_class_references(dir, &pattern, &config).await?;
+    matches.sort_by(|a, b| a.source_file.cmp(&b.source_file).then(a.class_name.cmp(&b.class_name)));
+
+    for reference in &matches {
+        println!("{}: {} ({})", reference.source_file.display(), reference.class_name, reference.context);
+    }
+    eprintln!("{} matches", matches.len());
+    Ok(())
+}
+
+/// Print the disk footprint of a mission by asset category
+async fn run_size(mission_dir: &Path, max_size: Option<u64>) -> Result<()> {
+    let config = MissionScannerConfig { max_mission_size: max_size, ..Default::default() };
+    let result = scan_mission(mission_dir, config.max_threads, &config).await?;
+    println!("Mission: {}", result.mission_name);
+    print!("{}", result.footprint);
+    Ok(())
+}
+
+/// Report drift between a mission's manifest.toml and its actual dependencies
+async fn run_manifest(mission_dir: &Path) -> Result<()> {
+    let manifest = MissionManifest::load(mission_dir)?
+        .ok_or_else(|| anyhow!("No manifest.toml in {}", mission_dir.display()))?;
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(mission_dir, config.max_threads, &config).await?;
+
+    let drift = check_manifest(&manifest, &result);
+    if drift.is_clean() {
+        println!("{}: dependencies match manifest", result.mission_name);
+        return Ok(());
+    }
+
+    for class in &drift.undeclared_classes {
+        println!("undeclared class: {}", class);
+    }
+    for class in &drift.unused_classes {
+        println!("declared but unused class: {}", class);
+    }
+    for prefix in &drift.unused_mods {
+        println!("declared but unused mod: {}", prefix);
+    }
+    Err(anyhow!("{} does not match its manifest", result.mission_name))
+}
+
+/// Report the classes the arsenals of each mission offer that the whitelist doesn't allow
+async fn run_arsenals(dir: &Path, whitelist: &Path, json: bool) -> Result<()> {
+    let whitelist = EquipmentWhitelist::load(whitelist)?;
+    let results = scan_missions(dir, &MissionScannerConfig::default()).await?;
+    let audits: Vec<_> = results.iter().map(|result| audit_arsenals(result, &whitelist)).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&audits)?);
+    } else {
+        for audit in &audits {
+            println!("{}: {} arsenals, {} violations", audit.mission_name, audit.arsenals, audit.violations.len());
+            for violation in &audit.violations {
+                println!("  {} in {} ({})", violation.class_name, violation.box_id, violation.source_file.display());
+            }
+        }
+    }
+
+    let violations: usize = audits.iter().map(|audit| audit.violations.len()).sum();
+    if violations > 0 {
+        return Err(anyhow!("{} arsenal classes are not on the whitelist", violations));
+    }
+    Ok(())
+}
+
+/// Print the medical items of each mission that no treatment uses, and the
+/// treatment items it never issues
+async fn run_medical(dir: &Path, config: &Path, json: bool) -> Result<()> {
+    let treatments = MedicalTreatments::load(config)?;
+    if treatments.treatments().is_empty() {
+        return Err(anyhow!("No medical treatments found in {}", config.display()));
+    }
+    let results = scan_missions(dir, &MissionScannerConfig::default()).await?;
+    let checks: Vec<_> = results.iter().map(|result| cross_check_medical(result, &treatments)).collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+        return Ok(());
+    }
+    for check in &checks {
+        println!("{}: {} unissued, {} unconfigured", check.mission_name, check.unissued.len(), check.unconfigured.len());
+        for item in &check.unissued {
+            println!("  never issued: {} (used by {})", item.class_name, item.treatments.join(", "));
+        }
+        for class_name in &check.unconfigured {
+            println!("  not configured: {}", class_name);
+        }
+    }
+    Ok(())
+}
+
+/// Print when each class was first introduced according to git history
+async fn run_history(mission_dir: &Path) -> Result<()> {
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(mission_dir, config.max_threads, &config).await?;
+
+    for introduction in class_history(&result)? {
+        println!("{} {} {} ({})", introduction.date, &introduction.commit[..introduction.commit.len().min(10)],
+            introduction.class_name, introduction.source_file.display());
+    }
+    Ok(())
+}
+
+/// Print the classes each side of a mission requires
+async fn run_sides(mission_dir: &Path) -> Result<()> {
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(mission_dir, config.max_threads, &config).await?;
+
+    for (side, classes) in result.dependencies_by_side() {
+        println!("{} ({} classes):", side.as_deref().unwrap_or("Unattributed"), classes.len());
+        for class in classes {
+            println!("  {}", class);
+        }
+    }
+    Ok(())
+}
+
+/// Print the lints of a mission's loadout configs, failing when there are any
+async fn run_lint(mission_dir: &Path, class_db: Option<&Path>) -> Result<()> {
+    let database = class_db.map(ClassDatabase::load_from_file).transpose()?;
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(mission_dir, config.max_threads, &config).await?;
+
+    let files = lint_mission_loadouts(&result, database.as_ref());
+    for file in &files {
+        for lint in &file.lints {
+            println!("{}: {}", file.file.display(), lint);
+        }
+    }
+
+    let lints: usize = files.iter().map(|file| file.lints.len()).sum();
+    if lints > 0 {
+        return Err(anyhow!("{} loadout lints", lints));
+    }
+    Ok(())
+}
+
+/// Report settings where a mission's description.ext conflicts with a server's configs
+fn run_compat(mission_dir: &Path, server_files: &[PathBuf]) -> Result<()> {
+    let mut conflicts = Vec::new();
+    for server_file in server_files {
+        let server = ConfigSettings::load(server_file)?;
+        conflicts.extend(check_mission_compatibility(&server, mission_dir)?);
+    }
+
+    if conflicts.is_empty() {
+        println!("{}: compatible with server settings", mission_dir.display());
+        return Ok(());
+    }
+    for conflict in &conflicts {
+        println!("{}", conflict);
+    }
+    Err(anyhow!("{} conflicts with server settings", mission_dir.display()))
+}
+
+/// Report classes a mission references that aren't in a class database
+async fn run_validate(args: ValidateArgs) -> Result<()> {
+    let mut config = MissionScannerConfig::default();
+    args.filter.apply(&mut config);
+
+    let class_db = match args.class_db {
+        Some(path) => path,
+        None => {
+            let cache_dir = resolve_cache_dir(args.cache_dir)?;
+            mission_scanner::classdb::latest_snapshot(&cache_dir).ok_or_else(|| anyhow!(
+                "No class database given and none downloaded into {}, run `classdb fetch` or pass --classdb",
+                cache_dir.display()
+            ))?
+        }
+    };
+    let database = ClassDatabase::load_from_file(&class_db)?;
+    let result = scan_mission(&args.mission_dir, config.max_threads, &config).await?;
+
+    let missing = database.missing_classes(&result);
+    for class in &missing {
+        println!("{}", class.class_name);
+        if !class.suggestions.is_empty() {
+            let names: Vec<&str> = class.suggestions.iter().map(|s| s.class_name.as_str()).collect();
+            println!("  did you mean: {}", names.join(", "));
+        }
+        for reference in &class.references {
+            let location = reference.location.map(|l| format!(":{}", l)).unwrap_or_default();
+            println!("  {}{}", reference.source_file.display(), location);
+        }
+    }
+
+    let mismatches = check_item_categories(&result, &database);
+    for mismatch in &mismatches {
+        let location = mismatch.location.map(|l| format!(":{}", l)).unwrap_or_default();
+        println!("{} is a {}, not a {} ({})", mismatch.class_name, mismatch.found, mismatch.expected, mismatch.usage);
+        println!("  {}{}", mismatch.source_file.display(), location);
+    }
+
+    if !missing.is_empty() {
+        return Err(anyhow!("{} classes missing from {}", missing.len(), class_db.display()));
+    }
+    if !mismatches.is_empty() {
+        return Err(anyhow!("{} classes of the wrong category", mismatches.len()));
+    }
+    println!("{}: all {} referenced classes exist", result.mission_name, result.class_dependencies.len());
+    Ok(())
+}
+
+/// Download a class database snapshot into the cache directory
+#[cfg(feature = "classdb-fetch")]
+async fn run_classdb_fetch(url: &str, sha256: Option<&str>, cache_dir: Option<PathBuf>) -> Result<()> {
+    let cache_dir = resolve_cache_dir(cache_dir)?;
+    let path = mission_scanner::classdb::fetch_class_db(url, sha256, &cache_dir).await?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Cache directory given on the command line, or the per-user one
+fn resolve_cache_dir(cache_dir: Option<PathBuf>) -> Result<PathBuf> {
+    cache_dir
+        .or_else(mission_scanner::classdb::default_cache_dir)
+        .ok_or_else(|| anyhow!("No per-user cache directory, pass --cache-dir"))
+}
+
+/// Load scan results saved as JSON, either the output of `scan`, a single mission or a list of missions
+fn load_scan_results(path: &Path) -> Result<Vec<MissionResults>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read scan file {}: {}", path.display(), e))?;
+
+    if let Ok(output) = serde_json::from_str::<ScanOutput>(&content) {
+        return Ok(output.missions);
+    }
+
+    if let Ok(results) = serde_json::from_str::<Vec<MissionResults>>(&content) {
+        return Ok(results);
+    }
+
+    let result = serde_json::from_str::<MissionResults>(&content)
+        .map_err(|e| anyhow!("Failed to parse scan file {}: {}", path.display(), e))?;
+    Ok(vec![result])
+}
@@ -0,0 +1,103 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::types::MissionResults;
+
+/// File name of the manifest inside a mission directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.toml";
+
+/// Dependencies a mission maker declares their mission to use
+///
+/// ```toml
+/// mods = ["rhs_", "ace_"]
+/// classes = ["ItemMap", "ItemCompass"]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MissionManifest {
+    /// Class name prefixes of mods the mission may use (e.g. `rhs_`)
+    #[serde(default)]
+    pub mods: Vec<String>,
+    /// Individual classes the mission may use
+    #[serde(default)]
+    pub classes: Vec<String>,
+}
+
+impl MissionManifest {
+    /// Parse a manifest from TOML
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow!("Invalid mission manifest: {}", e))
+    }
+
+    /// Load the manifest of a mission directory, if it has one
+    pub fn load(mission_dir: &Path) -> Result<Option<Self>> {
+        let path = mission_dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml_str(&content).map(Some)
+    }
+
+    /// Check if a class is covered by a declared class or mod prefix
+    pub fn declares(&self, class_name: &str) -> bool {
+        let class_name = class_name.to_lowercase();
+        self.classes.iter().any(|c| c.to_lowercase() == class_name)
+            || self.mods.iter().any(|m| class_name.starts_with(&m.to_lowercase()))
+    }
+}
+
+/// Difference between a mission's manifest and what it actually uses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestDrift {
+    /// Classes used but not covered by the manifest
+    pub undeclared_classes: BTreeSet<String>,
+    /// Declared classes the mission never uses
+    pub unused_classes: BTreeSet<String>,
+    /// Declared mod prefixes no used class starts with
+    pub unused_mods: BTreeSet<String>,
+}
+
+impl ManifestDrift {
+    /// Check if usage matches the manifest exactly
+    pub fn is_clean(&self) -> bool {
+        self.undeclared_classes.is_empty() && self.unused_classes.is_empty() && self.unused_mods.is_empty()
+    }
+}
+
+/// Compare scan results against a manifest in both directions.
+///
+/// Class names are compared case-insensitively.
+pub fn check_manifest(manifest: &MissionManifest, results: &MissionResults) -> ManifestDrift {
+    let used: HashSet<String> = results.class_dependencies.iter()
+        .filter(|dep| !dep.class_name.is_empty())
+        .map(|dep| dep.class_name.to_lowercase())
+        .collect();
+
+    let mut drift = ManifestDrift::default();
+
+    for dep in &results.class_dependencies {
+        if !dep.class_name.is_empty() && !manifest.declares(&dep.class_name) {
+            drift.undeclared_classes.insert(dep.class_name.to_string());
+        }
+    }
+
+    for class in &manifest.classes {
+        if !used.contains(&class.to_lowercase()) {
+            drift.unused_classes.insert(class.clone());
+        }
+    }
+
+    for prefix in &manifest.mods {
+        let prefix_lower = prefix.to_lowercase();
+        if !used.iter().any(|class| class.starts_with(&prefix_lower)) {
+            drift.unused_mods.insert(prefix.clone());
+        }
+    }
+
+    drift
+}
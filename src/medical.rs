@@ -0,0 +1,175 @@
+//! Cross-check of the medical items missions issue against ACE's treatments
+//!
+//! ACE defines its treatments in `ACE_Medical_Treatment_Actions`, each naming
+//! the items it uses (see [`MedicalItemProperties`]). A treatment none of whose
+//! items a mission hands out can't be performed in it, and a medical item no
+//! treatment uses is dead weight, usually left over from a medical mod the
+//! server no longer runs.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::Result;
+use parser_hpp::{HppClass, HppValue, MedicalItemProperties, flatten_class};
+use serde::{Serialize, Deserialize};
+
+use crate::scanner::{parse_config, ConfigContext};
+use crate::search::ClassPattern;
+use crate::types::{MissionResults, ReferenceType};
+use crate::vfs::OsFs;
+
+/// Config class holding the ACE medical treatments
+pub const TREATMENT_ACTIONS_CLASS: &str = "ACE_Medical_Treatment_Actions";
+
+/// Classes taken as medical items when a mission issues them, whether or not
+/// a treatment uses them
+pub const DEFAULT_MEDICAL_ITEMS: &[&str] = &[
+    "ACE_fieldDressing",
+    "ACE_*Bandage",
+    "ACE_quikclot",
+    "ACE_tourniquet",
+    "ACE_splint",
+    "ACE_morphine",
+    "ACE_epinephrine",
+    "ACE_adenosine",
+    "ACE_atropine",
+    "ACE_*IV*",
+    "ACE_surgicalKit",
+    "ACE_personalAidKit",
+    "ACE_bodyBag",
+    "kat_*",
+];
+
+/// Treatments of an ACE medical config
+#[derive(Debug, Clone, Default)]
+pub struct MedicalTreatments {
+    treatments: Vec<MedicalItemProperties>,
+}
+
+impl MedicalTreatments {
+    /// Read the treatments of a config's class tree, as from
+    /// [`parser_hpp::HppParser::parse_class_tree`].
+    ///
+    /// `ACE_Medical_Treatment_Actions` is looked up at any depth, and its
+    /// treatments are flattened so they get the items of their base treatment.
+    pub fn from_class_tree(classes: &[HppClass]) -> Self {
+        let mut treatments = Vec::new();
+        for actions in find_classes(classes, TREATMENT_ACTIONS_CLASS) {
+            let nested: Vec<HppClass> = nested(actions).cloned().collect();
+            treatments.extend(nested.iter()
+                .filter_map(|class| flatten_class(&class.name, &nested))
+                .map(|class| MedicalItemProperties::from_hpp_class(&class)));
+        }
+        Self { treatments }
+    }
+
+    /// Load the treatments of a config file, such as ACE's medical config
+    pub fn load(path: &Path) -> Result<Self> {
+        let parser = parse_config(&OsFs::default(), path, &ConfigContext::default())?;
+        Ok(Self::from_class_tree(&parser.parse_class_tree()))
+    }
+
+    /// The treatments read
+    pub fn treatments(&self) -> &[MedicalItemProperties] {
+        &self.treatments
+    }
+
+    /// Items used by at least one treatment, keyed by lowercase name with the
+    /// names of the treatments using them
+    fn items(&self) -> BTreeMap<String, (&str, Vec<&str>)> {
+        let mut items: BTreeMap<String, (&str, Vec<&str>)> = BTreeMap::new();
+        for treatment in &self.treatments {
+            for item in &treatment.items {
+                items.entry(item.to_lowercase())
+                    .or_insert_with(|| (item.as_str(), Vec::new()))
+                    .1.push(treatment.name.as_str());
+            }
+        }
+        items
+    }
+}
+
+/// Medical item a treatment uses that the mission never issues
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnissuedItem {
+    /// Class of the item
+    pub class_name: String,
+    /// Treatments using the item
+    pub treatments: Vec<String>,
+}
+
+/// Result of cross-checking a mission's medical items against the treatments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MedicalCrossCheck {
+    /// Name of the mission
+    pub mission_name: String,
+    /// Items treatments use that the mission never issues, sorted ignoring case
+    pub unissued: Vec<UnissuedItem>,
+    /// Medical items the mission issues that no treatment uses, as first
+    /// written and sorted ignoring case
+    pub unconfigured: Vec<String>,
+}
+
+impl MedicalCrossCheck {
+    /// Check if the mission issues exactly the items the treatments use
+    pub fn is_consistent(&self) -> bool {
+        self.unissued.is_empty() && self.unconfigured.is_empty()
+    }
+}
+
+/// Cross-check the medical items a scanned mission issues against treatments.
+///
+/// Every reference of the mission's loadouts, scripts and mission.sqm counts
+/// as issued, except removals and inheritance. An issued class is a medical
+/// item when a treatment uses it or it matches [`DEFAULT_MEDICAL_ITEMS`].
+pub fn cross_check_medical(mission: &MissionResults, treatments: &MedicalTreatments) -> MedicalCrossCheck {
+    let mut issued: BTreeMap<String, &str> = BTreeMap::new();
+    for reference in mission.class_dependencies.iter().filter(|reference| {
+        !reference.class_name.is_empty()
+            && !matches!(reference.reference_type, ReferenceType::Removal | ReferenceType::Inheritance)
+    }) {
+        issued.entry(reference.class_name.to_lowercase()).or_insert(&reference.class_name);
+    }
+
+    let items = treatments.items();
+    let unissued = items.iter()
+        .filter(|(key, _)| !issued.contains_key(*key))
+        .map(|(_, (class_name, treatments))| UnissuedItem {
+            class_name: class_name.to_string(),
+            treatments: treatments.iter().map(|name| name.to_string()).collect::<BTreeSet<_>>().into_iter().collect(),
+        })
+        .collect();
+
+    let patterns: Vec<ClassPattern> = DEFAULT_MEDICAL_ITEMS.iter().map(|pattern| ClassPattern::glob(pattern)).collect();
+    let unconfigured = issued.iter()
+        .filter(|(key, class_name)| !items.contains_key(*key) && patterns.iter().any(|pattern| pattern.matches(class_name)))
+        .map(|(_, class_name)| class_name.to_string())
+        .collect();
+
+    MedicalCrossCheck {
+        mission_name: mission.mission_name.clone(),
+        unissued,
+        unconfigured,
+    }
+}
+
+/// Classes of a tree with a name, ignoring case, at any depth
+fn find_classes<'a>(classes: impl IntoIterator<Item = &'a HppClass>, name: &str) -> Vec<&'a HppClass> {
+    let mut found = Vec::new();
+    for class in classes {
+        if class.name.eq_ignore_ascii_case(name) {
+            found.push(class);
+        } else {
+            found.extend(find_classes(nested(class), name));
+        }
+    }
+    found
+}
+
+/// Classes nested directly in a class
+fn nested(class: &HppClass) -> impl Iterator<Item = &HppClass> {
+    class.properties.iter().filter_map(|property| match &property.value {
+        HppValue::Class(nested) => Some(nested),
+        _ => None,
+    })
+}
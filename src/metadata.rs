@@ -0,0 +1,123 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::pbo::find_signatures;
+use crate::vfs::Vfs;
+
+/// File name of the metadata sidecar inside a mission directory
+pub const METADATA_FILE_NAME: &str = "mission.meta.toml";
+
+/// Information about a mission that can't be read from its files
+///
+/// ```toml
+/// tags = ["coop", "night"]
+/// author = "Tyen"
+/// contact = "tyen#1234"
+///
+/// [players]
+/// min = 20
+/// max = 40
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionMetadata {
+    /// Free-form tags such as `coop`, `tvt` or `zeus`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Supported player counts
+    #[serde(default)]
+    pub players: Option<PlayerRange>,
+    /// Mission author
+    #[serde(default)]
+    pub author: Option<String>,
+    /// How to reach the author
+    #[serde(default)]
+    pub contact: Option<String>,
+    /// Signatures found next to a packed mission, unset for mission directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures: Option<PboSignatures>,
+}
+
+/// `.bisign` files signing a PBO, named `<mission>.pbo.<key>.bisign`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PboSignatures {
+    /// Names of the keys the PBO is signed with, sorted
+    #[serde(default)]
+    pub keys: Vec<String>,
+}
+
+impl PboSignatures {
+    /// Check if the PBO has at least one signature
+    pub fn is_signed(&self) -> bool {
+        !self.keys.is_empty()
+    }
+}
+
+/// Range of supported player counts, both ends inclusive
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlayerRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl MissionMetadata {
+    /// Parse metadata from TOML
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow!("Invalid mission metadata: {}", e))
+    }
+
+    /// Load the metadata sidecar of a mission directory, if it has one
+    pub fn load(vfs: &dyn Vfs, mission_dir: &Path) -> Result<Option<Self>> {
+        let path = mission_dir.join(METADATA_FILE_NAME);
+        if !vfs.is_file(&path) {
+            return Ok(None);
+        }
+        let content = vfs.read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml_str(&content).map(Some)
+    }
+
+    /// Load the metadata of a packed mission: the sidecar of the unpacked
+    /// mission, with the signatures found next to the PBO
+    pub fn for_pbo(vfs: &dyn Vfs, pbo_path: &Path, metadata: Option<Self>) -> Self {
+        Self {
+            signatures: Some(find_signatures(vfs, pbo_path)),
+            ..metadata.unwrap_or_default()
+        }
+    }
+
+    /// Check if the mission has a tag, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+impl PlayerRange {
+    /// Check if two ranges share at least one player count
+    pub fn overlaps(&self, other: &PlayerRange) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+}
+
+impl FromStr for PlayerRange {
+    type Err = anyhow::Error;
+
+    /// Parse `20-40`, or a single count such as `24`
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |count: &str| count.trim().parse::<u32>()
+            .map_err(|e| anyhow!("Invalid player count '{}': {}", count.trim(), e));
+        let (min, max) = match s.split_once('-') {
+            Some((min, max)) => (parse(min)?, parse(max)?),
+            None => (parse(s)?, parse(s)?),
+        };
+        if min > max {
+            return Err(anyhow!("Invalid player range '{}': minimum is above maximum", s));
+        }
+        Ok(Self { min, max })
+    }
+}
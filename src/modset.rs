@@ -0,0 +1,142 @@
+//! Mods a mission needs, suggested from class name prefixes
+//!
+//! Mod makers prefix their class names (`rhsusf_`, `CUP_`, `ace_`), so the
+//! prefixes of a mission's dependencies suggest which mods the server modset
+//! has to load. The prefix table can be extended or replaced from TOML:
+//!
+//! ```toml
+//! "rhsusf_" = "RHS: United States Forces"
+//! "tfar_" = "Task Force Arrowhead Radio"
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::types::{ClassReference, ReferenceType};
+
+/// Prefixes of the mods known without any configuration
+pub const DEFAULT_MOD_PREFIXES: &[(&str, &str)] = &[
+    ("rhs_", "RHS: Armed Forces of the Russian Federation"),
+    ("rhsusf_", "RHS: United States Forces"),
+    ("rhsgref_", "RHS: GREF"),
+    ("rhssaf_", "RHS: SAF"),
+    ("CUP_", "CUP"),
+    ("ace_", "ACE3"),
+    ("acre_", "ACRE2"),
+    ("TC_", "TC"),
+    ("usp_", "USP Gear & Uniforms"),
+];
+
+/// Table of class name prefixes to the mods defining them.
+///
+/// Prefixes are matched ignoring case and the longest matching prefix wins.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ModPrefixes {
+    prefixes: BTreeMap<String, String>,
+}
+
+impl Default for ModPrefixes {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for (prefix, mod_name) in DEFAULT_MOD_PREFIXES {
+            table.insert(prefix, mod_name);
+        }
+        table
+    }
+}
+
+impl ModPrefixes {
+    /// Table without any prefixes
+    pub fn empty() -> Self {
+        Self { prefixes: BTreeMap::new() }
+    }
+
+    /// Parse a table of prefixes to mod names from TOML
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let prefixes: BTreeMap<String, String> = toml::from_str(content)
+            .map_err(|e| anyhow!("Invalid mod prefix table: {}", e))?;
+        let mut table = Self::empty();
+        for (prefix, mod_name) in &prefixes {
+            table.insert(prefix, mod_name);
+        }
+        Ok(table)
+    }
+
+    /// Load a table of prefixes from a TOML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Map a prefix to a mod, replacing the mod it mapped to before
+    pub fn insert(&mut self, prefix: &str, mod_name: &str) {
+        self.prefixes.insert(prefix.to_lowercase(), mod_name.to_string());
+    }
+
+    /// Add every prefix of another table, replacing the mods of shared prefixes
+    pub fn extend(&mut self, other: &ModPrefixes) {
+        self.prefixes.extend(other.prefixes.iter().map(|(prefix, mod_name)| (prefix.clone(), mod_name.clone())));
+    }
+
+    /// Get the mod defining a class, by its longest known prefix
+    pub fn mod_for(&self, class_name: &str) -> Option<&str> {
+        let class_name = class_name.to_lowercase();
+        self.prefixes.iter()
+            .filter(|(prefix, _)| class_name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, mod_name)| mod_name.as_str())
+    }
+
+    /// Suggest the mods needed by a mission's references.
+    ///
+    /// Removed classes are skipped, since taking an item away doesn't need its mod.
+    pub fn required_mods(&self, references: &[ClassReference]) -> RequiredMods {
+        let mut mods = BTreeSet::new();
+        let mut unmapped: BTreeMap<String, &str> = BTreeMap::new();
+        for reference in references.iter()
+            .filter(|reference| !reference.class_name.is_empty() && reference.reference_type != ReferenceType::Removal)
+        {
+            match self.mod_for(&reference.class_name) {
+                Some(mod_name) => {
+                    mods.insert(mod_name.to_string());
+                }
+                None => {
+                    unmapped.entry(reference.class_name.to_lowercase()).or_insert(&reference.class_name);
+                }
+            }
+        }
+        RequiredMods {
+            mods: mods.into_iter().collect(),
+            unmapped_classes: unmapped.into_values().map(str::to_string).collect(),
+        }
+    }
+}
+
+/// Mods a mission needs according to the prefixes of its dependencies
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RequiredMods {
+    /// Mods defining at least one dependency, sorted
+    pub mods: Vec<String>,
+    /// Dependencies whose prefix maps to no known mod, as first written and
+    /// sorted ignoring case. Vanilla classes are listed here too
+    pub unmapped_classes: Vec<String>,
+}
+
+impl RequiredMods {
+    /// Add the mods of addons (CfgPatches classes) a mission loads by name,
+    /// such as the ones it gives Zeus. Addons mapping to no mod are ignored,
+    /// since they aren't classes the mission references
+    pub fn add_addons(&mut self, prefixes: &ModPrefixes, addons: &[String]) {
+        let mods: BTreeSet<String> = self.mods.drain(..)
+            .chain(addons.iter().filter_map(|addon| prefixes.mod_for(addon)).map(str::to_string))
+            .collect();
+        self.mods = mods.into_iter().collect();
+    }
+}
@@ -0,0 +1,221 @@
+//! Reading packed missions (`.pbo` files)
+//!
+//! A PBO is a header of file entries, terminated by an empty entry, followed
+//! by the file contents in the same order. The first entry may instead hold
+//! `key=value` properties such as the `prefix`. Entries packed with method
+//! `Cprs` are LZSS compressed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+
+use crate::metadata::PboSignatures;
+use crate::vfs::Vfs;
+
+/// Packing method of a header entry followed by properties
+const PACKING_VERSION: u32 = 0x5665_7273;
+/// Packing method of an LZSS compressed entry
+const PACKING_COMPRESSED: u32 = 0x4370_7273;
+
+/// A file stored in a PBO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PboEntry {
+    /// Path inside the PBO, with `/` separators
+    pub name: String,
+    /// Decompressed content
+    pub data: Vec<u8>,
+}
+
+/// Contents of a PBO
+#[derive(Debug, Clone, Default)]
+pub struct Pbo {
+    /// Header properties, in file order
+    pub properties: Vec<(String, String)>,
+    /// Files, in file order
+    pub entries: Vec<PboEntry>,
+}
+
+impl Pbo {
+    /// Read a PBO file
+    pub fn open(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_bytes(&bytes).map_err(|e| anyhow!("Invalid PBO {}: {}", path.display(), e))
+    }
+
+    /// Parse a PBO held in memory
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, position: 0 };
+        let mut pbo = Pbo::default();
+        let mut headers = Vec::new();
+
+        loop {
+            let name = reader.string()?;
+            let packing = reader.u32()?;
+            let original_size = reader.u32()?;
+            let _reserved = reader.u32()?;
+            let _timestamp = reader.u32()?;
+            let data_size = reader.u32()?;
+
+            if name.is_empty() {
+                if packing == PACKING_VERSION {
+                    loop {
+                        let key = reader.string()?;
+                        if key.is_empty() {
+                            break;
+                        }
+                        pbo.properties.push((key, reader.string()?));
+                    }
+                    continue;
+                }
+                break;
+            }
+            headers.push((name, packing, original_size as usize, data_size as usize));
+        }
+
+        for (name, packing, original_size, data_size) in headers {
+            let data = reader.take(data_size)?;
+            let data = if packing == PACKING_COMPRESSED && original_size != data_size {
+                decompress_lzss(data, original_size)
+                    .map_err(|e| anyhow!("Failed to decompress {}: {}", name, e))?
+            } else {
+                data.to_vec()
+            };
+            pbo.entries.push(PboEntry { name: name.replace('\\', "/"), data });
+        }
+
+        Ok(pbo)
+    }
+
+    /// Value of a header property, ignoring case
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Write every file below a directory, returning the written paths
+    pub fn extract(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for entry in &self.entries {
+            // Entry names come from the file, so keep them inside the target directory
+            if entry.name.split('/').any(|part| part == ".." || part.contains(':')) || entry.name.starts_with('/') {
+                return Err(anyhow!("Refusing to extract {} outside of {}", entry.name, dir.display()));
+            }
+            let path = dir.join(&entry.name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::write(&path, &entry.data)
+                .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Check if a path looks like a PBO by its extension
+pub fn is_pbo(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pbo"))
+}
+
+/// Find the signatures of a PBO, the `<pbo>.<key>.bisign` files next to it.
+///
+/// File names are matched ignoring case, like the server does on Windows.
+pub fn find_signatures(vfs: &dyn Vfs, pbo_path: &Path) -> PboSignatures {
+    let Some(pbo_name) = pbo_path.file_name().and_then(|name| name.to_str()) else {
+        return PboSignatures::default();
+    };
+    let prefix = format!("{}.", pbo_name.to_ascii_lowercase());
+    let dir = pbo_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let mut keys: Vec<String> = vfs.list_dir(dir).into_iter()
+        .filter(|path| vfs.is_file(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            let lower = name.to_ascii_lowercase();
+            let key_len = lower.strip_prefix(&prefix)?.strip_suffix(".bisign")?.len();
+            Some(name[prefix.len()..prefix.len() + key_len].to_string())
+        })
+        .filter(|key| !key.is_empty())
+        .collect();
+    keys.sort_by_key(|key| key.to_lowercase());
+    PboSignatures { keys }
+}
+
+/// Decompress the LZSS variant used in PBOs, checking the trailing checksum
+pub fn decompress_lzss(input: &[u8], expected_size: usize) -> Result<Vec<u8>> {
+    // The size comes from the PBO header, so don't trust it further than the input can expand
+    let mut output = Vec::with_capacity(expected_size.min(input.len().saturating_mul(8).saturating_add(3)));
+    let mut reader = Reader { bytes: input, position: 0 };
+
+    while output.len() < expected_size {
+        let flags = reader.take(1)?[0];
+        for bit in 0..8 {
+            if output.len() >= expected_size {
+                break;
+            }
+            if flags & (1 << bit) != 0 {
+                output.push(reader.take(1)?[0]);
+                continue;
+            }
+
+            let pointer = reader.take(2)?;
+            let offset = pointer[0] as usize | ((pointer[1] as usize & 0xF0) << 4);
+            let length = (pointer[1] as usize & 0x0F) + 3;
+            if offset == 0 {
+                return Err(anyhow!("Back-reference with offset 0 at byte {}", reader.position - 2));
+            }
+            // Offsets reaching before the start of the output read as spaces
+            let start = output.len() as isize - offset as isize;
+            for i in 0..length {
+                if output.len() >= expected_size {
+                    break;
+                }
+                let position = start + i as isize;
+                let byte = if position < 0 { b' ' } else { output[position as usize] };
+                output.push(byte);
+            }
+        }
+    }
+
+    let checksum = reader.u32()?;
+    let actual = output.iter().fold(0u32, |sum, &byte| sum.wrapping_add(byte as u32));
+    if checksum != actual {
+        return Err(anyhow!("Checksum mismatch: expected {:#x}, got {:#x}", checksum, actual));
+    }
+    Ok(output)
+}
+
+/// Cursor over PBO bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("Unexpected end of data at byte {}", self.position))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Read a zero-terminated string
+    fn string(&mut self) -> Result<String> {
+        let rest = &self.bytes[self.position..];
+        let len = rest.iter().position(|&b| b == 0)
+            .ok_or_else(|| anyhow!("Unterminated string at byte {}", self.position))?;
+        let value = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.position += len + 1;
+        Ok(value)
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::types::MissionResults;
+
+/// Version of the portable scan format
+pub const PORTABLE_FORMAT_VERSION: u32 = 1;
+
+/// Scan results with every path stored relative to the scanned root
+///
+/// Lets a scan made on one machine be loaded and queried on another where the
+/// missions live under a different directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableScan {
+    /// Format version, checked on import
+    pub version: u32,
+    /// Mission results with root-relative paths
+    pub missions: Vec<MissionResults>,
+}
+
+/// Write scan results to a portable file with paths made relative to `root`.
+///
+/// Paths outside of `root` are kept as they are.
+pub fn export_portable(results: &[MissionResults], root: &Path, path: &Path) -> Result<()> {
+    let missions = results.iter()
+        .map(|mission| map_paths(mission.clone(), |p| p.strip_prefix(root).map(Path::to_path_buf).unwrap_or(p)))
+        .collect();
+
+    let scan = PortableScan { version: PORTABLE_FORMAT_VERSION, missions };
+    let content = serde_json::to_string_pretty(&scan)?;
+    fs::write(path, content)
+        .map_err(|e| anyhow!("Failed to write portable scan {}: {}", path.display(), e))
+}
+
+/// Load a portable scan, resolving its relative paths against `root`
+pub fn import_portable(path: &Path, root: &Path) -> Result<Vec<MissionResults>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read portable scan {}: {}", path.display(), e))?;
+    let scan: PortableScan = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse portable scan {}: {}", path.display(), e))?;
+
+    if scan.version != PORTABLE_FORMAT_VERSION {
+        return Err(anyhow!("Unsupported portable scan version {} (expected {})",
+            scan.version, PORTABLE_FORMAT_VERSION));
+    }
+
+    Ok(scan.missions.into_iter()
+        .map(|mission| map_paths(mission, |p| if p.is_relative() { root.join(p) } else { p }))
+        .collect())
+}
+
+/// Apply a path transformation to every path held by a mission result
+pub(crate) fn map_paths(mut mission: MissionResults, f: impl Fn(PathBuf) -> PathBuf) -> MissionResults {
+    mission.mission_dir = f(mission.mission_dir);
+    mission.sqm_file = mission.sqm_file.map(&f);
+    mission.sqf_files = mission.sqf_files.into_iter().map(&f).collect();
+    mission.cpp_files = mission.cpp_files.into_iter().map(&f).collect();
+    for dep in &mut mission.class_dependencies {
+        dep.source_file = f(std::mem::take(&mut dep.source_file));
+    }
+    for asset in &mut mission.asset_references {
+        asset.source_file = f(std::mem::take(&mut asset.source_file));
+    }
+    for quarantined in &mut mission.quarantined_files {
+        quarantined.path = f(std::mem::take(&mut quarantined.path));
+    }
+    for call in &mut mission.script_calls {
+        call.caller = f(std::mem::take(&mut call.caller));
+        call.script = f(std::mem::take(&mut call.script));
+    }
+    mission
+}
@@ -0,0 +1,75 @@
+//! Progress events of a scan
+//!
+//! Set [`MissionScannerConfig::progress`](crate::MissionScannerConfig::progress)
+//! to receive an event as each mission starts, each of its files is parsed
+//! and it finishes. Any `Fn(&ProgressEvent)` closure is a sink, so a GUI or
+//! web service can forward events to a progress bar or a channel without the
+//! scanner depending on either. Files are parsed in parallel, so sinks are
+//! called from several threads.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Something that happened during a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// A mission is about to be parsed
+    MissionStarted {
+        mission: String,
+        /// Number of files that will be parsed
+        files: usize,
+    },
+    /// A file of a mission was parsed, successfully or not
+    FileParsed {
+        mission: String,
+        file: PathBuf,
+    },
+    /// A mission was scanned
+    MissionFinished {
+        mission: String,
+        /// Number of dependencies found
+        dependencies: usize,
+    },
+}
+
+/// Receiver of progress events
+pub trait ProgressSink: Send + Sync {
+    /// Handle an event
+    fn event(&self, event: &ProgressEvent);
+}
+
+impl<F: Fn(&ProgressEvent) + Send + Sync> ProgressSink for F {
+    fn event(&self, event: &ProgressEvent) {
+        self(event)
+    }
+}
+
+/// Optional progress sink, ignoring events when unset
+#[derive(Clone, Default)]
+pub struct Progress(Option<Arc<dyn ProgressSink>>);
+
+impl Progress {
+    /// Send events to a sink
+    pub fn new(sink: impl ProgressSink + 'static) -> Self {
+        Self(Some(Arc::new(sink)))
+    }
+
+    /// Check if events go anywhere
+    pub fn is_set(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Send an event to the sink, if there is one
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(sink) = &self.0 {
+            sink.event(&event);
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Progress").field(&self.is_set()).finish()
+    }
+}
@@ -0,0 +1,117 @@
+//! Python module of the scanner, built with the `python` feature
+//!
+//! Build the extension with `maturin build --features python`. Results are
+//! the same JSON the CLI writes, handed to Python as plain dicts and lists:
+//!
+//! ```python
+//! import mission_scanner
+//!
+//! for mission in mission_scanner.scan_missions("missions", ignore_classes=["Land_*"]):
+//!     print(mission["mission_name"], len(mission["class_dependencies"]))
+//!
+//! missing = mission_scanner.validate("missions/co40_raid.Altis", "classes.json")
+//! ```
+//!
+//! Scans release the GIL, so other Python threads keep running meanwhile.
+
+use std::path::Path;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::{scan_mission, scan_missions, ClassDatabase, MissionScannerConfig};
+
+/// Configuration from the keyword arguments shared by the scanning functions
+fn config(ignore_classes: Option<Vec<String>>, min_confidence: &str) -> PyResult<MissionScannerConfig> {
+    let mut config = MissionScannerConfig::default();
+    for class in ignore_classes.unwrap_or_default() {
+        if class.contains(['*', '?']) {
+            config.ignore_patterns.push(class);
+        } else {
+            config.ignore_classes.push(class);
+        }
+    }
+    config.min_confidence = min_confidence.parse()
+        .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+    Ok(config)
+}
+
+/// Run a scan on a runtime of its own, without holding the GIL
+fn block_on<T: Send>(py: Python<'_>, future: impl Future<Output = anyhow::Result<T>> + Send) -> PyResult<T> {
+    py.detach(|| {
+        tokio::runtime::Runtime::new()
+            .map_err(anyhow::Error::from)
+            .and_then(|runtime| runtime.block_on(future))
+    })
+    .map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))
+}
+
+/// Convert a result to Python objects through its JSON form
+fn to_python<'py>(py: Python<'py>, value: &impl Serialize) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_string(value).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+/// Scan every mission below a directory, returning a list of mission results
+#[pyfunction(name = "scan_missions")]
+#[pyo3(signature = (dir, ignore_classes = None, min_confidence = "low"))]
+fn py_scan_missions<'py>(
+    py: Python<'py>,
+    dir: &str,
+    ignore_classes: Option<Vec<String>>,
+    min_confidence: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let config = config(ignore_classes, min_confidence)?;
+    let results = block_on(py, scan_missions(Path::new(dir), &config))?;
+    to_python(py, &results)
+}
+
+/// Scan a single mission directory, returning its results
+#[pyfunction(name = "scan_mission")]
+#[pyo3(signature = (mission_dir, ignore_classes = None, min_confidence = "low"))]
+fn py_scan_mission<'py>(
+    py: Python<'py>,
+    mission_dir: &str,
+    ignore_classes: Option<Vec<String>>,
+    min_confidence: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let config = config(ignore_classes, min_confidence)?;
+    let result = block_on(py, scan_mission(Path::new(mission_dir), config.max_threads, &config))?;
+    to_python(py, &result)
+}
+
+/// Class dependencies of mission.sqm content, sorted
+#[pyfunction(name = "extract_class_dependencies")]
+fn py_extract_class_dependencies(sqm_content: &str) -> Vec<String> {
+    let mut dependencies: Vec<String> = parser_sqm::extract_class_dependencies(sqm_content).into_iter().collect();
+    dependencies.sort();
+    dependencies
+}
+
+/// Classes a mission references that aren't in a class database (JSON or CSV),
+/// with suggestions of similar known classes
+#[pyfunction(name = "validate")]
+#[pyo3(signature = (mission_dir, class_db, ignore_classes = None, min_confidence = "low"))]
+fn py_validate<'py>(
+    py: Python<'py>,
+    mission_dir: &str,
+    class_db: &str,
+    ignore_classes: Option<Vec<String>>,
+    min_confidence: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let config = config(ignore_classes, min_confidence)?;
+    let database = ClassDatabase::load_from_file(Path::new(class_db))
+        .map_err(|e| PyRuntimeError::new_err(format!("{:#}", e)))?;
+    let result = block_on(py, scan_mission(Path::new(mission_dir), config.max_threads, &config))?;
+    to_python(py, &database.missing_classes(&result))
+}
+
+#[pymodule]
+fn mission_scanner(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(py_scan_missions, module)?)?;
+    module.add_function(wrap_pyfunction!(py_scan_mission, module)?)?;
+    module.add_function(wrap_pyfunction!(py_extract_class_dependencies, module)?)?;
+    module.add_function(wrap_pyfunction!(py_validate, module)?)?;
+    Ok(())
+}
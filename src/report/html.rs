@@ -0,0 +1,382 @@
+//! Self-contained HTML report for sharing audits with mission makers
+//!
+//! The report is a single file with its styles and a small sorting and search
+//! script inline, so it can be attached to a message or opened from a share. It
+//! lists the missions with their counts, and drills down into each mission's
+//! dependencies grouped by class. Within a mission, classes are grouped into
+//! collapsible sections by severity, which are paged by the script so thousands
+//! of them stay readable, and the search box hides the rows not matching it. Classes missing from a class
+//! database are highlighted, and each class links to the files referencing it.
+//! Packed missions show the keys they are signed with.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+
+use crate::classdb::ClassDatabase;
+use crate::metadata::PboSignatures;
+use crate::modset::ModPrefixes;
+use crate::types::{ClassReference, Confidence, MissionResults};
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.6em; text-align: left; vertical-align: top; }
+th.sortable { cursor: pointer; background: #f0f0f0; }
+th.sortable::after { content: ' \\2195'; color: #999; }
+tr.missing td { background: #fde2e2; }
+.missing-list { color: #a40000; }
+summary { cursor: pointer; font-size: 1.2em; margin: 0.5em 0; }
+details.severity { margin-left: 1em; }
+details.severity summary { font-size: 1em; }
+#search { width: 30em; padding: 0.3em; }
+.pager { margin: -1em 0 1.5em; }
+td.files a { display: block; }
+";
+
+const SCRIPT: &str = "\
+function showPage(group) {
+  var pager = group.querySelector('.pager');
+  if (!pager) return;
+  var size = Number(pager.dataset.pageSize);
+  var rows = group.querySelectorAll('tbody tr');
+  var pages = Math.ceil(rows.length / size);
+  var page = Math.min(Math.max(Number(pager.dataset.page) || 0, 0), pages - 1);
+  pager.dataset.page = page;
+  Array.prototype.forEach.call(rows, function (row, index) {
+    row.hidden = Math.floor(index / size) !== page;
+  });
+  pager.querySelector('span').textContent = 'Page ' + (page + 1) + ' of ' + pages;
+  pager.querySelector('.previous').disabled = page === 0;
+  pager.querySelector('.next').disabled = page === pages - 1;
+}
+document.querySelectorAll('.pager').forEach(function (pager) {
+  var group = pager.closest('details');
+  pager.querySelector('.previous').addEventListener('click', function () {
+    pager.dataset.page = Number(pager.dataset.page) - 1;
+    showPage(group);
+  });
+  pager.querySelector('.next').addEventListener('click', function () {
+    pager.dataset.page = Number(pager.dataset.page) + 1;
+    showPage(group);
+  });
+  showPage(group);
+});
+document.querySelectorAll('th.sortable').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table');
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var ascending = th.dataset.order !== 'asc';
+    th.dataset.order = ascending ? 'asc' : 'desc';
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[index].dataset.sort || a.cells[index].textContent;
+      var y = b.cells[index].dataset.sort || b.cells[index].textContent;
+      var order = isNaN(x) || isNaN(y) ? x.localeCompare(y) : x - y;
+      return ascending ? order : -order;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+    if (document.getElementById('search').value.trim() === '') showPage(table.closest('details'));
+  });
+});
+document.getElementById('search').addEventListener('input', function (event) {
+  var query = event.target.value.trim().toLowerCase();
+  document.querySelectorAll('section.mission').forEach(function (section) {
+    var found = 0;
+    section.querySelectorAll('details.severity').forEach(function (group) {
+      var shown = 0;
+      Array.prototype.forEach.call(group.querySelectorAll('tbody tr'), function (row) {
+        row.hidden = query !== '' && row.textContent.toLowerCase().indexOf(query) === -1;
+        if (!row.hidden) shown++;
+      });
+      // Search results aren't paged
+      var pager = group.querySelector('.pager');
+      if (pager) {
+        pager.hidden = query !== '';
+        if (query === '') showPage(group);
+      }
+      group.hidden = shown === 0;
+      if (query !== '') group.open = shown > 0;
+      found += shown;
+    });
+    section.hidden = found === 0;
+    if (query !== '') section.querySelector('details').open = found > 0;
+  });
+});
+";
+
+/// Rows shown at once in a severity group
+const PAGE_SIZE: usize = 100;
+
+/// Write the HTML report of scan results to a file.
+///
+/// Classes are attributed to mods by `mod_prefixes`. With a class database,
+/// classes it doesn't know are highlighted as missing.
+pub fn write_html(
+    results: &[MissionResults],
+    mod_prefixes: &ModPrefixes,
+    database: Option<&ClassDatabase>,
+    path: &Path,
+) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, to_html(results, mod_prefixes, database))
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Render the document written by [`write_html`]
+pub fn to_html(results: &[MissionResults], mod_prefixes: &ModPrefixes, database: Option<&ClassDatabase>) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Mission dependency report</title>\n");
+    let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+    html.push_str("<h1>Mission dependency report</h1>\n");
+
+    let classes: Vec<Vec<ClassRow>> = results.iter()
+        .map(|result| class_rows(result, mod_prefixes, database))
+        .collect();
+
+    html.push_str("<h2>Missions</h2>\n<table>\n<thead><tr>");
+    for header in ["Mission", "Classes", "References", "Mods", "Missing", "Signed with"] {
+        let _ = write!(html, "<th class=\"sortable\">{}</th>", header);
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for (index, (result, rows)) in results.iter().zip(&classes).enumerate() {
+        let missing = rows.iter().filter(|row| row.missing).count();
+        let _ = writeln!(
+            html,
+            "<tr{}><td><a href=\"#mission-{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            if missing > 0 { " class=\"missing\"" } else { "" },
+            index,
+            escape(&result.mission_name),
+            rows.len(),
+            result.class_dependencies.len(),
+            escape(&result.required_mods.mods.join(", ")),
+            missing,
+            match signatures(result) {
+                Some(signatures) if signatures.is_signed() => escape(&signatures.keys.join(", ")),
+                Some(_) => "<span class=\"missing-list\">unsigned</span>".to_string(),
+                None => String::new(),
+            },
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    html.push_str("<h2>Findings</h2>\n");
+    html.push_str("<p><input type=\"search\" id=\"search\" placeholder=\"Search classes, mods and files\" aria-label=\"Search\"></p>\n");
+    for (index, (result, rows)) in results.iter().zip(&classes).enumerate() {
+        write_mission(&mut html, index, result, rows, database.is_some());
+    }
+
+    let _ = writeln!(html, "<script>\n{}</script>\n</body>\n</html>", SCRIPT);
+    html
+}
+
+/// How much a class needs looking at, most first
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    /// Not in the class database
+    Missing,
+    /// Only guessed, every reference has low confidence
+    Uncertain,
+    /// In the class database, or not checked against one
+    Ok,
+}
+
+impl Severity {
+    const ALL: [Severity; 3] = [Severity::Missing, Severity::Uncertain, Severity::Ok];
+
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Missing => "Missing",
+            Severity::Uncertain => "Low confidence",
+            Severity::Ok => "Found",
+        }
+    }
+}
+
+/// A class a mission depends on, with every reference to it
+struct ClassRow<'a> {
+    class_name: &'a str,
+    mod_name: Option<&'a str>,
+    references: Vec<&'a ClassReference>,
+    missing: bool,
+}
+
+impl ClassRow<'_> {
+    fn severity(&self) -> Severity {
+        if self.missing {
+            Severity::Missing
+        } else if self.references.iter().all(|reference| reference.confidence == Confidence::Low) {
+            Severity::Uncertain
+        } else {
+            Severity::Ok
+        }
+    }
+}
+
+/// Group a mission's references by class, ignoring case, sorted by class name
+fn class_rows<'a>(
+    result: &'a MissionResults,
+    mod_prefixes: &'a ModPrefixes,
+    database: Option<&ClassDatabase>,
+) -> Vec<ClassRow<'a>> {
+    let mut by_class: BTreeMap<String, Vec<&ClassReference>> = BTreeMap::new();
+    for reference in result.class_dependencies.iter().filter(|reference| !reference.class_name.is_empty()) {
+        by_class.entry(reference.class_name.to_lowercase()).or_default().push(reference);
+    }
+
+    by_class.into_values()
+        .map(|references| {
+            let class_name: &str = &references[0].class_name;
+            ClassRow {
+                class_name,
+                mod_name: mod_prefixes.mod_for(class_name),
+                missing: database.is_some_and(|database| !database.class_exists(class_name)),
+                references,
+            }
+        })
+        .collect()
+}
+
+/// Write the drill-down section of a mission
+fn write_mission(html: &mut String, index: usize, result: &MissionResults, rows: &[ClassRow], checked: bool) {
+    let missing: Vec<&ClassRow> = rows.iter().filter(|row| row.missing).collect();
+    let _ = writeln!(
+        html,
+        "<section class=\"mission\" id=\"mission-{}\">\n<details{}>\n<summary>{} &ndash; {} classes{}</summary>",
+        index,
+        if missing.is_empty() { "" } else { " open" },
+        escape(&result.mission_name),
+        rows.len(),
+        if missing.is_empty() { String::new() } else { format!(", {} missing", missing.len()) },
+    );
+    let _ = writeln!(html, "<p>Directory: <code>{}</code></p>", escape(&result.mission_dir.to_string_lossy()));
+
+    match signatures(result) {
+        Some(signatures) if signatures.is_signed() => {
+            let _ = writeln!(html, "<p>Signed with: {}</p>", escape(&signatures.keys.join(", ")));
+        }
+        Some(_) => html.push_str("<p class=\"missing-list\">No .bisign signature next to the PBO.</p>\n"),
+        None => {}
+    }
+
+    if !result.required_mods.mods.is_empty() {
+        html.push_str("<p>Required mods:</p>\n<ul>\n");
+        for mod_name in &result.required_mods.mods {
+            let _ = writeln!(html, "<li>{}</li>", escape(mod_name));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !missing.is_empty() {
+        html.push_str("<p>Missing from the class database:</p>\n<ul class=\"missing-list\">\n");
+        for row in &missing {
+            let _ = writeln!(html, "<li><code>{}</code></li>", escape(row.class_name));
+        }
+        html.push_str("</ul>\n");
+    } else if checked {
+        html.push_str("<p>Every class is in the class database.</p>\n");
+    }
+
+    for severity in Severity::ALL {
+        let group: Vec<&ClassRow> = rows.iter().filter(|row| row.severity() == severity).collect();
+        if !group.is_empty() {
+            write_group(html, result, severity, &group);
+        }
+    }
+    html.push_str("</details>\n</section>\n");
+}
+
+/// Write the collapsible table of a mission's classes of one severity
+fn write_group(html: &mut String, result: &MissionResults, severity: Severity, rows: &[&ClassRow]) {
+    let _ = writeln!(
+        html,
+        "<details class=\"severity\"{}>\n<summary>{} &ndash; {} classes</summary>",
+        if severity == Severity::Missing { " open" } else { "" },
+        severity.label(),
+        rows.len(),
+    );
+    html.push_str("<table>\n<thead><tr>");
+    for header in ["Class", "Mod", "References", "Reference types", "Files"] {
+        let _ = write!(html, "<th class=\"sortable\">{}</th>", header);
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        let reference_types: BTreeSet<String> = row.references.iter()
+            .map(|reference| format!("{:?}", reference.reference_type))
+            .collect();
+        let files: BTreeSet<String> = row.references.iter()
+            .map(|reference| file_link(result, reference))
+            .collect();
+        let _ = writeln!(
+            html,
+            "<tr{}><td><code>{}</code></td><td data-sort=\"{}\">{}</td><td>{}</td><td>{}</td><td class=\"files\">{}</td></tr>",
+            if row.missing { " class=\"missing\"" } else { "" },
+            escape(row.class_name),
+            // Unattributed classes sort last
+            escape(row.mod_name.unwrap_or("~")),
+            escape(row.mod_name.unwrap_or("")),
+            row.references.len(),
+            escape(&reference_types.into_iter().collect::<Vec<_>>().join(", ")),
+            files.into_iter().collect::<String>(),
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+    if rows.len() > PAGE_SIZE {
+        let _ = writeln!(
+            html,
+            "<div class=\"pager\" data-page-size=\"{}\"><button class=\"previous\">Previous</button> <span></span> <button class=\"next\">Next</button></div>",
+            PAGE_SIZE,
+        );
+    }
+    html.push_str("</details>\n");
+}
+
+/// Signatures of a packed mission, `None` for mission directories
+fn signatures(result: &MissionResults) -> Option<&PboSignatures> {
+    result.metadata.as_ref().and_then(|metadata| metadata.signatures.as_ref())
+}
+
+/// Link to the file of a reference, shown relative to the mission with its line
+fn file_link(result: &MissionResults, reference: &ClassReference) -> String {
+    let relative = reference.source_file.strip_prefix(&result.mission_dir).unwrap_or(&reference.source_file);
+    let line = reference.location.map(|location| format!(":{}", location.line)).unwrap_or_default();
+
+    let path = reference.source_file.to_string_lossy().replace('\\', "/");
+    let href = if path.starts_with('/') {
+        format!("file://{}", path)
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        format!("file:///{}", path)
+    } else {
+        path
+    };
+    format!(
+        "<a href=\"{}\">{}{}</a>",
+        escape(&href.replace(' ', "%20")),
+        escape(&relative.to_string_lossy().replace('\\', "/")),
+        line,
+    )
+}
+
+/// Escape text for HTML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
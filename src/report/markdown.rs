@@ -0,0 +1,129 @@
+//! Compact markdown summary for merge request comments
+//!
+//! Meant to be posted by CI bots of mission repositories, so it stays short:
+//! a table of the missions, the classes a change added, the classes missing
+//! from a class database and the mods the missions need. Long lists are cut
+//! to the first few entries.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::classdb::ClassDatabase;
+use crate::report::diff_missions;
+use crate::types::MissionResults;
+
+/// Render the markdown summary of scan results.
+///
+/// With a `baseline` scan, such as one of the target branch, the classes each
+/// mission added are listed, most referenced first, and newly required mods
+/// are marked. With a class database, the classes it doesn't know are listed.
+/// Lists show at most `top` entries.
+pub fn to_markdown(
+    results: &[MissionResults],
+    baseline: Option<&[MissionResults]>,
+    database: Option<&ClassDatabase>,
+    top: usize,
+) -> String {
+    let baseline: Option<BTreeMap<&str, &MissionResults>> = baseline.map(|baseline| {
+        baseline.iter().map(|result| (result.mission_name.as_str(), result)).collect()
+    });
+
+    let mut added = Vec::new();
+    let mut missing = Vec::new();
+    let mut rows = Vec::new();
+    for result in results {
+        let mission_added = match &baseline {
+            Some(baseline) => match baseline.get(result.mission_name.as_str()) {
+                Some(old) => diff_missions(old, result).added,
+                None => unique_classes(result),
+            },
+            None => Vec::new(),
+        };
+        let mission_missing: Vec<String> = database
+            .map(|database| database.missing_classes(result).into_iter().map(|class| class.class_name).collect())
+            .unwrap_or_default();
+
+        rows.push(format!(
+            "| {} | {} | {} | {} |",
+            table_cell(&result.mission_name),
+            unique_classes(result).len(),
+            if baseline.is_some() { mission_added.len().to_string() } else { "-".to_string() },
+            if database.is_some() { mission_missing.len().to_string() } else { "-".to_string() },
+        ));
+
+        for class_name in mission_added {
+            let references = result.class_dependencies.iter()
+                .filter(|reference| reference.class_name.eq_ignore_ascii_case(&class_name))
+                .count();
+            added.push((references, class_name, result.mission_name.as_str()));
+        }
+        missing.extend(mission_missing.into_iter().map(|class_name| (class_name, result.mission_name.as_str())));
+    }
+
+    let mut markdown = String::from("## Mission dependencies\n\n");
+    markdown.push_str("| Mission | Classes | Added | Missing |\n|---|---:|---:|---:|\n");
+    for row in &rows {
+        markdown.push_str(row);
+        markdown.push('\n');
+    }
+
+    if baseline.is_some() {
+        added.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.to_lowercase().cmp(&b.1.to_lowercase())));
+        let _ = writeln!(markdown, "\n### New dependencies ({})\n", added.len());
+        if added.is_empty() {
+            markdown.push_str("No new classes.\n");
+        }
+        for (references, class_name, mission) in added.iter().take(top) {
+            let _ = writeln!(markdown, "- `{}` in {} ({} references)", class_name, mission, references);
+        }
+        more(&mut markdown, added.len(), top);
+    }
+
+    if database.is_some() {
+        let _ = writeln!(markdown, "\n### Missing classes ({})\n", missing.len());
+        if missing.is_empty() {
+            markdown.push_str("Every class is in the class database.\n");
+        }
+        for (class_name, mission) in missing.iter().take(top) {
+            let _ = writeln!(markdown, "- :x: `{}` in {}", class_name, mission);
+        }
+        more(&mut markdown, missing.len(), top);
+    }
+
+    let old_mods: Option<BTreeSet<&str>> = baseline.as_ref().map(|baseline| {
+        baseline.values().flat_map(|result| result.required_mods.mods.iter().map(String::as_str)).collect()
+    });
+    let mods: BTreeSet<&str> = results.iter()
+        .flat_map(|result| result.required_mods.mods.iter().map(String::as_str))
+        .collect();
+    let _ = writeln!(markdown, "\n### Mods ({})\n", mods.len());
+    if mods.is_empty() {
+        markdown.push_str("No mods detected.\n");
+    }
+    for mod_name in &mods {
+        let is_new = old_mods.as_ref().is_some_and(|old_mods| !old_mods.contains(mod_name));
+        let _ = writeln!(markdown, "- {}{}", mod_name, if is_new { " **(new)**" } else { "" });
+    }
+    markdown
+}
+
+/// Class names of a mission's references, distinct ignoring case
+fn unique_classes(result: &MissionResults) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    result.class_dependencies.iter()
+        .filter(|reference| !reference.class_name.is_empty() && seen.insert(reference.class_name.to_lowercase()))
+        .map(|reference| reference.class_name.to_string())
+        .collect()
+}
+
+/// Note how many entries of a list were left out
+fn more(markdown: &mut String, len: usize, top: usize) {
+    if len > top {
+        let _ = writeln!(markdown, "- ...and {} more", len - top);
+    }
+}
+
+/// Escape the pipes of a table cell
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
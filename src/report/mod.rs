@@ -0,0 +1,522 @@
+mod html;
+mod markdown;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use serde::{Serialize, Deserialize};
+
+use crate::filter::mission_variant;
+use crate::scanner::scan_mission_with_vfs;
+use crate::types::{Confidence, CountRange, DependencyOwner, MissionResults, MissionScannerConfig, ReferenceType, SourceLocation};
+use crate::vfs::Vfs;
+
+pub use html::{to_html, write_html};
+pub use markdown::to_markdown;
+
+/// Version of the written report files, bumped when their shape changes
+pub const REPORT_SCHEMA_VERSION: u32 = 4;
+
+/// File name of the aggregate summary written next to the mission reports
+pub const SUMMARY_FILE_NAME: &str = "summary.json";
+
+/// Header row of the CSV written by [`write_csv`]
+pub const CSV_HEADER: &str = "mission,file,line,class,reference_type,count";
+
+/// Parser a file is read with, by extension
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SourceKind {
+    /// mission.sqm
+    Sqm,
+    /// SQF scripts
+    Sqf,
+    /// Config files (.cpp, .hpp, .ext)
+    Config,
+}
+
+impl SourceKind {
+    /// Get the kind of a file from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "sqm" => Some(SourceKind::Sqm),
+            "sqf" => Some(SourceKind::Sqf),
+            "cpp" | "hpp" | "ext" => Some(SourceKind::Config),
+            _ => None,
+        }
+    }
+}
+
+/// A class reference with the file and parser it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReportReference {
+    /// Name of the class as written
+    pub class_name: String,
+    /// Type of reference
+    pub reference_type: ReferenceType,
+    /// Kind of file the reference was found in
+    pub source_kind: SourceKind,
+    /// File the reference was found in
+    pub source_file: PathBuf,
+    /// Context where the class is referenced
+    pub context: String,
+    /// Side, group and unit the reference belongs to, for references from mission.sqm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<DependencyOwner>,
+    /// Where in the file the class is referenced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+    /// How sure the scanner is that the name is used as a class
+    #[serde(default)]
+    pub confidence: Confidence,
+    /// Instances added, the most when `count_range` is set
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Fewest and most instances added, when random loop bounds make it vary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count_range: Option<CountRange>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// A scanned file and how many references it contributed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileSummary {
+    /// Path of the file
+    pub path: PathBuf,
+    /// Kind of file
+    pub source_kind: SourceKind,
+    /// Number of references found in the file
+    pub references: usize,
+}
+
+/// Reference counts of a report
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReportCounts {
+    /// Number of references
+    pub total_references: usize,
+    /// Number of distinct class names, ignoring case
+    pub unique_classes: usize,
+    /// Number of references per kind of file
+    pub by_source: BTreeMap<SourceKind, usize>,
+    /// Number of references per reference type
+    pub by_reference_type: BTreeMap<ReferenceType, usize>,
+}
+
+/// Every class reference of a mission in one shape, whichever parser found it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionReport {
+    /// Name of the mission
+    pub mission_name: String,
+    /// Path to the mission directory
+    pub mission_dir: PathBuf,
+    /// Every scanned file, including ones without references
+    pub files: Vec<FileSummary>,
+    /// Every class reference
+    pub references: Vec<ReportReference>,
+    /// Reference counts
+    pub counts: ReportCounts,
+}
+
+impl MissionReport {
+    /// Build a report from scan results
+    pub fn from_results(results: &MissionResults) -> Self {
+        let references: Vec<ReportReference> = results.class_dependencies.iter()
+            .filter_map(|dep| Some(ReportReference {
+                class_name: dep.class_name.to_string(),
+                reference_type: dep.reference_type.clone(),
+                source_kind: SourceKind::from_path(&dep.source_file)?,
+                source_file: dep.source_file.clone(),
+                context: dep.context.clone(),
+                owner: dep.owner.clone(),
+                location: dep.location,
+                confidence: dep.confidence,
+                count: dep.count,
+                count_range: dep.count_range,
+            }))
+            .collect();
+
+        let mut per_file: BTreeMap<&Path, usize> = BTreeMap::new();
+        let mut counts = ReportCounts { total_references: references.len(), ..ReportCounts::default() };
+        for reference in &references {
+            *per_file.entry(&reference.source_file).or_insert(0) += 1;
+            *counts.by_source.entry(reference.source_kind).or_insert(0) += 1;
+            *counts.by_reference_type.entry(reference.reference_type.clone()).or_insert(0) += 1;
+        }
+        counts.unique_classes = references.iter()
+            .map(|reference| reference.class_name.to_lowercase())
+            .collect::<BTreeSet<_>>()
+            .len();
+
+        let files = results.sqm_file.iter()
+            .chain(&results.sqf_files)
+            .chain(&results.cpp_files)
+            .filter_map(|path| Some(FileSummary {
+                path: path.clone(),
+                source_kind: SourceKind::from_path(path)?,
+                references: per_file.get(path.as_path()).copied().unwrap_or(0),
+            }))
+            .collect();
+
+        Self {
+            mission_name: results.mission_name.clone(),
+            mission_dir: results.mission_dir.clone(),
+            files,
+            references,
+            counts,
+        }
+    }
+
+    /// References found by one kind of parser
+    pub fn references_from(&self, source_kind: SourceKind) -> impl Iterator<Item = &ReportReference> {
+        self.references.iter().filter(move |reference| reference.source_kind == source_kind)
+    }
+
+    /// References at least as certain as a minimum confidence
+    pub fn references_with(&self, min_confidence: Confidence) -> impl Iterator<Item = &ReportReference> {
+        self.references.iter().filter(move |reference| reference.confidence >= min_confidence)
+    }
+}
+
+/// A mission report as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReportFile {
+    /// Version of the file format, see [`REPORT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// The mission report, with paths relative to the mission directory
+    pub report: MissionReport,
+}
+
+/// A mission's entry in the aggregate summary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionSummary {
+    /// Name of the mission
+    pub mission_name: String,
+    /// Report file of the mission, relative to the summary
+    pub report_file: PathBuf,
+    /// Reference counts of the mission
+    pub counts: ReportCounts,
+}
+
+/// Aggregate summary of every mission in a scan
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScanSummary {
+    /// Version of the file format, see [`REPORT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// Missions, sorted by name
+    pub missions: Vec<MissionSummary>,
+    /// Every class referenced by any mission, lowercase and sorted
+    pub classes: Vec<String>,
+    /// Missions made for several maps, see [`group_variants`]
+    #[serde(default)]
+    pub variants: Vec<MissionVariants>,
+}
+
+/// Variants of a mission made for different maps, such as `op_x.Altis` and
+/// `op_x.Malden`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionVariants {
+    /// Name of the mission without its map suffix
+    pub name: String,
+    /// Names of the variants, sorted
+    pub missions: Vec<String>,
+    /// Maps of the variants, sorted
+    pub maps: Vec<String>,
+    /// Classes referenced by every variant, lowercase and sorted
+    pub shared_classes: Vec<String>,
+    /// Classes referenced by any variant, lowercase and sorted
+    pub classes: Vec<String>,
+}
+
+/// Group missions that only differ by their map suffix, ignoring case.
+///
+/// Every mission is in a group, missions without a map suffix included.
+/// Groups are sorted by name.
+pub fn group_variants(results: &[MissionResults]) -> Vec<MissionVariants> {
+    let mut groups: BTreeMap<String, Vec<&MissionResults>> = BTreeMap::new();
+    for result in results {
+        let (name, _) = mission_variant(&result.mission_name);
+        groups.entry(name.to_lowercase()).or_default().push(result);
+    }
+
+    groups.into_values()
+        .map(|mut variants| {
+            variants.sort_by(|a, b| a.mission_name.cmp(&b.mission_name));
+            let class_sets: Vec<BTreeSet<String>> = variants.iter()
+                .map(|result| {
+                    result.class_dependencies.iter()
+                        .filter(|reference| !reference.class_name.is_empty())
+                        .map(|reference| reference.class_name.to_lowercase())
+                        .collect()
+                })
+                .collect();
+            let shared_classes = class_sets.iter()
+                .skip(1)
+                .fold(class_sets[0].clone(), |shared, classes| &shared & classes);
+            let maps: BTreeSet<&str> = variants.iter()
+                .filter_map(|result| mission_variant(&result.mission_name).1)
+                .collect();
+
+            MissionVariants {
+                name: mission_variant(&variants[0].mission_name).0.to_string(),
+                missions: variants.iter().map(|result| result.mission_name.clone()).collect(),
+                maps: maps.into_iter().map(str::to_string).collect(),
+                shared_classes: shared_classes.into_iter().collect(),
+                classes: class_sets.into_iter().flatten().collect::<BTreeSet<_>>().into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+/// Results of a scan as printed by `mission_scanner scan`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScanOutput {
+    /// Version of the output format, see [`REPORT_SCHEMA_VERSION`]
+    pub schema_version: u32,
+    /// Results of each scanned mission
+    pub missions: Vec<MissionResults>,
+}
+
+impl ScanOutput {
+    /// Tag scan results with the current format version
+    pub fn new(missions: Vec<MissionResults>) -> Self {
+        Self { schema_version: REPORT_SCHEMA_VERSION, missions }
+    }
+}
+
+/// Write a JSON report per mission and an aggregate summary into a directory.
+///
+/// Mission reports are written as `<mission_name>.json`, with characters other
+/// than letters, digits, `.`, `-` and `_` replaced by `_` and a number added
+/// when missions share a name, such as `co10_a.Altis-2.json`. The summary
+/// lists the file of each mission. Paths inside them are
+/// relative to the mission directory and references are sorted by file,
+/// location and class, so reports of two versions of a mission can be diffed.
+/// Returns the written files, the summary last.
+pub fn write_json_report(results: &[MissionResults], output_dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(output_dir)
+        .map_err(|e| anyhow!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let mut written = Vec::new();
+    let mut missions = Vec::new();
+    let mut classes = BTreeSet::new();
+    let mut taken = BTreeSet::from([SUMMARY_FILE_NAME.to_string()]);
+    for result in results {
+        let report = stable_report(MissionReport::from_results(result));
+        classes.extend(report.references.iter().map(|reference| reference.class_name.to_lowercase()));
+
+        let report_file = PathBuf::from(report_file_name(&report.mission_name, &mut taken));
+        missions.push(MissionSummary {
+            mission_name: report.mission_name.clone(),
+            report_file: report_file.clone(),
+            counts: report.counts.clone(),
+        });
+
+        let path = output_dir.join(&report_file);
+        write_json(&path, &ReportFile { schema_version: REPORT_SCHEMA_VERSION, report })?;
+        written.push(path);
+    }
+
+    missions.sort_by(|a, b| a.mission_name.cmp(&b.mission_name));
+    let summary = ScanSummary {
+        schema_version: REPORT_SCHEMA_VERSION,
+        missions,
+        classes: classes.into_iter().collect(),
+        variants: group_variants(results).into_iter().filter(|group| group.missions.len() > 1).collect(),
+    };
+    let path = output_dir.join(SUMMARY_FILE_NAME);
+    write_json(&path, &summary)?;
+    written.push(path);
+    Ok(written)
+}
+
+/// Make the paths of a report relative to its mission and sort its contents
+fn stable_report(mut report: MissionReport) -> MissionReport {
+    let mission_dir = std::mem::take(&mut report.mission_dir);
+    let relative = |path: &Path| path.strip_prefix(&mission_dir).unwrap_or(path).to_path_buf();
+    for file in &mut report.files {
+        file.path = relative(&file.path);
+    }
+    for reference in &mut report.references {
+        reference.source_file = relative(&reference.source_file);
+    }
+
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+    report.references.sort_by(|a, b| {
+        (&a.source_file, a.location, &a.class_name, &a.context)
+            .cmp(&(&b.source_file, b.location, &b.class_name, &b.context))
+    });
+    report
+}
+
+/// Write every class reference of the missions as CSV rows of
+/// `mission,file,line,class,reference_type,count`.
+///
+/// References to the same class with the same type on the same line are
+/// counted in one row, summing the instances each adds. Files are relative to the mission directory and the
+/// line is empty when unknown. Rows are sorted.
+pub fn write_csv(results: &[MissionResults], path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, to_csv(results)).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// File name of a mission's report that no other report of the scan has,
+/// ignoring case as some filesystems do
+fn report_file_name(mission_name: &str, taken: &mut BTreeSet<String>) -> String {
+    let sanitized: String = mission_name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    // Names like `..` would leave the output directory or be hidden
+    let stem = match sanitized.trim_start_matches('.') {
+        "" => "mission",
+        stem => stem,
+    };
+    let mut name = format!("{}.json", stem);
+    let mut suffix = 2;
+    while !taken.insert(name.to_lowercase()) {
+        name = format!("{}-{}.json", stem, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+/// Format the rows written by [`write_csv`]
+pub fn to_csv(results: &[MissionResults]) -> String {
+    let mut rows: BTreeMap<(&str, PathBuf, Option<usize>, &str, &ReferenceType), u64> = BTreeMap::new();
+    for result in results {
+        for dep in &result.class_dependencies {
+            let file = dep.source_file.strip_prefix(&result.mission_dir).unwrap_or(&dep.source_file).to_path_buf();
+            let key = (result.mission_name.as_str(), file, dep.location.map(|l| l.line), dep.class_name.as_str(), &dep.reference_type);
+            *rows.entry(key).or_insert(0) += u64::from(dep.count);
+        }
+    }
+
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for ((mission, file, line, class_name, reference_type), count) in rows {
+        let line = line.map(|line| line.to_string()).unwrap_or_default();
+        let fields = [
+            csv_field(mission),
+            csv_field(&file.to_string_lossy().replace('\\', "/")),
+            line,
+            csv_field(class_name),
+            format!("{:?}", reference_type),
+            count.to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    fs::write(path, json + "\n").map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Change in how often a class is referenced between two scans of a mission
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CountChange {
+    /// Name of the class as written in the new scan
+    pub class_name: String,
+    /// Number of references in the old scan
+    pub old_count: usize,
+    /// Number of references in the new scan
+    pub new_count: usize,
+}
+
+/// Class dependencies added and removed by an update of a mission
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionDiff {
+    /// Name of the mission in the new scan
+    pub mission_name: String,
+    /// Classes only the new scan references, sorted ignoring case
+    pub added: Vec<String>,
+    /// Classes only the old scan references, sorted ignoring case
+    pub removed: Vec<String>,
+    /// Classes both scans reference a different number of times
+    pub changed: Vec<CountChange>,
+}
+
+impl MissionDiff {
+    /// Check if the mission's dependencies didn't change
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the class dependencies of two scans of a mission.
+///
+/// Class names are compared ignoring case, so a class whose spelling changed
+/// case is neither added nor removed.
+pub fn diff_missions(old: &MissionResults, new: &MissionResults) -> MissionDiff {
+    let old_counts = class_counts(old);
+    let new_counts = class_counts(new);
+
+    let mut diff = MissionDiff {
+        mission_name: new.mission_name.clone(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (key, (class_name, new_count)) in &new_counts {
+        match old_counts.get(key) {
+            None => diff.added.push(class_name.to_string()),
+            Some((_, old_count)) if old_count != new_count => diff.changed.push(CountChange {
+                class_name: class_name.to_string(),
+                old_count: *old_count,
+                new_count: *new_count,
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old_counts.iter()
+        .filter(|(key, _)| !new_counts.contains_key(*key))
+        .map(|(_, (class_name, _))| class_name.to_string())
+        .collect();
+    diff
+}
+
+/// Number of references of each class by lowercase name, with the name as first written
+fn class_counts(results: &MissionResults) -> BTreeMap<String, (&str, usize)> {
+    let mut counts: BTreeMap<String, (&str, usize)> = BTreeMap::new();
+    for dep in &results.class_dependencies {
+        counts.entry(dep.class_name.to_lowercase())
+            .or_insert((dep.class_name.as_str(), 0))
+            .1 += 1;
+    }
+    counts
+}
+
+/// Scan a mission and build its report
+pub async fn build_report<V: Vfs + Clone + 'static>(vfs: &V, mission_dir: &Path, config: &MissionScannerConfig) -> Result<MissionReport> {
+    let results = scan_mission_with_vfs(vfs, mission_dir, config.max_threads, config).await?;
+    Ok(MissionReport::from_results(&results))
+}
@@ -0,0 +1,171 @@
+//! Custom mission rules written in Rhai
+//!
+//! Each rule is a Rhai script run once per mission. The script sees:
+//!
+//! - `mission`: the scan results (`mission_name`, `class_dependencies`, ...)
+//! - `entities`: objects placed in mission.sqm, each with `class_name`, `name`,
+//!   `attributes`, `custom_attributes` and `arsenal`
+//!
+//! and reports findings by calling `error(message)`, `warning(message)` or
+//! `info(message)`:
+//!
+//! ```rhai
+//! for dep in mission.class_dependencies {
+//!     if dep.class_name.starts_with("CUP_") {
+//!         warning(`CUP class ${dep.class_name} in ${dep.source_file}`);
+//!     }
+//! }
+//! ```
+//!
+//! Only available with the `rules` feature.
+
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use parser_sqm::{decode_sqm, extract_entities, SqmEntity, SqmValue};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use serde::{Serialize, Deserialize};
+
+use crate::types::MissionResults;
+
+/// File extension of rule scripts
+pub const RULE_EXTENSION: &str = "rhai";
+
+/// Severity of a rule finding
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FindingSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Finding reported by a rule script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleFinding {
+    /// Name of the rule that reported the finding
+    pub rule: String,
+    /// How serious the finding is
+    pub severity: FindingSeverity,
+    /// Message given by the rule
+    pub message: String,
+}
+
+/// Runs user-provided Rhai rules against scan results
+pub struct RuleEngine {
+    engine: Engine,
+    rules: Vec<(String, AST)>,
+    /// Findings reported by the script currently running
+    reported: Arc<Mutex<Vec<(FindingSeverity, String)>>>,
+}
+
+impl Default for RuleEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuleEngine {
+    /// Create an engine without any rules
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+
+        for (name, severity) in [
+            ("error", FindingSeverity::Error),
+            ("warning", FindingSeverity::Warning),
+            ("info", FindingSeverity::Info),
+        ] {
+            let reported = Arc::clone(&reported);
+            engine.register_fn(name, move |message: &str| {
+                reported.lock().unwrap().push((severity, message.to_string()));
+            });
+        }
+
+        Self { engine, rules: Vec::new(), reported }
+    }
+
+    /// Compile and add a rule
+    pub fn add_rule(&mut self, name: &str, script: &str) -> Result<()> {
+        let ast = self.engine.compile(script)
+            .map_err(|e| anyhow!("Failed to compile rule {}: {}", name, e))?;
+        self.rules.push((name.to_string(), ast));
+        Ok(())
+    }
+
+    /// Add every `.rhai` script in a directory as a rule named after its file
+    pub fn load_rules_dir(&mut self, dir: &Path) -> Result<usize> {
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .map_err(|e| anyhow!("Failed to read rules directory {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == RULE_EXTENSION))
+            .collect();
+        paths.sort();
+
+        for path in &paths {
+            let script = fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read rule {}: {}", path.display(), e))?;
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            self.add_rule(&name, &script)?;
+        }
+        Ok(paths.len())
+    }
+
+    /// Run every rule against a mission and collect their findings
+    pub fn evaluate(&self, mission: &MissionResults) -> Result<Vec<RuleFinding>> {
+        let mission_value = rhai::serde::to_dynamic(mission)
+            .map_err(|e| anyhow!("Failed to convert mission for rules: {}", e))?;
+
+        let entities: Array = match &mission.sqm_file {
+            Some(sqm_file) => {
+                let content = fs::read(sqm_file)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", sqm_file.display(), e))?;
+                let content = decode_sqm(&content)
+                    .map_err(|e| anyhow!("Failed to decode {}: {}", sqm_file.display(), e))?;
+                extract_entities(&content).iter().map(entity_to_dynamic).collect()
+            }
+            None => Array::new(),
+        };
+
+        let mut findings = Vec::new();
+        for (name, ast) in &self.rules {
+            let mut scope = Scope::new();
+            scope.push_constant("mission", mission_value.clone());
+            scope.push_constant("entities", entities.clone());
+
+            self.engine.run_ast_with_scope(&mut scope, ast)
+                .map_err(|e| anyhow!("Rule {} failed: {}", name, e))?;
+
+            findings.extend(self.reported.lock().unwrap().drain(..)
+                .map(|(severity, message)| RuleFinding { rule: name.clone(), severity, message }));
+        }
+        Ok(findings)
+    }
+}
+
+/// Convert a placed object to a Rhai map
+fn entity_to_dynamic(entity: &SqmEntity) -> Dynamic {
+    let attributes = |values: &std::collections::BTreeMap<String, SqmValue>| -> Map {
+        values.iter()
+            .map(|(key, value)| (key.as_str().into(), sqm_value_to_dynamic(value)))
+            .collect()
+    };
+
+    let mut map = Map::new();
+    map.insert("class_name".into(), entity.class_name.clone().into());
+    map.insert("name".into(), entity.name.clone().map_or(Dynamic::UNIT, Dynamic::from));
+    map.insert("attributes".into(), attributes(&entity.attributes).into());
+    map.insert("custom_attributes".into(), attributes(&entity.custom_attributes).into());
+    map.insert("arsenal".into(), entity.is_arsenal_enabled().into());
+    map.into()
+}
+
+/// Convert an SQM value to a Rhai value
+fn sqm_value_to_dynamic(value: &SqmValue) -> Dynamic {
+    match value {
+        SqmValue::String(s) => s.clone().into(),
+        SqmValue::Number(n) => (*n).into(),
+        SqmValue::Array(items) => items.iter().map(sqm_value_to_dynamic).collect::<Array>().into(),
+    }
+}
@@ -0,0 +1,84 @@
+//! Cache of what the scanner parses from each file
+//!
+//! Entries live in `<cache_dir>/files/`, one per file path, and hold what
+//! was parsed from the file with a blake3 hash of what it was parsed from. Config files also depend on the other config files of their
+//! mission through macros and includes, so their hash covers those too. An
+//! entry is only used when its hash and scanner version match, so editing one
+//! script of a large mission only reparses that script.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Serialize, Deserialize};
+
+use super::ParsedFile;
+
+/// Directory inside the cache directory holding file entries
+pub const FILE_CACHE_DIR: &str = "files";
+
+/// Cached parse of one file
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Version of the scanner that parsed the file
+    scanner_version: String,
+    /// Hash of the content the file was parsed from
+    hash: String,
+    #[serde(flatten)]
+    parsed: ParsedFile,
+}
+
+/// Parses of files, persisted in a cache directory
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Use the file cache inside a cache directory
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { dir: cache_dir.join(FILE_CACHE_DIR) }
+    }
+
+    /// Hash of a file's content and of the content it depends on, as a hex string
+    pub fn hash(content: &[u8], dependencies: &[u8]) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(content);
+        hasher.update(dependencies);
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Parse of a file from content with the given hash, if cached
+    pub fn get(&self, file: &Path, hash: &str) -> Option<ParsedFile> {
+        let content = fs::read(self.entry_path(file)).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&content).ok()?;
+        (entry.hash == hash && entry.scanner_version == env!("CARGO_PKG_VERSION"))
+            .then_some(entry.parsed)
+    }
+
+    /// Store the parse of a file, replacing its previous entry
+    pub fn insert(&self, file: &Path, hash: &str, parsed: &ParsedFile) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let entry = CacheEntry {
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+            hash: hash.to_string(),
+            parsed: parsed.clone(),
+        };
+
+        // Write then rename, so a scan running in parallel never reads half an entry
+        let path = self.entry_path(file);
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&temp_path, serde_json::to_vec(&entry)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Path of the entry of a file, named by the hash of the file's path
+    fn entry_path(&self, file: &Path) -> PathBuf {
+        let key = blake3::hash(file.to_string_lossy().as_bytes()).to_hex();
+        self.dir.join(format!("{}.json", key))
+    }
+}
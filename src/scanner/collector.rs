@@ -1,83 +1,178 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Result, anyhow};
-use walkdir::WalkDir;
+use log::debug;
 
-use crate::types::MissionFileResults;
+use crate::description_ext::MissionFunction;
+use crate::filter::mission_terrain;
+use crate::pbo::is_pbo;
+use crate::types::{AssetCategory, MissionFileResults, MissionFootprint, MissionScannerConfig};
+use crate::vfs::Vfs;
 
-/// Check if a path is a mission directory
-fn is_mission_directory(path: &Path) -> bool {
-    path.is_dir() && path.join("mission.sqm").exists()
+/// Check if a file is named `mission.sqm`, ignoring case if configured
+fn is_mission_sqm(path: &Path, ignore_case: bool) -> bool {
+    path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy();
+        if ignore_case { name.eq_ignore_ascii_case("mission.sqm") } else { name == "mission.sqm" }
+    })
 }
 
-/// Find mission.sqm in a directory
-pub fn find_mission_file(dir: &Path) -> Result<Option<PathBuf>> {
+/// Check if a file has one of the extensions, ignoring case if configured
+fn has_extension(path: &Path, extensions: &[String], ignore_case: bool) -> bool {
+    path.extension().is_some_and(|ext| {
+        let ext = ext.to_string_lossy();
+        extensions.iter().any(|allowed| if ignore_case { allowed.eq_ignore_ascii_case(&ext) } else { *allowed == ext })
+    })
+}
+
+/// Find mission.sqm in a directory.
+///
+/// With `ignore_case` set in the config, names such as `Mission.SQM` from
+/// missions synced from Windows are found too.
+pub fn find_mission_file(vfs: &dyn Vfs, dir: &Path, config: &MissionScannerConfig) -> Result<Option<PathBuf>> {
     let sqm_path = dir.join("mission.sqm");
-    if sqm_path.exists() {
-        Ok(Some(sqm_path))
-    } else {
-        Ok(None)
+    if vfs.is_file(&sqm_path) {
+        return Ok(Some(sqm_path));
+    }
+    if !config.ignore_case {
+        return Ok(None);
+    }
+    Ok(vfs.walk(dir).into_iter()
+        .find(|path| path.parent() == Some(dir) && is_mission_sqm(path, true) && vfs.is_file(path)))
+}
+
+/// Measure the disk footprint of a mission directory by asset category
+pub fn measure_mission_footprint(vfs: &dyn Vfs, dir: &Path) -> MissionFootprint {
+    let mut footprint = MissionFootprint::default();
+    for path in vfs.walk(dir) {
+        if !vfs.is_file(&path) {
+            continue;
+        }
+        let size = vfs.file_size(&path).unwrap_or(0);
+        let category = path.extension()
+            .map(|ext| AssetCategory::from_extension(&ext.to_string_lossy()))
+            .unwrap_or(AssetCategory::Other);
+
+        footprint.total_bytes += size;
+        *footprint.by_category.entry(category).or_insert(0) += size;
     }
+    footprint
 }
 
-/// Find all SQF files in a directory
-pub fn find_script_files(dir: &Path, allowed_extensions: &[String]) -> Result<Vec<PathBuf>> {
-    if !allowed_extensions.contains(&"sqf".to_string()) {
+/// Find all SQF files in a directory, if the config's extensions include them
+pub fn find_script_files(vfs: &dyn Vfs, dir: &Path, config: &MissionScannerConfig) -> Result<Vec<PathBuf>> {
+    if !config.file_extensions.contains(&"sqf".to_string()) {
         return Ok(Vec::new());
     }
 
+    let sqf = ["sqf".to_string()];
     let mut sqf_files = Vec::new();
-    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "sqf") {
-            sqf_files.push(path.to_path_buf());
+    for path in vfs.walk(dir) {
+        if has_extension(&path, &sqf, config.ignore_case) && vfs.is_file(&path) {
+            sqf_files.push(path);
         }
     }
     Ok(sqf_files)
 }
 
-/// Find all CPP/HPP files in a directory
-pub fn find_code_files(dir: &Path, allowed_extensions: &[String]) -> Result<Vec<PathBuf>> {
+/// Keep the files of a mission selected by the include and exclude globs of a config
+pub fn select_files(config: &MissionScannerConfig, mission_dir: &Path, files: &mut Vec<PathBuf>) {
+    let before = files.len();
+    files.retain(|file| config.is_file_selected(mission_dir, file));
+    if files.len() < before {
+        debug!("Left {} files of {} out by globs", before - files.len(), mission_dir.display());
+    }
+}
+
+/// Find the files of the functions a mission declares in `CfgFunctions`.
+///
+/// Paths are matched ignoring case, so files are found however their names
+/// and extensions are written. Functions in addons and files missing from
+/// the mission are left out.
+pub fn find_function_files(vfs: &dyn Vfs, dir: &Path, functions: &[MissionFunction]) -> Vec<PathBuf> {
+    let files: HashMap<String, PathBuf> = vfs.walk(dir).into_iter()
+        .filter(|path| vfs.is_file(path))
+        .filter_map(|path| {
+            let relative = normalize_mission_path(&path.strip_prefix(dir).ok()?.to_string_lossy());
+            Some((relative, path))
+        })
+        .collect();
+    let mut function_files = Vec::new();
+    for function in functions.iter().filter(|function| !function.file.starts_with('\\')) {
+        match files.get(&normalize_mission_path(&function.file)) {
+            Some(file) if !function_files.contains(file) => function_files.push(file.clone()),
+            Some(_) => {}
+            None => debug!("File of function {} not found: {}", function.name, function.file),
+        }
+    }
+    function_files
+}
+
+/// Lowercase a path from the mission root, with forward slashes and no leading slash
+pub fn normalize_mission_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches('/').to_lowercase()
+}
+
+/// Find all CPP/HPP files in a directory with the config's extensions
+pub fn find_code_files(vfs: &dyn Vfs, dir: &Path, config: &MissionScannerConfig) -> Result<Vec<PathBuf>> {
     // Check if any code file extensions are allowed
-    let has_code_extensions = allowed_extensions.iter().any(|ext| 
-        ext == "cpp" || ext == "hpp" || ext == "ext"
-    );
-    if !has_code_extensions {
+    let code_extensions: Vec<String> = config.file_extensions.iter()
+        .filter(|ext| *ext == "cpp" || *ext == "hpp" || *ext == "ext")
+        .cloned()
+        .collect();
+    if code_extensions.is_empty() {
         return Ok(Vec::new());
     }
 
     let mut cpp_files = Vec::new();
-    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(ext) = path.extension() {
-                let ext = ext.to_string_lossy().to_lowercase();
-                if allowed_extensions.contains(&ext.to_string()) {
-                    cpp_files.push(path.to_path_buf());
-                }
-            }
+    for path in vfs.walk(dir) {
+        if has_extension(&path, &code_extensions, config.ignore_case) && vfs.is_file(&path) {
+            cpp_files.push(path);
         }
     }
     Ok(cpp_files)
 }
 
-/// Collect mission files from a directory with configuration
-pub fn collect_mission_files(dir: &Path) -> Result<Vec<MissionFileResults>> {
+/// Find the files of a mission read by a custom extractor
+pub fn find_extractor_files(vfs: &dyn Vfs, dir: &Path, config: &MissionScannerConfig) -> Vec<PathBuf> {
+    if config.extractors.is_empty() {
+        return Vec::new();
+    }
+    vfs.walk(dir).into_iter()
+        .filter(|path| config.extractors.applies_to(path) && vfs.is_file(path))
+        .collect()
+}
+
+/// Find packed missions (`.pbo` files) in a directory
+pub fn find_pbo_files(vfs: &dyn Vfs, dir: &Path) -> Vec<PathBuf> {
+    vfs.walk(dir).into_iter()
+        .filter(|path| is_pbo(path) && vfs.is_file(path))
+        .collect()
+}
+
+/// Collect mission files from a directory with configuration.
+///
+/// Every directory holding a `mission.sqm` is a mission. Scripts and config
+/// files are collected with all their extensions, `ext` included.
+pub fn collect_mission_files(vfs: &dyn Vfs, dir: &Path, config: &MissionScannerConfig) -> Result<Vec<MissionFileResults>> {
     let mut results = Vec::new();
+    let config = MissionScannerConfig {
+        file_extensions: ["sqf", "cpp", "hpp", "ext"].iter().map(|ext| ext.to_string()).collect(),
+        ..config.clone()
+    };
     
-    let walker = WalkDir::new(dir);
-
-    // Track unique mission names to avoid duplicates
+    // Track unique mission names to avoid duplicates, such as a mission reached again through a symlink
     let mut seen_missions = HashSet::new();
     
-    for entry in walker.into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        
-        // Skip non-mission directories
-        if !is_mission_directory(path) {
+    for entry in vfs.walk(dir) {
+        // Missions are the directories holding a mission.sqm
+        if !is_mission_sqm(&entry, config.ignore_case) || !vfs.is_file(&entry) {
             continue;
         }
+        let Some(path) = entry.parent() else {
+            continue;
+        };
         
         // Get mission name from directory name
         let mission_name = path.file_name()
@@ -90,19 +185,17 @@ pub fn collect_mission_files(dir: &Path) -> Result<Vec<MissionFileResults>> {
             continue;
         }
         
-        // Find mission.sqm
-        let mission_file = find_mission_file(path)?;
-        
         // Find SQF files
-        let script_files = find_script_files(path, &["sqf".to_string()])?;
+        let script_files = find_script_files(vfs, path, &config)?;
         
         // Find CPP/HPP files
-        let code_files = find_code_files(path, &["cpp".to_string(), "hpp".to_string(), "ext".to_string()])?;
+        let code_files = find_code_files(vfs, path, &config)?;
         
         results.push(MissionFileResults {
+            map_name: mission_terrain(path).map(str::to_string),
             mission_name,
             mission_dir: path.to_path_buf(),
-            sqm_file: mission_file,
+            sqm_file: Some(entry.clone()),
             sqf_files: script_files,
             cpp_files: code_files,
         });
@@ -1,7 +1,12 @@
+mod cache;
 mod collector;
 mod parser;
+mod repro;
 mod scanner;
 
-pub use collector::{collect_mission_files, find_mission_file, find_script_files, find_code_files};
-pub use parser::parse_file;
-pub use scanner::scan_mission;
\ No newline at end of file
+pub use cache::{FileCache, FILE_CACHE_DIR};
+pub use collector::{collect_mission_files, find_mission_file, find_pbo_files, find_script_files, find_code_files, find_extractor_files, measure_mission_footprint, select_files};
+pub use parser::{parse_file, parse_file_with_vfs, ConfigContext, ParsedFile};
+pub(crate) use parser::{hpp_classes_to_references, parse_config, read_sqm};
+pub use repro::write_repro;
+pub use scanner::{scan_mission, scan_mission_with_vfs, scan_missions, scan_missions_with_vfs, scan_missions_stream, scan_missions_stream_with_vfs, scan_pbo};
\ No newline at end of file
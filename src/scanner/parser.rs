@@ -1,16 +1,19 @@
 // Std imports
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // External crate imports
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use log::{debug, warn};
-use parser_hpp::{parse_file as parser_hpp_file, HppValue};
-use sqf_analyzer::{Args, analyze_sqf};
-use parser_sqm::extract_class_dependencies;
+use parser_hpp::{decode_content, HppClass, HppParser, HppValue, MacroTable, ParseOptions};
+use parser_sqf::{locate_class_name, AnalysisOptions};
+use parser_sqm::{decode_sqm, extract_attributed_dependencies, extract_attributed_dependencies_parallel, extract_entities, extract_mission_objects, is_binarized};
+use serde::{Serialize, Deserialize};
 
 // Internal crate imports
-use crate::types::{ClassReference, ReferenceType};
+use crate::description_ext::{is_description_ext, parse_description_ext};
+use crate::interner::Interner;
+use crate::types::{AssetReference, ClassReference, Confidence, DependencyOwner, ReferenceType, SourceLocation};
+use crate::vfs::{OsFs, Vfs};
 
 /// Parse any supported file type and extract class dependencies.
 /// 
@@ -33,6 +36,38 @@ use crate::types::{ClassReference, ReferenceType};
 /// in the returned ClassReference objects. When comparing class names later,
 /// they should be compared case-insensitively.
 pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>> {
+    parse_file_with_vfs(&OsFs::default(), file_path)
+}
+
+/// Parse a file read through a [`Vfs`] and extract class dependencies.
+///
+/// See [`parse_file`]. Scripts are analyzed in memory, so they needn't be
+/// on disk.
+pub fn parse_file_with_vfs(vfs: &dyn Vfs, file_path: &Path) -> Result<Vec<ClassReference>> {
+    parse_file_in_mission(vfs, file_path, &ConfigContext::default()).map(|parsed| parsed.references)
+}
+
+/// What one parse of a file finds, as cached by [`FileCache`](super::FileCache)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedFile {
+    /// Class references found in the file
+    pub references: Vec<ClassReference>,
+    /// Textures and materials a script sets
+    #[serde(default)]
+    pub assets: Vec<AssetReference>,
+    /// Paths of the scripts a script runs or compiles, as written
+    #[serde(default)]
+    pub script_paths: Vec<String>,
+}
+
+impl From<Vec<ClassReference>> for ParsedFile {
+    fn from(references: Vec<ClassReference>) -> Self {
+        Self { references, ..Self::default() }
+    }
+}
+
+/// Parse a file with what the config files of its mission share
+pub(crate) fn parse_file_in_mission(vfs: &dyn Vfs, file_path: &Path, context: &ConfigContext) -> Result<ParsedFile> {
     let extension = file_path.extension()
         .and_then(|ext| ext.to_str())
         .ok_or_else(|| anyhow!("File has no extension: {}", file_path.display()))?
@@ -40,21 +75,26 @@ pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>> {
 
     debug!("Starting to parse file: {} (type: {})", file_path.display(), extension);
 
-    let result = match extension.as_str() {
-        "sqf" => parse_sqf(file_path),
-        "sqm" => parse_sqm(file_path),
-        "cpp" | "hpp" | "ext" => parse_hpp(file_path),
+    let mut result = match extension.as_str() {
+        "sqf" => parse_sqf(vfs, file_path, context),
+        "sqm" => parse_sqm(vfs, file_path, context).map(ParsedFile::from),
+        "ext" if is_description_ext(file_path) => parse_description_ext(vfs, file_path, context).map(|ext| ext.references.into()),
+        "cpp" | "hpp" | "ext" => parse_hpp(vfs, file_path, context).map(ParsedFile::from),
         _ => Err(anyhow!("Unsupported file type: {}", extension))
     };
 
     match &result {
-        Ok(deps) => debug!("Successfully parsed {} with {} dependencies", file_path.display(), deps.len()),
+        Ok(parsed) => debug!("Successfully parsed {} with {} dependencies", file_path.display(), parsed.references.len()),
         Err(e) => warn!("Failed to parse {}: {}", file_path.display(), e),
     }
 
+    if let Ok(parsed) = &mut result {
+        locate_references(vfs, file_path, &mut parsed.references);
+    }
+
     // Filter out empty class names
-    if let Ok(deps) = &result {
-        if deps.iter().any(|d| d.class_name.is_empty()) {
+    if let Ok(parsed) = &result {
+        if parsed.references.iter().any(|d| d.class_name.is_empty()) {
             warn!("Found empty class names in file: {}", file_path.display());
         }
     }
@@ -62,16 +102,122 @@ pub fn parse_file(file_path: &Path) -> Result<Vec<ClassReference>> {
     result
 }
 
+/// Set the location of each reference to where its class name first appears in the file
+fn locate_references(vfs: &dyn Vfs, file_path: &Path, deps: &mut [ClassReference]) {
+    let Ok(bytes) = vfs.read(file_path) else {
+        return;
+    };
+    // Positions in a binarized file don't correspond to lines
+    if is_binarized(&bytes) {
+        return;
+    }
+    let content = decode_content(&bytes).content;
+    for dep in deps.iter_mut().filter(|dep| dep.location.is_none()) {
+        dep.location = locate_class_name(&content, &dep.class_name)
+            .map(|(line, column)| SourceLocation { line, column });
+    }
+}
+
 /// Parse a loadout file and extract equipment information
-pub fn parse_hpp(file_path: &Path) -> Result<Vec<ClassReference>> {
+pub fn parse_hpp(vfs: &dyn Vfs, file_path: &Path, context: &ConfigContext) -> Result<Vec<ClassReference>> {
     debug!("Starting loadout file parse: {}", file_path.display());
     
-    // Parse using parser_hpp
-    let classes = parser_hpp_file(file_path)
-        .map_err(|e| anyhow!("Failed to parse loadout file: {:?}", e))?;
+    let classes = parse_config(vfs, file_path, context)?.parse_classes();
     
     debug!("Found {} classes in loadout file", classes.len());
     
+    Ok(hpp_classes_to_references(classes, file_path, &context.interner))
+}
+
+/// What the files of one mission share when they're parsed
+#[derive(Debug, Clone, Default)]
+pub struct ConfigContext {
+    /// Mission directory on disk, used as the workspace `#include`s resolve in
+    pub mission_dir: Option<PathBuf>,
+    /// Macros defined by the mission's config files
+    pub macros: MacroTable,
+    /// Hash of the mission's config files, which each config file may depend on
+    pub digest: String,
+    /// How the mission's scripts and the code in its mission.sqm are analyzed
+    pub sqf_options: AnalysisOptions,
+    /// Interner the class names of the mission's references share
+    pub interner: Interner,
+}
+
+impl ConfigContext {
+    /// Collect the `#define`s of a mission's config files, so macros resolve across files
+    pub fn for_mission(vfs: &dyn Vfs, mission_dir: &Path, config_files: &[PathBuf]) -> Self {
+        let mut macros = MacroTable::new();
+        let mut hasher = blake3::Hasher::new();
+        for file in config_files {
+            match vfs.read(file) {
+                Ok(bytes) => {
+                    hasher.update(file.to_string_lossy().as_bytes());
+                    hasher.update(&bytes);
+                    macros.collect(&decode_content(&bytes).content);
+                }
+                Err(e) => debug!("Failed to read {} for macros: {}", file.display(), e),
+            }
+        }
+        Self {
+            mission_dir: vfs.local_path(mission_dir),
+            macros,
+            digest: hasher.finalize().to_hex().to_string(),
+            sqf_options: AnalysisOptions::default(),
+            interner: Interner::default(),
+        }
+    }
+
+    /// What the references of a script depend on besides its content.
+    ///
+    /// `None` when custom signature or function tables are used, since their
+    /// content can't be hashed, so scripts aren't cached.
+    pub(crate) fn script_digest(&self) -> Option<String> {
+        let options = &self.sqf_options;
+        (options.command_signatures.is_none() && options.known_functions.is_none())
+            .then(|| format!("strict={};soft_fail={}", options.strict, options.soft_fail))
+    }
+}
+
+/// Check if a file is a config file, read with [`parse_config`]
+pub(crate) fn is_config_file(file_path: &Path) -> bool {
+    file_path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ["cpp", "hpp", "ext"].iter().any(|config| ext.eq_ignore_ascii_case(config)))
+}
+
+/// Read and preprocess a config file.
+///
+/// Files of a mission on disk are preprocessed in the mission directory so
+/// their includes resolve. Includes that don't resolve are logged and left out.
+pub(crate) fn parse_config(vfs: &dyn Vfs, file_path: &Path, context: &ConfigContext) -> Result<HppParser> {
+    let local_path = vfs.local_path(file_path);
+    let parser = match (&context.mission_dir, &local_path) {
+        (Some(mission_dir), Some(local_path)) if local_path.starts_with(mission_dir) => {
+            HppParser::new_in_workspace(mission_dir, local_path, ParseOptions::default(), &context.macros)
+        }
+        _ => {
+            let bytes = vfs.read(file_path)
+                .context("Failed to read loadout file")?;
+
+            // Invalid UTF-8 would make the preprocessor reject the whole file
+            let decoded = decode_content(&bytes);
+            if let Some(warning) = &decoded.warning {
+                warn!("{}: {}", file_path.display(), warning);
+            }
+            HppParser::new_with_macros(&decoded.content, ParseOptions::default(), &context.macros)
+        }
+    }
+    .map_err(|e| anyhow!("Failed to parse loadout file: {:?}", e))?;
+
+    for warning in parser.warnings() {
+        warn!("{}: {}", file_path.display(), warning);
+    }
+    Ok(parser)
+}
+
+/// Convert parsed loadout classes into class references
+pub(crate) fn hpp_classes_to_references(classes: Vec<HppClass>, file_path: &Path, interner: &Interner) -> Vec<ClassReference> {
     let mut dependencies = Vec::new();
     
     // Convert each class and its items to dependencies
@@ -81,34 +227,44 @@ pub fn parse_hpp(file_path: &Path) -> Result<Vec<ClassReference>> {
         // Add parent class as inheritance dependency if it exists
         if let Some(parent) = class.parent {
             dependencies.push(ClassReference {
-                class_name: parent,
+                class_name: interner.intern(&parent),
                 reference_type: ReferenceType::Inheritance,
                 context: format!("loadout:class:{}", file_path.display()),
-                source_file: file_path.to_path_buf()
+                source_file: file_path.to_path_buf(),
+                owner: None,
+                location: None,
+                confidence: Confidence::High,
+                count: 1,
+                count_range: None,
             });
         }
         
         // Add both array properties and string properties
         for property in class.properties {
             match &property.value {
-                HppValue::Array(items) => {
+                HppValue::Array(_) => {
                     // Process array properties (uniform[], vest[], etc.)
                     let property_name = property.name.to_lowercase();
                     if is_equipment_array(&property_name) {
                         debug!("Processing equipment array: {}", property_name);
                         
                         // Process each array item, stripping any extra quotes
-                        for item in items {
+                        for item in property.value.strings() {
                             // Skip empty items and preprocessor macros
                             let clean_item = item.trim().trim_matches('"');
                             if !clean_item.is_empty() && 
                                clean_item != "default" && 
                                !clean_item.starts_with("LIST_") {
                                 dependencies.push(ClassReference {
-                                    class_name: clean_item.to_string(),
+                                    class_name: interner.intern(clean_item),
                                     reference_type: ReferenceType::Direct,
                                     context: format!("loadout:{}:{}", property_name, file_path.display()),
-                                    source_file: file_path.to_path_buf()
+                                    source_file: file_path.to_path_buf(),
+                                    owner: None,
+                                    location: None,
+                                    confidence: Confidence::High,
+                                    count: 1,
+                                    count_range: None,
                                 });
                             }
                         }
@@ -121,10 +277,15 @@ pub fn parse_hpp(file_path: &Path) -> Result<Vec<ClassReference>> {
                         let clean_item = value.trim().trim_matches('"');
                         if !clean_item.is_empty() && clean_item != "default" {
                             dependencies.push(ClassReference {
-                                class_name: clean_item.to_string(),
+                                class_name: interner.intern(clean_item),
                                 reference_type: ReferenceType::Direct,
                                 context: format!("loadout:{}:{}", property_name, file_path.display()),
-                                source_file: file_path.to_path_buf()
+                                source_file: file_path.to_path_buf(),
+                                owner: None,
+                                location: None,
+                                confidence: Confidence::High,
+                                count: 1,
+                                count_range: None,
                             });
                         }
                     }
@@ -135,7 +296,7 @@ pub fn parse_hpp(file_path: &Path) -> Result<Vec<ClassReference>> {
     }
     
     debug!("Total of {} dependencies found in loadout file", dependencies.len());
-    Ok(dependencies)
+    dependencies
 }
 
 /// Determine if a property name is an equipment array we should process
@@ -164,60 +325,140 @@ fn is_equipment_property(name: &str) -> bool {
     EQUIPMENT_PROPERTIES.iter().any(|&prop_name| name == prop_name)
 }
 
-/// Parse a SQM file and extract class references
-pub fn parse_sqm(file_path: &Path) -> Result<Vec<ClassReference>> {
+/// Size above which the entities of a mission.sqm are parsed in parallel
+const PARALLEL_SQM_BYTES: usize = 4 * 1024 * 1024;
+
+/// Parse a SQM file and extract class references, analyzing the SQF code of
+/// its init attributes and triggers with the options of `config`
+pub fn parse_sqm(vfs: &dyn Vfs, file_path: &Path, config: &ConfigContext) -> Result<Vec<ClassReference>> {
     debug!("Starting SQM file parse: {}", file_path.display());
     
-    let content = fs::read_to_string(file_path)
-        .map_err(|e| anyhow!("Failed to read SQM file: {}", e))?;
+    let content = read_sqm(vfs, file_path)?;
     
-    let classes = extract_class_dependencies(&content);
+    let classes = if content.len() > PARALLEL_SQM_BYTES {
+        extract_attributed_dependencies_parallel(&content)
+    } else {
+        extract_attributed_dependencies(&content)
+    };
     
     let mut dependencies = Vec::new();
     for class in classes {
+        let owner = DependencyOwner {
+            side: class.owner.side,
+            group: class.owner.group,
+            unit: class.owner.unit,
+        };
         dependencies.push(ClassReference {
-            class_name: class,
+            class_name: config.interner.intern(&class.class_name),
             reference_type: ReferenceType::Direct,
             context: format!("sqm:{}", file_path.display()),
-            source_file: file_path.to_path_buf()
+            source_file: file_path.to_path_buf(),
+            owner: Some(owner).filter(|owner| *owner != DependencyOwner::default()),
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
         });
     }
+
+    // Init attributes and trigger statements are SQF, which can hand out equipment like any script
+    for entity in extract_entities(&content) {
+        if let Some(code) = entity.init() {
+            let owner = entity.name.as_deref().unwrap_or(&entity.class_name);
+            let context = format!("sqm:init:{}:{}", owner, file_path.display());
+            dependencies.extend(sqm_code_references(&code, &context, file_path, config));
+        }
+    }
+    for trigger in extract_mission_objects(&content).triggers {
+        let context = format!("sqm:trigger:{}:{}", trigger.name.as_deref().unwrap_or_default(), file_path.display());
+        for code in trigger.statements() {
+            dependencies.extend(sqm_code_references(code, &context, file_path, config));
+        }
+    }
     Ok(dependencies)
 }
 
-/// Wrapper around the sqf-analyzer crate that converts its output to our format
-pub fn parse_sqf(file_path: &Path) -> Result<Vec<ClassReference>> {
-    debug!("Starting SQF file parse using sqf-analyzer: {}", file_path.display());
+/// Class references of SQF code embedded in mission.sqm.
+///
+/// The code is soft failed, since a broken snippet shouldn't hide the rest of the mission.
+fn sqm_code_references(code: &str, context: &str, file_path: &Path, config: &ConfigContext) -> Vec<ClassReference> {
+    let options = AnalysisOptions { soft_fail: true, ..config.sqf_options.clone() };
+    match parser_sqf::parse_code_with_options(code, &options) {
+        Ok(references) => references.into_iter()
+            .map(|reference| ClassReference {
+                reference_type: if reference.removed { ReferenceType::Removal } else { ReferenceType::Direct },
+                class_name: config.interner.intern(&reference.class_name),
+                context: context.to_string(),
+                source_file: file_path.to_path_buf(),
+                owner: None,
+                location: None,
+                confidence: reference.confidence,
+                count: reference.count,
+                count_range: reference.count_range,
+            })
+            .collect(),
+        Err(e) => {
+            debug!("Failed to analyze SQF in {} ({}): {:?}", file_path.display(), context, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Read a mission.sqm as text, decoding binarized files
+pub(crate) fn read_sqm(vfs: &dyn Vfs, file_path: &Path) -> Result<String> {
+    let bytes = vfs.read(file_path)
+        .context("Failed to read SQM file")?;
+    decode_sqm(&bytes)
+        .map_err(|e| anyhow!("Failed to decode {}: {}", file_path.display(), e))
+}
+
+/// Read a SQF file as text
+fn read_sqf(vfs: &dyn Vfs, file_path: &Path) -> Result<String> {
+    let bytes = vfs.read(file_path)
+        .context("Failed to read SQF file")?;
+    Ok(decode_content(&bytes).content)
+}
+
+/// Parse a SQF file read through a [`Vfs`] in memory, so uploads and
+/// missions inside archives are analyzed like files on disk.
+///
+/// One analysis finds the classes its commands and functions are given, the
+/// assets it sets and the scripts it runs.
+pub fn parse_sqf(vfs: &dyn Vfs, file_path: &Path, config: &ConfigContext) -> Result<ParsedFile> {
+    debug!("Starting SQF file parse: {}", file_path.display());
     
-    // First, run with equipment functions to get direct equipment references
-    let equipment_args = Args {
-        path: file_path.to_path_buf(),
-        output: "text".to_string(),
-        full_paths: false,
-        include_vars: false,
-        equipment_only: false,
-        functions: Some("addItemToUniform,addItemToVest,addItemToBackpack,addItem,addWeapon,addWeaponItem,addMagazine,addMagazineCargo,addWeaponCargo,addItemCargo,forceAddUniform,addVest,addHeadgear,addGoggles,addBackpack,ace_arsenal_fnc_initBox".to_string()),
+    let content = read_sqf(vfs, file_path)?;
+    let virtual_name = file_path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "script.sqf".to_string());
+    let Some(result) = parser_sqf::analyze_str_with_options(&content, &virtual_name, &config.sqf_options)
+        .map_err(|e| anyhow!("Failed to parse SQF file {}: {:?}", file_path.display(), e))?
+    else {
+        return Ok(ParsedFile::default());
     };
     
-    // Use the sqf-analyzer crate to analyze the file for equipment
-    let mut items = analyze_sqf(&equipment_args)
-        .map_err(|e| anyhow!("Failed to parse SQF file with sqf-analyzer (equipment mode): {:?}", e))?;
-    
-    debug!("Found {} items in SQF file", items.len());
+    debug!("Found {} references in SQF file", result.references.len());
     
-    // Convert HashSet<String> to Vec<ClassReference>
-    let dependencies: Vec<ClassReference> = items.into_iter()
-        .map(|item| {
-            let reference_type = ReferenceType::Direct;
-            ClassReference {
-                class_name: item,
-                reference_type,
-                context: format!("sqf:equipment:{}", file_path.display()),
-                source_file: file_path.to_path_buf()
-            }
+    let context = format!("sqf:equipment:{}", file_path.display());
+    let references = result.references.into_iter()
+        .map(|reference| ClassReference {
+            reference_type: if reference.removed { ReferenceType::Removal } else { ReferenceType::Direct },
+            class_name: config.interner.intern(&reference.class_name),
+            context: context.clone(),
+            source_file: file_path.to_path_buf(),
+            owner: None,
+            location: reference.location.map(|location| SourceLocation { line: location.line, column: location.column }),
+            confidence: reference.confidence,
+            count: reference.count,
+            count_range: reference.count_range,
         })
         .collect();
-    
-    debug!("Converted {} SQF items to dependencies", dependencies.len());
-    Ok(dependencies)
+    let assets = result.assets.into_iter()
+        .map(|asset| AssetReference {
+            path: asset.path,
+            context: asset.context,
+            source_file: file_path.to_path_buf(),
+        })
+        .collect();
+    Ok(ParsedFile { references, assets, script_paths: result.script_paths })
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use log::{debug, warn};
+use regex::Regex;
+
+/// Number of lines kept on each side of the failure line in a snippet
+const SNIPPET_CONTEXT_LINES: usize = 5;
+
+/// Copy a file that failed to parse into `<output_dir>/repro/` for bug reports.
+///
+/// The repro folder holds the original file, the diagnostic, and a trimmed
+/// snippet around the failing line when the diagnostic mentions one.
+/// Returns the folder the repro was written to.
+pub fn write_repro(
+    file_path: &Path,
+    mission_dir: &Path,
+    diagnostic: &str,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    let repro_dir = output_dir.join("repro").join(repro_name(file_path, mission_dir));
+    fs::create_dir_all(&repro_dir)
+        .map_err(|e| anyhow!("Failed to create repro directory {}: {}", repro_dir.display(), e))?;
+
+    let file_name = file_path.file_name()
+        .ok_or_else(|| anyhow!("Invalid file path: {}", file_path.display()))?;
+    fs::copy(file_path, repro_dir.join(file_name))
+        .map_err(|e| anyhow!("Failed to copy {} to repro: {}", file_path.display(), e))?;
+
+    fs::write(repro_dir.join("diagnostic.txt"), format!("{}\n{}\n", file_path.display(), diagnostic))?;
+
+    if let Some(line) = failure_line(diagnostic) {
+        let content = fs::read(file_path)?;
+        let snippet = snippet_around(&String::from_utf8_lossy(&content), line);
+        fs::write(repro_dir.join("snippet.txt"), snippet)?;
+    }
+
+    debug!("Wrote parser repro for {} to {}", file_path.display(), repro_dir.display());
+    Ok(repro_dir)
+}
+
+/// Capture a repro if an output directory is configured, logging instead of failing
+pub(crate) fn capture_repro(file_path: &Path, mission_dir: &Path, diagnostic: &str, output_dir: Option<&Path>) {
+    if let Some(output_dir) = output_dir
+        && let Err(e) = write_repro(file_path, mission_dir, diagnostic, output_dir)
+    {
+        warn!("Failed to write repro for {}: {}", file_path.display(), e);
+    }
+}
+
+/// Build a unique, flat folder name from the mission and the file's path inside it
+fn repro_name(file_path: &Path, mission_dir: &Path) -> String {
+    let mission_name = mission_dir.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let relative = file_path.strip_prefix(mission_dir).unwrap_or(file_path);
+    let relative = relative.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("__");
+    format!("{}__{}", mission_name, relative)
+}
+
+/// Find the 1-based line number a diagnostic points at, if it mentions one
+fn failure_line(diagnostic: &str) -> Option<usize> {
+    let pattern = Regex::new(r"(?i)line[:\s]+(\d+)|:(\d+):\d+").ok()?;
+    let captures = pattern.captures(diagnostic)?;
+    captures.get(1).or_else(|| captures.get(2))?
+        .as_str().parse().ok()
+        .filter(|&line| line > 0)
+}
+
+/// Take the lines surrounding a 1-based line number, prefixed with their numbers
+fn snippet_around(content: &str, line: usize) -> String {
+    let start = line.saturating_sub(SNIPPET_CONTEXT_LINES + 1);
+    content.lines()
+        .enumerate()
+        .skip(start)
+        .take(SNIPPET_CONTEXT_LINES * 2 + 1)
+        .map(|(idx, text)| {
+            let marker = if idx + 1 == line { ">" } else { " " };
+            format!("{}{:5} | {}\n", marker, idx + 1, text)
+        })
+        .collect()
+}
@@ -1,43 +1,243 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use anyhow::{Result, anyhow};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use log::{debug, info, warn};
 use rayon::prelude::*;
 
-use crate::types::{MissionScannerConfig, MissionResults};
-use super::{collector, parser};
+use crate::description_ext::{is_description_ext, parse_description_ext};
+use crate::interner::Interner;
+use crate::metadata::MissionMetadata;
+use crate::modset::RequiredMods;
+use crate::pbo::{is_pbo, Pbo};
+use crate::portable::map_paths;
+use crate::progress::ProgressEvent;
+use crate::types::{ClassReference, CuratorSetup, MissionFootprint, MissionScannerConfig, MissionResults, QuarantinedFile, ScriptCall};
+use crate::vfs::{OsFs, Vfs};
+use super::{collector, parser, repro, ConfigContext, FileCache, ParsedFile};
 
 /// Scan a single mission directory with configuration
 pub async fn scan_mission(
     mission_dir: &Path,
     threads: usize,
     config: &MissionScannerConfig
+) -> Result<MissionResults> {
+    scan_mission_with_vfs(OsFs::for_config(config), mission_dir, threads, config).await
+}
+
+/// Scan every mission below a directory that is selected by the configured filter
+pub async fn scan_missions(dir: &Path, config: &MissionScannerConfig) -> Result<Vec<MissionResults>> {
+    scan_missions_with_vfs(OsFs::for_config(config), dir, config).await
+}
+
+/// Scan every mission below a directory read through a [`Vfs`] that is
+/// selected by the configured filter.
+///
+/// Missions ruled out by their terrain or metadata are skipped before
+/// scanning; the player count is checked against the slot count afterwards.
+pub async fn scan_missions_with_vfs<V: Vfs + Clone + 'static>(
+    vfs: &V,
+    dir: &Path,
+    config: &MissionScannerConfig
+) -> Result<Vec<MissionResults>> {
+    let results: Vec<_> = scan_missions_stream_with_vfs(vfs, dir, config).try_collect().await?;
+    info!("Selected {} missions in {}", results.len(), dir.display());
+    Ok(results)
+}
+
+/// Stream the results of every mission below a directory selected by the
+/// configured filter, each one as soon as it has been scanned
+pub fn scan_missions_stream<'a>(
+    dir: &'a Path,
+    config: &'a MissionScannerConfig
+) -> impl Stream<Item = Result<MissionResults>> + 'a {
+    scan_missions_stream_with_vfs(OsFs::for_config(config), dir, config)
+}
+
+/// Stream the results of every selected mission below a directory read through a [`Vfs`].
+///
+/// Both mission directories and packed missions (`.pbo` files) are scanned.
+/// Missions are scanned one at a time as the stream is polled, each on
+/// tokio's blocking pool, so the executor polling the stream stays free to
+/// write out results while later missions are still waiting.
+pub fn scan_missions_stream_with_vfs<'a, V: Vfs + Clone + 'static>(
+    vfs: &'a V,
+    dir: &'a Path,
+    config: &'a MissionScannerConfig
+) -> impl Stream<Item = Result<MissionResults>> + 'a {
+    let targets = {
+        let (vfs, dir, config) = (vfs.clone(), dir.to_path_buf(), config.clone());
+        run_blocking(move || {
+            let mut targets: Vec<PathBuf> = collector::collect_mission_files(&vfs, &dir, &config)?
+                .into_iter()
+                .map(|mission| mission.mission_dir)
+                .collect();
+            targets.extend(collector::find_pbo_files(&vfs, &dir));
+            Ok(targets)
+        })
+    };
+
+    stream::once(targets)
+        .flat_map(|targets| match targets {
+            Ok(targets) => stream::iter(targets).map(Ok).left_stream(),
+            Err(e) => stream::iter([Err(e)]).right_stream(),
+        })
+        .filter_map(move |target| async move {
+            let target = match target {
+                Ok(target) => target,
+                Err(e) => return Some(Err(e)),
+            };
+            let (vfs, config) = (vfs.clone(), config.clone());
+            run_blocking(move || if is_pbo(&target) {
+                scan_packed(&vfs, &target, &config, true)
+            } else {
+                scan_if_selected(&vfs, &target, &config)
+            }).await.transpose()
+        })
+}
+
+/// Scan a packed mission (`.pbo`).
+///
+/// The PBO is unpacked to a temporary directory for the scan. Paths in the
+/// results point inside the PBO, e.g. `co40_raid.Altis.pbo/mission.sqm`.
+pub async fn scan_pbo(pbo_path: &Path, config: &MissionScannerConfig) -> Result<MissionResults> {
+    let (pbo_path, config) = (pbo_path.to_path_buf(), config.clone());
+    run_blocking(move || {
+        scan_packed(OsFs::for_config(&config), &pbo_path, &config, false)?
+            .ok_or_else(|| anyhow!("Mission {} was not scanned", pbo_path.display()))
+    }).await
+}
+
+/// Run the blocking part of a scan on tokio's blocking pool.
+///
+/// Scans read files, parse on a rayon pool and sleep between read retries,
+/// none of which may happen on the thread polling the scan.
+async fn run_blocking<T: Send + 'static>(scan: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(scan).await
+        .map_err(|e| anyhow!("Scan task failed: {}", e))?
+}
+
+/// Unpack a PBO read through a [`Vfs`] and scan it, optionally only if the configured filter selects it
+fn scan_packed(
+    vfs: &dyn Vfs,
+    pbo_path: &Path,
+    config: &MissionScannerConfig,
+    selected_only: bool,
+) -> Result<Option<MissionResults>> {
+    let bytes = vfs.read(pbo_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", pbo_path.display(), e))?;
+    let pbo = Pbo::from_bytes(&bytes)
+        .map_err(|e| anyhow!("Invalid PBO {}: {}", pbo_path.display(), e))?;
+    let mission_name = pbo_path.file_stem()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid PBO file name: {}", pbo_path.display()))?;
+
+    static UNPACK_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let unpack_dir = std::env::temp_dir().join(format!("mission_scanner-{}-{}",
+        std::process::id(), UNPACK_COUNTER.fetch_add(1, Ordering::Relaxed)));
+    let mission_dir = unpack_dir.join(mission_name);
+    debug!("Unpacking {} to {}", pbo_path.display(), mission_dir.display());
+
+    let result = match pbo.extract(&mission_dir) {
+        Ok(_) if selected_only => scan_if_selected(OsFs::for_config(config), &mission_dir, config),
+        Ok(_) => scan_mission_blocking(OsFs::for_config(config), &mission_dir, config.max_threads, config).map(Some),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = std::fs::remove_dir_all(&unpack_dir) {
+        warn!("Failed to remove {}: {}", unpack_dir.display(), e);
+    }
+
+    // The unpacked files are gone, so point the results at the PBO instead
+    Ok(result?.map(|result| {
+        let mut result = map_paths(result, |path| match path.strip_prefix(&mission_dir) {
+            Ok(relative) if relative.as_os_str().is_empty() => pbo_path.to_path_buf(),
+            Ok(relative) => pbo_path.join(relative),
+            Err(_) => path,
+        });
+        result.metadata = Some(MissionMetadata::for_pbo(vfs, pbo_path, result.metadata.take()));
+        result
+    }))
+}
+
+/// Scan a mission if the configured filter selects it
+fn scan_if_selected(
+    vfs: &dyn Vfs,
+    mission_dir: &Path,
+    config: &MissionScannerConfig
+) -> Result<Option<MissionResults>> {
+    let metadata = load_metadata(vfs, mission_dir);
+    if !config.filter.may_match(mission_dir, metadata.as_ref()) {
+        debug!("Skipping {}, not selected by filter", mission_dir.display());
+        return Ok(None);
+    }
+
+    let result = scan_mission_blocking(vfs, mission_dir, config.max_threads, config)?;
+    if !config.filter.matches(&result) {
+        debug!("Dropping {}, not selected by filter", mission_dir.display());
+        return Ok(None);
+    }
+    Ok(Some(result))
+}
+
+/// Scan a single mission directory read through a [`Vfs`].
+///
+/// The scan runs on tokio's blocking pool, so the filesystem and the
+/// configuration are cloned into it; clones of a configuration share its
+/// interner and progress sink.
+pub async fn scan_mission_with_vfs<V: Vfs + Clone + 'static>(
+    vfs: &V,
+    mission_dir: &Path,
+    threads: usize,
+    config: &MissionScannerConfig
+) -> Result<MissionResults> {
+    let (vfs, mission_dir, config) = (vfs.clone(), mission_dir.to_path_buf(), config.clone());
+    run_blocking(move || scan_mission_blocking(&vfs, &mission_dir, threads, &config)).await
+}
+
+/// Scan a single mission directory, blocking until it is done
+fn scan_mission_blocking(
+    vfs: &dyn Vfs,
+    mission_dir: &Path,
+    threads: usize,
+    config: &MissionScannerConfig
 ) -> Result<MissionResults> {
     info!("Scanning mission directory: {}", mission_dir.display());
     debug!("Using {} threads", threads);
     debug!("Configuration: {:?}", config);
     
-    // Verify mission directory exists and is readable
-    if !mission_dir.exists() {
+    // Verify mission directory exists
+    if !vfs.is_dir(mission_dir) {
         return Err(anyhow!("Mission directory does not exist: {}", mission_dir.display()));
     }
     
-    if let Err(e) = std::fs::read_dir(mission_dir) {
-        return Err(anyhow!("Mission directory is not readable: {} - {}", mission_dir.display(), e));
-    }
-    
     // Get mission name from directory
     let mission_name = mission_dir.file_name()
         .and_then(|n| n.to_str())
         .ok_or_else(|| anyhow!("Invalid mission directory name"))?
         .to_string();
     
+    let footprint = measure_footprint(vfs, mission_dir, config);
+    let metadata = load_metadata(vfs, mission_dir);
+
     // Find mission files
-    let sqm_file = collector::find_mission_file(mission_dir)?;
-    let sqf_files = collector::find_script_files(mission_dir, &config.file_extensions)?;
-    let cpp_files = collector::find_code_files(mission_dir, &config.file_extensions)?;
+    let sqm_file = collector::find_mission_file(vfs, mission_dir, config)?
+        .filter(|file| config.is_file_selected(mission_dir, file));
+    let mut sqf_files = collector::find_script_files(vfs, mission_dir, config)?;
+    let mut cpp_files = collector::find_code_files(vfs, mission_dir, config)?;
+    collector::select_files(config, mission_dir, &mut sqf_files);
+    collector::select_files(config, mission_dir, &mut cpp_files);
+    let mut extractor_files = collector::find_extractor_files(vfs, mission_dir, config);
+    collector::select_files(config, mission_dir, &mut extractor_files);
+    // Directory walks don't have a stable order, the results should
+    sqf_files.sort();
+    cpp_files.sort();
+    extractor_files.sort();
     
-    if sqm_file.is_none() && sqf_files.is_empty() && cpp_files.is_empty() {
+    if sqm_file.is_none() && sqf_files.is_empty() && cpp_files.is_empty() && extractor_files.is_empty() {
         warn!("No mission files found in {}", mission_dir.display());
         return Ok(MissionResults {
             mission_name,
@@ -46,6 +246,15 @@ pub async fn scan_mission(
             sqf_files: Vec::new(),
             cpp_files: Vec::new(),
             class_dependencies: Vec::new(),
+            footprint,
+            asset_references: Vec::new(),
+            quarantined_files: Vec::new(),
+            metadata,
+            playable_slots: 0,
+            required_addons: Vec::new(),
+            required_mods: RequiredMods::default(),
+            script_calls: Vec::new(),
+            curator: None,
         });
     }
     
@@ -55,37 +264,100 @@ pub async fn scan_mission(
         cpp_files.len());
     
     let mut dependencies = Vec::new();
-    
-    // Process mission.sqm if present
-    if let Some(sqm_file) = &sqm_file {
-        debug!("Processing mission.sqm: {}", sqm_file.display());
-        match parser::parse_file(sqm_file) {
-            Ok(mut deps) => {
-                debug!("Found {} dependencies in SQM file", deps.len());
-                dependencies.append(&mut deps);
-            },
-            Err(e) => warn!("Failed to parse SQM file {}: {}", sqm_file.display(), e),
+    let mut quarantined_files = Vec::new();
+    let (playable_slots, required_addons, curator) = sqm_file.as_deref()
+        .map(|file| read_sqm_summary(vfs, file))
+        .unwrap_or_default();
+    // Config files are preprocessed one by one, so share their macros up front
+    let context = ConfigContext {
+        sqf_options: config.sqf_options.clone(),
+        interner: config.interner.clone(),
+        ..ConfigContext::for_mission(vfs, mission_dir, &cpp_files)
+    };
+    let description = cpp_files.iter()
+        .find(|file| is_description_ext(file))
+        .and_then(|file| parse_description_ext(vfs, file, &context)
+            .inspect_err(|e| debug!("Failed to read description.ext of {}: {:#}", mission_dir.display(), e))
+            .ok());
+    // Declared functions are scripts even where the directory walk didn't pick them up
+    if let Some(description) = &description
+        && config.file_extensions.iter().any(|ext| ext == "sqf")
+    {
+        let mut function_files = collector::find_function_files(vfs, mission_dir, &description.functions);
+        collector::select_files(config, mission_dir, &mut function_files);
+        for file in function_files {
+            if !sqf_files.contains(&file) {
+                sqf_files.push(file);
+            }
         }
+        sqf_files.sort();
     }
-    
-    // Process SQF files in parallel
-    let sqf_deps: Vec<_> = sqf_files.par_iter()
-        .flat_map(|file| {
-            debug!("Processing SQF file: {}", file.display());
-            parser::parse_file(file).unwrap_or_default()
-        })
-        .collect();
-    dependencies.extend(sqf_deps);
-    
-    // Process CPP/HPP files in parallel
-    let cpp_deps: Vec<_> = cpp_files.par_iter()
-        .flat_map(|file| {
-            debug!("Processing CPP/HPP file: {}", file.display());
-            parser::parse_file(file).unwrap_or_default()
+
+    // Parse every file of the mission in parallel, on a pool bounded by the thread count.
+    // Results are collected in file order, so they don't depend on scheduling.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.max(1))
+        .build()
+        .map_err(|e| anyhow!("Failed to create thread pool: {}", e))?;
+    let mut files: Vec<&PathBuf> = sqm_file.iter().chain(&sqf_files).chain(&cpp_files).collect();
+    // Files only custom extractors read come last
+    let parsed_files = files.len();
+    for file in &extractor_files {
+        if !files[..parsed_files].contains(&file) {
+            files.push(file);
+        }
+    }
+    config.progress.emit(ProgressEvent::MissionStarted { mission: mission_name.clone(), files: files.len() });
+    let results: Vec<_> = pool.install(|| files.par_iter()
+        .enumerate()
+        .map(|(index, file)| {
+            debug!("Processing file: {}", file.display());
+            let result = if index < parsed_files {
+                parse_or_capture(vfs, file, mission_dir, &context, config)
+            } else {
+                Ok(ParsedFile::default())
+            };
+            let result = result.map(|mut parsed| {
+                parsed.references.extend(run_extractors(vfs, file, config));
+                parsed
+            });
+            config.progress.emit(ProgressEvent::FileParsed { mission: mission_name.clone(), file: file.to_path_buf() });
+            result
         })
-        .collect();
-    dependencies.extend(cpp_deps);
+        .collect());
+
+    // Scripts give their textures and the scripts they run from the same analysis as their references
+    let mut asset_references = Vec::new();
+    let mut script_paths = Vec::new();
+    for (file, result) in files.iter().zip(results) {
+        match result {
+            Ok(parsed) => {
+                dependencies.extend(parsed.references);
+                asset_references.extend(parsed.assets);
+                if !parsed.script_paths.is_empty() {
+                    script_paths.push((file.to_path_buf(), parsed.script_paths));
+                }
+            }
+            Err(quarantined) => quarantined_files.push(quarantined),
+        }
+    }
+    dependencies.retain(|dep| config.keeps(dep));
+    let mut required_mods = config.mod_prefixes.required_mods(&dependencies);
+    // Addons given to Zeus are loaded even when nothing references their classes
+    if let Some(curator) = &curator {
+        required_mods.add_addons(&config.mod_prefixes, &curator.added_addons);
+    }
+    if !quarantined_files.is_empty() {
+        warn!("Quarantined {} files in {} after repeated read errors", quarantined_files.len(), mission_name);
+    }
     
+    // Sound and music files declared in description.ext
+    if let Some(description) = description {
+        asset_references.extend(description.assets);
+    }
+
+    let script_calls = resolve_script_calls(mission_dir, &sqf_files, script_paths);
+
     debug!("Total of {} dependencies found for mission {}", 
         dependencies.len(), mission_name);
     
@@ -98,6 +370,7 @@ pub async fn scan_mission(
     for class in &unique_classes {
         debug!("  - {}", class);
     }
+    config.progress.emit(ProgressEvent::MissionFinished { mission: mission_name.clone(), dependencies: dependencies.len() });
     
     Ok(MissionResults {
         mission_name,
@@ -106,5 +379,177 @@ pub async fn scan_mission(
         sqf_files,
         cpp_files,
         class_dependencies: dependencies,
+        footprint,
+        asset_references,
+        quarantined_files,
+        metadata,
+        playable_slots,
+        required_addons,
+        required_mods,
+        script_calls,
+        curator,
+    })
+}
+
+/// Parse a file, capturing a repro of it if parsing fails and repros are enabled.
+///
+/// IO errors are retried with backoff; a file that keeps failing to read is
+/// returned as quarantined instead.
+fn parse_or_capture(
+    vfs: &dyn Vfs,
+    file: &Path,
+    mission_dir: &Path,
+    context: &ConfigContext,
+    config: &MissionScannerConfig,
+) -> Result<ParsedFile, QuarantinedFile> {
+    let dependencies = if parser::is_config_file(file) { Some(context.digest.clone()) } else { context.script_digest() };
+    let cache = config.cache_dir.as_deref().filter(|_| dependencies.is_some()).map(FileCache::new);
+    let hash = cache.as_ref().and_then(|_| vfs.read(file).ok()).map(|content| {
+        FileCache::hash(&content, dependencies.as_deref().unwrap_or_default().as_bytes())
+    });
+    if let (Some(cache), Some(hash)) = (&cache, &hash)
+        && let Some(parsed) = cache.get(file, hash)
+    {
+        debug!("Using cached dependencies of {}", file.display());
+        return Ok(intern_references(parsed, &context.interner));
+    }
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let error = match parser::parse_file_in_mission(vfs, file, context) {
+            Ok(parsed) => {
+                if let (Some(cache), Some(hash)) = (&cache, &hash)
+                    && let Err(e) = cache.insert(file, hash, &parsed)
+                {
+                    warn!("Failed to cache dependencies of {}: {:#}", file.display(), e);
+                }
+                return Ok(parsed);
+            }
+            Err(e) => e,
+        };
+
+        if !is_io_failure(vfs, file, &error) {
+            let output_dir = config.output_dir.as_deref().filter(|_| config.extract_repros);
+            repro::capture_repro(file, mission_dir, &format!("{:#}", error), output_dir);
+            return Ok(ParsedFile::default());
+        }
+
+        if attempts > config.read_retries {
+            return Err(QuarantinedFile {
+                path: file.to_path_buf(),
+                error: format!("{:#}", error),
+                attempts,
+            });
+        }
+
+        let backoff = config.retry_backoff_ms.saturating_mul(1 << (attempts - 1).min(16));
+        debug!("Retrying {} in {}ms after read error: {:#}", file.display(), backoff, error);
+        std::thread::sleep(Duration::from_millis(backoff));
+    }
+}
+
+/// Share the class names of references read back from the cache with the rest of the scan
+fn intern_references(mut parsed: ParsedFile, interner: &Interner) -> ParsedFile {
+    for reference in &mut parsed.references {
+        reference.class_name = interner.intern(&reference.class_name);
+    }
+    parsed
+}
+
+/// References custom extractors find in a file, logging their failures
+fn run_extractors(vfs: &dyn Vfs, file: &Path, config: &MissionScannerConfig) -> Vec<ClassReference> {
+    let mut extractors = config.extractors.for_file(file).peekable();
+    if extractors.peek().is_none() {
+        return Vec::new();
+    }
+    let content = match vfs.read_to_string(file) {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Failed to read {} for custom extractors: {}", file.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut references = Vec::new();
+    for extractor in extractors {
+        match extractor.extract(&content) {
+            Ok(found) => references.extend(found.into_iter().map(|mut reference| {
+                reference.source_file = file.to_path_buf();
+                reference.class_name = config.interner.intern(&reference.class_name);
+                reference
+            })),
+            Err(e) => warn!("Extractor {} failed on {}: {:#}", extractor.name(), file.display(), e),
+        }
+    }
+    references
+}
+
+/// Check if a parse failure was caused by the file not being readable rather than its content
+fn is_io_failure(vfs: &dyn Vfs, file: &Path, error: &anyhow::Error) -> bool {
+    let is_transient = |e: &io::Error| e.kind() != io::ErrorKind::InvalidData;
+    // SQF parser errors only keep the IO error in their message, so check the file can still be read
+    error.chain().filter_map(|cause| cause.downcast_ref::<io::Error>()).any(is_transient)
+        || vfs.read(file).err().is_some_and(|e| is_transient(&e))
+}
+
+/// Find the scripts each script of a mission runs or compiles.
+///
+/// Paths are written from the mission root, usually with backslashes, and
+/// are matched against the mission's scripts ignoring case. Paths to files
+/// outside the mission, such as addon functions, are left out.
+fn resolve_script_calls(mission_dir: &Path, sqf_files: &[PathBuf], script_paths: Vec<(PathBuf, Vec<String>)>) -> Vec<ScriptCall> {
+    let scripts: HashMap<String, &PathBuf> = sqf_files.iter()
+        .filter_map(|file| {
+            let relative = file.strip_prefix(mission_dir).ok()?;
+            Some((collector::normalize_mission_path(&relative.to_string_lossy()), file))
+        })
+        .collect();
+    script_paths.into_iter()
+        .flat_map(|(caller, paths)| paths.into_iter()
+            .filter_map(|path| scripts.get(&collector::normalize_mission_path(&path)))
+            .map(|&script| ScriptCall { caller: caller.clone(), script: script.clone() })
+            .collect::<Vec<_>>())
+        .collect()
+}
+
+/// Count the playable units of a mission.sqm, list the addons it requires and
+/// read its Zeus setup, treating unreadable files as having none of them
+fn read_sqm_summary(vfs: &dyn Vfs, sqm_file: &Path) -> (usize, Vec<String>, Option<CuratorSetup>) {
+    match parser::read_sqm(vfs, sqm_file) {
+        Ok(content) => {
+            let playable_slots = parser_sqm::extract_entities(&content).iter()
+                .filter(|entity| entity.is_playable())
+                .count();
+            let curator = Some(parser_sqm::extract_curators(&content))
+                .filter(|setup| setup.is_present())
+                .map(CuratorSetup::from);
+            (playable_slots, parser_sqm::extract_required_addons(&content), curator)
+        }
+        Err(e) => {
+            debug!("Failed to read slots, addons and Zeus of {}: {}", sqm_file.display(), e);
+            (0, Vec::new(), None)
+        }
+    }
+}
+
+/// Load a mission's metadata sidecar, logging invalid ones instead of failing the scan
+fn load_metadata(vfs: &dyn Vfs, mission_dir: &Path) -> Option<MissionMetadata> {
+    MissionMetadata::load(vfs, mission_dir).unwrap_or_else(|e| {
+        warn!("Ignoring metadata of {}: {}", mission_dir.display(), e);
+        None
     })
+}
+
+/// Measure a mission's disk footprint and flag it against the configured size threshold
+fn measure_footprint(vfs: &dyn Vfs, mission_dir: &Path, config: &MissionScannerConfig) -> MissionFootprint {
+    let mut footprint = collector::measure_mission_footprint(vfs, mission_dir);
+    if let Some(max_size) = config.max_mission_size
+        && footprint.total_bytes > max_size
+    {
+        footprint.exceeds_threshold = true;
+        warn!("Mission {} is {} bytes, above the {} byte limit",
+            mission_dir.display(), footprint.total_bytes, max_size);
+    }
+    footprint
 }
\ No newline at end of file
@@ -0,0 +1,51 @@
+//! JSON Schema documents for the scanner's output types
+//!
+//! Lets consumers in other languages generate bindings against the JSON the
+//! scanner writes. Only available with the `schema` feature. The reports and
+//! scan output carry the format version they conform to in `schema_version`,
+//! see [`REPORT_SCHEMA_VERSION`](crate::report::REPORT_SCHEMA_VERSION).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::analysis::FileAnalysis;
+use crate::classdb::{ClassEntry, MissingClass};
+use crate::explain::ClassExplanation;
+use crate::report::{MissionDiff, MissionReport, ReportFile, ScanOutput, ScanSummary};
+use crate::types::{ClassReference, MissionResults};
+
+/// Generate the schema of every public output type, keyed by type name
+pub fn export() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("MissionResults", schema_for!(MissionResults)),
+        ("ClassReference", schema_for!(ClassReference)),
+        ("ClassExplanation", schema_for!(ClassExplanation)),
+        ("FileAnalysis", schema_for!(FileAnalysis)),
+        ("MissionReport", schema_for!(MissionReport)),
+        ("ReportFile", schema_for!(ReportFile)),
+        ("ScanSummary", schema_for!(ScanSummary)),
+        ("ScanOutput", schema_for!(ScanOutput)),
+        ("MissionDiff", schema_for!(MissionDiff)),
+        ("ClassEntry", schema_for!(ClassEntry)),
+        ("MissingClass", schema_for!(MissingClass)),
+    ])
+}
+
+/// Write every schema to `<dir>/<TypeName>.schema.json`
+pub fn export_to_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|e| anyhow!("Failed to create schema directory {}: {}", dir.display(), e))?;
+
+    for (name, schema) in export() {
+        let path = dir.join(format!("{}.schema.json", name));
+        let content = serde_json::to_string_pretty(&schema)?;
+        fs::write(&path, content)
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
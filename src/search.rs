@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use regex::{Regex, RegexBuilder};
+
+use crate::scanner::scan_mission;
+use crate::types::{ClassReference, MissionScannerConfig};
+
+/// Pattern used to match class names
+///
+/// Both forms match case-insensitively since Arma 3 class names are
+/// case-insensitive.
+#[derive(Debug, Clone)]
+pub enum ClassPattern {
+    /// Shell-style glob supporting `*` and `?`
+    Glob(String),
+    /// Regular expression matched anywhere in the class name
+    Regex(Regex),
+}
+
+impl ClassPattern {
+    /// Create a glob pattern
+    pub fn glob(pattern: &str) -> Self {
+        ClassPattern::Glob(pattern.to_lowercase())
+    }
+
+    /// Create a regex pattern
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid regex '{}': {}", pattern, e))?;
+        Ok(ClassPattern::Regex(regex))
+    }
+
+    /// Check if a class name matches this pattern
+    pub fn matches(&self, class_name: &str) -> bool {
+        match self {
+            ClassPattern::Glob(pattern) => glob_match(pattern, &class_name.to_lowercase()),
+            ClassPattern::Regex(regex) => regex.is_match(class_name),
+        }
+    }
+}
+
+/// Glob matching paths from a mission's root, e.g. `**/briefing/**` or `loadouts/*.sqf`
+///
+/// `*` and `?` match within a path segment and `**` matches any number of
+/// segments. Matched ignoring case with `/` and `\` as separators, since
+/// Arma 3 paths are case-insensitive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<String>,
+}
+
+impl PathPattern {
+    /// Create a path glob
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern.replace('\\', "/")
+            .to_lowercase()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self { segments }
+    }
+
+    /// Check if a path relative to the mission root matches this pattern
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/").to_lowercase();
+        let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+        segments_match(&self.segments, &segments)
+    }
+}
+
+/// Match glob segments against path segments, `**` matching any number of them
+fn segments_match(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => (0..=path.len()).any(|skip| segments_match(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first()
+            .is_some_and(|(segment, path_rest)| glob_match(first, segment) && segments_match(rest, path_rest)),
+    }
+}
+
+/// Match a glob pattern with `*` and `?` wildcards against a string
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern and the text index it matched from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Find class references matching a pattern in a mission directory.
+///
+/// Files are run through the regular parsers, so comments and strings that are
+/// not used as classes are not reported the way a plain text search would.
+pub async fn find_class_references(
+    dir: &Path,
+    pattern: &ClassPattern,
+    config: &MissionScannerConfig,
+) -> Result<Vec<ClassReference>> {
+    let result = scan_mission(dir, config.max_threads, config).await?;
+
+    Ok(result.class_dependencies.into_iter()
+        .filter(|dep| pattern.matches(&dep.class_name))
+        .collect())
+}
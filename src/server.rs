@@ -0,0 +1,233 @@
+//! HTTP service for scanning uploaded missions
+//!
+//! - `POST /scan` takes a zip of one or more mission folders, scans them and
+//!   returns `{"id": ..., "missions": ...}`
+//! - `GET /report/{id}` returns the [`ScanReport`] for a previous upload, or
+//!   with `?format=html` or `?format=markdown` the report in that format
+//!
+//! The last [`MAX_REPORTS`] reports are kept in memory, older ones are
+//! dropped. Paths in reports are relative to the root of the uploaded zip.
+//! With a class database, reports list the classes missing from it.
+//!
+//! Only available with the `server` feature.
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, anyhow};
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use log::{info, warn};
+use serde::{Serialize, Deserialize};
+
+use crate::classdb::{ClassDatabase, MissingClass};
+use crate::manifest::{check_manifest, ManifestDrift, MissionManifest};
+use crate::portable::map_paths;
+use crate::report::{to_html, to_markdown};
+use crate::scanner::{collect_mission_files, scan_mission};
+use crate::types::{MissionResults, MissionScannerConfig};
+use crate::vfs::OsFs;
+
+/// Largest accepted upload in bytes
+pub const MAX_UPLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Largest total size of the files in an upload once extracted, in bytes
+pub const MAX_EXTRACTED_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Most files and directories an upload may hold
+pub const MAX_ZIP_ENTRIES: usize = 10_000;
+
+/// Number of reports kept, the oldest is dropped when another one is added
+pub const MAX_REPORTS: usize = 64;
+
+/// Entries of the markdown report's lists
+const MARKDOWN_TOP: usize = 10;
+
+/// Results of scanning one upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanReport {
+    /// Id the report is served under
+    pub id: u64,
+    /// Scan results of each mission found in the upload
+    pub missions: Vec<MissionResults>,
+    /// Manifest drift of missions that ship a manifest.toml, by mission name
+    pub manifest_drift: BTreeMap<String, ManifestDrift>,
+    /// Classes missing from the server's class database, by mission name.
+    /// Empty when the server has no class database
+    #[serde(default)]
+    pub missing_classes: BTreeMap<String, Vec<MissingClass>>,
+}
+
+/// Format of a report served by `GET /report/{id}`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Html,
+    Markdown,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportQuery {
+    #[serde(default)]
+    format: ReportFormat,
+}
+
+/// Response to a scan upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanAccepted {
+    /// Id to fetch the report with
+    pub id: u64,
+    /// Number of missions found in the upload
+    pub missions: usize,
+}
+
+/// Shared state of the service
+struct ServerState {
+    config: MissionScannerConfig,
+    database: Option<Arc<ClassDatabase>>,
+    next_id: AtomicU64,
+    /// Reports by id; ids only grow, so the first one is the oldest
+    reports: Mutex<BTreeMap<u64, Arc<ScanReport>>>,
+}
+
+/// Build the service's routes.
+///
+/// With a class database, uploads are validated against it.
+pub fn router(config: MissionScannerConfig, database: Option<ClassDatabase>) -> Router {
+    let state = Arc::new(ServerState {
+        config,
+        database: database.map(Arc::new),
+        next_id: AtomicU64::new(1),
+        reports: Mutex::new(BTreeMap::new()),
+    });
+
+    Router::new()
+        .route("/scan", post(scan_upload))
+        .route("/report/{id}", get(get_report))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE))
+        .with_state(state)
+}
+
+/// Serve the service on an address until the process stops
+pub async fn serve(addr: SocketAddr, config: MissionScannerConfig, database: Option<ClassDatabase>) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .map_err(|e| anyhow!("Failed to bind {}: {}", addr, e))?;
+    info!("Listening on {}", addr);
+    axum::serve(listener, router(config, database)).await
+        .map_err(|e| anyhow!("Server error: {}", e))
+}
+
+async fn scan_upload(
+    State(state): State<Arc<ServerState>>,
+    body: Bytes,
+) -> Result<Json<ScanAccepted>, (StatusCode, String)> {
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let report = scan_zip(id, body, &state.config, state.database.as_deref()).await
+        .map_err(|e| {
+            warn!("Failed to scan upload {}: {:#}", id, e);
+            (StatusCode::BAD_REQUEST, format!("{:#}", e))
+        })?;
+
+    let accepted = ScanAccepted { id, missions: report.missions.len() };
+    let mut reports = state.reports.lock().unwrap();
+    reports.insert(id, Arc::new(report));
+    while reports.len() > MAX_REPORTS {
+        reports.pop_first();
+    }
+    Ok(Json(accepted))
+}
+
+async fn get_report(
+    State(state): State<Arc<ServerState>>,
+    UrlPath(id): UrlPath<u64>,
+    Query(query): Query<ReportQuery>,
+) -> Result<Response, StatusCode> {
+    let report = state.reports.lock().unwrap().get(&id).cloned().ok_or(StatusCode::NOT_FOUND)?;
+    let database = state.database.as_deref();
+    Ok(match query.format {
+        ReportFormat::Json => Json((*report).clone()).into_response(),
+        ReportFormat::Html => Html(to_html(&report.missions, &state.config.mod_prefixes, database)).into_response(),
+        ReportFormat::Markdown => (
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            to_markdown(&report.missions, None, database, MARKDOWN_TOP),
+        ).into_response(),
+    })
+}
+
+/// Extract a zip of missions to a temporary directory and scan every mission in it
+async fn scan_zip(id: u64, zip: Bytes, config: &MissionScannerConfig, database: Option<&ClassDatabase>) -> Result<ScanReport> {
+    // Extracting writes the whole upload to disk, so keep it off the executor threads
+    let extract_config = config.clone();
+    let (temp_dir, missions) = tokio::task::spawn_blocking(move || {
+        let temp_dir = tempfile::tempdir()?;
+        extract_zip(&zip, temp_dir.path())?;
+        let missions = collect_mission_files(OsFs::for_config(&extract_config), temp_dir.path(), &extract_config)?;
+        Ok::<_, anyhow::Error>((temp_dir, missions))
+    }).await.map_err(|e| anyhow!("Extracting upload failed: {}", e))??;
+    if missions.is_empty() {
+        return Err(anyhow!("No mission.sqm found in upload"));
+    }
+
+    let mut report = ScanReport {
+        id,
+        missions: Vec::new(),
+        manifest_drift: BTreeMap::new(),
+        missing_classes: BTreeMap::new(),
+    };
+    for mission in missions {
+        let result = scan_mission(&mission.mission_dir, config.max_threads, config).await?;
+
+        if let Some(manifest) = MissionManifest::load(&mission.mission_dir)? {
+            report.manifest_drift.insert(result.mission_name.clone(), check_manifest(&manifest, &result));
+        }
+        if let Some(database) = database {
+            let missing = database.missing_classes(&result);
+            if !missing.is_empty() {
+                report.missing_classes.insert(result.mission_name.clone(), missing);
+            }
+        }
+
+        // The extracted files are removed once the report is built
+        report.missions.push(map_paths(result, |p| relative_to(&p, temp_dir.path())));
+    }
+    Ok(report)
+}
+
+/// Extract an uploaded zip into a directory.
+///
+/// The sizes the entries declare are checked against [`MAX_EXTRACTED_SIZE`]
+/// before anything is written, since the body limit only caps the compressed
+/// size.
+fn extract_zip(zip: &[u8], dir: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip))
+        .map_err(|e| anyhow!("Upload is not a zip archive: {}", e))?;
+    if archive.len() > MAX_ZIP_ENTRIES {
+        return Err(anyhow!("Upload has {} entries, at most {} are allowed", archive.len(), MAX_ZIP_ENTRIES));
+    }
+    let mut total: u64 = 0;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index)
+            .map_err(|e| anyhow!("Invalid zip entry {}: {}", index, e))?;
+        total = total.saturating_add(entry.size());
+    }
+    if total > MAX_EXTRACTED_SIZE {
+        return Err(anyhow!("Upload extracts to {} bytes, at most {} are allowed", total, MAX_EXTRACTED_SIZE));
+    }
+    archive.extract(dir)
+        .map_err(|e| anyhow!("Failed to extract upload: {}", e))
+}
+
+/// Make a path relative to the upload root
+fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    path.strip_prefix(root).map(Path::to_path_buf).unwrap_or_else(|_| path.to_path_buf())
+}
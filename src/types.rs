@@ -1,6 +1,19 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use parser_sqf::AnalysisOptions;
 use serde::{Serialize, Deserialize};
 
+use crate::extractor::Extractors;
+use crate::filter::MissionFilter;
+use crate::interner::{Interner, Symbol};
+use crate::metadata::MissionMetadata;
+use crate::modset::{ModPrefixes, RequiredMods};
+use crate::progress::Progress;
+use crate::search::{ClassPattern, PathPattern};
+
+pub use mission_scanner_types::{Confidence, CountRange, ItemKind, ReferenceType, UsageContext};
+
 /// Default file extensions to scan
 pub const DEFAULT_FILE_EXTENSIONS: &[&str] = &["sqm", "sqf", "cpp", "hpp"];
 
@@ -38,6 +51,106 @@ pub struct MissionScannerConfig {
     pub max_threads: usize,
     /// Extract only specific file extensions (empty = all)
     pub file_extensions: Vec<String>,
+    /// Flag missions whose total size in bytes is above this threshold
+    #[serde(default)]
+    pub max_mission_size: Option<u64>,
+    /// Directory for scanner output such as parser failure repros
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Copy files that fail to parse into `<output_dir>/repro/`
+    #[serde(default)]
+    pub extract_repros: bool,
+    /// Times to retry a file that fails with an IO error before quarantining it
+    #[serde(default = "default_read_retries")]
+    pub read_retries: u32,
+    /// Delay before the first retry in milliseconds, doubled for each further retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Missions to select when scanning a directory of missions
+    #[serde(default)]
+    pub filter: MissionFilter,
+    /// Classes left out of the results, such as vanilla items every mission uses.
+    /// Matched ignoring case
+    #[serde(default)]
+    pub ignore_classes: Vec<String>,
+    /// Globs (`*` and `?`) of classes left out of the results, e.g. `Land_*`
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// Follow symbolic links while collecting files, such as loadout folders
+    /// shared between missions. Each directory is walked once, so link cycles end
+    #[serde(default = "default_true")]
+    pub follow_symlinks: bool,
+    /// Match `mission.sqm` and file extensions ignoring case, as Arma does,
+    /// e.g. `Mission.SQM` or `init.SQF` in missions synced from Windows
+    #[serde(default = "default_true")]
+    pub ignore_case: bool,
+    /// Globs of the files to scan, relative to the mission root, e.g.
+    /// `loadouts/**`. Every file when empty
+    #[serde(default)]
+    pub include_globs: Vec<String>,
+    /// Globs of files left out of the scan, relative to the mission root,
+    /// e.g. `**/briefing/**`. Applied after `include_globs`
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// Directory caching the references parsed from each file, so unchanged
+    /// files aren't parsed again. No caching when unset
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Receiver of progress events while scanning
+    #[serde(skip)]
+    pub progress: Progress,
+    /// Custom extractors reading files the built-in parsers don't know
+    #[serde(skip)]
+    pub extractors: Extractors,
+    /// References less certain than this are left out of the results
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: Confidence,
+    /// How scripts and the code in mission.sqm are analyzed, e.g. strictly for audits
+    #[serde(skip)]
+    pub sqf_options: AnalysisOptions,
+    /// Class name prefixes of mods, used to suggest the mods a mission needs
+    #[serde(default)]
+    pub mod_prefixes: ModPrefixes,
+    /// Class names of the references found so far, shared by every mission
+    /// scanned with this configuration
+    #[serde(skip)]
+    pub interner: Interner,
+}
+
+impl MissionScannerConfig {
+    /// Check if a class is left out of the results by the ignore list or patterns
+    pub fn is_ignored(&self, class_name: &str) -> bool {
+        self.ignore_classes.iter().any(|ignored| ignored.eq_ignore_ascii_case(class_name))
+            || self.ignore_patterns.iter().any(|pattern| ClassPattern::glob(pattern).matches(class_name))
+    }
+
+    /// Check if a file of a mission is scanned according to the include and exclude globs
+    pub fn is_file_selected(&self, mission_dir: &Path, file: &Path) -> bool {
+        let relative = file.strip_prefix(mission_dir).unwrap_or(file).to_string_lossy();
+        (self.include_globs.is_empty() || self.include_globs.iter().any(|glob| PathPattern::new(glob).matches(&relative)))
+            && !self.exclude_globs.iter().any(|glob| PathPattern::new(glob).matches(&relative))
+    }
+
+    /// Check if a reference is kept in the results, by its class and confidence
+    pub fn keeps(&self, reference: &ClassReference) -> bool {
+        reference.confidence >= self.min_confidence && !self.is_ignored(&reference.class_name)
+    }
+}
+
+fn default_read_retries() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    100
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_confidence() -> Confidence {
+    Confidence::Low
 }
 
 impl Default for MissionScannerConfig {
@@ -45,6 +158,25 @@ impl Default for MissionScannerConfig {
         Self {
             max_threads: num_cpus::get(),
             file_extensions: DEFAULT_FILE_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
+            max_mission_size: None,
+            output_dir: None,
+            extract_repros: false,
+            read_retries: default_read_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            filter: MissionFilter::default(),
+            ignore_classes: Vec::new(),
+            ignore_patterns: Vec::new(),
+            follow_symlinks: true,
+            ignore_case: true,
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            cache_dir: None,
+            progress: Progress::default(),
+            extractors: Extractors::default(),
+            min_confidence: default_min_confidence(),
+            sqf_options: AnalysisOptions::default(),
+            mod_prefixes: ModPrefixes::default(),
+            interner: Interner::default(),
         }
     }
 }
@@ -56,6 +188,8 @@ pub struct MissionFileResults {
     pub mission_name: String,
     /// Path to the mission directory
     pub mission_dir: PathBuf,
+    /// Map the mission is made for, from the suffix of its directory name
+    pub map_name: Option<String>,
     /// Path to the mission.sqm file if it exists
     pub sqm_file: Option<PathBuf>,
     /// List of SQF files in the mission
@@ -66,6 +200,7 @@ pub struct MissionFileResults {
 
 /// Result of analyzing mission dependencies
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MissionResults {
     /// Name of the mission
     pub mission_name: String,
@@ -79,32 +214,302 @@ pub struct MissionResults {
     pub cpp_files: Vec<PathBuf>,
     /// List of class dependencies
     pub class_dependencies: Vec<ClassReference>,
+    /// Disk footprint of the mission
+    #[serde(default)]
+    pub footprint: MissionFootprint,
+    /// Texture and material paths the mission's scripts apply to objects
+    #[serde(default)]
+    pub asset_references: Vec<AssetReference>,
+    /// Files skipped because reading them kept failing
+    #[serde(default)]
+    pub quarantined_files: Vec<QuarantinedFile>,
+    /// Contents of the mission's mission.meta.toml, if it has one
+    #[serde(default)]
+    pub metadata: Option<MissionMetadata>,
+    /// Number of playable units in mission.sqm
+    #[serde(default)]
+    pub playable_slots: usize,
+    /// Addons mission.sqm declares in its `addons[]` and `addOnsAuto[]` arrays
+    #[serde(default)]
+    pub required_addons: Vec<String>,
+    /// Mods the mission needs according to its dependencies' prefixes
+    #[serde(default)]
+    pub required_mods: RequiredMods,
+    /// Scripts of the mission run or compiled by its other scripts
+    #[serde(default)]
+    pub script_calls: Vec<ScriptCall>,
+    /// Zeus setup of mission.sqm, unset when the mission has no Zeus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curator: Option<CuratorSetup>,
+}
+
+/// Script of a mission run or compiled by another, e.g. with `execVM` or `preprocessFile`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScriptCall {
+    /// Script running the other one
+    pub caller: PathBuf,
+    /// Script run, resolved to a file of the mission
+    pub script: PathBuf,
+}
+
+/// File skipped after repeated IO errors (network shares, antivirus locks)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct QuarantinedFile {
+    /// Path of the file
+    pub path: PathBuf,
+    /// Error of the last attempt
+    pub error: String,
+    /// Number of attempts made
+    pub attempts: u32,
+}
+
+impl MissionResults {
+    /// Class names used by each side, keyed by side name.
+    ///
+    /// References that can't be attributed to a side (scripts, configs) are
+    /// listed under `None`.
+    pub fn dependencies_by_side(&self) -> BTreeMap<Option<String>, BTreeSet<String>> {
+        let mut by_side: BTreeMap<Option<String>, BTreeSet<String>> = BTreeMap::new();
+        for dep in &self.class_dependencies {
+            let side = dep.owner.as_ref().and_then(|owner| owner.side.clone());
+            by_side.entry(side).or_default().insert(dep.class_name.to_string());
+        }
+        by_side
+    }
+
+    /// Scripts an entry script runs, directly or through the scripts it runs,
+    /// starting with the entry script itself
+    pub fn scripts_reached_from<'a>(&'a self, entry: &'a Path) -> Vec<&'a Path> {
+        let mut reached = vec![entry];
+        let mut next = 0;
+        while let Some(&script) = reached.get(next) {
+            for call in self.script_calls.iter().filter(|call| call.caller == script) {
+                if !reached.contains(&call.script.as_path()) {
+                    reached.push(&call.script);
+                }
+            }
+            next += 1;
+        }
+        reached
+    }
+
+    /// References found in an entry script or any script it runs, directly or transitively
+    pub fn dependencies_of_script(&self, entry: &Path) -> Vec<&ClassReference> {
+        let scripts = self.scripts_reached_from(entry);
+        self.class_dependencies.iter()
+            .filter(|dep| scripts.contains(&dep.source_file.as_path()))
+            .collect()
+    }
+}
+
+/// Mod asset (texture, material) referenced by path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AssetReference {
+    /// Path to the asset as written in the source
+    pub path: String,
+    /// Context where the asset is referenced
+    pub context: String,
+    /// Source file
+    pub source_file: PathBuf,
+}
+
+/// Category of a file for size reporting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum AssetCategory {
+    /// Audio files (.ogg, .wss, .wav)
+    Sounds,
+    /// Textures and pictures (.paa, .jpg, .png)
+    Images,
+    /// Scripts and configs (.sqf, .hpp, .ext, ...)
+    Scripts,
+    /// The mission.sqm file
+    Sqm,
+    /// Anything else
+    Other,
+}
+
+impl AssetCategory {
+    /// Categorize a file by its extension
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_lowercase().as_str() {
+            "ogg" | "wss" | "wav" | "mp3" => AssetCategory::Sounds,
+            "paa" | "pac" | "jpg" | "jpeg" | "png" | "tga" => AssetCategory::Images,
+            "sqf" | "sqs" | "fsm" | "cpp" | "hpp" | "ext" | "h" | "inc" | "html" | "xml" => AssetCategory::Scripts,
+            "sqm" => AssetCategory::Sqm,
+            _ => AssetCategory::Other,
+        }
+    }
+}
+
+/// Disk footprint of a mission broken down by asset category
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MissionFootprint {
+    /// Total size of all files in bytes
+    pub total_bytes: u64,
+    /// Size in bytes per asset category
+    pub by_category: BTreeMap<AssetCategory, u64>,
+    /// Whether the mission is above the configured size threshold
+    pub exceeds_threshold: bool,
+}
+
+impl std::fmt::Display for MissionFootprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Total: {} bytes{}", self.total_bytes,
+            if self.exceeds_threshold { " (above size limit)" } else { "" })?;
+        for (category, bytes) in &self.by_category {
+            writeln!(f, "  {:?}: {} bytes", category, bytes)?;
+        }
+        Ok(())
+    }
 }
 
 /// Class dependency information
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClassReference {
     /// Name of the class
     /// Note: Arma 3 class names are case-insensitive. When comparing class names,
     /// they should be converted to lowercase first.
-    pub class_name: String,
+    pub class_name: Symbol,
     /// Type of reference
     pub reference_type: ReferenceType,
     /// Context where the class is referenced
     pub context: String,
     /// Source file
     pub source_file: PathBuf,
+    /// Side, group and unit the reference belongs to, for references from mission.sqm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<DependencyOwner>,
+    /// Where the class name first appears in the source file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+    /// How sure the scanner is that the name is used as a class
+    #[serde(default)]
+    pub confidence: Confidence,
+    /// Instances added, counting loop iterations and `[class, count]`
+    /// arguments of scripts. The most when `count_range` is set
+    #[serde(default = "default_count")]
+    pub count: u32,
+    /// Fewest and most instances added, when random loop bounds make it vary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count_range: Option<CountRange>,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+impl ClassReference {
+    /// Direct reference to a class, certain and without a source file or location yet
+    pub fn direct(class_name: &str, context: impl Into<String>) -> Self {
+        Self {
+            class_name: class_name.into(),
+            reference_type: ReferenceType::Direct,
+            context: context.into(),
+            source_file: PathBuf::new(),
+            owner: None,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+        }
+    }
+}
+
+/// Line and column in a source file, both starting at 1
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
 }
 
-/// Type of reference to a class
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-pub enum ReferenceType {
-    /// Direct reference to a class
-    Direct,
-    /// Inheritance from a parent class
-    Inheritance,
-    /// Reference through a variable
-    Variable,
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Side, group and unit of a mission that a class reference belongs to
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DependencyOwner {
+    /// Side of the owning group or object (`West`, `East`, `Independent`, `Civilian`, `Empty`)
+    pub side: Option<String>,
+    /// Callsign of the owning group
+    pub group: Option<String>,
+    /// Variable name of the owning unit
+    pub unit: Option<String>,
+}
+
+/// Addons a Zeus module gives its curator by default
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CuratorAddons {
+    /// No addons
+    None,
+    /// Addons the scenario uses
+    Scenario,
+    /// Every official addon
+    Official,
+    /// Every loaded addon, unofficial ones included
+    All,
+}
+
+impl From<parser_sqm::CuratorAddons> for CuratorAddons {
+    fn from(addons: parser_sqm::CuratorAddons) -> Self {
+        match addons {
+            parser_sqm::CuratorAddons::None => CuratorAddons::None,
+            parser_sqm::CuratorAddons::Scenario => CuratorAddons::Scenario,
+            parser_sqm::CuratorAddons::Official => CuratorAddons::Official,
+            parser_sqm::CuratorAddons::All => CuratorAddons::All,
+        }
+    }
+}
+
+/// Zeus game master module (`ModuleCurator_F`) of a mission
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CuratorModule {
+    /// Variable name of the module
+    pub name: Option<String>,
+    /// Who becomes the curator: a player UID, a unit's variable name or `#adminLogged`
+    pub owner: Option<String>,
+    /// Addons available by default, unset when mission.sqm doesn't say
+    pub addons: Option<CuratorAddons>,
+}
+
+/// Zeus setup of a mission and the addons it exposes to curators
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CuratorSetup {
+    /// Game master modules
+    pub modules: Vec<CuratorModule>,
+    /// Addons (CfgPatches classes) added for the curators by
+    /// `ModuleCuratorAddAddons_F` modules, sorted
+    #[serde(default)]
+    pub added_addons: Vec<String>,
+}
+
+impl From<parser_sqm::CuratorSetup> for CuratorSetup {
+    fn from(setup: parser_sqm::CuratorSetup) -> Self {
+        Self {
+            modules: setup.modules.into_iter()
+                .map(|module| CuratorModule {
+                    name: module.name,
+                    owner: module.owner,
+                    addons: module.addons.map(CuratorAddons::from),
+                })
+                .collect(),
+            added_addons: setup.added_addons,
+        }
+    }
 }
 
 /// Represents the source of an inventory item reference
@@ -0,0 +1,212 @@
+//! Filesystem abstraction used by the scanner
+//!
+//! The collector and parsers read missions through [`Vfs`] rather than
+//! `std::fs`, so tests and embedders can scan in-memory or layered filesystems.
+//! [`OsFs`] is the default and reads from disk.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::debug;
+use walkdir::WalkDir;
+
+use crate::types::MissionScannerConfig;
+
+/// Read-only view of a filesystem holding missions
+pub trait Vfs: Send + Sync {
+    /// Read a whole file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Check if a path is a file
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Check if a path is a directory
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// List a directory and everything below it, the directory itself included
+    fn walk(&self, dir: &Path) -> Vec<PathBuf>;
+
+    /// List the entries directly in a directory
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        self.walk(dir).into_iter().filter(|path| path.parent() == Some(dir)).collect()
+    }
+
+    /// Read a whole file as UTF-8
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Check if a path exists
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    /// Size of a file in bytes
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|content| content.len() as u64)
+    }
+
+    /// Path of the file on disk, for tools that can only read from disk.
+    ///
+    /// Config files are preprocessed in a workspace on disk when they have
+    /// one, so `#include`s of other mission files resolve.
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        let _ = path;
+        None
+    }
+}
+
+/// The real filesystem
+#[derive(Debug, Clone, Copy)]
+pub struct OsFs {
+    /// Follow symbolic links while walking directories. A directory reached
+    /// through several links is only walked once, so link cycles end
+    pub follow_symlinks: bool,
+}
+
+impl Default for OsFs {
+    fn default() -> Self {
+        Self { follow_symlinks: true }
+    }
+}
+
+impl OsFs {
+    /// The filesystem as configured for a scan
+    pub fn for_config(config: &MissionScannerConfig) -> &'static Self {
+        if config.follow_symlinks {
+            &Self { follow_symlinks: true }
+        } else {
+            &Self { follow_symlinks: false }
+        }
+    }
+}
+
+impl Vfs for OsFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn walk(&self, dir: &Path) -> Vec<PathBuf> {
+        // Through links a directory can be reached again, even from outside the walk, so
+        // each one is entered once by its real path
+        let mut visited = HashSet::new();
+        WalkDir::new(dir).follow_links(self.follow_symlinks).into_iter()
+            .filter_entry(|e| !self.follow_symlinks || !e.file_type().is_dir()
+                || fs::canonicalize(e.path()).map_or(true, |path| visited.insert(path)))
+            .filter_map(|e| e.inspect_err(|e| debug!("Skipped while walking {}: {}", dir.display(), e)).ok())
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        match fs::read_dir(dir) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(e) => {
+                debug!("Failed to list {}: {}", dir.display(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        fs::metadata(path).map(|m| m.len())
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(path.to_path_buf())
+    }
+}
+
+/// A shared filesystem, for scanning through a [`Vfs`] that can't be cloned
+/// or is too large to be
+impl<V: Vfs + ?Sized> Vfs for Arc<V> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        (**self).read(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        (**self).is_file(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        (**self).is_dir(path)
+    }
+
+    fn walk(&self, dir: &Path) -> Vec<PathBuf> {
+        (**self).walk(dir)
+    }
+
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        (**self).list_dir(dir)
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        (**self).read_to_string(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        (**self).exists(path)
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        (**self).file_size(path)
+    }
+
+    fn local_path(&self, path: &Path) -> Option<PathBuf> {
+        (**self).local_path(path)
+    }
+}
+
+/// Filesystem held in memory, mainly for tests
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFs {
+    files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFs {
+    /// Create an empty filesystem
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a file; its parent directories exist implicitly
+    pub fn insert(&mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        self.files.insert(path.into(), content.into());
+    }
+}
+
+impl Vfs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.get(path)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display())))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.files.keys().any(|file| file != path && file.starts_with(path))
+    }
+
+    fn walk(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut entries = BTreeSet::new();
+        for file in self.files.keys().filter(|file| file.starts_with(dir)) {
+            entries.extend(file.ancestors().take_while(|p| p.starts_with(dir)).map(Path::to_path_buf));
+        }
+        entries.into_iter().collect()
+    }
+}
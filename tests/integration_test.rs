@@ -1,11 +1,75 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use anyhow::Result;
 use log::debug;
 
 use mission_scanner::{
+    AssetCategory,
+    analyze_description_ext,
+    analyze_file,
+    audit_arsenals,
+    audit_formatting,
+    check_item_categories,
+    check_compatibility,
+    check_manifest,
+    class_history,
+    cross_check_medical,
+    diff_missions,
+    explain_class,
+    export_portable,
+    find_class_references,
+    group_variants,
+    import_portable,
+    mission_terrain,
+    mission_variant,
+    parse_file_with_vfs,
     scan_mission,
+    scan_mission_with_vfs,
+    scan_missions_stream_with_vfs,
+    scan_missions_with_vfs,
+    scan_pbo,
+    to_csv,
+    to_html,
+    to_markdown,
+    write_json_report,
+    write_repro,
+    ClassDatabase,
+    ClassEntry,
+    ClassPattern,
+    Confidence,
+    CountRange,
+    ConfigSettings,
+    CuratorAddons,
+    CuratorSetup,
+    FileAnalysisOptions,
+    Interner,
+    ItemKind,
+    MemoryFs,
+    MissionFilter,
+    MissionMetadata,
+    PboSignatures,
+    MissionReport,
+    ClassReference,
+    CountChange,
+    DependencyOwner,
+    EquipmentWhitelist,
+    Extractor,
+    MedicalTreatments,
+    MissionManifest,
+    MissionResults,
+    ModPrefixes,
     MissionScannerConfig,
+    Pbo,
+    PathPattern,
+    PlayerRange,
     ReferenceType,
+    ReportFile,
+    RequiredMods,
+    ScanSummary,
+    ScriptCall,
+    SourceKind,
+    SourceLocation,
+    UnissuedItem,
 };
 
 use env_logger;
@@ -139,4 +203,2396 @@ async fn test_mission_class_dependencies() -> Result<()> {
     assert!(reference_types.contains(&ReferenceType::Variable), "Should find variable references");
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_scan_output_is_json() -> Result<()> {
+    // Parsers must log instead of printing, or the JSON on stdout breaks
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_mission_scanner"))
+        .arg("scan")
+        .arg(get_test_data_dir())
+        .output()?;
+    assert!(output.status.success(), "scan failed: {}", String::from_utf8_lossy(&output.stderr));
+    let scan: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert!(scan["missions"].as_array().is_some_and(|missions| !missions.is_empty()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_explain_class() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(&test_dir, num_cpus::get(), &config).await?;
+
+    // Lookups are case-insensitive like Arma class names
+    let explanation = explain_class("RHS_WEAP_MG42", &[result], &ModPrefixes::default(), None);
+    assert!(explanation.is_used(), "rhs_weap_mg42 should be referenced");
+    assert_eq!(explanation.prefix.as_deref(), Some("RHS_"));
+    assert!(explanation.usages.iter().all(|u| u.mission_name == "test_mission_1"));
+
+    let missing = explain_class("not_a_real_class", &[], &ModPrefixes::default(), None);
+    assert!(!missing.is_used());
+    assert_eq!(missing.exists, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_class_mod_and_database() -> Result<()> {
+    let database: ClassDatabase = [ClassEntry {
+        name: "rhs_weap_ak74m".to_string(),
+        parent: None,
+        config: Some("CfgWeapons".to_string()),
+        source: Some("rhs_c_weapons".to_string()),
+        category: None,
+    }].into_iter().collect();
+
+    let known = explain_class("RHS_weap_AK74M", &[], &ModPrefixes::default(), Some(&database));
+    assert_eq!(known.mod_name.as_deref(), Some("RHS: Armed Forces of the Russian Federation"));
+    assert_eq!(known.exists, Some(true));
+    assert_eq!(known.source.as_deref(), Some("rhs_c_weapons"));
+    assert!(known.to_string().contains("Class database: defined by rhs_c_weapons"));
+
+    let unknown = explain_class("xyz_rifle", &[], &ModPrefixes::default(), Some(&database));
+    assert_eq!(unknown.mod_name, None);
+    assert_eq!(unknown.exists, Some(false));
+    assert!(unknown.to_string().contains("Mod: (unknown)"));
+    assert!(unknown.to_string().contains("Class database: missing"));
+
+    Ok(())
+}
+
+#[test]
+fn test_class_pattern_matching() -> Result<()> {
+    let glob = ClassPattern::glob("rhs_weap_*");
+    assert!(glob.matches("rhs_weap_mg42"));
+    assert!(glob.matches("RHS_WEAP_ak74m"));
+    assert!(!glob.matches("rhsusf_weap_glock17g4"));
+
+    let single = ClassPattern::glob("ACE_?orphine");
+    assert!(single.matches("ACE_morphine"));
+    assert!(!single.matches("ACE_epinephrine"));
+
+    let regex = ClassPattern::regex("^acre_prc(343|148)$")?;
+    assert!(regex.matches("ACRE_PRC343"));
+    assert!(!regex.matches("ACRE_PRC152"));
+    assert!(ClassPattern::regex("(").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_path_patterns() {
+    let briefing = PathPattern::new("**/briefing/**");
+    assert!(briefing.matches("briefing/admin.sqf"));
+    assert!(briefing.matches("scripts\\Briefing\\tasks\\intro.sqf"));
+    assert!(!briefing.matches("briefing.sqf"));
+
+    let loadouts = PathPattern::new("loadouts/*.sqf");
+    assert!(loadouts.matches("Loadouts/Arsenal.SQF"));
+    assert!(!loadouts.matches("loadouts/roles/rifleman.sqf"));
+    assert!(!loadouts.matches("scripts/loadouts/arsenal.sqf"));
+
+    let mission_dir = Path::new("/missions/op_alpha.Altis");
+    let config = MissionScannerConfig {
+        include_globs: vec!["loadouts/**".to_string(), "init.sqf".to_string()],
+        exclude_globs: vec!["**/_*.hpp".to_string()],
+        ..MissionScannerConfig::default()
+    };
+    assert!(config.is_file_selected(mission_dir, &mission_dir.join("init.sqf")));
+    assert!(config.is_file_selected(mission_dir, &mission_dir.join("loadouts/player_loadout.hpp")));
+    assert!(!config.is_file_selected(mission_dir, &mission_dir.join("loadouts/_macros.hpp")));
+    assert!(!config.is_file_selected(mission_dir, &mission_dir.join("initserver.sqf")));
+    assert!(MissionScannerConfig::default().is_file_selected(mission_dir, &mission_dir.join("initserver.sqf")));
+}
+
+#[tokio::test]
+async fn test_scan_file_globs() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let config = MissionScannerConfig {
+        exclude_globs: vec!["**/briefing/**".to_string(), "mission.sqm".to_string()],
+        ..MissionScannerConfig::default()
+    };
+
+    let result = scan_mission(&test_dir, 1, &config).await?;
+    assert!(result.sqm_file.is_none());
+    assert!(!result.sqf_files.is_empty());
+    assert!(result.sqf_files.iter().all(|file| !file.starts_with(test_dir.join("briefing"))));
+    assert!(result.class_dependencies.iter().all(|dep| !dep.source_file.starts_with(test_dir.join("briefing"))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_arsenal_whitelist_audit() -> Result<()> {
+    let whitelist = EquipmentWhitelist::parse("# Uniforms\nTarkov_Uniforms_*\n\nV_PlateCarrier* # vests\nitemmap\n");
+    assert_eq!(whitelist.len(), 3);
+    assert!(whitelist.allows("tarkov_uniforms_12"));
+    assert!(whitelist.allows("ItemMap"));
+    assert!(!whitelist.allows("rhs_tortila_black"));
+
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(&test_dir, 1, &config).await?;
+    let audit = audit_arsenals(&result, &whitelist);
+
+    assert_eq!(audit.arsenals, 1);
+    assert!(!audit.is_compliant());
+    let violations: Vec<&str> = audit.violations.iter().map(|v| v.class_name.as_str()).collect();
+    assert!(violations.contains(&"rhs_tortila_black"));
+    assert!(!violations.contains(&"Tarkov_Uniforms_1"));
+    assert!(!violations.contains(&"ItemMap"));
+    assert!(audit.violations.iter().all(|v| v.box_id == "arsenal" && v.source_file.ends_with("arsenal.sqf")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_class_references() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let config = MissionScannerConfig::default();
+
+    let pattern = ClassPattern::glob("rhs_weap_*");
+    let matches = find_class_references(&test_dir, &pattern, &config).await?;
+
+    assert!(!matches.is_empty(), "Should find rhs weapons");
+    assert!(matches.iter().all(|m| pattern.matches(&m.class_name)));
+    assert!(matches.iter().any(|m| m.class_name == "rhs_weap_mg42"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_analyze_single_file() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let loadout = test_dir.join("loadouts").join("player_loadout.hpp");
+
+    let analysis = analyze_file(&loadout, &FileAnalysisOptions::default())?;
+    assert!(analysis.diagnostics.is_empty(), "Loadout should parse cleanly");
+    assert!(!analysis.references.is_empty());
+    assert!(analysis.symbols.iter().any(|s| s.name == "ar" && s.parent.as_deref() == Some("r")));
+    assert_eq!(analysis.metrics.reference_count, analysis.references.len());
+    assert!(analysis.metrics.line_count > 0);
+    assert!(analysis.metrics.shared_class_count.is_none());
+
+    // With a cached mission scan the file's classes can be related to the rest of the mission
+    let config = MissionScannerConfig::default();
+    let mission = scan_mission(&test_dir, num_cpus::get(), &config).await?;
+    let options = FileAnalysisOptions { mission_context: Some(Arc::new(mission)) };
+    let analysis = analyze_file(&loadout, &options)?;
+    assert!(analysis.metrics.shared_class_count.is_some());
+
+    assert!(analyze_file(&test_dir.join("missing.sqf"), &options).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mission_footprint() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(&test_dir, num_cpus::get(), &config).await?;
+
+    let footprint = &result.footprint;
+    assert!(footprint.total_bytes > 0);
+    assert!(footprint.by_category.get(&AssetCategory::Sqm).copied().unwrap_or(0) > 0);
+    assert!(footprint.by_category.get(&AssetCategory::Scripts).copied().unwrap_or(0) > 0);
+    assert_eq!(footprint.by_category.values().sum::<u64>(), footprint.total_bytes);
+    assert!(!footprint.exceeds_threshold);
+
+    let config = MissionScannerConfig { max_mission_size: Some(1), ..MissionScannerConfig::default() };
+    let result = scan_mission(&test_dir, num_cpus::get(), &config).await?;
+    assert!(result.footprint.exceeds_threshold);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_repro() -> Result<()> {
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let file = test_dir.join("loadouts").join("player_loadout.hpp");
+    let output_dir = std::env::temp_dir().join(format!("mission_scanner_repro_{}", std::process::id()));
+
+    let repro_dir = write_repro(&file, &test_dir, "Unexpected token at line 12", &output_dir)?;
+    assert!(repro_dir.starts_with(output_dir.join("repro")));
+    assert!(repro_dir.join("player_loadout.hpp").exists());
+
+    let diagnostic = std::fs::read_to_string(repro_dir.join("diagnostic.txt"))?;
+    assert!(diagnostic.contains("Unexpected token at line 12"));
+
+    let snippet = std::fs::read_to_string(repro_dir.join("snippet.txt"))?;
+    assert!(snippet.lines().any(|l| l.starts_with(">   12 |")));
+    assert_eq!(snippet.lines().count(), 11);
+
+    std::fs::remove_dir_all(&output_dir)?;
+    Ok(())
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn test_schema_export() -> Result<()> {
+    let schemas = mission_scanner::schema::export();
+    for name in ["MissionResults", "ClassExplanation", "FileAnalysis", "MissionReport"] {
+        let schema = serde_json::to_value(&schemas[name])?;
+        assert_eq!(schema["title"], name);
+    }
+
+    let mission = serde_json::to_value(&schemas["MissionResults"])?;
+    assert!(mission["properties"]["class_dependencies"].is_object());
+    assert!(mission["definitions"]["ClassReference"].is_object());
+
+    for name in ["ScanOutput", "ReportFile", "ScanSummary"] {
+        let schema = serde_json::to_value(&schemas[name])?;
+        assert!(schema["required"].as_array().unwrap().contains(&"schema_version".into()), "{}", name);
+    }
+    for name in ["ClassReference", "MissingClass", "MissionDiff"] {
+        assert!(schemas.contains_key(name));
+    }
+
+    let output = serde_json::to_value(mission_scanner::ScanOutput::new(Vec::new()))?;
+    assert_eq!(output["schema_version"], mission_scanner::report::REPORT_SCHEMA_VERSION);
+
+    Ok(())
+}
+
+#[test]
+fn test_portable_round_trip() -> Result<()> {
+    let build_root = PathBuf::from("/srv/build/missions");
+    let mission_dir = build_root.join("op_alpha.Altis");
+    let mission = MissionResults {
+        mission_name: "op_alpha.Altis".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: Some(mission_dir.join("mission.sqm")),
+        sqf_files: vec![mission_dir.join("init.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![ClassReference {
+            class_name: "rhs_weap_m4a1".into(),
+            reference_type: ReferenceType::Direct,
+            context: "addWeapon".to_string(),
+            source_file: mission_dir.join("init.sqf"),
+            owner: None,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+        }],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let file = std::env::temp_dir().join(format!("mission_scanner_portable_{}.json", std::process::id()));
+    export_portable(&[mission], &build_root, &file)?;
+
+    let exported = std::fs::read_to_string(&file)?;
+    assert!(!exported.contains("/srv/build"), "Exported paths should be relative");
+
+    let laptop_root = PathBuf::from("/home/maintainer/missions");
+    let imported = import_portable(&file, &laptop_root)?;
+    std::fs::remove_file(&file)?;
+
+    let mission = &imported[0];
+    assert_eq!(mission.mission_dir, laptop_root.join("op_alpha.Altis"));
+    assert_eq!(mission.sqm_file.as_deref(), Some(laptop_root.join("op_alpha.Altis/mission.sqm").as_path()));
+    assert_eq!(mission.class_dependencies[0].source_file, laptop_root.join("op_alpha.Altis/init.sqf"));
+
+    Ok(())
+}
+
+#[test]
+fn test_manifest_drift() -> Result<()> {
+    let mission_dir = PathBuf::from("op_bravo.Altis");
+    let reference = |class_name: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "addItem".to_string(),
+        source_file: mission_dir.join("init.sqf"),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "op_bravo.Altis".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: Vec::new(),
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("RHS_weap_m4a1"),
+            reference("ItemMap"),
+            reference("CUP_arifle_AK74"),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let manifest = MissionManifest::from_toml_str(r#"
+        mods = ["rhs_", "ace_"]
+        classes = ["itemmap", "ItemCompass"]
+    "#)?;
+    let drift = check_manifest(&manifest, &mission);
+
+    assert!(!drift.is_clean());
+    assert_eq!(drift.undeclared_classes.iter().collect::<Vec<_>>(), ["CUP_arifle_AK74"]);
+    assert_eq!(drift.unused_classes.iter().collect::<Vec<_>>(), ["ItemCompass"]);
+    assert_eq!(drift.unused_mods.iter().collect::<Vec<_>>(), ["ace_"]);
+
+    assert!(MissionManifest::from_toml_str("mods = 5").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_class_history() -> Result<()> {
+    use std::process::Command;
+
+    let repo = std::env::temp_dir().join(format!("mission_scanner_history_{}", std::process::id()));
+    std::fs::create_dir_all(&repo)?;
+    let git = |args: &[&str], date: &str| -> Result<()> {
+        let status = Command::new("git")
+            .arg("-C").arg(&repo)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status()?;
+        assert!(status.success(), "git {:?} failed", args);
+        Ok(())
+    };
+
+    let init = repo.join("init.sqf");
+    git(&["init", "-q"], "2024-01-01T00:00:00Z")?;
+    std::fs::write(&init, "player addWeapon \"rhs_weap_m4a1\";\n")?;
+    git(&["add", "."], "2024-01-01T00:00:00Z")?;
+    git(&["commit", "-q", "-m", "Add rifle"], "2024-01-01T00:00:00Z")?;
+    std::fs::write(&init, "player addWeapon \"rhs_weap_m4a1\";\nplayer addVest \"rhsusf_spcs_ocp\";\n")?;
+    git(&["commit", "-q", "-am", "Add vest"], "2024-03-01T00:00:00Z")?;
+
+    let reference = |class_name: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "init".to_string(),
+        source_file: init.clone(),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "history".to_string(),
+        mission_dir: repo.clone(),
+        sqm_file: None,
+        sqf_files: vec![init.clone()],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![reference("rhsusf_spcs_ocp"), reference("rhs_weap_m4a1")],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let history = class_history(&mission)?;
+    std::fs::remove_dir_all(&repo)?;
+
+    let names: Vec<_> = history.iter().map(|h| h.class_name.as_str()).collect();
+    assert_eq!(names, ["rhs_weap_m4a1", "rhsusf_spcs_ocp"]);
+    assert!(history[0].date.starts_with("2024-01-01"));
+    assert!(history[1].date.starts_with("2024-03-01"));
+    assert_ne!(history[0].commit, history[1].commit);
+
+    Ok(())
+}
+
+#[cfg(feature = "rules")]
+#[test]
+fn test_rule_engine() -> Result<()> {
+    use mission_scanner::rules::{FindingSeverity, RuleEngine};
+
+    let mission_dir = PathBuf::from("op_charlie.Altis");
+    let reference = |class_name: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "addWeapon".to_string(),
+        source_file: mission_dir.join("init.sqf"),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "op_charlie.Altis".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: Vec::new(),
+        cpp_files: Vec::new(),
+        class_dependencies: vec![reference("CUP_arifle_AK74"), reference("rhs_weap_m4a1")],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let mut engine = RuleEngine::new();
+    engine.add_rule("no_cup", r#"
+        for dep in mission.class_dependencies {
+            if dep.class_name.starts_with("CUP_") {
+                warning(`CUP class ${dep.class_name}`);
+            }
+        }
+    "#)?;
+    engine.add_rule("count", r#"
+        info(`${mission.class_dependencies.len()} classes, ${entities.len()} entities`);
+    "#)?;
+    assert!(engine.add_rule("broken", "let x = ;").is_err());
+
+    let findings = engine.evaluate(&mission)?;
+    assert_eq!(findings.len(), 2);
+    assert_eq!(findings[0].rule, "no_cup");
+    assert_eq!(findings[0].severity, FindingSeverity::Warning);
+    assert_eq!(findings[0].message, "CUP class CUP_arifle_AK74");
+    assert_eq!(findings[1].message, "2 classes, 0 entities");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_collect_symlinks_and_mixed_case() -> Result<()> {
+    use mission_scanner::scanner::collect_mission_files;
+    use mission_scanner::vfs::OsFs;
+    use std::os::unix::fs::symlink;
+
+    let root = std::env::temp_dir().join(format!("mission_scanner_symlinks_{}", std::process::id()));
+    let mission_dir = root.join("missions").join("co20_synced.Tanoa");
+    let shared = root.join("shared_loadouts");
+    std::fs::create_dir_all(&mission_dir)?;
+    std::fs::create_dir_all(&shared)?;
+    std::fs::write(mission_dir.join("Mission.SQM"), "version=54;\n")?;
+    std::fs::write(mission_dir.join("Init.SQF"), "player addItem \"ItemGPS\";")?;
+    std::fs::write(shared.join("rifleman.HPP"), "class rifleman {};")?;
+    symlink(&shared, mission_dir.join("loadouts"))?;
+    // A link back up the tree must not make the walk loop
+    symlink(&root, shared.join("back_to_root"))?;
+
+    let config = MissionScannerConfig::default();
+    let missions = collect_mission_files(OsFs::for_config(&config), &root.join("missions"), &config)?;
+    assert_eq!(missions.len(), 1);
+    assert_eq!(missions[0].sqm_file, Some(mission_dir.join("Mission.SQM")));
+    assert_eq!(missions[0].sqf_files, vec![mission_dir.join("Init.SQF")]);
+    assert_eq!(missions[0].cpp_files, vec![mission_dir.join("loadouts").join("rifleman.HPP")]);
+
+    let no_links = MissionScannerConfig { follow_symlinks: false, ..MissionScannerConfig::default() };
+    let missions = collect_mission_files(OsFs::for_config(&no_links), &root.join("missions"), &no_links)?;
+    assert!(missions[0].cpp_files.is_empty());
+
+    let match_case = MissionScannerConfig { ignore_case: false, ..MissionScannerConfig::default() };
+    assert!(collect_mission_files(OsFs::for_config(&match_case), &root.join("missions"), &match_case)?.is_empty());
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_in_memory_mission() -> Result<()> {
+    use mission_scanner::scanner::collect_mission_files;
+
+    let root = PathBuf::from("/missions");
+    let mission_dir = root.join("co10_memory.Stratis");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("mission.sqm"), "version=54;\nclass Mission {};\n");
+    vfs.insert(mission_dir.join("init.sqf"),
+        "player addItem \"ItemGPS\"; _flag setObjectTexture [0, \"\\mymod\\data\\flag.paa\"]; [] execVM \"scripts\\gear.sqf\";");
+    vfs.insert(mission_dir.join("scripts").join("gear.sqf"), "player addWeapon \"arifle_MX_F\";");
+    vfs.insert(mission_dir.join("loadouts").join("rifleman.hpp"),
+        "class rifleman { uniform[] = {\"U_B_CombatUniform_mcam\"}; };");
+
+    let missions = collect_mission_files(&vfs, &root, &MissionScannerConfig::default())?;
+    assert_eq!(missions.len(), 1);
+    assert_eq!(missions[0].mission_name, "co10_memory.Stratis");
+    assert_eq!(missions[0].sqf_files, vec![mission_dir.join("init.sqf"), mission_dir.join("scripts").join("gear.sqf")]);
+    assert_eq!(missions[0].cpp_files, vec![mission_dir.join("loadouts").join("rifleman.hpp")]);
+
+    let config = MissionScannerConfig::default();
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    assert!(result.sqm_file.is_some());
+    assert!(result.footprint.total_bytes > 0);
+    assert!(result.class_dependencies.iter().any(|d| d.class_name == "U_B_CombatUniform_mcam"));
+    // Scripts are analyzed from memory too
+    assert!(result.class_dependencies.iter().any(|d| d.class_name == "ItemGPS"));
+    assert!(result.class_dependencies.iter().any(|d| d.class_name == "arifle_MX_F"));
+    assert!(result.asset_references.iter().any(|asset| asset.path == "\\mymod\\data\\flag.paa"));
+    assert_eq!(result.script_calls, [ScriptCall {
+        caller: mission_dir.join("init.sqf"),
+        script: mission_dir.join("scripts").join("gear.sqf"),
+    }]);
+
+    assert!(scan_mission_with_vfs(&vfs, &root.join("missing"), 1, &config).await.is_err());
+
+    Ok(())
+}
+
+/// Reads one class per line from `*.gear` files, failing on empty files
+struct GearList;
+
+impl Extractor for GearList {
+    fn name(&self) -> &str {
+        "gear list"
+    }
+
+    fn applies_to(&self, file: &Path) -> bool {
+        file.extension().is_some_and(|ext| ext == "gear")
+    }
+
+    fn extract(&self, content: &str) -> Result<Vec<ClassReference>> {
+        if content.trim().is_empty() {
+            anyhow::bail!("Empty gear list");
+        }
+        Ok(content.lines().map(|line| ClassReference::direct(line.trim(), "gear list")).collect())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_extractors() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/co10_gear.Altis");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("gear").join("rifleman.gear"), "arifle_MX_F\nItemMap\n");
+    vfs.insert(mission_dir.join("gear").join("empty.gear"), "");
+    vfs.insert(mission_dir.join("notes.txt"), "arifle_Katiba_F");
+
+    let mut config = MissionScannerConfig {
+        ignore_classes: vec!["ItemMap".to_string()],
+        ..MissionScannerConfig::default()
+    };
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    assert!(result.class_dependencies.is_empty());
+
+    config.extractors.register(GearList);
+    assert!(format!("{:?}", config.extractors).contains("gear list"));
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    let classes: Vec<_> = result.class_dependencies.iter().map(|dep| dep.class_name.as_str()).collect();
+    assert_eq!(classes, ["arifle_MX_F"]);
+    assert_eq!(result.class_dependencies[0].source_file, mission_dir.join("gear").join("rifleman.gear"));
+
+    Ok(())
+}
+
+#[test]
+fn test_dependencies_by_side() {
+    let mission_dir = PathBuf::from("tvt_border.Tanoa");
+    let reference = |class_name: &str, side: Option<&str>| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "sqm".to_string(),
+        source_file: mission_dir.join("mission.sqm"),
+        owner: side.map(|side| DependencyOwner { side: Some(side.to_string()), ..Default::default() }),
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "tvt_border.Tanoa".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: Some(mission_dir.join("mission.sqm")),
+        sqf_files: Vec::new(),
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("rhs_weap_ak74m", Some("East")),
+            reference("rhs_uniform_msv_emr", Some("East")),
+            reference("CUP_U_B_USArmy_Base", Some("West")),
+            reference("ItemMap", None),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let by_side = mission.dependencies_by_side();
+    assert_eq!(by_side.len(), 3);
+    assert_eq!(by_side[&Some("East".to_string())].len(), 2);
+    assert!(by_side[&Some("West".to_string())].contains("CUP_U_B_USArmy_Base"));
+    assert!(by_side[&None].contains("ItemMap"));
+}
+
+#[test]
+fn test_dependencies_of_script() {
+    let mission_dir = PathBuf::from("co30_convoy.Altis");
+    let script = |name: &str| mission_dir.join(name);
+    let reference = |class_name: &str, file: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "sqf".to_string(),
+        source_file: script(file),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let call = |caller: &str, called: &str| ScriptCall { caller: script(caller), script: script(called) };
+    let mission = MissionResults {
+        mission_name: "co30_convoy.Altis".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![script("init.sqf"), script("loadouts/rifleman.sqf"), script("loadouts/common.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("ItemMap", "init.sqf"),
+            reference("rhs_weap_m4a1", "loadouts/rifleman.sqf"),
+            reference("ACE_fieldDressing", "loadouts/common.sqf"),
+            reference("B_Heli_Light_01_F", "respawn.sqf"),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: vec![
+            call("init.sqf", "loadouts/rifleman.sqf"),
+            call("loadouts/rifleman.sqf", "loadouts/common.sqf"),
+            // Scripts calling each other must not loop forever
+            call("loadouts/common.sqf", "loadouts/rifleman.sqf"),
+        ],
+        curator: None,
+    };
+
+    let entry = script("init.sqf");
+    assert_eq!(mission.scripts_reached_from(&entry).len(), 3);
+    let classes: Vec<_> = mission.dependencies_of_script(&entry).iter()
+        .map(|dep| dep.class_name.as_str())
+        .collect();
+    assert_eq!(classes, ["ItemMap", "rhs_weap_m4a1", "ACE_fieldDressing"]);
+    assert_eq!(mission.dependencies_of_script(&script("loadouts/common.sqf")).len(), 2);
+}
+
+#[tokio::test]
+async fn test_script_calls_are_resolved() -> Result<()> {
+    let root = std::env::temp_dir().join(format!("mission_scanner_script_calls_{}", std::process::id()));
+    let mission_dir = root.join("co30_convoy.Altis");
+    std::fs::create_dir_all(mission_dir.join("loadouts"))?;
+    std::fs::write(mission_dir.join("init.sqf"), concat!(
+        "[player] execVM \"Loadouts\\rifleman.sqf\";\n",
+        "fnc_missing = compile preprocessFileLineNumbers \"missing.sqf\";\n",
+    ))?;
+    std::fs::write(mission_dir.join("loadouts").join("rifleman.sqf"), "player addWeapon \"rhs_weap_m4a1\";")?;
+
+    let results = scan_mission(&mission_dir, 1, &MissionScannerConfig::default()).await?;
+    assert_eq!(results.script_calls, [ScriptCall {
+        caller: mission_dir.join("init.sqf"),
+        script: mission_dir.join("loadouts").join("rifleman.sqf"),
+    }]);
+    assert!(results.dependencies_of_script(&mission_dir.join("init.sqf")).iter()
+        .any(|dep| dep.class_name == "rhs_weap_m4a1"));
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn test_server_scan_upload() -> Result<()> {
+    use std::io::Write;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use mission_scanner::server::{router, ScanAccepted, ScanReport};
+    use tower::ServiceExt;
+
+    let database: ClassDatabase = [ClassEntry {
+        name: "ItemMap".to_string(),
+        parent: None,
+        config: None,
+        source: None,
+        category: None,
+    }].into_iter().collect();
+    let app = router(MissionScannerConfig::default(), Some(database));
+
+    let response = app.clone()
+        .oneshot(Request::post("/scan").body(Body::from("not a zip"))?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app.clone()
+        .oneshot(Request::get("/report/42").body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Zip up a fixture mission
+    let mission_dir = get_test_data_dir().join("test_mission_1");
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for entry in walkdir::WalkDir::new(&mission_dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_file() {
+            let name = entry.path().strip_prefix(mission_dir.parent().unwrap())?;
+            zip.start_file(name.to_string_lossy().replace('\\', "/"), zip::write::SimpleFileOptions::default())?;
+            zip.write_all(&std::fs::read(entry.path())?)?;
+        }
+    }
+    let zip = zip.finish()?.into_inner();
+
+    let response = app.clone()
+        .oneshot(Request::post("/scan").body(Body::from(zip))?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let accepted: ScanAccepted = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(accepted.missions, 1);
+
+    let response = app.clone()
+        .oneshot(Request::get(format!("/report/{}", accepted.id)).body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: ScanReport = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(report.id, accepted.id);
+    assert_eq!(report.missions[0].mission_name, "test_mission_1");
+    assert_eq!(report.missions[0].mission_dir, PathBuf::from("test_mission_1"));
+    assert!(!report.missions[0].class_dependencies.is_empty());
+    assert!(report.missing_classes["test_mission_1"].iter().any(|class| class.class_name == "rhs_weap_mg42"));
+
+    let response = app.clone()
+        .oneshot(Request::get(format!("/report/{}?format=html", accepted.id)).body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = String::from_utf8(to_bytes(response.into_body(), usize::MAX).await?.to_vec())?;
+    assert!(html.starts_with("<!DOCTYPE html>"));
+
+    let response = app
+        .oneshot(Request::get(format!("/report/{}?format=markdown", accepted.id)).body(Body::empty())?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/markdown; charset=utf-8");
+
+    Ok(())
+}
+
+#[cfg(feature = "server")]
+#[tokio::test]
+async fn test_server_rejects_oversized_uploads() -> Result<()> {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use mission_scanner::server::{router, MAX_ZIP_ENTRIES};
+    use tower::ServiceExt;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for index in 0..=MAX_ZIP_ENTRIES {
+        zip.add_directory(format!("dir{}", index), zip::write::SimpleFileOptions::default())?;
+    }
+    let zip = zip.finish()?.into_inner();
+
+    let response = router(MissionScannerConfig::default(), None)
+        .oneshot(Request::post("/scan").body(Body::from(zip))?)
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let message = String::from_utf8(to_bytes(response.into_body(), usize::MAX).await?.to_vec())?;
+    assert!(message.contains("entries"), "{}", message);
+
+    Ok(())
+}
+
+#[cfg(feature = "classdb-fetch")]
+#[test]
+fn test_classdb_checksum() {
+    use mission_scanner::classdb::{parse_checksum_file, verify_sha256};
+
+    let snapshot = b"class CfgWeapons {};";
+    let digest = parse_checksum_file(
+        "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855  empty.json\n").unwrap();
+    assert_eq!(digest, "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    assert!(verify_sha256(b"", &digest).is_ok());
+    assert!(verify_sha256(snapshot, &digest).is_err());
+
+    assert_eq!(parse_checksum_file("not a checksum"), None);
+    assert_eq!(parse_checksum_file(""), None);
+}
+
+#[cfg(feature = "classdb-fetch")]
+#[test]
+fn test_classdb_snapshot_file_name() {
+    use mission_scanner::classdb::snapshot_file_name;
+
+    let name = |url: &str| snapshot_file_name(&url.parse().unwrap()).ok();
+    // Tokens in the query don't end up in the file name
+    assert_eq!(name("https://example.com/snapshots/classes.csv?token=secret#top").as_deref(), Some("classes.csv"));
+    assert_eq!(name("https://example.com/snapshots/classes%20v2.json").as_deref(), Some("classes_20v2.json"));
+    assert_eq!(name("https://example.com/snapshots/%2E%2E"), None);
+    assert_eq!(name("https://example.com/snapshots/"), None);
+    assert_eq!(name("https://example.com"), None);
+}
+
+#[test]
+fn test_classdb_latest_snapshot() -> Result<()> {
+    use mission_scanner::classdb::{latest_snapshot, CLASSDB_CACHE_DIR};
+
+    let cache_dir = std::env::temp_dir().join(format!("mission_scanner_classdb_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    assert_eq!(latest_snapshot(&cache_dir), None);
+
+    let dir = cache_dir.join(CLASSDB_CACHE_DIR);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("classes-1.json"), "[]")?;
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(dir.join("classes-2.json"), "[]")?;
+    assert_eq!(latest_snapshot(&cache_dir), Some(dir.join("classes-2.json")));
+
+    std::fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_formatting_audit() {
+    let consistent = audit_formatting("class Base {\n    items[] = {};\n};\n");
+    assert!(consistent.is_consistent());
+    assert!(consistent.findings().is_empty());
+
+    let mixed = audit_formatting("class Base {\r\n\titems[] = {};\n    uniform = \"\";\r\n\n};\r\n");
+    assert_eq!(mixed.crlf_lines, 3);
+    assert_eq!(mixed.lf_lines, 2);
+    assert_eq!(mixed.tab_indented_lines, 1);
+    assert_eq!(mixed.space_indented_lines, 1);
+    assert_eq!(mixed.findings(), vec![
+        "Mixed line endings: 3 CRLF, 2 LF".to_string(),
+        "Mixed indentation: 1 lines with tabs, 1 with spaces".to_string(),
+    ]);
+}
+
+#[test]
+fn test_server_compatibility() {
+    let server = ConfigSettings::parse(r#"
+        hostname = "Community Server";
+        // Required by the community
+        class DifficultyPresets {
+            class CustomDifficulty {
+                class Options {
+                    thirdPersonView = 0;
+                    reducedDamage = 0;
+                };
+            };
+        };
+        respawnTemplates[] = {"Revive", "MenuPosition"};
+    "#);
+    assert_eq!(server.get("THIRDPERSONVIEW"), Some("0"));
+    assert_eq!(server.get("hostname"), Some("\"Community Server\""));
+
+    let mission = ConfigSettings::parse(r#"
+        onLoadName = "Operation Charlie";
+        respawnTemplates[] = { "revive","MenuPosition" };
+        /* Third person for the tutorial */
+        class CfgDifficultyPresets {
+            class Options {
+                thirdPersonView = 1;
+            };
+        };
+    "#);
+
+    let conflicts = check_compatibility(&server, &mission);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].setting, "thirdPersonView");
+    assert_eq!(conflicts[0].server_value, "0");
+    assert_eq!(conflicts[0].mission_value, "1");
+}
+
+#[tokio::test]
+async fn test_quarantine_unreadable_files() -> Result<()> {
+    use std::io;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use mission_scanner::Vfs;
+
+    /// Fails every read of `locked` and the first read of `flaky`
+    struct FlakyFs {
+        inner: MemoryFs,
+        locked: PathBuf,
+        flaky: PathBuf,
+        flaky_failures: AtomicU32,
+    }
+
+    impl Vfs for FlakyFs {
+        fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+            if path == self.locked
+                || (path == self.flaky && self.flaky_failures.fetch_add(1, Ordering::SeqCst) == 0)
+            {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "locked by another process"));
+            }
+            self.inner.read(path)
+        }
+        fn is_file(&self, path: &Path) -> bool { self.inner.is_file(path) }
+        fn is_dir(&self, path: &Path) -> bool { self.inner.is_dir(path) }
+        fn walk(&self, dir: &Path) -> Vec<PathBuf> { self.inner.walk(dir) }
+    }
+
+    let mission_dir = PathBuf::from("/missions/co12_flaky.Altis");
+    let locked = mission_dir.join("locked.hpp");
+    let flaky = mission_dir.join("flaky.hpp");
+    let mut inner = MemoryFs::new();
+    inner.insert(&locked, "class locked { uniform = \"U_B_CombatUniform_mcam\"; };");
+    inner.insert(&flaky, "class flaky { vest = \"V_PlateCarrier1_rgr\"; };");
+    // The scan clones the filesystem, so the failure counter is shared through an Arc
+    let vfs = std::sync::Arc::new(FlakyFs { inner, locked: locked.clone(), flaky, flaky_failures: AtomicU32::new(0) });
+
+    let config = MissionScannerConfig {
+        file_extensions: vec!["hpp".to_string()],
+        read_retries: 2,
+        retry_backoff_ms: 1,
+        ..MissionScannerConfig::default()
+    };
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+
+    assert_eq!(result.quarantined_files.len(), 1);
+    assert_eq!(result.quarantined_files[0].path, locked);
+    assert_eq!(result.quarantined_files[0].attempts, 3);
+    assert!(result.quarantined_files[0].error.contains("locked by another process"));
+    assert!(result.class_dependencies.iter().any(|d| d.class_name == "V_PlateCarrier1_rgr"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_macros_shared_across_config_files() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/co12_macros.Tanoa");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("macros.hpp"), "#define RIFLE \"rhs_weap_m4a1\"\n#define UNIFORM \"rhs_uniform_g3_m81\"\n");
+    vfs.insert(mission_dir.join("loadouts").join("rifleman.hpp"),
+        "class rifleman { uniform[] = {UNIFORM}; weapons[] = {RIFLE}; };");
+
+    let config = MissionScannerConfig::default();
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    let names: Vec<&str> = result.class_dependencies.iter().map(|d| d.class_name.as_str()).collect();
+    assert!(names.contains(&"rhs_weap_m4a1"), "{:?}", names);
+    assert!(names.contains(&"rhs_uniform_g3_m81"), "{:?}", names);
+    assert!(!names.contains(&"RIFLE"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_includes_resolve_in_mission_dir() -> Result<()> {
+    let mission_dir = std::env::temp_dir().join(format!("mission_scanner_include_{}", std::process::id()));
+    std::fs::create_dir_all(mission_dir.join("loadouts"))?;
+    std::fs::write(mission_dir.join("gear.hpp"), "class BaseGear { vest[] = {\"rhsusf_spcs_ocp\"}; };\n")?;
+    std::fs::write(mission_dir.join("loadouts").join("rifleman.hpp"),
+        "#include \"..\\gear.hpp\"\n#include \"\\x\\tmf\\missing.hpp\"\nclass rifleman : BaseGear { uniform[] = {\"U_B_CombatUniform_mcam\"}; };\n")?;
+
+    let config = MissionScannerConfig::default();
+    let result = scan_mission(&mission_dir, 1, &config).await;
+    std::fs::remove_dir_all(&mission_dir)?;
+
+    // The unresolved include is left out rather than failing the file
+    let names: Vec<String> = result?.class_dependencies.into_iter()
+        .filter(|d| d.source_file.ends_with("rifleman.hpp"))
+        .map(|d| d.class_name.to_string())
+        .collect();
+    assert!(names.iter().any(|n| n == "U_B_CombatUniform_mcam"), "{:?}", names);
+    assert!(names.iter().any(|n| n == "rhsusf_spcs_ocp"), "{:?}", names);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_cache() -> Result<()> {
+    use mission_scanner::scanner::{ConfigContext, FileCache, ParsedFile};
+    use mission_scanner::vfs::OsFs;
+
+    let root = std::env::temp_dir().join(format!("mission_scanner_file_cache_{}", std::process::id()));
+    let mission_dir = root.join("co20_cached.Altis");
+    let cache_dir = root.join("cache");
+    let loadout = mission_dir.join("loadout.hpp");
+    std::fs::create_dir_all(&mission_dir)?;
+    std::fs::write(&loadout, "class rifleman { uniform[] = {\"U_B_CombatUniform_mcam\"}; };")?;
+
+    let config = MissionScannerConfig { cache_dir: Some(cache_dir.clone()), ..MissionScannerConfig::default() };
+    let first = scan_mission(&mission_dir, 1, &config).await?;
+    assert!(first.class_dependencies.iter().any(|d| d.class_name == "U_B_CombatUniform_mcam"));
+    assert_eq!(std::fs::read_dir(cache_dir.join("files"))?.count(), 1);
+
+    // An unchanged file is read from the cache rather than parsed again
+    let cache = FileCache::new(&cache_dir);
+    let content = std::fs::read(&loadout)?;
+    let context = ConfigContext::for_mission(&OsFs::default(), &mission_dir, std::slice::from_ref(&loadout));
+    let hash = FileCache::hash(&content, context.digest.as_bytes());
+    let mut cached = first.class_dependencies.clone();
+    cached[0].class_name = "from_cache".into();
+    cache.insert(&loadout, &hash, &ParsedFile::from(cached))?;
+    let second = scan_mission(&mission_dir, 1, &config).await?;
+    assert!(second.class_dependencies.iter().any(|d| d.class_name == "from_cache"));
+
+    // Editing the file invalidates its entry
+    std::fs::write(&loadout, [content.as_slice(), b"\nclass medic { uniform[] = {\"U_B_CombatUniform_mcam_vest\"}; };"].concat())?;
+    let third = scan_mission(&mission_dir, 1, &config).await?;
+    assert!(!third.class_dependencies.iter().any(|d| d.class_name == "from_cache"));
+    assert!(third.class_dependencies.iter().any(|d| d.class_name == "U_B_CombatUniform_mcam_vest"));
+
+    std::fs::remove_dir_all(&root)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parallel_parsing_is_deterministic() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/co40_parallel.Altis");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("mission.sqm"), "version=54;\nclass Mission {};\n");
+    for (i, uniform) in ["U_B_CombatUniform_mcam", "U_O_CombatUniform_ocamo", "U_I_CombatUniform", "U_C_Poloshirt_blue"].iter().enumerate() {
+        vfs.insert(mission_dir.join("loadouts").join(format!("role{}.hpp", i)),
+            format!("class role{} {{ uniform[] = {{\"{}\"}}; }};", i, uniform));
+    }
+
+    let config = MissionScannerConfig::default();
+    let names = |result: &MissionResults| result.class_dependencies.iter()
+        .map(|d| (d.source_file.clone(), d.class_name.clone()))
+        .collect::<Vec<_>>();
+    let sequential = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    let parallel = scan_mission_with_vfs(&vfs, &mission_dir, 4, &config).await?;
+    assert_eq!(names(&sequential), names(&parallel));
+    assert_eq!(sequential.cpp_files, parallel.cpp_files);
+    assert!(sequential.cpp_files.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(sequential.class_dependencies.len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_progress_events() -> Result<()> {
+    use std::sync::Mutex;
+    use mission_scanner::{Progress, ProgressEvent};
+
+    let mission_dir = PathBuf::from("/missions/co8_progress.Malden");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("loadouts").join("rifleman.hpp"), "class rifleman {};");
+    vfs.insert(mission_dir.join("loadouts").join("medic.hpp"), "class medic {};");
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let config = MissionScannerConfig {
+        progress: Progress::new(move |event: &ProgressEvent| sink.lock().unwrap().push(event.clone())),
+        ..MissionScannerConfig::default()
+    };
+    scan_mission_with_vfs(&vfs, &mission_dir, 2, &config).await?;
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0], ProgressEvent::MissionStarted { mission: "co8_progress.Malden".to_string(), files: 2 });
+    assert_eq!(events.iter().filter(|e| matches!(e, ProgressEvent::FileParsed { .. })).count(), 2);
+    assert!(matches!(events[3], ProgressEvent::MissionFinished { dependencies: 0, .. }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mission_metadata_sidecar() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/co30_night_raid.Altis");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("init.sqf"), "");
+    vfs.insert(mission_dir.join("mission.meta.toml"), r#"
+        tags = ["Coop", "night"]
+        author = "Tyen"
+
+        [players]
+        min = 20
+        max = 30
+    "#);
+
+    let config = MissionScannerConfig { file_extensions: Vec::new(), ..MissionScannerConfig::default() };
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    let metadata = result.metadata.expect("metadata should be loaded");
+    assert!(metadata.has_tag("coop"));
+    assert!(!metadata.has_tag("tvt"));
+    assert_eq!(metadata.author.as_deref(), Some("Tyen"));
+    assert_eq!(metadata.players.map(|p| (p.min, p.max)), Some((20, 30)));
+
+    // A broken sidecar doesn't fail the scan
+    vfs.insert(mission_dir.join("mission.meta.toml"), "tags = [");
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    assert_eq!(result.metadata, None);
+
+    assert_eq!(MissionMetadata::from_toml_str("")?, MissionMetadata::default());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ignore_classes() -> Result<()> {
+    let config = MissionScannerConfig {
+        ignore_classes: vec!["ItemMap".to_string()],
+        ignore_patterns: vec!["Land_*".to_string(), "ACE_?ieldDressing".to_string()],
+        ..MissionScannerConfig::default()
+    };
+    assert!(config.is_ignored("itemmap"));
+    assert!(config.is_ignored("Land_HelipadEmpty_F"));
+    assert!(config.is_ignored("ace_fieldDressing"));
+    assert!(!config.is_ignored("ItemMap_Old"));
+    assert!(!config.is_ignored("rhs_weap_m4a1"));
+
+    let test_dir = get_test_data_dir().join("test_mission_1");
+    let default_config = MissionScannerConfig::default();
+    let result = scan_mission(&test_dir, 1, &default_config).await?;
+    let Some(first) = result.class_dependencies.first().map(|dep| dep.class_name.clone()) else {
+        return Ok(());
+    };
+
+    let config = MissionScannerConfig { ignore_classes: vec![first.to_uppercase()], ..MissionScannerConfig::default() };
+    let filtered = scan_mission(&test_dir, 1, &config).await?;
+    assert!(filtered.class_dependencies.iter().all(|dep| !dep.class_name.eq_ignore_ascii_case(&first)));
+    assert_eq!(
+        filtered.class_dependencies.len(),
+        result.class_dependencies.iter().filter(|dep| !dep.class_name.eq_ignore_ascii_case(&first)).count()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_description_ext_sections() {
+    use parser_hpp::{HppClass, HppProperty, HppValue};
+
+    let class = |name: &str, parent: Option<&str>, properties: Vec<(&str, HppValue)>| HppClass {
+        name: name.to_string(),
+        parent: parent.map(str::to_string),
+        properties: properties.into_iter()
+            .map(|(name, value)| HppProperty { name: name.to_string(), value, append: false })
+            .collect(),
+    };
+    let array = |items: &[&str]| HppValue::Array(items.iter().map(|s| HppValue::from(*s)).collect());
+    let text = |value: &str| HppValue::String(value.to_string());
+
+    let classes = vec![
+        class("CfgRespawnInventory", None, vec![
+            ("WEST1", HppValue::Class(class("WEST1", Some("B_Soldier_base_F"), vec![
+                ("displayName", text("Rifleman")),
+                ("weapons", array(&["arifle_MX_F", "Throw", "Put"])),
+                ("linkedItems", array(&["ItemMap"])),
+                ("uniformClass", text("U_B_CombatUniform_mcam")),
+            ]))),
+            ("WEST2", HppValue::Class(class("WEST2", Some("WEST1"), vec![
+                ("backpack", text("B_AssaultPack_mcamo")),
+            ]))),
+        ]),
+        class("CfgSounds", None, vec![
+            ("sounds", array(&[])),
+            ("alarm", HppValue::Class(class("alarm", None, vec![
+                ("name", text("alarm")),
+                ("sound", array(&["sounds\\alarm.ogg", "1", "1"])),
+            ]))),
+        ]),
+        class("CfgMusic", None, vec![
+            ("intro", HppValue::Class(class("intro", None, vec![("sound", array(&["music\\intro.ogg", "1", "1"]))]))),
+        ]),
+        class("CfgLoadouts", None, vec![
+            ("medic", HppValue::Class(class("medic", None, vec![("items", array(&["ACE_fieldDressing"]))]))),
+        ]),
+        class("CfgFunctions", None, vec![
+            ("convoy", HppValue::Class(class("convoy", None, vec![
+                ("tag", text("CNV")),
+                ("gear", HppValue::Class(class("gear", None, vec![
+                    ("file", text("scripts\\gear")),
+                    ("rifleman", HppValue::Class(class("rifleman", None, vec![]))),
+                    ("medic", HppValue::Class(class("medic", None, vec![("file", text("loadouts\\medic.sqf"))]))),
+                ]))),
+                ("common", HppValue::Class(class("common", None, vec![
+                    ("spawnWave", HppValue::Class(class("spawnWave", None, vec![]))),
+                ]))),
+            ]))),
+        ]),
+    ];
+
+    let file = PathBuf::from("/missions/ext_test/description.ext");
+    let interner = Interner::new();
+    let ext = analyze_description_ext(&classes, &file, &interner);
+    let names: Vec<&str> = ext.references.iter().map(|r| r.class_name.as_str()).collect();
+    assert_eq!(names, ["B_Soldier_base_F", "arifle_MX_F", "Throw", "Put", "ItemMap", "U_B_CombatUniform_mcam", "B_AssaultPack_mcamo", "ACE_fieldDressing"]);
+    // Class names are interned as the references are built
+    assert_eq!(ext.references[1].class_name.as_str().as_ptr(), interner.intern("arifle_MX_F").as_str().as_ptr());
+    assert_eq!(ext.references[0].reference_type, ReferenceType::Inheritance);
+    assert_eq!(ext.references[1].reference_type, ReferenceType::RespawnInventory);
+    assert_eq!(ext.references[7].reference_type, ReferenceType::Direct);
+    assert_eq!(ext.references[1].context, "description.ext:CfgRespawnInventory/WEST1/weapons");
+
+    let assets: Vec<&str> = ext.assets.iter().map(|a| a.path.as_str()).collect();
+    assert_eq!(assets, ["sounds\\alarm.ogg", "music\\intro.ogg"]);
+    assert_eq!(ext.assets[1].context, "description.ext:CfgMusic/intro");
+
+    let functions: Vec<(&str, &str)> = ext.functions.iter().map(|f| (f.name.as_str(), f.file.as_str())).collect();
+    assert_eq!(functions, [
+        ("CNV_fnc_rifleman", "scripts\\gear\\fn_rifleman.sqf"),
+        ("CNV_fnc_medic", "loadouts\\medic.sqf"),
+        ("CNV_fnc_spawnWave", "functions\\common\\fn_spawnWave.sqf"),
+    ]);
+}
+
+#[test]
+fn test_medical_cross_check() {
+    use parser_hpp::{HppClass, HppProperty, HppValue};
+
+    let class = |name: &str, parent: Option<&str>, properties: Vec<(&str, HppValue)>| HppClass {
+        name: name.to_string(),
+        parent: parent.map(str::to_string),
+        properties: properties.into_iter()
+            .map(|(name, value)| HppProperty { name: name.to_string(), value, append: false })
+            .collect(),
+    };
+    let items = |items: &[&str]| HppValue::Array(items.iter().map(|s| HppValue::from(*s)).collect());
+    let config = vec![
+        class("ACE_Medical_Treatment_Actions", None, vec![
+            ("BasicBandage", HppValue::Class(class("BasicBandage", None, vec![
+                ("category", "bandage".into()),
+                ("items", items(&["ACE_fieldDressing"])),
+            ]))),
+            // Inherits its items
+            ("FieldDressing", HppValue::Class(class("FieldDressing", Some("BasicBandage"), vec![]))),
+            ("Morphine", HppValue::Class(class("Morphine", None, vec![("items", items(&["ACE_morphine"]))]))),
+            ("CheckPulse", HppValue::Class(class("CheckPulse", None, vec![("items", items(&[]))]))),
+        ]),
+    ];
+    let treatments = MedicalTreatments::from_class_tree(&config);
+    assert_eq!(treatments.treatments().len(), 4);
+
+    let mission_dir = PathBuf::from("/missions/medical_test");
+    let reference = |class_name: &str, reference_type: ReferenceType| ClassReference {
+        class_name: class_name.into(),
+        reference_type,
+        context: "test".to_string(),
+        source_file: mission_dir.join("loadout.hpp"),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "medical_test".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: Vec::new(),
+        cpp_files: vec![mission_dir.join("loadout.hpp")],
+        class_dependencies: vec![
+            reference("ace_fielddressing", ReferenceType::Direct),
+            reference("ACE_morphine", ReferenceType::Removal),
+            reference("kat_chestSeal", ReferenceType::Direct),
+            reference("ACE_quikclot", ReferenceType::RespawnInventory),
+            reference("arifle_MX_F", ReferenceType::Direct),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let check = cross_check_medical(&mission, &treatments);
+    assert!(!check.is_consistent());
+    assert_eq!(check.unissued, [UnissuedItem {
+        class_name: "ACE_morphine".to_string(),
+        treatments: vec!["Morphine".to_string()],
+    }]);
+    assert_eq!(check.unconfigured, ["ACE_quikclot", "kat_chestSeal"]);
+}
+
+#[tokio::test]
+async fn test_function_files_are_scanned() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/co20_functions.Malden");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(mission_dir.join("mission.sqm"), "version=54;\nclass Mission {};\n");
+    vfs.insert(mission_dir.join("description.ext"), r#"
+        class CfgFunctions {
+            class CNV {
+                class gear {
+                    file = "Scripts\Gear";
+                    class rifleman {};
+                };
+                class addon {
+                    class external { file = "\x\cnv\addons\main\fn_external.sqf"; };
+                };
+            };
+        };
+    "#);
+    vfs.insert(mission_dir.join("scripts").join("gear").join("FN_RIFLEMAN.SQF"), "player addItem \"ItemGPS\";");
+
+    let config = MissionScannerConfig {
+        file_extensions: ["sqm", "sqf", "hpp", "ext"].map(str::to_string).to_vec(),
+        ..MissionScannerConfig::default()
+    };
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    assert_eq!(result.sqf_files, [mission_dir.join("scripts").join("gear").join("FN_RIFLEMAN.SQF")]);
+    Ok(())
+}
+
+/// Names of the missions below a directory selected by a filter, sorted
+async fn scan_names(vfs: &MemoryFs, dir: &Path, filter: MissionFilter) -> Result<Vec<String>> {
+    let config = MissionScannerConfig { file_extensions: Vec::new(), filter, ..MissionScannerConfig::default() };
+    let mut names: Vec<_> = scan_missions_with_vfs(vfs, dir, &config).await?
+        .into_iter()
+        .map(|m| m.mission_name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[tokio::test]
+async fn test_filtered_scan() -> Result<()> {
+    let sqm = |playable: usize| {
+        let units: String = (0..playable)
+            .map(|i| format!(r#"
+                class Item{} {{
+                    dataType="Object";
+                    class Attributes {{
+                        isPlayable=1;
+                    }};
+                    type="B_Soldier_F";
+                }};"#, i))
+            .collect();
+        format!(r#"class Mission {{
+            class Entities {{
+                class Item0 {{
+                    dataType="Group";
+                    side="West";
+                    class Entities {{{}
+                    }};
+                }};
+            }};
+        }};"#, units)
+    };
+
+    let mut vfs = MemoryFs::new();
+    let root = PathBuf::from("/missions");
+    vfs.insert(root.join("co40_raid.Altis/mission.sqm"), sqm(0));
+    vfs.insert(root.join("co40_raid.Altis/mission.meta.toml"), "tags = [\"coop\"]\n[players]\nmin = 20\nmax = 40\n");
+    vfs.insert(root.join("co10_patrol.Stratis/mission.sqm"), sqm(0));
+    vfs.insert(root.join("co10_patrol.Stratis/mission.meta.toml"), "tags = [\"coop\"]\n[players]\nmin = 1\nmax = 10\n");
+    vfs.insert(root.join("tvt_clash.Altis/mission.sqm"), sqm(0));
+    vfs.insert(root.join("tvt_clash.Altis/mission.meta.toml"), "tags = [\"tvt\"]\n");
+    vfs.insert(root.join("sp_test.Altis/mission.sqm"), sqm(2));
+
+    assert_eq!(scan_names(&vfs, &root, MissionFilter::default()).await?.len(), 4);
+
+    let coop_altis = MissionFilter {
+        tags: vec!["COOP".to_string()],
+        terrain: Some("altis".to_string()),
+        players: Some("20-40".parse()?),
+    };
+    assert_eq!(scan_names(&vfs, &root, coop_altis).await?, ["co40_raid.Altis"]);
+
+    // Missions without a player range in their metadata use their slot count
+    let small = MissionFilter { players: Some("2".parse()?), ..MissionFilter::default() };
+    assert_eq!(scan_names(&vfs, &root, small).await?, ["co10_patrol.Stratis", "sp_test.Altis"]);
+
+    assert_eq!(mission_terrain(Path::new("/missions/co40_raid.Altis")), Some("Altis"));
+    assert_eq!(mission_terrain(Path::new("/missions/no_terrain")), None);
+    assert!("40-20".parse::<PlayerRange>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_mission_report() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/report_test");
+    let reference = |class_name: &str, reference_type: ReferenceType, file: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type,
+        context: "test".to_string(),
+        source_file: mission_dir.join(file),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+
+    let results = MissionResults {
+        mission_name: "report_test".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: Some(mission_dir.join("mission.sqm")),
+        sqf_files: vec![mission_dir.join("init.sqf"), mission_dir.join("empty.sqf")],
+        cpp_files: vec![mission_dir.join("loadout.hpp")],
+        class_dependencies: vec![
+            reference("rhs_weap_m4a1", ReferenceType::Direct, "mission.sqm"),
+            reference("RHS_weap_M4A1", ReferenceType::Direct, "init.sqf"),
+            ClassReference {
+                count: 5,
+                count_range: Some(CountRange { min: 2, max: 5 }),
+                ..reference("ACE_fieldDressing", ReferenceType::Variable, "init.sqf")
+            },
+            reference("B_Soldier_F", ReferenceType::Inheritance, "loadout.hpp"),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let report = MissionReport::from_results(&results);
+    assert_eq!(report.counts.total_references, 4);
+    assert_eq!(report.counts.unique_classes, 3);
+    assert_eq!(report.counts.by_source[&SourceKind::Sqf], 2);
+    assert_eq!(report.counts.by_source[&SourceKind::Sqm], 1);
+    assert_eq!(report.counts.by_reference_type[&ReferenceType::Direct], 2);
+    assert_eq!(report.references_from(SourceKind::Config).count(), 1);
+    let bandages = report.references.iter().find(|r| r.class_name == "ACE_fieldDressing").unwrap();
+    assert_eq!(bandages.count, 5);
+    assert_eq!(bandages.count_range, Some(CountRange { min: 2, max: 5 }));
+
+    // Files without references are still listed
+    assert_eq!(report.files.len(), 4);
+    let empty = report.files.iter().find(|f| f.path.ends_with("empty.sqf")).unwrap();
+    assert_eq!((empty.source_kind, empty.references), (SourceKind::Sqf, 0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_required_addons() -> Result<()> {
+    let mut vfs = MemoryFs::new();
+    let mission_dir = PathBuf::from("/missions/addons_test.Altis");
+    vfs.insert(mission_dir.join("mission.sqm"), r#"version=54;
+addons[]={"A3_Characters_F","rhsusf_c_troops"};
+class Mission {
+    addOnsAuto[]={"ace_cargo"};
+};
+"#);
+
+    let config = MissionScannerConfig::default();
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    assert_eq!(result.required_addons, ["A3_Characters_F", "rhsusf_c_troops", "ace_cargo"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sqm_code_is_scanned() -> Result<()> {
+    let mut vfs = MemoryFs::new();
+    let mission_dir = PathBuf::from("/missions/trigger_test.Altis");
+    vfs.insert(mission_dir.join("mission.sqm"), r#"class Mission {
+    class Entities {
+        class Item0 {
+            dataType="Trigger";
+            position[]={150,5,250};
+            class Attributes {
+                name="trg_resupply";
+                condition="this";
+                onActivation="supply_box addWeaponCargo [""rhs_weap_m4a1"",2];";
+            };
+            type="EmptyDetector";
+        };
+        class Item1 {
+            dataType="Object";
+            class Attributes {
+                init="this addItemCargo [""ACE_fieldDressing"",10]; this removeItem ""ACE_morphine"";";
+                name="medical_box";
+            };
+            type="Box_NATO_Support_F";
+        };
+        class Item2 {
+            dataType="Object";
+            class Attributes {
+                init="this addAction [""Take rifle"", ""(_this select 1) addWeapon 'arifle_MX_F'""];";
+                name="rifle_rack";
+            };
+            type="Land_WeaponRack_F";
+        };
+    };
+};
+"#);
+
+    let config = MissionScannerConfig::default();
+    let result = scan_mission_with_vfs(&vfs, &mission_dir, 1, &config).await?;
+    let reference = result.class_dependencies.iter()
+        .find(|dep| dep.class_name == "rhs_weap_m4a1")
+        .expect("class from trigger statement");
+    assert!(reference.context.starts_with("sqm:trigger:trg_resupply:"));
+    assert_eq!(reference.confidence, Confidence::High);
+
+    let reference = result.class_dependencies.iter()
+        .find(|dep| dep.class_name == "ACE_fieldDressing")
+        .expect("class from init attribute");
+    assert!(reference.context.starts_with("sqm:init:medical_box:"));
+    assert_eq!(reference.reference_type, ReferenceType::Direct);
+
+    let reference = result.class_dependencies.iter()
+        .find(|dep| dep.class_name == "ACE_morphine")
+        .expect("class removed in init attribute");
+    assert_eq!(reference.reference_type, ReferenceType::Removal);
+
+    let reference = result.class_dependencies.iter()
+        .find(|dep| dep.class_name == "arifle_MX_F")
+        .expect("class from code given to addAction as a string");
+    assert!(reference.context.starts_with("sqm:init:rifle_rack:"));
+
+    Ok(())
+}
+
+#[test]
+fn test_min_confidence() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/confidence_test");
+    let reference = |class_name: &str, confidence: Confidence| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: mission_dir.join("init.sqf"),
+        owner: None,
+        location: None,
+        confidence,
+        count: 1,
+        count_range: None,
+    };
+
+    let config = MissionScannerConfig {
+        min_confidence: Confidence::Medium,
+        ..MissionScannerConfig::default()
+    };
+    assert!(config.keeps(&reference("rhs_weap_m4a1", Confidence::High)));
+    assert!(config.keeps(&reference("rhs_mag_30Rnd_556x45_M855A1_Stanag", Confidence::Medium)));
+    assert!(!config.keeps(&reference("respawn_west", Confidence::Low)));
+    assert!(MissionScannerConfig::default().keeps(&reference("respawn_west", Confidence::Low)));
+
+    let results = MissionResults {
+        mission_name: "confidence_test".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![mission_dir.join("init.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("rhs_weap_m4a1", Confidence::High),
+            reference("respawn_west", Confidence::Low),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+    let report = MissionReport::from_results(&results);
+    let names: Vec<_> = report.references_with(Confidence::Medium).map(|r| r.class_name.as_str()).collect();
+    assert_eq!(names, ["rhs_weap_m4a1"]);
+
+    // Scans saved before confidence was recorded read as high confidence
+    let saved: ClassReference = serde_json::from_str(r#"{
+        "class_name": "ItemMap",
+        "reference_type": "Direct",
+        "context": "test",
+        "source_file": "init.sqf"
+    }"#)?;
+    assert_eq!(saved.confidence, Confidence::High);
+
+    assert_eq!("MEDIUM".parse::<Confidence>()?, Confidence::Medium);
+    assert!("certain".parse::<Confidence>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_missions() -> Result<()> {
+    let mission_dir = PathBuf::from("/missions/diff_test");
+    let mission = |classes: &[&str]| MissionResults {
+        mission_name: "diff_test".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![mission_dir.join("init.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: classes.iter().map(|class_name| ClassReference {
+            class_name: (*class_name).into(),
+            reference_type: ReferenceType::Direct,
+            context: "test".to_string(),
+            source_file: mission_dir.join("init.sqf"),
+            owner: None,
+            location: None,
+            confidence: Confidence::High,
+            count: 1,
+            count_range: None,
+        }).collect(),
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let old = mission(&["rhs_weap_m4a1", "ItemMap", "ACE_fieldDressing", "ACE_fieldDressing"]);
+    let new = mission(&["RHS_weap_M4A1", "itemmap", "itemmap", "ACE_fieldDressing", "ACE_fieldDressing", "rhsusf_spcs_ocp"]);
+
+    let diff = diff_missions(&old, &new);
+    assert_eq!(diff.added, ["rhsusf_spcs_ocp"]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(diff.changed, [CountChange { class_name: "itemmap".to_string(), old_count: 1, new_count: 2 }]);
+
+    let reverse = diff_missions(&new, &old);
+    assert_eq!(reverse.removed, ["rhsusf_spcs_ocp"]);
+    assert!(diff_missions(&old, &old).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_group_variants() {
+    let mission = |mission_name: &str, classes: &[&str]| {
+        let mission_dir = PathBuf::from("/missions").join(mission_name);
+        MissionResults {
+            mission_name: mission_name.to_string(),
+            mission_dir: mission_dir.clone(),
+            sqm_file: None,
+            sqf_files: vec![mission_dir.join("init.sqf")],
+            cpp_files: Vec::new(),
+            class_dependencies: classes.iter().map(|class_name| ClassReference {
+                class_name: (*class_name).into(),
+                reference_type: ReferenceType::Direct,
+                context: "test".to_string(),
+                source_file: mission_dir.join("init.sqf"),
+                owner: None,
+                location: None,
+                confidence: Confidence::High,
+                count: 1,
+                count_range: None,
+            }).collect(),
+            footprint: Default::default(),
+            asset_references: Vec::new(),
+            quarantined_files: Vec::new(),
+            metadata: None,
+            playable_slots: 0,
+            required_addons: Vec::new(),
+            required_mods: Default::default(),
+            script_calls: Vec::new(),
+            curator: None,
+        }
+    };
+
+    assert_eq!(mission_variant("op_x.Altis"), ("op_x", Some("Altis")));
+    assert_eq!(mission_variant("op_x.Malden.pbo"), ("op_x", Some("Malden")));
+    assert_eq!(mission_variant("training"), ("training", None));
+    assert_eq!(mission_variant(".hidden"), (".hidden", None));
+
+    let results = [
+        mission("op_x.Malden", &["ItemMap", "B_Heli_Transport_01_F"]),
+        mission("training", &["ItemMap"]),
+        mission("OP_X.Altis", &["itemmap", "B_Boat_Transport_01_F"]),
+    ];
+    let groups = group_variants(&results);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].name, "OP_X");
+    assert_eq!(groups[0].missions, ["OP_X.Altis", "op_x.Malden"]);
+    assert_eq!(groups[0].maps, ["Altis", "Malden"]);
+    assert_eq!(groups[0].shared_classes, ["itemmap"]);
+    assert_eq!(groups[0].classes, ["b_boat_transport_01_f", "b_heli_transport_01_f", "itemmap"]);
+    assert_eq!(groups[1].missions, ["training"]);
+    assert!(groups[1].maps.is_empty());
+}
+
+#[test]
+fn test_required_mods() -> Result<()> {
+    let reference = |class_name: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: PathBuf::from("init.sqf"),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let references = [
+        reference("rhsusf_spcs_ocp"),
+        reference("rhs_weap_ak74m"),
+        reference("ACE_fieldDressing"),
+        reference("tc_custom_vest"),
+        reference("tfar_anprc152"),
+        reference("TFAR_anprc152"),
+        reference("ItemMap"),
+        ClassReference { reference_type: ReferenceType::Removal, ..reference("CUP_NVG_PVS14") },
+    ];
+
+    let mut prefixes = ModPrefixes::default();
+    assert_eq!(prefixes.mod_for("RHSUSF_spcs_ocp"), Some("RHS: United States Forces"));
+    let required = prefixes.required_mods(&references);
+    assert_eq!(required.mods, ["ACE3", "RHS: Armed Forces of the Russian Federation", "RHS: United States Forces", "TC"]);
+    assert_eq!(required.unmapped_classes, ["ItemMap", "tfar_anprc152"]);
+
+    prefixes.extend(&ModPrefixes::from_toml_str(r#"
+        "tfar_" = "Task Force Arrowhead Radio"
+        "ace_" = "ACE3 (custom)"
+    "#)?);
+    let required = prefixes.required_mods(&references);
+    assert!(required.mods.contains(&"Task Force Arrowhead Radio".to_string()));
+    assert!(required.mods.contains(&"ACE3 (custom)".to_string()));
+    assert_eq!(required.unmapped_classes, ["ItemMap"]);
+
+    assert!(ModPrefixes::from_toml_str("rhs_ = [").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_zeus_addons_are_required() -> Result<()> {
+    let curator: CuratorSetup = serde_json::from_str(r##"{
+        "modules": [{ "name": "zeus_1", "owner": "#adminLogged", "addons": "official" }],
+        "added_addons": ["ace_arsenal", "CUP_Weapons_AK", "my_unknown_addon"]
+    }"##)?;
+    assert_eq!(curator.modules[0].addons, Some(CuratorAddons::Official));
+
+    let prefixes = ModPrefixes::default();
+    let mut required = RequiredMods { mods: vec!["TC".to_string()], unmapped_classes: Vec::new() };
+    required.add_addons(&prefixes, &curator.added_addons);
+    assert_eq!(required.mods, ["ACE3", "CUP", "TC"]);
+    assert!(required.unmapped_classes.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_write_json_report() -> Result<()> {
+    let output_dir = std::env::temp_dir().join(format!("mission_scanner_json_report_{}", std::process::id()));
+    let mission = |name: &str, classes: &[&str]| {
+        let mission_dir = PathBuf::from("/missions").join(name);
+        MissionResults {
+            mission_name: name.to_string(),
+            mission_dir: mission_dir.clone(),
+            sqm_file: None,
+            sqf_files: vec![mission_dir.join("init.sqf")],
+            cpp_files: Vec::new(),
+            class_dependencies: classes.iter().enumerate().map(|(i, class_name)| ClassReference {
+                class_name: (*class_name).into(),
+                reference_type: ReferenceType::Direct,
+                context: "test".to_string(),
+                source_file: mission_dir.join("init.sqf"),
+                owner: None,
+                location: Some(SourceLocation { line: classes.len() - i, column: 1 }),
+                confidence: Confidence::High,
+                count: 1,
+                count_range: None,
+            }).collect(),
+            footprint: Default::default(),
+            asset_references: Vec::new(),
+            quarantined_files: Vec::new(),
+            metadata: None,
+            playable_slots: 0,
+            required_addons: Vec::new(),
+            required_mods: Default::default(),
+            script_calls: Vec::new(),
+            curator: None,
+        }
+    };
+    let results = vec![
+        mission("co10_b.Stratis", &["ItemMap", "rhs_weap_m4a1"]),
+        mission("co10_a.Altis", &["ItemMap", "ACE_fieldDressing", "ItemCompass"]),
+    ];
+
+    let written = write_json_report(&results, &output_dir)?;
+    assert_eq!(written.len(), 3);
+    assert!(written.last().unwrap().ends_with(mission_scanner::report::SUMMARY_FILE_NAME));
+
+    let summary: ScanSummary = serde_json::from_str(&std::fs::read_to_string(written.last().unwrap())?)?;
+    assert_eq!(summary.schema_version, mission_scanner::report::REPORT_SCHEMA_VERSION);
+    assert_eq!(summary.missions.iter().map(|m| m.mission_name.as_str()).collect::<Vec<_>>(), ["co10_a.Altis", "co10_b.Stratis"]);
+    assert_eq!(summary.classes, ["ace_fielddressing", "itemcompass", "itemmap", "rhs_weap_m4a1"]);
+
+    let report: ReportFile = serde_json::from_str(&std::fs::read_to_string(output_dir.join(&summary.missions[0].report_file))?)?;
+    assert_eq!(report.schema_version, mission_scanner::report::REPORT_SCHEMA_VERSION);
+    assert_eq!(report.report.counts.total_references, 3);
+    // Paths are relative to the mission and references are sorted by location
+    assert!(report.report.references.iter().all(|r| r.source_file == Path::new("init.sqf")));
+    assert_eq!(report.report.references.iter().map(|r| r.class_name.as_str()).collect::<Vec<_>>(), ["ItemCompass", "ACE_fieldDressing", "ItemMap"]);
+
+    // Writing the same results again gives identical files
+    let first = std::fs::read_to_string(&written[0])?;
+    write_json_report(&results, &output_dir)?;
+    assert_eq!(std::fs::read_to_string(&written[0])?, first);
+
+    // Missions sharing a name, or named like a path, get files of their own in the directory
+    let clashing = vec![
+        mission("co10_a.Altis", &["ItemMap"]),
+        mission("CO10_A.Altis", &["ItemGPS"]),
+        mission("../summary", &["ItemWatch"]),
+    ];
+    let clashing_dir = output_dir.join("clashing");
+    let written = write_json_report(&clashing, &clashing_dir)?;
+    assert_eq!(written.len(), 4);
+    assert!(written.iter().all(|path| path.parent() == Some(clashing_dir.as_path())));
+    let summary: ScanSummary = serde_json::from_str(&std::fs::read_to_string(written.last().unwrap())?)?;
+    let files: std::collections::BTreeSet<_> = summary.missions.iter().map(|m| m.report_file.to_string_lossy().into_owned()).collect();
+    assert_eq!(files, std::collections::BTreeSet::from(["co10_a.Altis.json".to_string(), "CO10_A.Altis-2.json".to_string(), "_summary.json".to_string()]));
+
+    std::fs::remove_dir_all(&output_dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_csv_export() {
+    let mission_dir = PathBuf::from("/missions/csv_test");
+    let reference = |class_name: &str, file: &str, line: Option<usize>| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: mission_dir.join(file),
+        owner: None,
+        location: line.map(|line| SourceLocation { line, column: 1 }),
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let results = MissionResults {
+        mission_name: "csv_test".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: Some(mission_dir.join("mission.sqm")),
+        sqf_files: vec![mission_dir.join("scripts/loadout.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("rhs_weap_m4a1", "scripts/loadout.sqf", Some(4)),
+            reference("ItemMap", "scripts/loadout.sqf", Some(2)),
+            reference("ItemMap", "scripts/loadout.sqf", Some(2)),
+            reference("B_Soldier_F", "mission.sqm", None),
+            reference("odd,name", "mission.sqm", None),
+            ClassReference { count: 10, ..reference("ACE_fieldDressing", "scripts/loadout.sqf", Some(6)) },
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    assert_eq!(to_csv(&[results]), "\
+mission,file,line,class,reference_type,count
+csv_test,mission.sqm,,B_Soldier_F,Direct,1
+csv_test,mission.sqm,,\"odd,name\",Direct,1
+csv_test,scripts/loadout.sqf,2,ItemMap,Direct,2
+csv_test,scripts/loadout.sqf,4,rhs_weap_m4a1,Direct,1
+csv_test,scripts/loadout.sqf,6,ACE_fieldDressing,Direct,10
+");
+}
+
+#[test]
+fn test_html_report() {
+    let mission_dir = PathBuf::from("/missions/html_test");
+    let reference = |class_name: &str, file: &str| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: mission_dir.join(file),
+        owner: None,
+        location: Some(SourceLocation { line: 3, column: 1 }),
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let results = MissionResults {
+        mission_name: "html_test<&>".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![mission_dir.join("loadout.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![
+            reference("rhs_weap_m4a1", "loadout.sqf"),
+            reference("RHS_weap_M4A1", "init.sqf"),
+            reference("ItemMap", "loadout.sqf"),
+        ],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: Some(MissionMetadata {
+            signatures: Some(PboSignatures::default()),
+            ..MissionMetadata::default()
+        }),
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+    let database: ClassDatabase = ["ItemMap"]
+        .into_iter()
+        .map(|name| ClassEntry { name: name.to_string(), parent: None, config: None, source: None, category: None })
+        .collect();
+
+    let html = to_html(std::slice::from_ref(&results), &ModPrefixes::default(), Some(&database));
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("html_test&lt;&amp;&gt;"));
+    assert!(html.contains("<tr class=\"missing\"><td><code>rhs_weap_m4a1</code></td><td data-sort=\"RHS: Armed Forces of the Russian Federation\">"));
+    assert!(html.contains("<tr><td><code>ItemMap</code></td>"));
+    assert!(html.contains("<a href=\"file:///missions/html_test/init.sqf\">init.sqf:3</a>"));
+    assert!(html.contains("<ul class=\"missing-list\">\n<li><code>rhs_weap_m4a1</code></li>\n</ul>"));
+    assert!(html.contains("<details class=\"severity\" open>\n<summary>Missing &ndash; 1 classes</summary>"));
+    assert!(html.contains("<details class=\"severity\">\n<summary>Found &ndash; 1 classes</summary>"));
+    assert!(!html.contains("Low confidence"));
+    assert!(html.contains("<input type=\"search\" id=\"search\""));
+    assert!(html.contains("<td><span class=\"missing-list\">unsigned</span></td></tr>"));
+    assert!(html.contains("<p class=\"missing-list\">No .bisign signature next to the PBO.</p>"));
+    assert!(!html.contains("class=\"pager\""));
+
+    // Large groups get a pager, small ones are shown whole
+    let mut large = results;
+    large.class_dependencies.extend((0..150).map(|i| reference(&format!("ItemMap_{}", i), "loadout.sqf")));
+    let html = to_html(&[large], &ModPrefixes::default(), Some(&database));
+    assert!(html.contains("<summary>Missing &ndash; 151 classes</summary>"));
+    assert_eq!(html.matches("<div class=\"pager\" data-page-size=\"100\">").count(), 1);
+}
+
+#[test]
+fn test_markdown_report() {
+    let mission = |classes: &[&str], mods: &[&str]| {
+        let mission_dir = PathBuf::from("/missions/md_test");
+        MissionResults {
+            mission_name: "md_test".to_string(),
+            mission_dir: mission_dir.clone(),
+            sqm_file: None,
+            sqf_files: Vec::new(),
+            cpp_files: Vec::new(),
+            class_dependencies: classes.iter().map(|class_name| ClassReference {
+                class_name: (*class_name).into(),
+                reference_type: ReferenceType::Direct,
+                context: "test".to_string(),
+                source_file: mission_dir.join("init.sqf"),
+                owner: None,
+                location: None,
+                confidence: Confidence::High,
+                count: 1,
+                count_range: None,
+            }).collect(),
+            footprint: Default::default(),
+            asset_references: Vec::new(),
+            quarantined_files: Vec::new(),
+            metadata: None,
+            playable_slots: 0,
+            required_addons: Vec::new(),
+            required_mods: RequiredMods { mods: mods.iter().map(|m| m.to_string()).collect(), unmapped_classes: Vec::new() },
+            script_calls: Vec::new(),
+            curator: None,
+        }
+    };
+    let old = mission(&["ItemMap"], &[]);
+    let new = mission(&["ItemMap", "ace_fieldDressing", "rhs_weap_m4a1", "rhs_weap_m4a1", "ACE_fieldDressing", "ace_morphine"], &["ACE3", "RHS: Armed Forces of the Russian Federation"]);
+    let database: ClassDatabase = ["ItemMap", "ACE_fieldDressing", "ace_morphine"]
+        .into_iter()
+        .map(|name| ClassEntry { name: name.to_string(), parent: None, config: None, source: None, category: None })
+        .collect();
+
+    let markdown = to_markdown(&[new], Some(&[old]), Some(&database), 2);
+    assert_eq!(markdown, "\
+## Mission dependencies
+
+| Mission | Classes | Added | Missing |
+|---|---:|---:|---:|
+| md_test | 4 | 3 | 1 |
+
+### New dependencies (3)
+
+- `ace_fieldDressing` in md_test (2 references)
+- `rhs_weap_m4a1` in md_test (2 references)
+- ...and 1 more
+
+### Missing classes (1)
+
+- :x: `rhs_weap_m4a1` in md_test
+
+### Mods (2)
+
+- ACE3 **(new)**
+- RHS: Armed Forces of the Russian Federation **(new)**
+");
+}
+
+#[test]
+fn test_reference_locations() -> Result<()> {
+    let sqm_path = PathBuf::from("/missions/located/mission.sqm");
+    let mut vfs = MemoryFs::new();
+    vfs.insert(&sqm_path, r#"class Mission {
+    class Entities {
+        class Item0 {
+            dataType="Object";
+            type="B_Soldier_F";
+        };
+    };
+};"#);
+
+    let deps = parse_file_with_vfs(&vfs, &sqm_path)?;
+    let soldier = deps.iter().find(|d| d.class_name == "B_Soldier_F").expect("soldier should be found");
+    assert_eq!(soldier.location, Some(SourceLocation { line: 5, column: 19 }));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_missions_stream() -> Result<()> {
+    use futures::StreamExt;
+
+    let root = PathBuf::from("/missions");
+    let mut vfs = MemoryFs::new();
+    for name in ["op_alpha.Altis", "op_bravo.Tanoa", "op_charlie.Altis"] {
+        vfs.insert(root.join(name).join("mission.sqm"), "class Mission {};");
+    }
+    let config = MissionScannerConfig {
+        file_extensions: Vec::new(),
+        filter: MissionFilter { terrain: Some("Altis".to_string()), ..MissionFilter::default() },
+        ..MissionScannerConfig::default()
+    };
+
+    let mut stream = Box::pin(scan_missions_stream_with_vfs(&vfs, &root, &config));
+    let mut names = Vec::new();
+    while let Some(result) = stream.next().await {
+        names.push(result?.mission_name);
+    }
+    names.sort();
+    assert_eq!(names, ["op_alpha.Altis", "op_charlie.Altis"]);
+
+    // A missing directory yields no missions rather than an error
+    let missing = scan_missions_stream_with_vfs(&vfs, Path::new("/elsewhere"), &config);
+    assert_eq!(missing.count().await, 0);
+
+    Ok(())
+}
+
+/// Build a PBO from `(name, packing method, original size, stored data)` entries
+fn build_pbo(properties: &[(&str, &str)], entries: &[(&str, u32, usize, Vec<u8>)]) -> Vec<u8> {
+    let header = |bytes: &mut Vec<u8>, name: &str, packing: u32, original: usize, size: usize| {
+        bytes.extend(name.as_bytes());
+        bytes.push(0);
+        for value in [packing, original as u32, 0, 0, size as u32] {
+            bytes.extend(value.to_le_bytes());
+        }
+    };
+
+    let mut bytes = Vec::new();
+    header(&mut bytes, "", 0x5665_7273, 0, 0);
+    for (key, value) in properties {
+        bytes.extend(format!("{}\0{}\0", key, value).as_bytes());
+    }
+    bytes.push(0);
+    for (name, packing, original, data) in entries {
+        header(&mut bytes, name, *packing, *original, data.len());
+    }
+    header(&mut bytes, "", 0, 0, 0);
+    for (_, _, _, data) in entries {
+        bytes.extend(data);
+    }
+    bytes
+}
+
+#[tokio::test]
+async fn test_scan_pbo() -> Result<()> {
+    // "abc" as literals, then a pointer 3 back repeating 6 bytes, then the byte sum
+    let mut compressed = vec![0x07, b'a', b'b', b'c', 0x03, 0x03];
+    compressed.extend((b"abcabcabc".iter().map(|&b| b as u32).sum::<u32>()).to_le_bytes());
+
+    let sqm = b"class Mission {};".to_vec();
+    let bytes = build_pbo(&[("prefix", "co40_raid.Altis")], &[
+        ("mission.sqm", 0, 0, sqm.clone()),
+        ("scripts\\notes.txt", 0x4370_7273, 9, compressed),
+    ]);
+
+    let pbo = Pbo::from_bytes(&bytes)?;
+    assert_eq!(pbo.property("PREFIX"), Some("co40_raid.Altis"));
+    assert_eq!(pbo.entries.len(), 2);
+    assert_eq!(pbo.entries[0].data, sqm);
+    assert_eq!(pbo.entries[1].name, "scripts/notes.txt");
+    assert_eq!(pbo.entries[1].data, b"abcabcabc");
+    assert!(Pbo::from_bytes(&bytes[..bytes.len() - 4]).is_err());
+
+    let dir = std::env::temp_dir().join(format!("mission_scanner_pbo_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let pbo_path = dir.join("co40_raid.Altis.pbo");
+    std::fs::write(&pbo_path, &bytes)?;
+    std::fs::write(dir.join("co40_raid.Altis.pbo.tyen_v1.bisign"), b"")?;
+
+    let config = MissionScannerConfig { file_extensions: Vec::new(), ..MissionScannerConfig::default() };
+    let result = scan_pbo(&pbo_path, &config).await?;
+    assert_eq!(result.mission_name, "co40_raid.Altis");
+    assert_eq!(result.mission_dir, pbo_path);
+    assert_eq!(result.sqm_file, Some(pbo_path.join("mission.sqm")));
+    let signatures = result.metadata.and_then(|metadata| metadata.signatures);
+    assert_eq!(signatures, Some(PboSignatures { keys: vec!["tyen_v1".to_string()] }));
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_pbo_signatures() {
+    use mission_scanner::pbo::find_signatures;
+
+    let mut vfs = MemoryFs::new();
+    vfs.insert("/missions/co40_raid.Altis.pbo", "");
+    vfs.insert("/missions/co40_raid.Altis.pbo.tyen_v2.bisign", "");
+    vfs.insert("/missions/CO40_RAID.altis.pbo.Server_Key.bisign", "");
+    vfs.insert("/missions/co40_raid.Altis.pbo.bisign", "");
+    vfs.insert("/missions/tvt20_hold.Stratis.pbo.tyen_v2.bisign", "");
+    vfs.insert("/missions/keys/co40_raid.Altis.pbo.nested.bisign", "");
+
+    let signatures = find_signatures(&vfs, Path::new("/missions/co40_raid.Altis.pbo"));
+    assert_eq!(signatures.keys, ["Server_Key", "tyen_v2"]);
+    assert!(signatures.is_signed());
+
+    let unsigned = find_signatures(&vfs, Path::new("/missions/tvt10_wait.Malden.pbo"));
+    assert!(!unsigned.is_signed());
+}
+
+#[test]
+fn test_lzss_rejects_bad_input() {
+    use mission_scanner::pbo::decompress_lzss;
+
+    // A pointer with offset 0 would repeat bytes not written yet
+    assert!(decompress_lzss(&[0x01, b'a', 0x00, 0x00], 4).is_err());
+    // A header claiming a huge size fails on the short input instead of allocating it
+    assert!(decompress_lzss(&[0xFF, b'a'], usize::MAX).is_err());
+}
+
+#[test]
+fn test_class_categories_from_parents() {
+    let entry = |name: &str, parent: Option<&str>, config: &str| ClassEntry {
+        name: name.to_string(),
+        parent: parent.map(str::to_string),
+        config: Some(config.to_string()),
+        source: None,
+        category: None,
+    };
+    let database: ClassDatabase = [
+        entry("arifle_MX_F", Some("arifle_MX_Base_F"), "CfgWeapons"),
+        entry("arifle_MX_Base_F", Some("Rifle_Base_F"), "CfgWeapons"),
+        entry("Rifle_Base_F", Some("Rifle"), "CfgWeapons"),
+        entry("Rifle", Some("RifleCore"), "CfgWeapons"),
+        entry("V_PlateCarrier1_rgr", Some("Vest_NoCamo_Base"), "CfgWeapons"),
+        entry("Vest_NoCamo_Base", Some("ItemCore"), "CfgWeapons"),
+        entry("B_AssaultPack_mcamo", Some("B_AssaultPack_Base"), "CfgVehicles"),
+        entry("B_AssaultPack_Base", Some("Bag_Base"), "CfgVehicles"),
+        entry("B_MRAP_01_F", Some("MRAP_01_base_F"), "CfgVehicles"),
+        entry("30Rnd_65x39_caseless_mag", None, "CfgMagazines"),
+        // Parents looping must not hang
+        entry("loop_a", Some("loop_b"), "CfgWeapons"),
+        entry("loop_b", Some("loop_a"), "CfgWeapons"),
+        ClassEntry { category: Some(ItemKind::Item), ..entry("tc_custom_rifle", Some("arifle_MX_F"), "CfgWeapons") },
+    ].into_iter().collect();
+
+    assert_eq!(database.category("ARIFLE_MX_F"), Some(ItemKind::Weapon));
+    assert_eq!(database.category("V_PlateCarrier1_rgr"), Some(ItemKind::Vest));
+    assert_eq!(database.category("B_AssaultPack_mcamo"), Some(ItemKind::Backpack));
+    assert_eq!(database.category("B_MRAP_01_F"), Some(ItemKind::Vehicle));
+    assert_eq!(database.category("30Rnd_65x39_caseless_mag"), Some(ItemKind::Magazine));
+    assert_eq!(database.category("tc_custom_rifle"), Some(ItemKind::Item));
+    assert_eq!(database.category("loop_a"), None);
+    assert_eq!(database.category("unknown_class"), None);
+}
+
+#[test]
+fn test_item_category_mismatches() -> Result<()> {
+    let mission_dir = std::env::temp_dir().join(format!("mission_scanner_categories_{}", std::process::id()));
+    std::fs::create_dir_all(&mission_dir)?;
+    let script = mission_dir.join("loadout.sqf");
+    std::fs::write(&script, "player addWeapon \"V_PlateCarrier1_rgr\";\nplayer addWeapon \"arifle_MX_F\";\nplayer addVest \"V_PlateCarrier1_rgr\";\n")?;
+
+    let database: ClassDatabase = [
+        ClassEntry { name: "arifle_MX_F".to_string(), parent: None, config: None, source: None, category: Some(ItemKind::Weapon) },
+        ClassEntry { name: "V_PlateCarrier1_rgr".to_string(), parent: None, config: None, source: None, category: Some(ItemKind::Vest) },
+    ].into_iter().collect();
+    let mission = MissionResults {
+        mission_name: "categories".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![script.clone()],
+        cpp_files: Vec::new(),
+        class_dependencies: Vec::new(),
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let mismatches = check_item_categories(&mission, &database);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].class_name, "V_PlateCarrier1_rgr");
+    assert_eq!((mismatches[0].expected, mismatches[0].found), (ItemKind::Weapon, ItemKind::Vest));
+    assert_eq!(mismatches[0].location.map(|location| location.line), Some(1));
+
+    std::fs::remove_dir_all(&mission_dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_class_database_validation() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("mission_scanner_classdb_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let csv = dir.join("classes.csv");
+    std::fs::write(&csv, "name,parent,config,source,category\nrhs_weap_m4a1,rhs_weap_m4_Base,CfgWeapons,rhsusf,Weapon\nItemMap,,CfgWeapons,,\n\"srifle_DMR_07_blk_F\",,CfgWeapons,\"Rifle, 6.5mm \"\"Zafir\"\"\",Weapon\n")?;
+    let database = ClassDatabase::load_from_file(&csv)?;
+    assert_eq!(database.len(), 3);
+    // Quoted fields keep their commas and escaped quotes
+    assert_eq!(database.get("srifle_DMR_07_blk_F").and_then(|c| c.source.as_deref()), Some("Rifle, 6.5mm \"Zafir\""));
+    assert_eq!(database.category("srifle_DMR_07_blk_F"), Some(ItemKind::Weapon));
+    assert!(database.class_exists("RHS_WEAP_M4A1"));
+    assert_eq!(database.get("rhs_weap_m4a1").and_then(|c| c.source.as_deref()), Some("rhsusf"));
+    assert_eq!(database.get("itemmap").and_then(|c| c.parent.as_deref()), None);
+    assert_eq!(database.category("rhs_weap_m4a1"), Some(ItemKind::Weapon));
+    assert_eq!(database.category("ItemMap"), None);
+
+    let json = dir.join("classes.json");
+    std::fs::write(&json, r#"["ItemMap", {"name": "ItemCompass", "config": "CfgWeapons"}]"#)?;
+    let database = ClassDatabase::load_from_file(&json)?;
+    assert!(database.class_exists("itemcompass"));
+    assert!(!database.class_exists("ItemGPS"));
+
+    let mission_dir = PathBuf::from("/missions/validate");
+    let reference = |class_name: &str, line: usize| ClassReference {
+        class_name: class_name.into(),
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: mission_dir.join("init.sqf"),
+        owner: None,
+        location: Some(SourceLocation { line, column: 1 }),
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let mission = MissionResults {
+        mission_name: "validate".to_string(),
+        mission_dir: mission_dir.clone(),
+        sqm_file: None,
+        sqf_files: vec![mission_dir.join("init.sqf")],
+        cpp_files: Vec::new(),
+        class_dependencies: vec![reference("ItemMap", 1), reference("ItemGPS", 2), reference("itemgps", 7)],
+        footprint: Default::default(),
+        asset_references: Vec::new(),
+        quarantined_files: Vec::new(),
+        metadata: None,
+        playable_slots: 0,
+        required_addons: Vec::new(),
+        required_mods: Default::default(),
+        script_calls: Vec::new(),
+        curator: None,
+    };
+
+    let missing = database.missing_classes(&mission);
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].class_name, "ItemGPS");
+    assert_eq!(missing[0].references.iter().filter_map(|r| r.location).map(|l| l.line).collect::<Vec<_>>(), [2, 7]);
+    assert_eq!(missing[0].suggestions[0].class_name, "ItemMap");
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_class_suggestions() {
+    let database: ClassDatabase = ["rhs_weap_m4a1", "rhs_weap_m4a1_carryhandle", "rhs_weap_ak74m", "CUP_arifle_M4A1", "ACE_fieldDressing", "ACE_elasticBandage"]
+        .into_iter()
+        .map(|name| ClassEntry { name: name.to_string(), parent: None, config: None, source: None, category: None })
+        .collect();
+
+    let suggestions = database.suggest("rhs_weap_m4a1_carryhandel", 3);
+    assert_eq!(suggestions.first().map(|s| s.class_name.as_str()), Some("rhs_weap_m4a1_carryhandle"));
+    assert!(suggestions.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    // A class from the same mod ranks above the same name from another mod
+    let suggestions = database.suggest("rhs_weap_M4A2", 3);
+    assert_eq!(suggestions[0].class_name, "rhs_weap_m4a1");
+    let cup = suggestions.iter().find(|s| s.class_name == "CUP_arifle_M4A1");
+    assert!(cup.is_none_or(|cup| cup.score < suggestions[0].score));
+
+    assert_eq!(database.suggest("ACE_fieldDresing", 1)[0].class_name, "ACE_fieldDressing");
+    assert!(database.suggest("Land_Wreck_Heli_Attack_01_F", 3).is_empty());
+
+    assert_eq!(mission_scanner::classdb::levenshtein("kitten", "sitting"), 3);
+    assert!((mission_scanner::classdb::jaro_winkler("martha", "marhta") - 0.961).abs() < 0.001);
+    assert_eq!(mission_scanner::classdb::similarity("ItemMap", "itemmap"), 1.0);
+}
+
+#[test]
+fn test_class_names_are_interned() {
+    let interner = Interner::new();
+    let first = interner.intern("ACE_fieldDressing");
+    let second = interner.intern("ACE_fieldDressing");
+    assert_eq!(first.as_str().as_ptr(), second.as_str().as_ptr());
+    assert_eq!(interner.intern("ace_fielddressing"), "ace_fielddressing");
+    assert_eq!(interner.len(), 2);
+
+    // Symbols are written and read back as plain strings
+    let reference = ClassReference {
+        class_name: first,
+        reference_type: ReferenceType::Direct,
+        context: "test".to_string(),
+        source_file: PathBuf::from("init.sqf"),
+        owner: None,
+        location: None,
+        confidence: Confidence::High,
+        count: 1,
+        count_range: None,
+    };
+    let json = serde_json::to_value(&reference).unwrap();
+    assert_eq!(json["class_name"], "ACE_fieldDressing");
+    let read: ClassReference = serde_json::from_value(json).unwrap();
+    assert_eq!(read.class_name, "ACE_fieldDressing");
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn test_ffi_errors() {
+    use std::ffi::{CStr, CString};
+    use mission_scanner::ffi::{mission_scanner_free_string, mission_scanner_last_error, mission_scanner_scan_mission, mission_scanner_validate};
+
+    let last_error = || unsafe {
+        let error = mission_scanner_last_error();
+        assert!(!error.is_null());
+        let message = CStr::from_ptr(error).to_string_lossy().into_owned();
+        mission_scanner_free_string(error);
+        message
+    };
+
+    let result = unsafe { mission_scanner_scan_mission(std::ptr::null()) };
+    assert!(result.is_null());
+    assert_eq!(last_error(), "Argument mission_dir is null");
+
+    let mission_dir = CString::new("tests/missing_mission").unwrap();
+    let class_db = CString::new("tests/missing_classes.json").unwrap();
+    let result = unsafe { mission_scanner_validate(mission_dir.as_ptr(), class_db.as_ptr()) };
+    assert!(result.is_null());
+    assert!(last_error().contains("missing_classes.json"));
+}